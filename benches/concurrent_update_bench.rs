@@ -0,0 +1,102 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use criterion::*;
+use ik_rs::core::ik_segmenter::{IKSegmenter, TokenMode};
+use ik_rs::dict::dictionary::GLOBAL_DICT;
+
+const TEXT: &str =
+    "张华考上了北京大学；李萍进了中等技术学校；我在百货公司当售货员：我们都有光明的前途";
+
+// 分词线程数
+const READERS: usize = 4;
+// 每次写入的词数及两次写入的间隔
+const BURST_SIZE: usize = 200;
+const BURST_INTERVAL: Duration = Duration::from_millis(2);
+
+// 后台线程持续地成批 add_words, 直到 stop 被置位, 返回写入的批数
+fn spawn_writer(stop: Arc<AtomicBool>) -> thread::JoinHandle<usize> {
+    thread::spawn(move || {
+        let mut bursts = 0;
+        while !stop.load(Ordering::Relaxed) {
+            let words = (0..BURST_SIZE)
+                .map(|i| format!("并发新词{}号{}", bursts, i))
+                .collect::<Vec<_>>();
+            GLOBAL_DICT
+                .write()
+                .unwrap()
+                .add_words(words.iter().map(String::as_str).collect());
+            bursts += 1;
+            thread::sleep(BURST_INTERVAL);
+        }
+        bursts
+    })
+}
+
+// 多个线程同时分词 duration, 返回总分词次数及单次分词耗时的 p99、最大值
+fn run_readers(duration: Duration) -> (usize, Duration, Duration) {
+    let handles = (0..READERS)
+        .map(|_| {
+            thread::spawn(move || {
+                let mut ik = IKSegmenter::new();
+                let mut latencies = Vec::new();
+                let deadline = Instant::now() + duration;
+                while Instant::now() < deadline {
+                    let start = Instant::now();
+                    black_box(ik.tokenize(TEXT, TokenMode::SEARCH));
+                    latencies.push(start.elapsed());
+                }
+                latencies
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut latencies = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect::<Vec<_>>();
+    latencies.sort();
+    let p99 = latencies[latencies.len() * 99 / 100];
+    (latencies.len(), p99, *latencies.last().unwrap())
+}
+
+fn concurrent_update_benchmark(c: &mut Criterion) {
+    // 预热, 确保词典已加载
+    IKSegmenter::new().tokenize("预热", TokenMode::SEARCH);
+    let duration = Duration::from_secs(2);
+    let (count, p99, max) = run_readers(duration);
+    println!(
+        "baseline: {} tokenize/s, p99 {:?}, max {:?}",
+        count as u64 / duration.as_secs(),
+        p99,
+        max
+    );
+    let stop = Arc::new(AtomicBool::new(false));
+    let writer = spawn_writer(stop.clone());
+    let (count, p99, max) = run_readers(duration);
+    stop.store(true, Ordering::Relaxed);
+    let bursts = writer.join().unwrap();
+    println!(
+        "with {} add_words bursts: {} tokenize/s, p99 {:?}, max {:?}",
+        bursts,
+        count as u64 / duration.as_secs(),
+        p99,
+        max
+    );
+
+    let mut ik = IKSegmenter::new();
+    c.bench_function("tokenize", |b| {
+        b.iter(|| black_box(ik.tokenize(TEXT, TokenMode::SEARCH)))
+    });
+    let stop = Arc::new(AtomicBool::new(false));
+    let writer = spawn_writer(stop.clone());
+    c.bench_function("tokenize during add_words bursts", |b| {
+        b.iter(|| black_box(ik.tokenize(TEXT, TokenMode::SEARCH)))
+    });
+    stop.store(true, Ordering::Relaxed);
+    writer.join().unwrap();
+}
+
+criterion_group!(benches, concurrent_update_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+
+use criterion::*;
+use ik_rs::core::ik_segmenter::{IKSegmenter, TokenMode};
+use ik_rs::core::interner::Interner;
+
+const CORPUS: [&str; 6] = [
+    "张华考上了北京大学；李萍进了中等技术学校；我在百货公司当售货员：我们都有光明的前途",
+    "据新华社报道，国家统计局今日发布数据，上半年国内生产总值同比增长百分之五",
+    "这款手机采用6.1英寸屏幕，支持5G网络，售价为3999元，即日起在各大电商平台销售",
+    "北京市今天白天晴转多云，北风二三级，最高气温二十八度，夜间多云，最低气温十八度",
+    "用户反馈：物流很快，包装完好，商品与描述一致，好评！下次还会再来购买",
+    "中华人民共和国是工人阶级领导的、以工农联盟为基础的人民民主专政的社会主义国家",
+];
+
+// 模拟索引时反复出现的文本
+fn corpus() -> Vec<&'static str> {
+    CORPUS
+        .iter()
+        .cycle()
+        .take(CORPUS.len() * 50)
+        .copied()
+        .collect()
+}
+
+// 统计词元文本的分配次数: 不同的 Arc 指针个数
+fn distinct_allocations(ik: &mut IKSegmenter) -> (usize, usize) {
+    let mut total = 0;
+    let mut pointers = HashSet::new();
+    for text in corpus() {
+        for lexeme in ik.tokenize(text, TokenMode::INDEX) {
+            total += 1;
+            pointers.insert(lexeme.get_shared_text().unwrap().as_ptr() as usize);
+        }
+    }
+    (total, pointers.len())
+}
+
+fn intern_benchmark(c: &mut Criterion) {
+    let mut plain = IKSegmenter::new();
+    let mut interned = IKSegmenter::new().with_interner(Interner::shared());
+    let (total, allocations) = distinct_allocations(&mut interned);
+    println!(
+        "tokens: {}, text allocations with interner: {}",
+        total, allocations
+    );
+
+    c.bench_function("tokenize corpus", |b| {
+        b.iter(|| {
+            for text in corpus() {
+                black_box(plain.tokenize(text, TokenMode::INDEX));
+            }
+        })
+    });
+    c.bench_function("tokenize corpus interned", |b| {
+        b.iter(|| {
+            for text in corpus() {
+                black_box(interned.tokenize(text, TokenMode::INDEX));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, intern_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+use std::time::{Duration, Instant};
+
+use criterion::*;
+use ik_rs::core::ik_segmenter::{IKSegmenter, TokenMode};
+
+const SHORT_QUERIES: [&str; 8] = [
+    "北京大学",
+    "中华人民共和国",
+    "手机壳",
+    "iphone 14",
+    "三个苹果",
+    "红烧肉做法",
+    "rust教程",
+    "天气",
+];
+
+// 统计单次短查询分词耗时的 p99
+fn p99_latency(ik: &mut IKSegmenter, mode: TokenMode) -> Duration {
+    let mut latencies = Vec::with_capacity(SHORT_QUERIES.len() * 1000);
+    for _ in 0..1000 {
+        for query in SHORT_QUERIES {
+            let start = Instant::now();
+            black_box(ik.tokenize(query, mode));
+            latencies.push(start.elapsed());
+        }
+    }
+    latencies.sort();
+    latencies[latencies.len() * 99 / 100]
+}
+
+fn short_query_benchmark(c: &mut Criterion) {
+    let mut ik = IKSegmenter::new();
+    for mode in [TokenMode::SEARCH, TokenMode::INDEX] {
+        // 预热, 确保词典已加载
+        ik.tokenize("预热", mode);
+        println!(
+            "short query {:?} p99: {:?}",
+            mode,
+            p99_latency(&mut ik, mode)
+        );
+        c.bench_function(&format!("short query {:?}", mode), |b| {
+            b.iter(|| {
+                for query in SHORT_QUERIES {
+                    black_box(ik.tokenize(query, mode));
+                }
+            })
+        });
+    }
+}
+
+criterion_group!(benches, short_query_benchmark);
+criterion_main!(benches);
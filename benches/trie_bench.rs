@@ -12,7 +12,7 @@ fn trie_build() -> Trie {
 }
 
 fn trie_match() {
-    let mut trie = trie_build();
+    let trie = trie_build();
     trie.match_word("Back".chars());
     trie.match_word("Tea".chars());
 }
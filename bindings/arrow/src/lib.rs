@@ -0,0 +1,82 @@
+//! 将 ik-rs 的批量分词结果转为 Arrow RecordBatch, 可直接写入 Parquet 或交给 DataFusion
+//! 列: doc_id(UInt64)、token(LargeUtf8)、start/end(UInt32, 字节偏移)、type(Utf8)
+#![cfg(feature = "arrow")]
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, LargeStringArray, StringArray, UInt32Array, UInt64Array};
+use arrow::buffer::{Buffer, OffsetBuffer, ScalarBuffer};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use ik_rs::core::ik_segmenter::TokenMode;
+use ik_rs::core::token_batch::TokenBatch;
+use ik_rs::with_thread_segmenter;
+
+/// tokenize_to_arrow 输出的 schema
+pub fn token_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("doc_id", DataType::UInt64, false),
+        Field::new("token", DataType::LargeUtf8, false),
+        Field::new("start", DataType::UInt32, false),
+        Field::new("end", DataType::UInt32, false),
+        Field::new("type", DataType::Utf8, false),
+    ]))
+}
+
+/// 对 texts 分词, 每个词元一行, doc_id 为文档在 texts 中的下标
+pub fn tokenize_to_arrow<I, S>(texts: I, mode: TokenMode) -> Result<RecordBatch, ArrowError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let batch = with_thread_segmenter(|ik| ik.tokenize_batch(texts, mode));
+    to_record_batch(batch)
+}
+
+/// 将 TokenBatch 转为 RecordBatch, 词元文本及偏移直接作为 Arrow 缓冲区, 不逐行复制
+pub fn to_record_batch(batch: TokenBatch) -> Result<RecordBatch, ArrowError> {
+    let types = batch
+        .types
+        .iter()
+        .map(|t| Some(t.as_str()))
+        .collect::<StringArray>();
+    let offsets = OffsetBuffer::new(ScalarBuffer::from(batch.token_offsets));
+    let values = Buffer::from_vec(batch.token_values.into_bytes());
+    let tokens = LargeStringArray::try_new(offsets, values, None)?;
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from(batch.doc_ids)),
+        Arc::new(tokens),
+        Arc::new(UInt32Array::from(batch.starts)),
+        Arc::new(UInt32Array::from(batch.ends)),
+        Arc::new(types),
+    ];
+    RecordBatch::try_new(token_schema(), columns)
+}
+
+#[cfg(test)]
+mod test {
+    use arrow::array::Array;
+
+    use super::*;
+
+    #[test]
+    fn test_tokenize_to_arrow() {
+        let batch = tokenize_to_arrow(["中华人民共和国", "iPhone手机"], TokenMode::SEARCH).unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        let tokens = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<LargeStringArray>()
+            .unwrap();
+        assert_eq!(tokens.value(0), "中华人民共和国");
+        assert_eq!(tokens.value(2), "手机");
+        let doc_ids = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert_eq!(doc_ids.values().to_vec(), vec![0, 1, 1]);
+        assert_eq!(batch.column(4).len(), 3);
+    }
+}
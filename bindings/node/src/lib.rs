@@ -0,0 +1,45 @@
+//! ik-rs 的 Node.js 绑定, 与 Rust/tantivy 后端使用同一份分词逻辑,
+//! 便于前端在查询时得到与索引一致的切分
+#![cfg(feature = "node")]
+
+use std::cell::RefCell;
+
+use ik_rs::core::ik_segmenter::{IKSegmenter, TokenMode};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+thread_local! {
+    // Node 的 JS 线程独立的分词器
+    static IK: RefCell<IKSegmenter> = RefCell::new(IKSegmenter::new());
+}
+
+/// 返回给 JS 的词元, 偏移以 UTF-16 码元为单位, 可直接用于 String.prototype.slice
+#[napi(object)]
+pub struct Token {
+    pub text: String,
+    // 词元类型, 如 CN_WORD、ENGLISH
+    pub token_type: String,
+    pub position: u32,
+    pub start: u32,
+    pub end: u32,
+}
+
+/// tokenize(text, mode?): mode 为 "ik_smart"(默认) 或 "ik_max"
+#[napi]
+pub fn tokenize(text: String, mode: Option<String>) -> Result<Vec<Token>> {
+    let mode = match mode.as_deref() {
+        Some(mode) => TokenMode::try_from(mode).map_err(|e| Error::new(Status::InvalidArg, e))?,
+        None => TokenMode::SEARCH,
+    };
+    let records = IK.with(|ik| ik.borrow_mut().tokenize_records(&text, mode));
+    Ok(records
+        .into_iter()
+        .map(|record| Token {
+            token_type: record.lexeme_type.as_str().to_string(),
+            text: record.text,
+            position: record.position as u32,
+            start: record.offsets.utf16.start as u32,
+            end: record.offsets.utf16.end as u32,
+        })
+        .collect())
+}
@@ -0,0 +1,57 @@
+//! 从标准输入读取 NDJSON 文档, 分词后向标准输出写出 NDJSON 词元, 见 ik_rs::Worker
+//!
+//! cargo run --release --features worker --example ndjson_worker -- --mode ik_max --threads 4 < docs.ndjson
+//!
+//! 对接 Kafka 时可以与 kcat 组成管道, 写出端阻塞时读取随之暂停:
+//! kcat -C -b broker -t docs -u | ndjson_worker | kcat -P -b broker -t tokens
+use std::io::{self, BufReader, BufWriter};
+
+use ik_rs::core::ik_segmenter::{TokenMode, TokenizeOptions};
+use ik_rs::Worker;
+
+fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let mut mode = TokenMode::SEARCH;
+    let mut threads = None;
+    let mut text_field = None;
+    let mut lucene_names = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .unwrap_or_else(|| panic!("missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--mode" => mode = TokenMode::try_from(value().as_str()).unwrap(),
+            "--threads" => threads = Some(value().parse().expect("invalid --threads")),
+            "--text-field" => text_field = Some(value().clone()),
+            "--lucene-names" => lucene_names = true,
+            _ => panic!(
+                "unknown argument {}, usage: ndjson_worker [--mode ik_max|ik_smart] [--threads N] [--text-field text] [--lucene-names]",
+                arg
+            ),
+        }
+    }
+    let mut worker = Worker::new(TokenizeOptions {
+        lowercase: true,
+        ..TokenizeOptions::new(mode)
+    });
+    worker = worker.with_lucene_names(lucene_names);
+    if let Some(threads) = threads {
+        worker = worker.with_threads(threads);
+    }
+    if let Some(field) = text_field.as_deref() {
+        worker = worker.with_text_field(field);
+    }
+    let stdout = io::stdout();
+    match worker.run(BufReader::new(io::stdin()), BufWriter::new(stdout.lock())) {
+        Ok(stats) => eprintln!(
+            "{} documents, {} tokens, {} errors",
+            stats.documents, stats.tokens, stats.errors
+        ),
+        Err(error) => {
+            eprintln!("error: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,10 @@
+// 需要 protoc, 可通过 PROTOC 环境变量指定路径
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let server = std::env::var_os("CARGO_FEATURE_SERVER").is_some();
+    let transport = std::env::var_os("CARGO_FEATURE_TRANSPORT").is_some();
+    tonic_build::configure()
+        .build_server(server)
+        .build_client(transport)
+        .compile(&["../proto/ik.proto"], &["../proto"])?;
+    Ok(())
+}
@@ -0,0 +1,10 @@
+//! proto/ik.proto 生成的类型及 Analyzer 服务的实现
+pub mod pb {
+    tonic::include_proto!("ik.v1");
+}
+
+#[cfg(feature = "server")]
+mod service;
+
+#[cfg(feature = "server")]
+pub use crate::service::IkAnalyzerService;
@@ -0,0 +1,17 @@
+use ik_rs_grpc::pb::analyzer_server::AnalyzerServer;
+use ik_rs_grpc::IkAnalyzerService;
+
+// 监听地址, 可通过 IK_GRPC_ADDR 指定
+const DEFAULT_ADDR: &str = "127.0.0.1:50051";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let addr = std::env::var("IK_GRPC_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_string());
+    log::info!("ik gRPC server listening on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(AnalyzerServer::new(IkAnalyzerService))
+        .serve(addr.parse()?)
+        .await?;
+    Ok(())
+}
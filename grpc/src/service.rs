@@ -0,0 +1,144 @@
+use ik_rs::core::ik_segmenter::TokenMode;
+use ik_rs::dict::dictionary::{reload_global_dict, GLOBAL_DICT};
+use ik_rs::dict::updater::WordOp;
+use ik_rs::with_thread_segmenter;
+use tonic::{Request, Response, Status};
+
+use crate::pb::analyzer_server::Analyzer;
+use crate::pb::{
+    AnalyzeRequest, AnalyzeResponse, DictAddRequest, DictAddResponse, DictReloadRequest,
+    DictReloadResponse, Mode, Token,
+};
+
+/// 使用全局词典的 Analyzer 服务, 分词及词典的修改在 tokio 的阻塞线程池中执行
+#[derive(Debug, Default)]
+pub struct IkAnalyzerService;
+
+#[tonic::async_trait]
+impl Analyzer for IkAnalyzerService {
+    async fn analyze(
+        &self,
+        request: Request<AnalyzeRequest>,
+    ) -> Result<Response<AnalyzeResponse>, Status> {
+        let request = request.into_inner();
+        let mode = match Mode::from_i32(request.mode) {
+            Some(Mode::IkSmart) => TokenMode::SEARCH,
+            Some(Mode::IkMax) => TokenMode::INDEX,
+            None => return Err(Status::invalid_argument("unknown mode")),
+        };
+        let records = tokio::task::spawn_blocking(move || {
+            with_thread_segmenter(|ik| ik.tokenize_records(&request.text, mode))
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+        let tokens = records
+            .into_iter()
+            .map(|record| Token {
+                r#type: record.lexeme_type.as_str().to_string(),
+                text: record.text,
+                position: record.position as u32,
+                start_byte: record.offsets.bytes.start as u32,
+                end_byte: record.offsets.bytes.end as u32,
+                start_char: record.offsets.chars.start as u32,
+                end_char: record.offsets.chars.end as u32,
+            })
+            .collect();
+        Ok(Response::new(AnalyzeResponse { tokens }))
+    }
+
+    async fn dict_add(
+        &self,
+        request: Request<DictAddRequest>,
+    ) -> Result<Response<DictAddResponse>, Status> {
+        let request = request.into_inner();
+        let stop_words = request.stop_words;
+        let ops = request
+            .words
+            .into_iter()
+            .map(|word| {
+                if stop_words {
+                    WordOp::AddStop(word)
+                } else {
+                    WordOp::Add(word)
+                }
+            })
+            .collect();
+        // 写锁可能等待正在进行的分词, 不在 tokio 的工作线程中等待
+        let version =
+            tokio::task::spawn_blocking(move || GLOBAL_DICT.write().unwrap().apply_word_ops(ops))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(DictAddResponse { version }))
+    }
+
+    async fn dict_reload(
+        &self,
+        _request: Request<DictReloadRequest>,
+    ) -> Result<Response<DictReloadResponse>, Status> {
+        let report = tokio::task::spawn_blocking(reload_global_dict)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(DictReloadResponse {
+            success: report.success,
+            version: report.version,
+            errors: report.errors,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_analyze() {
+        let service = IkAnalyzerService;
+        let response = service
+            .analyze(Request::new(AnalyzeRequest {
+                text: "中华人民共和国".to_string(),
+                mode: Mode::IkSmart as i32,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.tokens.len(), 1);
+        let token = &response.tokens[0];
+        assert_eq!(token.text, "中华人民共和国");
+        assert_eq!(token.r#type, "CN_WORD");
+        assert_eq!((token.start_byte, token.end_byte), (0, 21));
+        assert_eq!((token.start_char, token.end_char), (0, 7));
+
+        let error = service
+            .analyze(Request::new(AnalyzeRequest {
+                text: String::new(),
+                mode: 7,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_dict_add() {
+        let service = IkAnalyzerService;
+        let version = service
+            .dict_add(Request::new(DictAddRequest {
+                words: vec!["嵘峰沧澜".to_string()],
+                stop_words: false,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .version;
+        assert!(version > 0);
+        let response = service
+            .analyze(Request::new(AnalyzeRequest {
+                text: "嵘峰沧澜".to_string(),
+                mode: Mode::IkSmart as i32,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.tokens[0].text, "嵘峰沧澜");
+    }
+}
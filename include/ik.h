@@ -0,0 +1,65 @@
+/*
+ * ik-rs 的 C 接口, 由 src/ffi.rs 实现
+ * 编译: cargo rustc --release --no-default-features --features ffi --crate-type cdylib
+ * 词典加载方式与 Rust crate 相同, 即 ik.yml 或 IK_CONFIG 指定的配置文件
+ */
+#ifndef IK_RS_H
+#define IK_RS_H
+
+#include <stddef.h>
+#include <stdint.h>
+#include <sys/types.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* 分词模式 */
+#define IK_MODE_INDEX 0
+#define IK_MODE_SEARCH 1
+
+/* 词元类型, 与 LexemeType::id 一致 */
+#define IK_TYPE_UNKNOWN 0
+#define IK_TYPE_ENGLISH 1
+#define IK_TYPE_ARABIC 2
+#define IK_TYPE_LETTER 3
+#define IK_TYPE_CNWORD 4
+#define IK_TYPE_OTHER_CJK 8
+#define IK_TYPE_CNUM 16
+#define IK_TYPE_COUNT 32
+#define IK_TYPE_CQUAN 48
+#define IK_TYPE_CNCHAR 64
+#define IK_TYPE_SPECIAL 128
+#define IK_TYPE_EMOJI 129
+#define IK_TYPE_ORG 130
+#define IK_TYPE_LOC 131
+
+/* 词元, 偏移以 UTF-8 字节为单位, end_byte 不包含 */
+typedef struct {
+    uint32_t begin_byte;
+    uint32_t end_byte;
+    uint32_t type_id;
+    uint32_t position;
+} IkToken;
+
+/* ik_tokenize 返回的数组, 出错时 tokens 为 NULL */
+typedef struct {
+    IkToken *tokens;
+    size_t len;
+} IkTokenArray;
+
+/* 对 text 开始的 len 个 UTF-8 字节分词, 结果需用 ik_token_array_free 释放
+ * 全角字符先转为半角再分词, 偏移对应原文 */
+IkTokenArray ik_tokenize(const char *text, size_t len, int mode);
+
+/* 释放 ik_tokenize 返回的数组 */
+void ik_token_array_free(IkTokenArray array);
+
+/* 写入调用方提供的 buf, 返回词元总数(可能大于 cap), 出错时返回 -1 */
+ssize_t ik_tokenize_into(const char *text, size_t len, int mode, IkToken *buf, size_t cap);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* IK_RS_H */
@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use tantivy::tokenizer::{RemoveLongFilter, StopWordFilter, TextAnalyzer};
+
+use crate::core::ik_segmenter::TokenMode;
+use crate::df_filter::DfPruningFilter;
+use crate::dict::synonym::SynonymMap;
+use crate::pinyin::{PinyinAbbrFilter, PinyinTable};
+use crate::IkTokenizer;
+
+/// 构造基于 IkTokenizer 的 tantivy TextAnalyzer
+///
+/// ```
+/// use ik_rs::core::ik_segmenter::TokenMode;
+/// use ik_rs::IkAnalyzer;
+///
+/// let analyzer = IkAnalyzer::builder()
+///     .mode(TokenMode::SEARCH)
+///     .lowercase(false)
+///     .stopwords(vec!["的".to_string()])
+///     .build();
+/// ```
+pub struct IkAnalyzer;
+
+impl IkAnalyzer {
+    pub fn builder() -> IkAnalyzerBuilder {
+        IkAnalyzerBuilder::default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IkAnalyzerBuilder {
+    mode: TokenMode,
+    lowercase: bool,
+    // 在词典停止词之外额外过滤的词
+    stopwords: Vec<String>,
+    // 过滤字节长度不小于该值的词元
+    max_token_length: Option<usize>,
+    // 按文档频率过滤词元
+    df_pruning: Option<DfPruningFilter>,
+    // 追加拼音首字母缩写词元
+    pinyin_abbreviations: Option<Arc<PinyinTable>>,
+    // 追加同义词词元
+    synonyms: Option<Arc<SynonymMap>>,
+    // 保留词典中的停止词
+    keep_stop_words: bool,
+    // 去掉零宽字符及双向文本控制符, 为 None 时取自配置
+    sanitize_controls: Option<bool>,
+    // 只对本分析器生效的扩展词及停止词
+    extra_words: Vec<String>,
+    extra_stop_words: Vec<String>,
+}
+
+impl Default for IkAnalyzerBuilder {
+    fn default() -> Self {
+        IkAnalyzerBuilder {
+            mode: TokenMode::default(),
+            lowercase: true,
+            stopwords: Vec::new(),
+            max_token_length: None,
+            df_pruning: None,
+            pinyin_abbreviations: None,
+            synonyms: None,
+            keep_stop_words: false,
+            sanitize_controls: None,
+            extra_words: Vec::new(),
+            extra_stop_words: Vec::new(),
+        }
+    }
+}
+
+impl IkAnalyzerBuilder {
+    pub fn mode(mut self, mode: TokenMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn lowercase(mut self, lowercase: bool) -> Self {
+        self.lowercase = lowercase;
+        self
+    }
+
+    pub fn stopwords(mut self, stopwords: Vec<String>) -> Self {
+        self.stopwords = stopwords;
+        self
+    }
+
+    pub fn max_token_length(mut self, max_token_length: usize) -> Self {
+        self.max_token_length = Some(max_token_length);
+        self
+    }
+
+    pub fn df_pruning(mut self, filter: DfPruningFilter) -> Self {
+        self.df_pruning = Some(filter);
+        self
+    }
+
+    /// 索引时为中文词元追加拼音首字母缩写, 如 "北京大学" 追加 "bjdx", 用于自动补全
+    pub fn pinyin_abbreviations(mut self, table: Arc<PinyinTable>) -> Self {
+        self.pinyin_abbreviations = Some(table);
+        self
+    }
+
+    /// 在原词元的位置追加同义词词元, 见 IkTokenizer::with_synonyms
+    pub fn synonyms(mut self, synonyms: Arc<SynonymMap>) -> Self {
+        self.synonyms = Some(synonyms);
+        self
+    }
+
+    /// 保留词典中的停止词, 见 IkTokenizer::with_keep_stop_words
+    /// stopwords 指定的词仍会被过滤
+    pub fn keep_stop_words(mut self, keep_stop_words: bool) -> Self {
+        self.keep_stop_words = keep_stop_words;
+        self
+    }
+
+    /// 去掉零宽字符及双向文本控制符, 见 IkTokenizer::with_sanitize_controls
+    pub fn sanitize_controls(mut self, sanitize_controls: bool) -> Self {
+        self.sanitize_controls = Some(sanitize_controls);
+        self
+    }
+
+    /// 只对本分析器生效的扩展词, 见 IkTokenizer::with_extra_words
+    pub fn extra_words(mut self, words: &[&str]) -> Self {
+        self.extra_words
+            .extend(words.iter().map(|word| word.to_string()));
+        self
+    }
+
+    /// 只对本分析器生效的停止词, 见 IkTokenizer::with_extra_stop_words
+    pub fn extra_stop_words(mut self, words: &[&str]) -> Self {
+        self.extra_stop_words
+            .extend(words.iter().map(|word| word.to_string()));
+        self
+    }
+
+    pub fn build(self) -> TextAnalyzer {
+        let mut tokenizer = IkTokenizer::new(self.mode)
+            .with_lowercase(self.lowercase)
+            .with_keep_stop_words(self.keep_stop_words);
+        if let Some(sanitize_controls) = self.sanitize_controls {
+            tokenizer = tokenizer.with_sanitize_controls(sanitize_controls);
+        }
+        if let Some(synonyms) = self.synonyms {
+            tokenizer = tokenizer.with_synonyms(synonyms);
+        }
+        if !self.extra_words.is_empty() || !self.extra_stop_words.is_empty() {
+            let words = self
+                .extra_words
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            let stop_words = self
+                .extra_stop_words
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            tokenizer = tokenizer
+                .with_extra_words(&words)
+                .with_extra_stop_words(&stop_words);
+        }
+        let mut analyzer = TextAnalyzer::from(tokenizer);
+        if !self.stopwords.is_empty() {
+            analyzer = analyzer.filter(StopWordFilter::remove(self.stopwords));
+        }
+        if let Some(limit) = self.max_token_length {
+            analyzer = analyzer.filter(RemoveLongFilter::limit(limit));
+        }
+        if let Some(filter) = self.df_pruning {
+            analyzer = analyzer.filter(filter);
+        }
+        if let Some(table) = self.pinyin_abbreviations {
+            analyzer = analyzer.filter(PinyinAbbrFilter::new(table));
+        }
+        analyzer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tokens(analyzer: &TextAnalyzer, text: &str) -> Vec<String> {
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = Vec::new();
+        while let Some(token) = stream.next() {
+            tokens.push(token.text.clone());
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_analyzer_builder() {
+        let analyzer = IkAnalyzer::builder().mode(TokenMode::SEARCH).build();
+        assert_eq!(
+            tokens(&analyzer, "Hello 北京大学"),
+            vec!["hello", "北京大学"]
+        );
+
+        let analyzer = IkAnalyzer::builder()
+            .mode(TokenMode::SEARCH)
+            .lowercase(false)
+            .stopwords(vec!["北京大学".to_string()])
+            .build();
+        assert_eq!(tokens(&analyzer, "Hello 北京大学"), vec!["Hello"]);
+
+        let analyzer = IkAnalyzer::builder()
+            .mode(TokenMode::SEARCH)
+            .max_token_length(6)
+            .build();
+        assert_eq!(tokens(&analyzer, "Hello 北京大学"), vec!["hello"]);
+    }
+
+    #[test]
+    fn test_extra_words() {
+        let analyzer = IkAnalyzer::builder()
+            .mode(TokenMode::SEARCH)
+            .extra_words(&["嵘峰科技"])
+            .extra_stop_words(&["北京大学"])
+            .build();
+        assert_eq!(tokens(&analyzer, "嵘峰科技北京大学"), vec!["嵘峰科技"]);
+        let analyzer = IkAnalyzer::builder().mode(TokenMode::SEARCH).build();
+        assert!(!tokens(&analyzer, "嵘峰科技北京大学").contains(&"嵘峰科技".to_string()));
+    }
+}
@@ -0,0 +1,515 @@
+//! ik 命令行工具, 输出格式为 text/json/tsv, 便于接入 shell 管道
+//!
+//! ik tokenize [--mode smart|max] [--format text|json|tsv] [FILE|-]
+//! ik dict check [--format text|json|tsv] FILE...
+//! ik dict freq [--mode smart|max] [--max-match] [--min-count N] CORPUS...
+//! ik bench [--mode smart|max] [--format text|json|tsv] [--iterations N] FILE
+//! ik coverage [--mode smart|max] [--format text|json|tsv] [FILE|-]
+//! ik eval [--format text|json|tsv] GOLD
+//! ik config check [ik.yml]
+//! ik serve [ADDR], 需启用 server feature
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::process;
+use std::time::Instant;
+
+use ik_rs::config::default_config::{DefaultConfig, IK_CONFIG_NAME};
+use ik_rs::core::ik_segmenter::TokenMode;
+use ik_rs::core::token_record::LuceneToken;
+use ik_rs::dict::dictionary::GLOBAL_DICT;
+use ik_rs::dict::freq::FreqCounter;
+use ik_rs::eval::evaluate;
+use ik_rs::{coverage_holes, with_thread_segmenter, CompareConfig};
+use serde::Serialize;
+
+const USAGE: &str = "usage:
+    ik tokenize [--mode smart|max] [--format text|json|tsv] [FILE|-]
+    ik dict check [--format text|json|tsv] FILE...
+    ik dict freq [--mode smart|max] [--max-match] [--min-count N] CORPUS...
+    ik bench [--mode smart|max] [--format text|json|tsv] [--iterations N] FILE
+    ik coverage [--mode smart|max] [--format text|json|tsv] [FILE|-]
+    ik eval [--format text|json|tsv] GOLD
+    ik config check [ik.yml]
+    ik serve [ADDR]    (server feature, default 127.0.0.1:9200)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Tsv,
+}
+
+// 解析后的公共参数, 未消费的位置参数保存在 paths 中
+struct Args {
+    mode: TokenMode,
+    format: Format,
+    iterations: usize,
+    // dict freq 使用正向最大匹配而不是分词器
+    max_match: bool,
+    min_count: u64,
+    paths: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Args, String> {
+        let mut parsed = Args {
+            mode: TokenMode::SEARCH,
+            format: Format::Text,
+            iterations: 1,
+            max_match: false,
+            min_count: 1,
+            paths: Vec::new(),
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || {
+                iter.next()
+                    .ok_or_else(|| format!("missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "--mode" => parsed.mode = parse_mode(value()?)?,
+                "--format" => parsed.format = parse_format(value()?)?,
+                "--iterations" => {
+                    parsed.iterations = value()?
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| "--iterations must be a positive integer".to_string())?
+                }
+                "--max-match" => parsed.max_match = true,
+                "--min-count" => {
+                    parsed.min_count = value()?
+                        .parse()
+                        .map_err(|_| "--min-count must be an integer".to_string())?
+                }
+                "-" => parsed.paths.push(arg.clone()),
+                _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+                _ => parsed.paths.push(arg.clone()),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+// smart/max 之外也接受 ES 中的分析器名 ik_smart/ik_max
+fn parse_mode(mode: &str) -> Result<TokenMode, String> {
+    match mode {
+        "smart" => Ok(TokenMode::SEARCH),
+        "max" => Ok(TokenMode::INDEX),
+        _ => TokenMode::try_from(mode),
+    }
+}
+
+fn parse_format(format: &str) -> Result<Format, String> {
+    match format {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        "tsv" => Ok(Format::Tsv),
+        _ => Err(format!(
+            "unknown format {}, expect text, json or tsv",
+            format
+        )),
+    }
+}
+
+// - 表示标准输入
+fn open_input(path: &str) -> Result<Box<dyn BufRead>, String> {
+    if path == "-" {
+        return Ok(Box::new(BufReader::new(io::stdin())));
+    }
+    let file = File::open(path).map_err(|e| format!("open {} error: {}", path, e))?;
+    Ok(Box::new(BufReader::new(file)))
+}
+
+fn read_lines(path: &str) -> Result<Vec<String>, String> {
+    open_input(path)?
+        .lines()
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("read {} error: {}", path, e))
+}
+
+fn write_error(e: io::Error) -> String {
+    format!("write error: {}", e)
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("serialize to json")
+}
+
+// TSV 字段中的制表符及换行替换为空格
+fn tsv_field(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+// 逐行分词, 每行输入对应 text/json 的一行输出, tsv 则每个词元一行
+// 偏移及类型名与 ES _analyze 相同, 偏移以 UTF-16 码元为单位
+// 全角字符先转为半角再分词, 输出的词元取自原文
+fn tokenize(args: Args) -> Result<(), String> {
+    let path = match args.paths.as_slice() {
+        [] => "-",
+        [path] => path.as_str(),
+        _ => return Err("tokenize accepts only one input".to_string()),
+    };
+    let input = open_input(path)?;
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if args.format == Format::Tsv {
+        writeln!(out, "line\tposition\ttoken\tstart_offset\tend_offset\ttype")
+            .map_err(write_error)?;
+    }
+    for (index, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| format!("read {} error: {}", path, e))?;
+        let records = with_thread_segmenter(|ik| ik.tokenize_records(&line, args.mode));
+        let tokens = records
+            .iter()
+            .enumerate()
+            .map(|(position, record)| record.to_lucene(position))
+            .collect::<Vec<_>>();
+        match args.format {
+            Format::Text => {
+                let words = tokens.iter().map(|t| t.token).collect::<Vec<_>>();
+                writeln!(out, "{}", words.join(" "))
+            }
+            Format::Json => {
+                #[derive(Serialize)]
+                struct Line<'a> {
+                    line: usize,
+                    tokens: &'a [LuceneToken<'a>],
+                }
+                writeln!(
+                    out,
+                    "{}",
+                    to_json(&Line {
+                        line: index + 1,
+                        tokens: &tokens,
+                    })
+                )
+            }
+            Format::Tsv => tokens.iter().try_for_each(|t| {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    index + 1,
+                    t.position,
+                    tsv_field(t.token),
+                    t.start_offset,
+                    t.end_offset,
+                    t.token_type
+                )
+            }),
+        }
+        .map_err(write_error)?;
+    }
+    out.flush().map_err(write_error)
+}
+
+// 按 Dictionary::lint 检查词典文件, 有问题时返回 false
+fn dict_check(args: Args) -> Result<bool, String> {
+    if args.paths.is_empty() {
+        return Err("dict check needs at least one dictionary file".to_string());
+    }
+    #[derive(Serialize)]
+    struct Issue<'a> {
+        file: &'a str,
+        line: usize,
+        word: &'a str,
+        kind: String,
+    }
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if args.format == Format::Tsv {
+        writeln!(out, "file\tline\tword\tkind").map_err(write_error)?;
+    }
+    let mut clean = true;
+    for path in args.paths.iter() {
+        let lines = read_lines(path)?;
+        let issues = GLOBAL_DICT.read().unwrap().lint(lines.iter());
+        clean &= issues.is_empty();
+        for issue in issues.iter() {
+            let kind = format!("{:?}", issue.kind);
+            match args.format {
+                Format::Text => writeln!(out, "{}: {}", path, issue),
+                Format::Json => writeln!(
+                    out,
+                    "{}",
+                    to_json(&Issue {
+                        file: path,
+                        line: issue.line,
+                        word: &issue.word,
+                        kind,
+                    })
+                ),
+                Format::Tsv => writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}",
+                    path,
+                    issue.line,
+                    tsv_field(&issue.word),
+                    kind
+                ),
+            }
+            .map_err(write_error)?;
+        }
+        if args.format == Format::Text {
+            writeln!(
+                out,
+                "{}: {} lines, {} issues",
+                path,
+                lines.len(),
+                issues.len()
+            )
+            .map_err(write_error)?;
+        }
+    }
+    out.flush().map_err(write_error)?;
+    Ok(clean)
+}
+
+// 统计语料中词典词的词频, 以 "词\t词频" 格式写到标准输出, 见 ik_rs::dict::freq
+fn dict_freq(args: Args) -> Result<(), String> {
+    if args.paths.is_empty() {
+        return Err("dict freq needs at least one corpus file".to_string());
+    }
+    let mut counter = FreqCounter::new();
+    for path in args.paths.iter() {
+        let input = open_input(path)?;
+        for line in input.lines() {
+            let line = line.map_err(|e| format!("read {} error: {}", path, e))?;
+            if args.max_match {
+                counter.count_max_match(&GLOBAL_DICT.read().unwrap(), &line);
+            } else {
+                with_thread_segmenter(|ik| counter.count_tokens(ik, &line, args.mode));
+            }
+        }
+    }
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    counter.write_dict(&mut out, args.min_count)?;
+    out.flush().map_err(write_error)
+}
+
+// 逐行报告 IK 没有输出任何词元的区间, 见 ik_rs::coverage_holes, 区间以字符为单位
+fn coverage(args: Args) -> Result<(), String> {
+    let path = match args.paths.as_slice() {
+        [] => "-",
+        [path] => path.as_str(),
+        _ => return Err("coverage accepts only one input".to_string()),
+    };
+    let lines = read_lines(path)?;
+    let holes = coverage_holes(&lines, CompareConfig::new(args.mode));
+    #[derive(Serialize)]
+    struct Hole<'a> {
+        line: usize,
+        start: usize,
+        end: usize,
+        text: &'a str,
+        context: &'a str,
+    }
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if args.format == Format::Tsv {
+        writeln!(out, "line\tstart\tend\ttext\tcontext").map_err(write_error)?;
+    }
+    for hole in holes.iter() {
+        let line = hole.text_index + 1;
+        match args.format {
+            Format::Text => writeln!(out, "line {}: {:?} in {:?}", line, hole.text, hole.context),
+            Format::Json => writeln!(
+                out,
+                "{}",
+                to_json(&Hole {
+                    line,
+                    start: hole.span.start,
+                    end: hole.span.end,
+                    text: &hole.text,
+                    context: &hole.context,
+                })
+            ),
+            Format::Tsv => writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                line,
+                hole.span.start,
+                hole.span.end,
+                tsv_field(&hole.text),
+                tsv_field(&hole.context)
+            ),
+        }
+        .map_err(write_error)?;
+    }
+    out.flush().map_err(write_error)
+}
+
+// 对文件逐行分词 iterations 遍, 词典加载不计入耗时
+fn bench(args: Args) -> Result<(), String> {
+    let path = match args.paths.as_slice() {
+        [path] => path.as_str(),
+        _ => return Err("bench needs exactly one input file".to_string()),
+    };
+    let lines = read_lines(path)?;
+    #[derive(Serialize)]
+    struct Report {
+        lines: usize,
+        chars: usize,
+        bytes: usize,
+        tokens: usize,
+        iterations: usize,
+        elapsed_ms: f64,
+        chars_per_sec: f64,
+        mb_per_sec: f64,
+    }
+    with_thread_segmenter(|ik| ik.tokenize("预热", args.mode));
+    let mut tokens = 0;
+    let start = Instant::now();
+    with_thread_segmenter(|ik| {
+        for _ in 0..args.iterations {
+            for line in lines.iter() {
+                tokens += ik.tokenize(line, args.mode).len();
+            }
+        }
+    });
+    let elapsed = start.elapsed().as_secs_f64();
+    let chars = lines.iter().map(|l| l.chars().count()).sum::<usize>() * args.iterations;
+    let bytes = lines.iter().map(String::len).sum::<usize>() * args.iterations;
+    let report = Report {
+        lines: lines.len() * args.iterations,
+        chars,
+        bytes,
+        tokens,
+        iterations: args.iterations,
+        elapsed_ms: elapsed * 1000.0,
+        chars_per_sec: chars as f64 / elapsed.max(f64::EPSILON),
+        mb_per_sec: bytes as f64 / 1_000_000.0 / elapsed.max(f64::EPSILON),
+    };
+    match args.format {
+        Format::Text => println!(
+            "{} lines, {} chars, {} tokens in {:.1} ms: {:.0} chars/s, {:.2} MB/s",
+            report.lines,
+            report.chars,
+            report.tokens,
+            report.elapsed_ms,
+            report.chars_per_sec,
+            report.mb_per_sec
+        ),
+        Format::Json => println!("{}", to_json(&report)),
+        Format::Tsv => println!(
+            "lines\tchars\tbytes\ttokens\titerations\telapsed_ms\tchars_per_sec\tmb_per_sec\n{}\t{}\t{}\t{}\t{}\t{:.3}\t{:.0}\t{:.3}",
+            report.lines,
+            report.chars,
+            report.bytes,
+            report.tokens,
+            report.iterations,
+            report.elapsed_ms,
+            report.chars_per_sec,
+            report.mb_per_sec
+        ),
+    }
+    Ok(())
+}
+
+// 对 SIGHAN 格式的标准切分语料评测 SEARCH 模式, 见 ik_rs::eval
+fn eval(args: Args) -> Result<(), String> {
+    let path = match args.paths.as_slice() {
+        [path] => path.as_str(),
+        _ => return Err("eval needs exactly one gold file".to_string()),
+    };
+    let gold = read_lines(path)?;
+    let report = with_thread_segmenter(|ik| evaluate(ik, &gold));
+    #[derive(Serialize)]
+    struct Report {
+        sentences: usize,
+        gold_words: usize,
+        predicted_words: usize,
+        correct_words: usize,
+        precision: f64,
+        recall: f64,
+        f1: f64,
+    }
+    let json = Report {
+        sentences: report.sentences,
+        gold_words: report.gold_words,
+        predicted_words: report.predicted_words,
+        correct_words: report.correct_words,
+        precision: report.precision(),
+        recall: report.recall(),
+        f1: report.f1(),
+    };
+    match args.format {
+        Format::Text => println!("{}", report),
+        Format::Json => println!("{}", to_json(&json)),
+        Format::Tsv => println!(
+            "sentences\tgold_words\tpredicted_words\tcorrect_words\tprecision\trecall\tf1\n{}\t{}\t{}\t{}\t{:.4}\t{:.4}\t{:.4}",
+            json.sentences,
+            json.gold_words,
+            json.predicted_words,
+            json.correct_words,
+            json.precision,
+            json.recall,
+            json.f1
+        ),
+    }
+    Ok(())
+}
+
+// 检查配置文件并输出解析后的有效配置
+fn config_check(args: Args) -> Result<bool, String> {
+    let path = match args.paths.as_slice() {
+        [] => PathBuf::from(IK_CONFIG_NAME),
+        [path] => PathBuf::from(path),
+        _ => return Err("config check accepts only one file".to_string()),
+    };
+    let check = DefaultConfig::check(&path)?;
+    print!("{}", check);
+    Ok(check.is_ok())
+}
+
+// 启动与 ES _analyze 兼容的 HTTP 服务, 见 ik_rs::AnalyzeServer
+#[cfg(feature = "server")]
+fn serve(args: Args) -> Result<(), String> {
+    let addr = match args.paths.as_slice() {
+        [] => "127.0.0.1:9200",
+        [addr] => addr.as_str(),
+        _ => return Err("serve accepts only one address".to_string()),
+    };
+    let server = ik_rs::AnalyzeServer::bind(addr)?;
+    eprintln!("listening on http://{}/_analyze", server.local_addr()?);
+    server.serve()
+}
+
+fn run(args: &[String]) -> Result<bool, String> {
+    let command = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match command.as_slice() {
+        ["tokenize", ..] => tokenize(Args::parse(&args[1..])?).map(|_| true),
+        ["dict", "check", ..] => dict_check(Args::parse(&args[2..])?),
+        ["dict", "freq", ..] => dict_freq(Args::parse(&args[2..])?).map(|_| true),
+        ["bench", ..] => bench(Args::parse(&args[1..])?).map(|_| true),
+        ["coverage", ..] => coverage(Args::parse(&args[1..])?).map(|_| true),
+        ["eval", ..] => eval(Args::parse(&args[1..])?).map(|_| true),
+        ["config", "check", ..] => config_check(Args::parse(&args[2..])?),
+        #[cfg(feature = "server")]
+        ["serve", ..] => serve(Args::parse(&args[1..])?).map(|_| true),
+        ["help" | "--help" | "-h"] => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+// 退出码: 0 成功, 1 检查发现问题, 2 参数或读写错误
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match run(&args) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(error) => {
+            // 下游关闭管道(如 | head)时安静退出
+            if error.contains("Broken pipe") {
+                return;
+            }
+            eprintln!("error: {}", error);
+            process::exit(2);
+        }
+    }
+}
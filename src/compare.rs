@@ -0,0 +1,272 @@
+use std::ops::Range;
+
+use crate::core::char_util::{char_type_of, CharType};
+use crate::core::ik_segmenter::{IKSegmenter, TokenMode, TokenizeOptions};
+use crate::core::lexeme::Lexeme;
+
+// 差异区间两侧保留的上下文字符数
+const CONTEXT_CHARS: usize = 5;
+
+/// 对比实验中的一组分词配置
+pub struct CompareConfig {
+    segmenter: IKSegmenter,
+    mode: TokenMode,
+}
+
+impl CompareConfig {
+    /// 使用全局配置和全局词典
+    pub fn new(mode: TokenMode) -> Self {
+        Self::with_segmenter(IKSegmenter::new(), mode)
+    }
+
+    /// 使用自行构造的分词器, 如不同的子分词器或类型优先级
+    pub fn with_segmenter(segmenter: IKSegmenter, mode: TokenMode) -> Self {
+        CompareConfig { segmenter, mode }
+    }
+}
+
+/// 两组配置分词结果的一处差异
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenDiff {
+    // 文本在语料中的下标
+    pub text_index: usize,
+    // 差异区间, 以字符为单位
+    pub span: Range<usize>,
+    // 差异区间及其前后的文本
+    pub context: String,
+    // 区间内只出现在配置 A 中的词元
+    pub tokens_a: Vec<String>,
+    // 区间内只出现在配置 B 中的词元
+    pub tokens_b: Vec<String>,
+}
+
+/// 分别用两组配置对语料分词, 报告结果不同的位置
+/// 相互重叠的差异词元合并为一处差异
+pub fn compare<S: AsRef<str>>(
+    texts: &[S],
+    mut config_a: CompareConfig,
+    mut config_b: CompareConfig,
+) -> Vec<TokenDiff> {
+    let mut diffs = Vec::new();
+    for (text_index, text) in texts.iter().enumerate() {
+        let text = text.as_ref();
+        let a = config_a.segmenter.tokenize(text, config_a.mode);
+        let b = config_b.segmenter.tokenize(text, config_b.mode);
+        // (起始, 结束, 文本, 是否来自 A)
+        let mut differing = Vec::new();
+        for l in a.iter().filter(|l| !contains(&b, l)) {
+            differing.push((
+                l.get_begin(),
+                l.get_end_position(),
+                l.get_lexeme_text(),
+                true,
+            ));
+        }
+        for l in b.iter().filter(|l| !contains(&a, l)) {
+            differing.push((
+                l.get_begin(),
+                l.get_end_position(),
+                l.get_lexeme_text(),
+                false,
+            ));
+        }
+        differing.sort_by_key(|(begin, end, _, _)| (*begin, *end));
+
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut current: Option<TokenDiff> = None;
+        for (begin, end, token, from_a) in differing {
+            let overlaps = matches!(current.as_ref(), Some(diff) if begin < diff.span.end);
+            if !overlaps {
+                if let Some(diff) = current.take() {
+                    diffs.push(with_context(diff, &chars));
+                }
+                current = Some(TokenDiff {
+                    text_index,
+                    span: begin..end,
+                    context: String::new(),
+                    tokens_a: Vec::new(),
+                    tokens_b: Vec::new(),
+                });
+            }
+            let diff = current.as_mut().unwrap();
+            diff.span.end = diff.span.end.max(end);
+            if from_a {
+                diff.tokens_a.push(token.to_string());
+            } else {
+                diff.tokens_b.push(token.to_string());
+            }
+        }
+        if let Some(diff) = current {
+            diffs.push(with_context(diff, &chars));
+        }
+    }
+    diffs
+}
+
+/// IK 没有输出任何词元的一段文本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageHole {
+    // 文本在语料中的下标
+    pub text_index: usize,
+    // 未覆盖的区间, 以字符为单位
+    pub span: Range<usize>,
+    // 区间内的文本
+    pub text: String,
+    // 区间及其前后的文本
+    pub context: String,
+}
+
+/// 用近似 ICU 默认规则的切分与 IK 对照, 报告规则切分出了词而 IK 没有输出任何词元的区间
+/// 用于在大规模语料中查找词典的覆盖漏洞; 停止词视为已覆盖, 相邻的未覆盖词合并为一处
+pub fn coverage_holes<S: AsRef<str>>(texts: &[S], mut config: CompareConfig) -> Vec<CoverageHole> {
+    let options = TokenizeOptions {
+        keep_stop_words: true,
+        ..TokenizeOptions::new(config.mode)
+    };
+    let mut holes = Vec::new();
+    for (text_index, text) in texts.iter().enumerate() {
+        let text = text.as_ref();
+        let lexemes = config.segmenter.tokenize_with(text, options);
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut covered = vec![false; chars.len()];
+        for l in lexemes.iter() {
+            let end = l.get_end_position().min(chars.len());
+            covered[l.get_begin().min(end)..end]
+                .iter_mut()
+                .for_each(|c| *c = true);
+        }
+        let mut current: Option<Range<usize>> = None;
+        for span in fallback_tokenize(text) {
+            if covered[span.clone()].iter().any(|&c| c) {
+                if let Some(hole) = current.take() {
+                    holes.push(coverage_hole(text_index, hole, &chars));
+                }
+                continue;
+            }
+            current = match current {
+                Some(hole) => Some(hole.start..span.end),
+                None => Some(span),
+            };
+        }
+        if let Some(hole) = current {
+            holes.push(coverage_hole(text_index, hole, &chars));
+        }
+    }
+    holes
+}
+
+/// 近似 ICU 默认规则的切分, 不依赖词典: 汉字及 emoji 单字成词,
+/// 其余连续的字母、数字(包括 IK 不支持的文字, 如西里尔字母)成词, 返回各词的字符区间
+pub fn fallback_tokenize(text: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut word_start = None;
+    for (i, c) in text.chars().enumerate() {
+        let char_type = char_type_of(&c);
+        let single = matches!(char_type, CharType::CHINESE | CharType::EMOJI);
+        let in_word = !single && (char_type != CharType::USELESS || c.is_alphanumeric());
+        if !in_word {
+            if let Some(start) = word_start.take() {
+                spans.push(start..i);
+            }
+            if single {
+                spans.push(i..i + 1);
+            }
+        } else if word_start.is_none() {
+            word_start = Some(i);
+        }
+    }
+    if let Some(start) = word_start {
+        spans.push(start..text.chars().count());
+    }
+    spans
+}
+
+fn coverage_hole(text_index: usize, span: Range<usize>, chars: &[char]) -> CoverageHole {
+    CoverageHole {
+        text_index,
+        text: chars[span.clone()].iter().collect(),
+        context: context(&span, chars),
+        span,
+    }
+}
+
+fn contains(lexemes: &[Lexeme], lexeme: &Lexeme) -> bool {
+    lexemes.iter().any(|l| {
+        l.get_begin() == lexeme.get_begin()
+            && l.get_length() == lexeme.get_length()
+            && l.get_lexeme_text() == lexeme.get_lexeme_text()
+    })
+}
+
+fn with_context(mut diff: TokenDiff, chars: &[char]) -> TokenDiff {
+    diff.context = context(&diff.span, chars);
+    diff
+}
+
+fn context(span: &Range<usize>, chars: &[char]) -> String {
+    let begin = span.start.saturating_sub(CONTEXT_CHARS);
+    let end = (span.end + CONTEXT_CHARS).min(chars.len());
+    chars[begin..end].iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let texts = ["中华人民共和国", "hello world"];
+        let diffs = compare(
+            &texts,
+            CompareConfig::new(TokenMode::INDEX),
+            CompareConfig::new(TokenMode::SEARCH),
+        );
+        assert_eq!(diffs.len(), 1);
+        let diff = &diffs[0];
+        assert_eq!(diff.text_index, 0);
+        assert_eq!(diff.span, 0..7);
+        assert_eq!(diff.context, "中华人民共和国");
+        assert!(diff.tokens_a.contains(&"中华".to_string()));
+        assert!(diff.tokens_b.is_empty());
+
+        let diffs = compare(
+            &texts,
+            CompareConfig::new(TokenMode::SEARCH),
+            CompareConfig::new(TokenMode::SEARCH),
+        );
+        assert!(diffs.is_empty());
+    }
+
+    #[test]
+    fn test_fallback_tokenize() {
+        let text = "iPhone 14售价, привет朋友😀";
+        let words = fallback_tokenize(text)
+            .into_iter()
+            .map(|span| {
+                text.chars()
+                    .skip(span.start)
+                    .take(span.len())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            words,
+            vec!["iPhone", "14", "售", "价", "привет", "朋", "友", "😀"]
+        );
+    }
+
+    #[test]
+    fn test_coverage_holes() {
+        let texts = ["中华人民共和国", "他说привет мир的", "hello"];
+        let holes = coverage_holes(&texts, CompareConfig::new(TokenMode::SEARCH));
+        assert_eq!(
+            holes,
+            vec![CoverageHole {
+                text_index: 1,
+                span: 2..12,
+                text: "привет мир".to_string(),
+                context: "他说привет мир的".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! 与 jieba-rs 调用方式相同的接口, 基于全局词典及本线程的分词器
+//!
+//! 与 jieba 相同, 输出的片段拼接后即为原文: 标点、空白等 IK 不输出的字符逐字作为片段,
+//! 停止词保留, 片段保持原文的大小写; 偏移以字符为单位
+use crate::core::ik_segmenter::{TokenMode, TokenizeOptions};
+use crate::with_thread_segmenter;
+
+/// 精确模式切分, 对应 Jieba::cut(text, false)
+pub fn cut(text: &str) -> Vec<&str> {
+    pieces(text, TokenMode::SEARCH)
+        .into_iter()
+        .map(|(word, _, _)| word)
+        .collect()
+}
+
+/// 搜索引擎模式切分, 对应 Jieba::cut_for_search(text, false)
+/// 长词及其中的短词都会输出, 片段之间可能重叠
+pub fn cut_for_search(text: &str) -> Vec<&str> {
+    pieces(text, TokenMode::INDEX)
+        .into_iter()
+        .map(|(word, _, _)| word)
+        .collect()
+}
+
+/// 精确模式切分并输出 (片段, 起始字符位置, 结束字符位置),
+/// 对应 Jieba::tokenize(text, TokenizeMode::Default, false)
+pub fn tokenize(text: &str) -> Vec<(&str, usize, usize)> {
+    pieces(text, TokenMode::SEARCH)
+}
+
+// 分词结果加上未被任何词元覆盖的字符, 按起始位置排列
+fn pieces(text: &str, mode: TokenMode) -> Vec<(&str, usize, usize)> {
+    let options = TokenizeOptions {
+        keep_stop_words: true,
+        ..TokenizeOptions::new(mode)
+    };
+    let lexemes = with_thread_segmenter(|ik| ik.tokenize_with(text, options));
+    let char_indices = text.char_indices().collect::<Vec<_>>();
+    let mut covered = vec![false; char_indices.len()];
+    let mut pieces = Vec::with_capacity(lexemes.len());
+    for lexeme in lexemes.iter() {
+        let (begin, end) = (lexeme.get_begin(), lexeme.get_end_position());
+        covered[begin..end].iter_mut().for_each(|c| *c = true);
+        pieces.push((&text[lexeme.byte_range()], begin, end));
+    }
+    for (i, (byte_offset, c)) in char_indices.into_iter().enumerate() {
+        if !covered[i] {
+            pieces.push((&text[byte_offset..byte_offset + c.len_utf8()], i, i + 1));
+        }
+    }
+    // 稳定排序, 起始位置相同的词元保持分词器的输出顺序
+    pieces.sort_by_key(|(_, begin, _)| *begin);
+    pieces
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cut() {
+        let text = "我来到北京清华大学, iPhone 12";
+        let words = cut(text);
+        assert_eq!(words.concat(), text);
+        assert_eq!(
+            words,
+            vec![
+                "我",
+                "来到",
+                "北京",
+                "清华大学",
+                ",",
+                " ",
+                "iPhone",
+                " ",
+                "12"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cut_for_search() {
+        let words = cut_for_search("清华大学");
+        assert_eq!(words[0], "清华大学");
+        assert!(words.contains(&"清华"));
+        assert!(words.contains(&"大学"));
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("他说的确实在理。"),
+            vec![
+                ("他", 0, 1),
+                ("说", 1, 2),
+                ("的确", 2, 4),
+                ("实", 4, 5),
+                ("在理", 5, 7),
+                ("。", 7, 8),
+            ]
+        );
+    }
+}
@@ -0,0 +1,2 @@
+//! 与其他分词库调用方式相同的接口, 便于迁移
+pub mod jieba;
@@ -1,8 +1,217 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use crate::config::default_config::{DefaultConfig, IK_CONFIG_NAME};
+use crate::core::char_util::DEFAULT_AUTO_SCRIPT_THRESHOLD;
+use crate::core::ik_segmenter::TokenMode;
+use crate::core::letter_segmentor::{ConnectorPolicy, IdentifierRule};
+use crate::core::lexeme::{LexemeType, DEFAULT_TYPE_PRIORITY};
+
+// 全局配置, 在 GLOBAL_DICT 首次使用时确定, 之后不可更改
+static GLOBAL_CONFIG: OnceCell<Arc<dyn Configuration>> = OnceCell::new();
+
+// 隐式确定全局配置时的错误, 见 config_error
+static CONFIG_ERROR: OnceCell<String> = OnceCell::new();
+
+/// 主词典、量词词典及停止词词典的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DictSource {
+    /// 读取配置中的词典文件; 启用 embedded-dict 时, GLOBAL_DICT 在主词典文件不存在时使用内置词典
+    #[default]
+    Auto,
+    /// 只读取词典文件, 文件不存在时报错
+    File,
+    /// 使用编译时嵌入的词典, 不读取任何词典文件(包括扩展词典), 需要启用 embedded-dict
+    Embedded,
+}
+
+impl TryFrom<&str> for DictSource {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "auto" => Ok(DictSource::Auto),
+            "file" => Ok(DictSource::File),
+            "embedded" => Ok(DictSource::Embedded),
+            _ => Err(format!(
+                "invalid dict source: {}, expect auto, file or embedded",
+                value
+            )),
+        }
+    }
+}
+
 // 配置管理类接口
 
-pub trait Configuration {
-    fn get_main_dictionary(&self) -> String;
-    fn get_quantifier_dictionary(&self) -> String;
-    fn get_ext_dictionaries(&self) -> Vec<String>;
-    fn get_ext_stop_word_dictionaries(&self) -> Vec<String>;
+// 词典路径使用 PathBuf, 以支持 windows 路径及非 UTF-8 路径
+pub trait Configuration: Send + Sync {
+    fn get_main_dictionary(&self) -> PathBuf;
+    fn get_quantifier_dictionary(&self) -> PathBuf;
+    fn get_ext_dictionaries(&self) -> Vec<PathBuf>;
+    fn get_ext_stop_word_dictionaries(&self) -> Vec<PathBuf>;
+
+    // 词典从文件读取还是使用内置词典
+    fn get_dict_source(&self) -> DictSource {
+        DictSource::default()
+    }
+
+    // 是否默认保留停止词, 作用于 IKSegmenter::tokenize 及 IkTokenizer, 可通过 with_keep_stop_words 覆盖
+    fn get_keep_stop_words(&self) -> bool {
+        false
+    }
+
+    // 字母数字词元中连接符的处理策略
+    fn get_connector_policy(&self) -> ConnectorPolicy {
+        ConnectorPolicy::default()
+    }
+
+    // 连接相邻中日韩文字片段的连接符, 如 '_'、'#', 被连接的片段整体输出为一个词元
+    fn get_cjk_connectors(&self) -> Vec<char> {
+        Vec::new()
+    }
+
+    // 是否将数字范围及分数(3-5, 1/2)作为一个数字词元
+    fn get_numeric_range(&self) -> bool {
+        false
+    }
+
+    // 启用的标识符识别规则(版本号、标准编号等)
+    fn get_identifier_rules(&self) -> Vec<IdentifierRule> {
+        Vec::new()
+    }
+
+    // 识别出的文件路径是否额外输出各级路径片段
+    fn get_path_segments(&self) -> bool {
+        false
+    }
+
+    // 是否按 AABB、ABAB、AA 规则识别词典中没有的叠词, 如 高高兴兴、研究研究、看看
+    fn get_reduplication(&self) -> bool {
+        false
+    }
+
+    // 是否按 公司、大学、路、区 等后缀识别词典中没有的机构名、地名
+    fn get_suffix_entities(&self) -> bool {
+        false
+    }
+
+    // INDEX 模式下是否也合并数量词, 如 一两 + 天 => 一两天, 合并后的词元与其组成词元一同输出
+    fn get_index_merge_quantifiers(&self) -> bool {
+        false
+    }
+
+    // 是否输出标点及特殊符号词元, 保证输入中的标点不会被丢弃
+    fn get_full_coverage(&self) -> bool {
+        false
+    }
+
+    // 是否检测文本的书写系统, 以字母数字为主时跳过中文相关的子分词器
+    fn get_auto_script(&self) -> bool {
+        false
+    }
+
+    // auto_script 判断文本以字母数字为主的占比阈值, 0.5~1.0, 为 1.0 时只跳过不含中日韩文字的文本
+    fn get_auto_script_threshold(&self) -> f32 {
+        DEFAULT_AUTO_SCRIPT_THRESHOLD
+    }
+
+    // IkTokenizer 是否在分词前去掉零宽字符及双向文本控制符
+    fn get_sanitize_controls(&self) -> bool {
+        false
+    }
+
+    // 加载及运行时增删词条时是否正规化词条(全角转半角、英文转小写), 与分词的输入保持一致
+    fn get_regularize_dict(&self) -> bool {
+        true
+    }
+
+    // 编译词典缓存的路径, 设置后 GLOBAL_DICT 优先从缓存加载
+    fn get_compiled_dictionary(&self) -> Option<PathBuf> {
+        None
+    }
+
+    // 标点及特殊符号词典, 为空时使用内置的符号集合
+    fn get_special_chars_dictionary(&self) -> Option<PathBuf> {
+        None
+    }
+
+    // 同义词词典, 由 SynonymMap::from_config 加载
+    fn get_synonym_dictionary(&self) -> Option<PathBuf> {
+        None
+    }
+
+    // 远程扩展词典的 URL, 由 watch_remote_dicts 轮询
+    fn get_remote_ext_dictionaries(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // 远程扩展停止词词典的 URL
+    fn get_remote_ext_stop_word_dictionaries(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // IkTokenizer::default 使用的分词模式
+    fn get_default_mode(&self) -> TokenMode {
+        TokenMode::default()
+    }
+
+    // 同一位置、相同长度的候选词元的类型优先级, 靠前的优先
+    fn get_type_priority(&self) -> Vec<LexemeType> {
+        DEFAULT_TYPE_PRIORITY.to_vec()
+    }
+}
+
+/// 设置全局配置, 必须在 GLOBAL_DICT/GLOBAL_IK 首次使用前调用
+/// 若全局配置已经确定(已调用过或已开始分词), 则返回错误
+pub fn init_global_config(cfg: Arc<dyn Configuration>) -> Result<(), String> {
+    GLOBAL_CONFIG
+        .set(cfg)
+        .map_err(|_| "global configuration already initialized".to_string())
+}
+
+/// 按以下顺序确定配置:
+/// 1. 环境变量 IK_CONFIG 指定的配置文件
+/// 2. 当前工作目录下的 ik.yml
+/// 3. 以上文件都不存在时使用内置默认配置
+///
+/// 之后再用 IK_* 环境变量覆盖, 环境变量优先于配置文件, 见 DefaultConfig::with_env
+/// 配置文件无法解析或环境变量的值无效时返回错误, 需要在启动时报错可以
+/// `init_with_config(load_default_config()?)`
+pub fn load_default_config() -> Result<DefaultConfig, String> {
+    let config = match std::env::var_os("IK_CONFIG") {
+        Some(path) => DefaultConfig::load(path)?,
+        None => {
+            let conf_file_path = Path::new(IK_CONFIG_NAME);
+            if conf_file_path.is_file() {
+                DefaultConfig::load(conf_file_path)?
+            } else {
+                DefaultConfig::default()
+            }
+        }
+    };
+    config.with_env()
+}
+
+/// 获取全局配置, 未通过 init_global_config 设置时使用 load_default_config
+/// load_default_config 出错时使用内置默认配置, 错误见 config_error, status 报告为 Degraded
+pub fn global_config() -> Arc<dyn Configuration> {
+    GLOBAL_CONFIG
+        .get_or_init(|| {
+            let config = load_default_config().unwrap_or_else(|error| {
+                log::error!("invalid configuration, using built-in defaults: {}", error);
+                let _ = CONFIG_ERROR.set(error);
+                DefaultConfig::default()
+            });
+            Arc::new(config)
+        })
+        .clone()
+}
+
+/// 隐式确定全局配置时 load_default_config 的错误
+pub fn config_error() -> Option<&'static str> {
+    CONFIG_ERROR.get().map(String::as_str)
 }
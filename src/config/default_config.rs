@@ -1,84 +1,601 @@
 extern crate serde;
 extern crate serde_yaml;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
 use std::fs::File;
 use std::io::{BufReader, Read};
 use std::marker::{Send, Sync};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::vec::Vec;
 
 use serde::{Deserialize, Serialize};
 
-use crate::config::configuration::Configuration;
+use crate::config::configuration::{Configuration, DictSource};
+use crate::core::char_util::DEFAULT_AUTO_SCRIPT_THRESHOLD;
+use crate::core::ik_segmenter::TokenMode;
+use crate::core::letter_segmentor::{ConnectorPolicy, IdentifierRule};
+use crate::core::lexeme::{LexemeType, DEFAULT_TYPE_PRIORITY};
 
 // 分词器配置文件路径
 pub const IK_CONFIG_NAME: &str = "ik.yml";
 
+fn default_true() -> bool {
+    true
+}
+
+fn default_auto_script_threshold() -> f32 {
+    DEFAULT_AUTO_SCRIPT_THRESHOLD
+}
+
+// 配置文件中可以出现但已不再使用的键
+const IGNORED_KEYS: [&str; 1] = ["use_smart"];
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DefaultConfig {
-    main_dict: String,
-    quantifier_dict: String,
-    stop_word_dict: String,
-    ext_dicts: Vec<String>,
-    ext_stop_word_dicts: Vec<String>,
+    main_dict: PathBuf,
+    quantifier_dict: PathBuf,
+    stop_word_dict: PathBuf,
+    ext_dicts: Vec<PathBuf>,
+    ext_stop_word_dicts: Vec<PathBuf>,
+    #[serde(default)]
+    dict_source: DictSource,
+    #[serde(default)]
+    keep_stop_words: bool,
+    #[serde(default)]
+    connector_policy: ConnectorPolicy,
+    #[serde(default)]
+    cjk_connectors: Vec<char>,
+    #[serde(default)]
+    numeric_range: bool,
+    #[serde(default)]
+    identifier_rules: Vec<IdentifierRule>,
+    #[serde(default)]
+    path_segments: bool,
+    #[serde(default)]
+    reduplication: bool,
+    #[serde(default)]
+    suffix_entities: bool,
+    #[serde(default)]
+    index_merge_quantifiers: bool,
+    #[serde(default)]
+    full_coverage: bool,
+    #[serde(default)]
+    auto_script: bool,
+    #[serde(default = "default_auto_script_threshold")]
+    auto_script_threshold: f32,
+    #[serde(default)]
+    sanitize_controls: bool,
+    #[serde(default = "default_true")]
+    regularize_dict: bool,
+    #[serde(default)]
+    special_chars_dict: Option<PathBuf>,
+    #[serde(default)]
+    compiled_dict: Option<PathBuf>,
+    #[serde(default)]
+    synonyms_dict: Option<PathBuf>,
+    #[serde(default)]
+    type_priority: Option<Vec<LexemeType>>,
+    #[serde(default)]
+    remote_ext_dict: Vec<String>,
+    #[serde(default)]
+    remote_ext_stopwords: Vec<String>,
+    // 默认分词模式, ik_max 或 ik_smart
+    #[serde(default)]
+    default_mode: Option<String>,
+    // 配置文件所在的目录, 相对路径相对于该目录; 不是从文件读取时为 None, 相对于当前工作目录
+    #[serde(skip)]
+    base_dir: Option<PathBuf>,
+}
+
+/// 内置的默认配置, 与仓库中的 ik.yml 相同
+impl Default for DefaultConfig {
+    fn default() -> Self {
+        DefaultConfig {
+            main_dict: PathBuf::from("dict/main2012.dic"),
+            quantifier_dict: PathBuf::from("dict/quantifier.dic"),
+            stop_word_dict: PathBuf::from("dict/stopword.dic"),
+            ext_dicts: vec![PathBuf::from("dict/ext_dict/ext.dic")],
+            ext_stop_word_dicts: vec![PathBuf::from("dict/ext_stop_word/stop.dic")],
+            dict_source: DictSource::default(),
+            keep_stop_words: false,
+            connector_policy: ConnectorPolicy::default(),
+            cjk_connectors: Vec::new(),
+            numeric_range: false,
+            identifier_rules: Vec::new(),
+            path_segments: false,
+            reduplication: false,
+            suffix_entities: false,
+            index_merge_quantifiers: false,
+            full_coverage: false,
+            auto_script: false,
+            auto_script_threshold: DEFAULT_AUTO_SCRIPT_THRESHOLD,
+            sanitize_controls: false,
+            regularize_dict: true,
+            special_chars_dict: None,
+            compiled_dict: None,
+            synonyms_dict: None,
+            type_priority: None,
+            remote_ext_dict: Vec::new(),
+            remote_ext_stopwords: Vec::new(),
+            default_mode: None,
+            base_dir: None,
+        }
+    }
 }
 
 unsafe impl Sync for DefaultConfig {}
 unsafe impl Send for DefaultConfig {}
 
 impl DefaultConfig {
+    /// 读取配置文件, 失败时 panic, 需要处理错误时使用 load
     pub fn new<P: AsRef<Path>>(conf_file_path: P) -> DefaultConfig {
-        let file = File::open(conf_file_path).expect("open file error!");
+        DefaultConfig::load(conf_file_path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 读取配置文件, 错误信息包含文件路径及出错的行列
+    /// 未知的键只记录警告, 完整的检查见 check
+    pub fn load<P: AsRef<Path>>(conf_file_path: P) -> Result<DefaultConfig, String> {
+        let (config, unknown_keys) = DefaultConfig::parse(conf_file_path.as_ref())?;
+        for key in unknown_keys {
+            log::warn!(
+                "unknown key {} in {}",
+                key,
+                conf_file_path.as_ref().display()
+            );
+        }
+        Ok(config)
+    }
+
+    /// 读取并检查配置文件: 未知的键、不存在的词典文件
+    /// 无法读取或解析时返回 Err
+    pub fn check<P: AsRef<Path>>(conf_file_path: P) -> Result<ConfigCheck, String> {
+        let (config, unknown_keys) = DefaultConfig::parse(conf_file_path.as_ref())?;
+        let mut issues = unknown_keys
+            .into_iter()
+            .map(ConfigIssue::UnknownKey)
+            .collect::<Vec<_>>();
+        let mut files = Vec::new();
+        // 使用内置词典时不读取词典文件
+        if config.get_dict_source() != DictSource::Embedded {
+            files.push(("main_dict", config.get_main_dictionary()));
+            files.push(("quantifier_dict", config.get_quantifier_dictionary()));
+            files.extend(
+                config
+                    .get_ext_dictionaries()
+                    .into_iter()
+                    .map(|path| ("ext_dicts", path)),
+            );
+            // 第一个是 stop_word_dict
+            for (i, path) in config
+                .get_ext_stop_word_dictionaries()
+                .into_iter()
+                .enumerate()
+            {
+                let key = if i == 0 {
+                    "stop_word_dict"
+                } else {
+                    "ext_stop_word_dicts"
+                };
+                files.push((key, path));
+            }
+        }
+        files.extend(
+            config
+                .get_special_chars_dictionary()
+                .map(|path| ("special_chars_dict", path)),
+        );
+        files.extend(
+            config
+                .get_synonym_dictionary()
+                .map(|path| ("synonyms_dict", path)),
+        );
+        for (key, path) in files {
+            if !path.is_file() {
+                issues.push(ConfigIssue::MissingFile {
+                    key: key.to_string(),
+                    path,
+                });
+            }
+        }
+        Ok(ConfigCheck { config, issues })
+    }
+
+    // 解析配置文件, 同时返回未知的键
+    fn parse(path: &Path) -> Result<(DefaultConfig, Vec<String>), String> {
+        let file = File::open(path).map_err(|e| format!("open {} error: {}", path.display(), e))?;
         let mut reader = BufReader::new(file);
         let mut yaml_str: String = "".to_string();
         reader
             .read_to_string(&mut yaml_str)
-            .expect("read ik.yaml error!");
-        let config: DefaultConfig =
-            serde_yaml::from_str(yaml_str.as_str()).expect("read ik.yml error!");
-        config
+            .map_err(|e| format!("read {} error: {}", path.display(), e))?;
+        // 直接从文本反序列化, 错误信息中带有行列
+        let mut config: DefaultConfig = serde_yaml::from_str(&yaml_str)
+            .map_err(|e| format!("invalid {}: {}", path.display(), e))?;
+        config.base_dir = path.parent().map(Path::to_path_buf);
+        let value: serde_yaml::Value = serde_yaml::from_str(&yaml_str)
+            .map_err(|e| format!("invalid {}: {}", path.display(), e))?;
+        // 已知的键取自序列化后的配置, 新增字段时无需维护列表
+        let known = serde_yaml::to_value(&config).map_err(|e| e.to_string())?;
+        let mut unknown_keys = Vec::new();
+        if let (Some(mapping), Some(known)) = (value.as_mapping(), known.as_mapping()) {
+            for key in mapping.keys() {
+                let name = key.as_str().map(String::from).unwrap_or_else(|| {
+                    serde_yaml::to_string(key)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string()
+                });
+                if !known.contains_key(key) && !IGNORED_KEYS.contains(&name.as_str()) {
+                    unknown_keys.push(name);
+                }
+            }
+        }
+        Ok((config, unknown_keys))
+    }
+}
+
+impl DefaultConfig {
+    /// 仅由环境变量构造, 未设置的项使用内置默认配置, 用于不便附带 ik.yml 的容器部署
+    pub fn from_env() -> Result<DefaultConfig, String> {
+        DefaultConfig::default().with_env()
+    }
+
+    /// 用环境变量覆盖配置项, 未设置的环境变量不影响对应的配置项
+    ///
+    /// - IK_MAIN_DICT / IK_QUANTIFIER_DICT / IK_STOP_WORD_DICT / IK_COMPILED_DICT: 词典路径
+    /// - IK_EXT_DICTS / IK_EXT_STOP_WORD_DICTS: 路径列表, unix 下以 ':' 分隔, windows 下以 ';' 分隔
+    /// - IK_REMOTE_DICT_URL / IK_REMOTE_STOPWORDS_URL: URL 列表, 以 ',' 分隔
+    /// - IK_MODE_DEFAULT: ik_max 或 ik_smart
+    /// - IK_DICT_SOURCE: auto、file 或 embedded
+    /// - IK_FULL_COVERAGE / IK_AUTO_SCRIPT / IK_NUMERIC_RANGE / IK_PATH_SEGMENTS /
+    ///   IK_REDUPLICATION / IK_SUFFIX_ENTITIES / IK_INDEX_MERGE_QUANTIFIERS /
+    ///   IK_SANITIZE_CONTROLS / IK_REGULARIZE_DICT / IK_KEEP_STOP_WORDS:
+    ///   true/false 或 1/0
+    /// - IK_AUTO_SCRIPT_THRESHOLD: 0.5~1.0 之间的小数
+    ///
+    /// 相对路径与配置文件中的路径一样, 相对于配置文件所在的目录, 没有配置文件时相对于当前工作目录
+    pub fn with_env(self) -> Result<DefaultConfig, String> {
+        self.with_vars(|key| std::env::var_os(key))
+    }
+
+    // 用 var_os 提供的变量覆盖配置项, 便于测试
+    // 路径可以不是 UTF-8, 其余的值必须是 UTF-8
+    fn with_vars<F: Fn(&str) -> Option<OsString>>(
+        mut self,
+        var_os: F,
+    ) -> Result<DefaultConfig, String> {
+        let var = |key: &str| -> Result<Option<String>, String> {
+            var_os(key)
+                .map(|value| {
+                    value
+                        .into_string()
+                        .map_err(|value| format!("{}: invalid unicode {:?}", key, value))
+                })
+                .transpose()
+        };
+        if let Some(path) = var_os("IK_MAIN_DICT") {
+            self.main_dict = PathBuf::from(path);
+        }
+        if let Some(path) = var_os("IK_QUANTIFIER_DICT") {
+            self.quantifier_dict = PathBuf::from(path);
+        }
+        if let Some(path) = var_os("IK_STOP_WORD_DICT") {
+            self.stop_word_dict = PathBuf::from(path);
+        }
+        if let Some(path) = var_os("IK_COMPILED_DICT") {
+            self.compiled_dict = Some(PathBuf::from(path));
+        }
+        if let Some(paths) = var_os("IK_EXT_DICTS") {
+            self.ext_dicts = split_paths(&paths);
+        }
+        if let Some(paths) = var_os("IK_EXT_STOP_WORD_DICTS") {
+            self.ext_stop_word_dicts = split_paths(&paths);
+        }
+        if let Some(urls) = var("IK_REMOTE_DICT_URL")? {
+            self.remote_ext_dict = split_list(&urls);
+        }
+        if let Some(urls) = var("IK_REMOTE_STOPWORDS_URL")? {
+            self.remote_ext_stopwords = split_list(&urls);
+        }
+        if let Some(mode) = var("IK_MODE_DEFAULT")? {
+            TokenMode::try_from(mode.as_str()).map_err(|e| format!("IK_MODE_DEFAULT: {}", e))?;
+            self.default_mode = Some(mode);
+        }
+        if let Some(source) = var("IK_DICT_SOURCE")? {
+            self.dict_source = DictSource::try_from(source.as_str())
+                .map_err(|e| format!("IK_DICT_SOURCE: {}", e))?;
+        }
+        if let Some(value) = var("IK_AUTO_SCRIPT_THRESHOLD")? {
+            self.auto_script_threshold = value
+                .parse::<f32>()
+                .ok()
+                .filter(|threshold| (0.5..=1.0).contains(threshold))
+                .ok_or_else(|| {
+                    format!(
+                        "IK_AUTO_SCRIPT_THRESHOLD: expect a number between 0.5 and 1.0, found {:?}",
+                        value
+                    )
+                })?;
+        }
+        let flags: [(&str, &mut bool); 10] = [
+            ("IK_FULL_COVERAGE", &mut self.full_coverage),
+            ("IK_AUTO_SCRIPT", &mut self.auto_script),
+            ("IK_NUMERIC_RANGE", &mut self.numeric_range),
+            ("IK_PATH_SEGMENTS", &mut self.path_segments),
+            ("IK_REDUPLICATION", &mut self.reduplication),
+            ("IK_SUFFIX_ENTITIES", &mut self.suffix_entities),
+            (
+                "IK_INDEX_MERGE_QUANTIFIERS",
+                &mut self.index_merge_quantifiers,
+            ),
+            ("IK_SANITIZE_CONTROLS", &mut self.sanitize_controls),
+            ("IK_REGULARIZE_DICT", &mut self.regularize_dict),
+            ("IK_KEEP_STOP_WORDS", &mut self.keep_stop_words),
+        ];
+        for (key, flag) in flags {
+            if let Some(value) = var(key)? {
+                *flag = parse_bool(&value).ok_or_else(|| {
+                    format!("{}: expect true/false or 1/0, found {:?}", key, value)
+                })?;
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl DefaultConfig {
+    // 相对路径相对于配置文件所在的目录, 绝对路径保持不变
+    fn resolve(&self, path: &Path) -> PathBuf {
+        match self.base_dir.as_deref() {
+            Some(dir) => dir.join(path),
+            None => path.to_path_buf(),
+        }
+    }
+}
+
+fn split_paths(paths: &OsStr) -> Vec<PathBuf> {
+    std::env::split_paths(paths)
+        .filter(|path| !path.as_os_str().is_empty())
+        .collect()
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// 配置检查中发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigIssue {
+    // 配置文件中的未知键, 多为拼写错误
+    UnknownKey(String),
+    // 词典文件不存在, path 为解析后的路径
+    MissingFile { key: String, path: PathBuf },
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigIssue::UnknownKey(key) => write!(f, "unknown key {}", key),
+            ConfigIssue::MissingFile { key, path } => {
+                write!(f, "{}: file {} not found", key, path.display())
+            }
+        }
+    }
+}
+
+/// DefaultConfig::check 的结果, Display 输出解析后的有效配置及发现的问题
+#[derive(Debug)]
+pub struct ConfigCheck {
+    pub config: DefaultConfig,
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigCheck {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ConfigCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cfg = &self.config;
+        writeln!(f, "main_dict: {}", cfg.get_main_dictionary().display())?;
+        writeln!(
+            f,
+            "quantifier_dict: {}",
+            cfg.get_quantifier_dictionary().display()
+        )?;
+        writeln!(f, "ext_dicts: {:?}", cfg.get_ext_dictionaries())?;
+        writeln!(
+            f,
+            "stop_word_dicts: {:?}",
+            cfg.get_ext_stop_word_dictionaries()
+        )?;
+        writeln!(
+            f,
+            "special_chars_dict: {:?}",
+            cfg.get_special_chars_dictionary()
+        )?;
+        writeln!(f, "compiled_dict: {:?}", cfg.get_compiled_dictionary())?;
+        writeln!(f, "synonyms_dict: {:?}", cfg.get_synonym_dictionary())?;
+        writeln!(f, "dict_source: {:?}", cfg.get_dict_source())?;
+        writeln!(f, "keep_stop_words: {}", cfg.get_keep_stop_words())?;
+        writeln!(f, "connector_policy: {:?}", cfg.get_connector_policy())?;
+        writeln!(f, "cjk_connectors: {:?}", cfg.get_cjk_connectors())?;
+        writeln!(f, "numeric_range: {}", cfg.get_numeric_range())?;
+        writeln!(f, "identifier_rules: {:?}", cfg.get_identifier_rules())?;
+        writeln!(f, "path_segments: {}", cfg.get_path_segments())?;
+        writeln!(f, "reduplication: {}", cfg.get_reduplication())?;
+        writeln!(f, "suffix_entities: {}", cfg.get_suffix_entities())?;
+        writeln!(
+            f,
+            "index_merge_quantifiers: {}",
+            cfg.get_index_merge_quantifiers()
+        )?;
+        writeln!(f, "full_coverage: {}", cfg.get_full_coverage())?;
+        writeln!(f, "auto_script: {}", cfg.get_auto_script())?;
+        writeln!(
+            f,
+            "auto_script_threshold: {}",
+            cfg.get_auto_script_threshold()
+        )?;
+        writeln!(f, "sanitize_controls: {}", cfg.get_sanitize_controls())?;
+        writeln!(f, "regularize_dict: {}", cfg.get_regularize_dict())?;
+        writeln!(f, "type_priority: {:?}", cfg.get_type_priority())?;
+        writeln!(f, "default_mode: {:?}", cfg.get_default_mode())?;
+        writeln!(
+            f,
+            "remote_ext_dict: {:?}",
+            cfg.get_remote_ext_dictionaries()
+        )?;
+        writeln!(
+            f,
+            "remote_ext_stopwords: {:?}",
+            cfg.get_remote_ext_stop_word_dictionaries()
+        )?;
+        for issue in self.issues.iter() {
+            writeln!(f, "error: {}", issue)?;
+        }
+        Ok(())
     }
 }
 
 /// Configuration 默认实现
 impl Configuration for DefaultConfig {
-    fn get_main_dictionary(&self) -> String {
-        let mut root_path = env!("CARGO_MANIFEST_DIR").to_string();
-        root_path.push('/');
-        root_path.push_str(self.main_dict.as_str());
-        root_path
+    fn get_main_dictionary(&self) -> PathBuf {
+        self.resolve(&self.main_dict)
     }
 
-    fn get_quantifier_dictionary(&self) -> String {
-        let mut root_path = env!("CARGO_MANIFEST_DIR").to_string();
-        root_path.push('/');
-        root_path.push_str(self.quantifier_dict.as_str());
-        root_path
+    fn get_quantifier_dictionary(&self) -> PathBuf {
+        self.resolve(&self.quantifier_dict)
     }
 
-    fn get_ext_dictionaries(&self) -> Vec<String> {
-        let mut dicts = Vec::new();
-        for dict in &self.ext_dicts {
-            let mut root_path = env!("CARGO_MANIFEST_DIR").to_string();
-            root_path.push('/');
-            root_path.push_str(dict);
-            dicts.push(root_path);
-        }
+    fn get_ext_dictionaries(&self) -> Vec<PathBuf> {
+        self.ext_dicts
+            .iter()
+            .map(|dict| self.resolve(dict))
+            .collect()
+    }
+
+    fn get_ext_stop_word_dictionaries(&self) -> Vec<PathBuf> {
+        let mut dicts = vec![self.resolve(&self.stop_word_dict)];
+        dicts.extend(
+            self.ext_stop_word_dicts
+                .iter()
+                .map(|dict| self.resolve(dict)),
+        );
         dicts
     }
 
-    fn get_ext_stop_word_dictionaries(&self) -> Vec<String> {
-        let mut dicts = Vec::new();
-        let mut stop_word_full = env!("CARGO_MANIFEST_DIR").to_string();
-        stop_word_full.push('/');
-        stop_word_full.push_str(&self.stop_word_dict);
-        dicts.push(stop_word_full);
-        for dict in &self.ext_stop_word_dicts {
-            let mut root_path = env!("CARGO_MANIFEST_DIR").to_string();
-            root_path.push('/');
-            root_path.push_str(dict);
-            dicts.push(root_path);
+    fn get_dict_source(&self) -> DictSource {
+        self.dict_source
+    }
+
+    fn get_keep_stop_words(&self) -> bool {
+        self.keep_stop_words
+    }
+
+    fn get_connector_policy(&self) -> ConnectorPolicy {
+        self.connector_policy
+    }
+
+    fn get_cjk_connectors(&self) -> Vec<char> {
+        self.cjk_connectors.clone()
+    }
+
+    fn get_numeric_range(&self) -> bool {
+        self.numeric_range
+    }
+
+    fn get_identifier_rules(&self) -> Vec<IdentifierRule> {
+        self.identifier_rules.clone()
+    }
+
+    fn get_path_segments(&self) -> bool {
+        self.path_segments
+    }
+
+    fn get_reduplication(&self) -> bool {
+        self.reduplication
+    }
+
+    fn get_suffix_entities(&self) -> bool {
+        self.suffix_entities
+    }
+
+    fn get_index_merge_quantifiers(&self) -> bool {
+        self.index_merge_quantifiers
+    }
+
+    fn get_full_coverage(&self) -> bool {
+        self.full_coverage
+    }
+
+    fn get_auto_script(&self) -> bool {
+        self.auto_script
+    }
+
+    fn get_auto_script_threshold(&self) -> f32 {
+        self.auto_script_threshold
+    }
+
+    fn get_sanitize_controls(&self) -> bool {
+        self.sanitize_controls
+    }
+
+    fn get_regularize_dict(&self) -> bool {
+        self.regularize_dict
+    }
+
+    fn get_type_priority(&self) -> Vec<LexemeType> {
+        self.type_priority
+            .clone()
+            .unwrap_or_else(|| DEFAULT_TYPE_PRIORITY.to_vec())
+    }
+
+    fn get_remote_ext_dictionaries(&self) -> Vec<String> {
+        self.remote_ext_dict.clone()
+    }
+
+    fn get_remote_ext_stop_word_dictionaries(&self) -> Vec<String> {
+        self.remote_ext_stopwords.clone()
+    }
+
+    fn get_compiled_dictionary(&self) -> Option<PathBuf> {
+        self.compiled_dict.as_deref().map(|path| self.resolve(path))
+    }
+
+    fn get_special_chars_dictionary(&self) -> Option<PathBuf> {
+        self.special_chars_dict
+            .as_deref()
+            .map(|path| self.resolve(path))
+    }
+
+    fn get_synonym_dictionary(&self) -> Option<PathBuf> {
+        self.synonyms_dict.as_deref().map(|path| self.resolve(path))
+    }
+
+    fn get_default_mode(&self) -> TokenMode {
+        match self.default_mode.as_deref().map(TokenMode::try_from) {
+            Some(Ok(mode)) => mode,
+            Some(Err(error)) => {
+                log::warn!("invalid default_mode: {}", error);
+                TokenMode::default()
+            }
+            None => TokenMode::default(),
         }
-        dicts
     }
 }
 
@@ -92,9 +609,167 @@ mod test {
         let conf_file_path = Path::new(root_path).join(IK_CONFIG_NAME);
         let config = DefaultConfig::new(conf_file_path);
         println!("{:?}", config);
-        println!("{}", config.get_main_dictionary());
-        println!("{}", config.get_quantifier_dictionary());
+        println!("{}", config.get_main_dictionary().display());
+        println!("{}", config.get_quantifier_dictionary().display());
         println!("{:?}", config.get_ext_dictionaries());
         println!("{:?}", config.get_ext_stop_word_dictionaries());
     }
+
+    #[test]
+    fn test_env() {
+        use std::collections::HashMap;
+
+        // 使用当前平台的绝对路径及路径分隔符
+        let main_dict = std::env::temp_dir().join("main.dic");
+        let ext_dicts = std::env::join_paths([Path::new("dict/a.dic"), &main_dict]).unwrap();
+        let vars = HashMap::from([
+            ("IK_MAIN_DICT", main_dict.clone().into_os_string()),
+            ("IK_EXT_DICTS", ext_dicts),
+            (
+                "IK_REMOTE_DICT_URL",
+                "http://h/a.dic, http://h/b.dic".into(),
+            ),
+            ("IK_MODE_DEFAULT", "ik_smart".into()),
+            ("IK_FULL_COVERAGE", "1".into()),
+            ("IK_AUTO_SCRIPT_THRESHOLD", "0.75".into()),
+            ("IK_DICT_SOURCE", "embedded".into()),
+            ("IK_KEEP_STOP_WORDS", "true".into()),
+        ]);
+        let config = DefaultConfig::default()
+            .with_vars(|key| vars.get(key).cloned())
+            .unwrap();
+        assert_eq!(config.get_main_dictionary(), main_dict);
+        // 没有配置文件, 相对路径相对于当前工作目录
+        assert_eq!(
+            config.get_quantifier_dictionary(),
+            Path::new("dict/quantifier.dic")
+        );
+        assert_eq!(
+            config.get_ext_dictionaries(),
+            vec![PathBuf::from("dict/a.dic"), main_dict]
+        );
+        assert_eq!(
+            config.get_remote_ext_dictionaries(),
+            vec!["http://h/a.dic", "http://h/b.dic"]
+        );
+        assert_eq!(config.get_default_mode(), TokenMode::SEARCH);
+        assert!(config.get_full_coverage());
+        assert!(!config.get_auto_script());
+        assert_eq!(config.get_auto_script_threshold(), 0.75);
+        assert_eq!(config.get_dict_source(), DictSource::Embedded);
+        assert!(config.get_keep_stop_words());
+
+        let error = DefaultConfig::default()
+            .with_vars(|key| (key == "IK_MODE_DEFAULT").then(|| "smart".into()))
+            .unwrap_err();
+        assert!(error.starts_with("IK_MODE_DEFAULT"), "{}", error);
+        assert!(DefaultConfig::default()
+            .with_vars(|key| (key == "IK_AUTO_SCRIPT").then(|| "yes".into()))
+            .is_err());
+        assert!(DefaultConfig::default()
+            .with_vars(|key| (key == "IK_AUTO_SCRIPT_THRESHOLD").then(|| "0.2".into()))
+            .is_err());
+        assert!(DefaultConfig::default()
+            .with_vars(|key| (key == "IK_DICT_SOURCE").then(|| "remote".into()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        // 相对路径相对于配置文件所在的目录, 与编译时的目录无关
+        let dir = std::env::temp_dir().join(format!("ik-resolve-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let absolute = std::env::temp_dir().join("ext.dic");
+        std::fs::write(
+            dir.join(IK_CONFIG_NAME),
+            format!(
+                "main_dict: dict/main2012.dic\nquantifier_dict: q.dic\nstop_word_dict: s.dic\n\
+                 ext_dicts: [{:?}]\next_stop_word_dicts: []\n",
+                absolute
+            ),
+        )
+        .unwrap();
+        let config = DefaultConfig::load(dir.join(IK_CONFIG_NAME)).unwrap();
+        assert_eq!(
+            config.get_main_dictionary(),
+            dir.join("dict").join("main2012.dic")
+        );
+        assert_eq!(config.get_ext_dictionaries(), vec![absolute]);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let config = DefaultConfig::load(root.join(IK_CONFIG_NAME)).unwrap();
+        assert!(config.get_main_dictionary().is_file());
+        assert_eq!(
+            DefaultConfig::default().get_main_dictionary(),
+            Path::new("dict/main2012.dic")
+        );
+        assert_eq!(
+            split_paths(OsStr::new("")),
+            Vec::<PathBuf>::new(),
+            "empty list"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_path() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = OsStr::from_bytes(b"/data/\xffmain.dic");
+        let config = DefaultConfig::default()
+            .with_vars(|key| (key == "IK_MAIN_DICT").then(|| path.to_os_string()))
+            .unwrap();
+        assert_eq!(config.get_main_dictionary().as_os_str(), path);
+        // 非路径的值必须是 UTF-8
+        assert!(DefaultConfig::default()
+            .with_vars(|key| (key == "IK_MODE_DEFAULT").then(|| path.to_os_string()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let root_path = env!("CARGO_MANIFEST_DIR");
+        let check = DefaultConfig::check(Path::new(root_path).join(IK_CONFIG_NAME)).unwrap();
+        assert!(check.is_ok(), "{}", check);
+        assert!(check.to_string().contains("main2012.dic"));
+
+        let path = std::env::temp_dir().join(format!("ik-check-{}.yml", std::process::id()));
+        let dict = |name: &str| Path::new(root_path).join("dict").join(name);
+        std::fs::write(
+            &path,
+            format!(
+                "main_dict: {:?}\nquantifier_dict: {:?}\nstop_word_dict: {:?}\n\
+                 ext_dicts: [dict/missing.dic]\next_stop_word_dicts: []\n\
+                 use_smart: true\nfull_coverag: true\n",
+                dict("main2012.dic"),
+                dict("quantifier.dic"),
+                dict("stopword.dic")
+            ),
+        )
+        .unwrap();
+        let check = DefaultConfig::check(&path).unwrap();
+        assert_eq!(check.issues.len(), 2, "{}", check);
+        assert_eq!(
+            check.issues[0],
+            ConfigIssue::UnknownKey("full_coverag".to_string())
+        );
+        assert!(matches!(
+            &check.issues[1],
+            ConfigIssue::MissingFile { key, path } if key == "ext_dicts" && path.ends_with("dict/missing.dic")
+        ));
+        assert!(DefaultConfig::load(&path).is_ok());
+
+        std::fs::write(&path, "main_dict: [\n").unwrap();
+        let error = DefaultConfig::load(&path).unwrap_err();
+        assert!(error.contains("ik-check-"), "{}", error);
+        std::fs::write(&path, "main_dict: a\nquantifier_dict: b\n").unwrap();
+        let error = DefaultConfig::load(&path).unwrap_err();
+        assert!(error.contains("missing field"), "{}", error);
+        std::fs::write(&path, "main_dict: a\nquantifier_dict: [b]\n").unwrap();
+        let error = DefaultConfig::load(&path).unwrap_err();
+        assert!(error.contains("line 2"), "{}", error);
+        std::fs::remove_file(&path).unwrap();
+        assert!(DefaultConfig::load(&path).unwrap_err().starts_with("open "));
+    }
 }
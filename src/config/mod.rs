@@ -1,2 +1,2 @@
-pub(crate) mod configuration;
-pub(crate) mod default_config;
+pub mod configuration;
+pub mod default_config;
@@ -0,0 +1,65 @@
+use crate::core::lexeme::Lexeme;
+
+/// SEARCH 模式的一个词元与 INDEX 模式中相应词元的对应关系
+#[derive(Debug, Clone)]
+pub struct Alignment {
+    // SEARCH 模式的词元
+    pub parent: Lexeme,
+    // 完全落在 parent 区间内的 INDEX 模式词元
+    pub children: Vec<Lexeme>,
+    // 与 parent 区间相交但跨越其边界的 INDEX 模式词元
+    pub crossing: Vec<Lexeme>,
+}
+
+/// 将 SEARCH 模式的词元与覆盖相同区间的 INDEX 模式词元对齐
+/// 两组词元都需按起始位置排列, 即 tokenize 的输出顺序
+pub fn align(search: &[Lexeme], index: &[Lexeme]) -> Vec<Alignment> {
+    let mut alignments = Vec::with_capacity(search.len());
+    for parent in search {
+        let begin = parent.get_begin_position();
+        let end = parent.get_end_position();
+        let mut children = Vec::new();
+        let mut crossing = Vec::new();
+        for l in index {
+            if l.get_begin_position() >= end || l.get_end_position() <= begin {
+                continue;
+            }
+            if l.get_begin_position() >= begin && l.get_end_position() <= end {
+                children.push(l.clone());
+            } else {
+                crossing.push(l.clone());
+            }
+        }
+        alignments.push(Alignment {
+            parent: parent.clone(),
+            children,
+            crossing,
+        });
+    }
+    alignments
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::lexeme::LexemeType;
+
+    #[test]
+    fn test_align() {
+        // 中华人民共和国 / 中华 人民 共和国 共和 国
+        let search = vec![Lexeme::new(0, 0, 7, LexemeType::CNWORD)];
+        let index = vec![
+            Lexeme::new(0, 0, 7, LexemeType::CNWORD),
+            Lexeme::new(0, 0, 2, LexemeType::CNWORD),
+            Lexeme::new(0, 2, 2, LexemeType::CNWORD),
+            Lexeme::new(0, 4, 3, LexemeType::CNWORD),
+            Lexeme::new(0, 6, 2, LexemeType::CNWORD),
+            Lexeme::new(0, 8, 1, LexemeType::CNWORD),
+        ];
+        let alignments = align(&search, &index);
+        assert_eq!(alignments.len(), 1);
+        assert_eq!(alignments[0].children.len(), 4);
+        assert_eq!(alignments[0].crossing.len(), 1);
+        assert_eq!(alignments[0].crossing[0].get_begin(), 6);
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use unicode_blocks;
 
 #[derive(Debug, PartialEq)]
@@ -7,16 +9,23 @@ pub enum CharType {
     ENGLISH,
     CHINESE,
     OtherCjk,
+    // 表情符号, 如 😀 ❤ 🇨🇳
+    EMOJI,
 }
 
 // identify CharType Of char
 pub fn char_type_of(input: &char) -> CharType {
-    if ('0'..='9').contains(input) {
+    if input.is_ascii_digit() || numeral_value(*input).is_some() {
+        // 罗马数字、带圈数字等同阿拉伯数字处理
         return CharType::ARABIC;
-    } else if ('a'..='z').contains(input) || ('A'..='Z').contains(input) {
+    } else if input.is_ascii_alphabetic() {
         return CharType::ENGLISH;
-    } else {
-        let ub = unicode_blocks::find_unicode_block(*input).unwrap();
+    } else if is_emoji(input) {
+        return CharType::EMOJI;
+    } else if is_extended_ideograph(input) {
+        // 扩展B区及以后的汉字, UTF-8 编码为 4 字节
+        return CharType::CHINESE;
+    } else if let Some(ub) = unicode_blocks::find_unicode_block(*input) {
         if ub == unicode_blocks::CJK_UNIFIED_IDEOGRAPHS
             || ub == unicode_blocks::CJK_COMPATIBILITY_IDEOGRAPHS
             || ub == unicode_blocks::CJK_UNIFIED_IDEOGRAPHS_EXTENSION_A
@@ -39,20 +48,174 @@ pub fn char_type_of(input: &char) -> CharType {
     CharType::USELESS
 }
 
+/// 文本的书写系统, 见 detect_script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    // 只含中日韩文字
+    Han,
+    // 只含字母、数字
+    Latin,
+    // 两者都有
+    Mixed,
+    // 不含可切分的字符
+    Unknown,
+}
+
+/// auto_script 的默认阈值, 见 detect_script_with
+pub const DEFAULT_AUTO_SCRIPT_THRESHOLD: f32 = 0.9;
+
+/// 按字符类型的占比粗略判断文本的书写系统, 标点、空白等不计入
+pub fn detect_script(text: &str) -> Script {
+    detect_script_with(text.chars(), 1.0)
+}
+
+/// 与 detect_script 相同, 但一类字符的占比不低于 threshold(0.5~1.0)即视为该书写系统
+/// 如 threshold 为 0.9 时, 夹杂个别汉字的英文文本也判断为 Latin; 为 1.0 时与 detect_script 相同
+pub fn detect_script_with<I: IntoIterator<Item = char>>(chars: I, threshold: f32) -> Script {
+    let mut cjk = 0usize;
+    let mut latin = 0usize;
+    for c in chars {
+        if is_fullwidth_punctuation(&c) {
+            continue;
+        }
+        match char_type_of(&c) {
+            CharType::CHINESE | CharType::OtherCjk => cjk += 1,
+            CharType::ENGLISH | CharType::ARABIC => latin += 1,
+            CharType::USELESS | CharType::EMOJI => {}
+        }
+    }
+    let total = (cjk + latin) as f32;
+    let threshold = threshold.clamp(0.5, 1.0);
+    match (cjk, latin) {
+        (0, 0) => Script::Unknown,
+        (_, 0) => Script::Han,
+        (0, _) => Script::Latin,
+        _ if cjk as f32 >= total * threshold => Script::Han,
+        _ if latin as f32 >= total * threshold => Script::Latin,
+        _ => Script::Mixed,
+    }
+}
+
+// 罗马数字(Ⅷ)及带圈、带括号、带点数字(①⑴⒈❶)的数值
+pub fn numeral_value(input: char) -> Option<u32> {
+    let code = input as u32;
+    let value = match code {
+        // Ⅰ..Ⅻ, ⅰ..ⅻ
+        0x2160..=0x216B => code - 0x2160 + 1,
+        0x2170..=0x217B => code - 0x2170 + 1,
+        // Ⅼ Ⅽ Ⅾ Ⅿ, ⅼ ⅽ ⅾ ⅿ
+        0x216C | 0x217C => 50,
+        0x216D | 0x217D => 100,
+        0x216E | 0x217E => 500,
+        0x216F | 0x217F => 1000,
+        // ①..⑳, ⑴..⒇, ⒈..⒛
+        0x2460..=0x2473 => code - 0x2460 + 1,
+        0x2474..=0x2487 => code - 0x2474 + 1,
+        0x2488..=0x249B => code - 0x2488 + 1,
+        // ⓪, ⓫..⓴, ⓵..⓾
+        0x24EA => 0,
+        0x24EB..=0x24F4 => code - 0x24EB + 11,
+        0x24F5..=0x24FE => code - 0x24F5 + 1,
+        // ❶..❿, ➀..➉, ➊..➓
+        0x2776..=0x277F => code - 0x2776 + 1,
+        0x2780..=0x2789 => code - 0x2780 + 1,
+        0x278A..=0x2793 => code - 0x278A + 1,
+        // ㉑..㉟, ㊱..㊿
+        0x3251..=0x325F => code - 0x3251 + 21,
+        0x32B1..=0x32BF => code - 0x32B1 + 36,
+        _ => return None,
+    };
+    Some(value)
+}
+
+// 表情符号, 包括杂项符号、装饰符号及组成国旗的区域指示符
+pub fn is_emoji(input: &char) -> bool {
+    matches!(
+        *input as u32,
+        0x2600..=0x27BF | 0x1F1E6..=0x1F1FF | 0x1F300..=0x1F64F | 0x1F680..=0x1F6FF | 0x1F900..=0x1FAFF
+    )
+}
+
+// 接在表情符号后、与之组成一个表情的字符: 变体选择符、肤色修饰符、键帽符号及标签字符
+pub fn is_emoji_modifier(input: &char) -> bool {
+    matches!(
+        *input as u32,
+        0xFE0E | 0xFE0F | 0x1F3FB..=0x1F3FF | 0x20E3 | 0xE0020..=0xE007F
+    )
+}
+
+// 区域指示符, 两个组成一面国旗, 如 🇨🇳
+fn is_regional_indicator(input: &char) -> bool {
+    matches!(*input as u32, 0x1F1E6..=0x1F1FF)
+}
+
+/// 从 start 开始的表情符号序列的结束位置(不含), start 处不是表情符号时返回 None
+/// 修饰符及零宽连接符(U+200D)连接的表情整体算作一个, 如 👍🏻 👨‍👩‍👧 🇨🇳
+pub fn match_emoji(input: &[char], start: usize) -> Option<usize> {
+    let first = input.get(start)?;
+    if !is_emoji(first) || is_emoji_modifier(first) {
+        return None;
+    }
+    let mut end = start + 1;
+    if is_regional_indicator(first) {
+        if matches!(input.get(end), Some(c) if is_regional_indicator(c)) {
+            end += 1;
+        }
+        return Some(end);
+    }
+    loop {
+        while matches!(input.get(end), Some(c) if is_emoji_modifier(c)) {
+            end += 1;
+        }
+        match (input.get(end), input.get(end + 1)) {
+            (Some('\u{200D}'), Some(next)) if is_emoji(next) => end += 2,
+            _ => return Some(end),
+        }
+    }
+}
+
+// 扩展B区至扩展I区的汉字及兼容汉字补充
+fn is_extended_ideograph(input: &char) -> bool {
+    matches!(
+        *input as u32,
+        0x20000..=0x2A6DF | 0x2A700..=0x2EE5F | 0x2F800..=0x2FA1F | 0x30000..=0x323AF
+    )
+}
+
+// 全角标点符号, 与半角 ASCII 标点一一对应
+// char_type_of 仍将其视为 OtherCjk (与原有的切分结果一致)
+pub(crate) fn is_fullwidth_punctuation(input: &char) -> bool {
+    matches!(*input as u32, 0xFF01..=0xFF0F | 0xFF1A..=0xFF20 | 0xFF3B..=0xFF40 | 0xFF5B..=0xFF65)
+}
+
+// 标点、空白等无效字符, 包括全角标点
+pub(crate) fn is_useless_char(input: &char) -> bool {
+    is_fullwidth_punctuation(input) || char_type_of(input) == CharType::USELESS
+}
+
 // full char -> half char && lowercase
 pub fn regularize(input: char) -> char {
+    // 包括由全角转换而来的字母
+    to_halfwidth(input).to_ascii_lowercase()
+}
+
+// full char -> half char, 保留大小写
+pub fn to_halfwidth(input: char) -> char {
     let mut input_code = input as u32;
     if input_code == 12288 {
         input_code -= 12256; // 空格
     } else if (65281..=65374).contains(&input_code) {
         input_code -= 65248; // 全角字符
-    } else if input_code >= 'A' as u32 && input_code <= 'Z' as u32 {
-        input_code += 32; // lowercase
     }
 
     char::from_u32(input_code).unwrap()
 }
 
+// 全角字符转为半角, 字符数不变, 各字符的位置与原文一一对应
+pub fn to_halfwidth_str(input: &str) -> String {
+    input.chars().map(to_halfwidth).collect()
+}
+
 pub fn regularize_str(input: &str) -> String {
     let mut regular_str = "".to_string();
     for c in input.chars() {
@@ -61,6 +224,40 @@ pub fn regularize_str(input: &str) -> String {
     regular_str
 }
 
+/// 是否是零宽字符或双向文本控制符
+/// 这些字符不可见, 出现在词语内部时会切断词元或混入词元文本
+pub fn is_invisible_control(c: char) -> bool {
+    matches!(
+        c,
+        // 零宽空格、零宽非连接符、零宽连接符、从左到右及从右到左标记
+        '\u{200B}'..='\u{200F}'
+        // 双向文本嵌入及覆盖
+        | '\u{202A}'..='\u{202E}'
+        // 词连接符及不可见运算符
+        | '\u{2060}'..='\u{2064}'
+        // 双向文本隔离
+        | '\u{2066}'..='\u{2069}'
+        // 阿拉伯字母标记
+        | '\u{061C}'
+        // 零宽不换行空格(BOM)
+        | '\u{FEFF}'
+    )
+}
+
+/// 字符范围 chars 在 s 中对应的字节范围, 超出字符串的部分被截断
+pub fn byte_range(s: &str, chars: Range<usize>) -> Range<usize> {
+    let mut offsets = s
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(s.len()));
+    let start = offsets.nth(chars.start).unwrap_or(s.len());
+    let end = match chars.end.checked_sub(chars.start + 1) {
+        Some(n) => offsets.nth(n).unwrap_or(s.len()),
+        None => start,
+    };
+    start..end
+}
+
 pub fn utf8_slice(s: &str, begin: usize, end: usize) -> &str {
     if end < begin {
         return "";
@@ -90,3 +287,109 @@ pub fn utf8_till(s: &str, end: usize) -> &str {
 pub fn utf8_len(s: &str) -> usize {
     s.chars().count()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detect_script() {
+        assert_eq!(detect_script("hello, world 2022!"), Script::Latin);
+        assert_eq!(detect_script("北京大学"), Script::Han);
+        assert_eq!(detect_script("こんにちは"), Script::Han);
+        assert_eq!(detect_script("iphone14 手机"), Script::Mixed);
+        assert_eq!(detect_script("，。 "), Script::Unknown);
+
+        let text = "the quick brown fox jumps over the lazy 狗";
+        assert_eq!(detect_script(text), Script::Mixed);
+        assert_eq!(detect_script_with(text.chars(), 0.9), Script::Latin);
+        assert_eq!(
+            detect_script_with("iphone14 手机".chars(), 0.9),
+            Script::Mixed
+        );
+        assert_eq!(detect_script_with("北京大学a".chars(), 0.8), Script::Han);
+        assert_eq!(detect_script_with(text.chars(), 1.5), Script::Mixed);
+    }
+
+    #[test]
+    fn test_regularize() {
+        assert_eq!(regularize_str("Ｕ盘 ４Ｓ店\u{3000}ABC"), "u盘 4s店 abc");
+        assert_eq!(to_halfwidth('Ｕ'), 'U');
+        assert_eq!(to_halfwidth('\u{3000}'), ' ');
+    }
+
+    #[test]
+    fn test_byte_range() {
+        let s = "a😀中文b";
+        assert_eq!(byte_range(s, 0..1), 0..1);
+        assert_eq!(byte_range(s, 2..4), 5..11);
+        assert_eq!(byte_range(s, 4..9), 11..12);
+        assert_eq!(byte_range(s, 3..3), 8..8);
+        assert_eq!(byte_range(s, 9..10), 12..12);
+    }
+
+    #[test]
+    fn test_numeral() {
+        assert_eq!(numeral_value('Ⅷ'), Some(8));
+        assert_eq!(numeral_value('ⅻ'), Some(12));
+        assert_eq!(numeral_value('Ⅿ'), Some(1000));
+        assert_eq!(numeral_value('①'), Some(1));
+        assert_eq!(numeral_value('⑳'), Some(20));
+        assert_eq!(numeral_value('⑴'), Some(1));
+        assert_eq!(numeral_value('⒛'), Some(20));
+        assert_eq!(numeral_value('❿'), Some(10));
+        assert_eq!(numeral_value('㊿'), Some(50));
+        assert_eq!(numeral_value('a'), None);
+        assert_eq!(numeral_value('一'), None);
+        assert_eq!(char_type_of(&'Ⅷ'), CharType::ARABIC);
+        assert_eq!(char_type_of(&'①'), CharType::ARABIC);
+        // 全角标点仍为 OtherCjk, 但属于无效字符
+        assert_eq!(char_type_of(&'！'), CharType::OtherCjk);
+        assert!(is_useless_char(&'！'));
+        assert!(is_useless_char(&'。'));
+        assert!(!is_useless_char(&'好'));
+    }
+
+    #[test]
+    fn test_emoji_and_extended_ideograph() {
+        for c in ['😀', '❤', '🇨', '🦀', '🫠'] {
+            assert_eq!(char_type_of(&c), CharType::EMOJI, "{}", c);
+        }
+        // 扩展B区、扩展G区及兼容汉字补充
+        for c in ['𠀀', '𪚥', '𰻞', '\u{2F800}'] {
+            assert_eq!(char_type_of(&c), CharType::CHINESE, "{}", c);
+        }
+        // 未分配的码位不会 panic
+        assert_eq!(char_type_of(&'\u{2FE0}'), CharType::USELESS);
+        assert_eq!(char_type_of(&'\u{FE0F}'), CharType::USELESS);
+        assert_eq!(detect_script("😀😀 hello"), Script::Latin);
+    }
+
+    #[test]
+    fn test_match_emoji() {
+        let emoji = |text: &str| -> Vec<String> {
+            let chars = text.chars().collect::<Vec<_>>();
+            let mut cursor = 0;
+            let mut emojis = Vec::new();
+            while cursor < chars.len() {
+                match match_emoji(&chars, cursor) {
+                    Some(end) => {
+                        emojis.push(chars[cursor..end].iter().collect());
+                        cursor = end;
+                    }
+                    None => cursor += 1,
+                }
+            }
+            emojis
+        };
+        assert_eq!(emoji("好😀😀"), vec!["😀", "😀"]);
+        assert_eq!(emoji("👍🏻赞"), vec!["👍🏻"]);
+        assert_eq!(
+            emoji("👨\u{200D}👩\u{200D}👧"),
+            vec!["👨\u{200D}👩\u{200D}👧"]
+        );
+        assert_eq!(emoji("❤\u{FE0F}\u{200D}"), vec!["❤\u{FE0F}"]);
+        assert_eq!(emoji("🇨🇳🇺"), vec!["🇨🇳", "🇺"]);
+        assert!(emoji("\u{1F3FB}abc").is_empty());
+    }
+}
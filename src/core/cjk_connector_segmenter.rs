@@ -0,0 +1,90 @@
+use crate::core::char_util::{char_type_of, CharType};
+use crate::core::lexeme::{Lexeme, LexemeType};
+use crate::core::segmentor::Segmenter;
+
+// 子分词器标签
+const SEGMENTER_NAME: &str = "CJK_CONNECTOR_SEGMENTER";
+
+// 中日韩文字连接符子分词器
+// 将由指定连接符连接的中日韩文字片段整体输出为一个 CNWORD 词元, 如 你好_世界、新品#限量版
+// 片段内部的词仍由 CJKSegmenter 输出, SEARCH 模式下整体词元胜出
+#[derive(Debug)]
+pub struct CjkConnectorSegmenter {
+    connectors: Vec<char>,
+}
+
+impl Segmenter for CjkConnectorSegmenter {
+    fn analyze(&mut self, input: &[char]) -> Vec<Lexeme> {
+        let mut new_lexemes = Vec::new();
+        let mut cursor = 0;
+        while cursor < input.len() {
+            if !is_cjk(&input[cursor]) {
+                cursor += 1;
+                continue;
+            }
+            let start = cursor;
+            let mut end = skip_cjk(input, cursor);
+            let mut joined = false;
+            // 连接符前后都是中日韩文字时才连接
+            while end + 1 < input.len() && self.is_connector(&input[end]) && is_cjk(&input[end + 1])
+            {
+                end = skip_cjk(input, end + 1);
+                joined = true;
+            }
+            if joined {
+                new_lexemes.push(Lexeme::new(0, start, end - start, LexemeType::CNWORD));
+            }
+            cursor = end;
+        }
+        new_lexemes
+    }
+
+    fn name(&self) -> &str {
+        SEGMENTER_NAME
+    }
+
+    fn is_cjk_only(&self) -> bool {
+        true
+    }
+}
+
+impl CjkConnectorSegmenter {
+    pub fn new(connectors: Vec<char>) -> Self {
+        CjkConnectorSegmenter { connectors }
+    }
+
+    pub fn is_connector(&self, c: &char) -> bool {
+        self.connectors.contains(c)
+    }
+}
+
+fn is_cjk(c: &char) -> bool {
+    matches!(char_type_of(c), CharType::CHINESE | CharType::OtherCjk)
+}
+
+// 跳过从 cursor 开始的连续中日韩文字, 返回第一个非中日韩文字的位置
+fn skip_cjk(input: &[char], mut cursor: usize) -> usize {
+    while cursor < input.len() && is_cjk(&input[cursor]) {
+        cursor += 1;
+    }
+    cursor
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cjk_connector() {
+        let mut segmenter = CjkConnectorSegmenter::new(vec!['_', '#']);
+        let input = "你好_世界 新品#限量#版 a_b 中_ _文"
+            .chars()
+            .collect::<Vec<_>>();
+        let spans = segmenter
+            .analyze(&input)
+            .iter()
+            .map(|l| (l.get_begin(), l.get_length()))
+            .collect::<Vec<_>>();
+        assert_eq!(spans, vec![(0, 5), (6, 7)]);
+    }
+}
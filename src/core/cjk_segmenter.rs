@@ -1,13 +1,15 @@
 use crate::core::char_util::{char_type_of, CharType};
 use crate::core::lexeme::{Lexeme, LexemeType};
 use crate::core::segmentor::Segmenter;
-use crate::dict::dictionary::GLOBAL_DICT;
+use crate::dict::dictionary::DictRef;
 
 const SEGMENTER_NAME: &str = "CJK_SEGMENTER";
 
 // 中文-日韩文子分词器
 #[derive(Debug, Default)]
-pub struct CJKSegmenter {}
+pub struct CJKSegmenter {
+    dict: DictRef,
+}
 
 impl Segmenter for CJKSegmenter {
     fn analyze(&mut self, chars: &[char]) -> Vec<Lexeme> {
@@ -16,16 +18,15 @@ impl Segmenter for CJKSegmenter {
         for (cursor, curr_char) in chars.iter().enumerate() {
             let curr_char_type = char_type_of(curr_char);
             if CharType::USELESS != curr_char_type {
-                let hit_options = GLOBAL_DICT.lock().unwrap().match_in_main_dict_with_offset(
-                    chars.iter().copied(),
-                    cursor,
-                    char_count - cursor,
-                );
+                let hit_options =
+                    self.dict
+                        .match_in_main_dict_with_offset(chars, cursor, char_count - cursor);
                 for hit in hit_options.iter() {
                     if hit.is_match() {
                         // 输出当前的词
-                        let new_lexeme =
-                            Lexeme::new(0, hit.begin, hit.end - hit.begin + 1, LexemeType::CNWORD);
+                        let new_lexeme = Lexeme::from_range(0, hit.range(), LexemeType::CNWORD)
+                            .with_freq(hit.freq)
+                            .with_pos(hit.pos.clone());
                         new_lexemes.push(new_lexeme);
                     }
                 }
@@ -37,10 +38,20 @@ impl Segmenter for CJKSegmenter {
     fn name(&self) -> &str {
         SEGMENTER_NAME
     }
+
+    fn is_cjk_only(&self) -> bool {
+        true
+    }
 }
 
 impl CJKSegmenter {
     pub fn new() -> Self {
-        CJKSegmenter {}
+        CJKSegmenter::default()
+    }
+
+    // 使用指定的词典
+    pub(crate) fn with_dict(mut self, dict: DictRef) -> Self {
+        self.dict = dict;
+        self
     }
 }
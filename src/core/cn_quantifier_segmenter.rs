@@ -4,7 +4,7 @@ use super::ordered_linked_list::OrderedLinkedList;
 use crate::core::char_util::{char_type_of, CharType};
 use crate::core::lexeme::{Lexeme, LexemeType};
 use crate::core::segmentor::Segmenter;
-use crate::dict::dictionary::GLOBAL_DICT;
+use crate::dict::dictionary::DictRef;
 
 const SEGMENTER_NAME: &str = "QUAN_SEGMENTER";
 
@@ -13,6 +13,7 @@ pub struct CnQuantifierSegmenter {
     n_start: i32,
     n_end: i32,
     chn_number_chars: HashSet<char>,
+    dict: DictRef,
 }
 
 impl Segmenter for CnQuantifierSegmenter {
@@ -33,6 +34,10 @@ impl Segmenter for CnQuantifierSegmenter {
     fn name(&self) -> &str {
         SEGMENTER_NAME
     }
+
+    fn is_cjk_only(&self) -> bool {
+        true
+    }
 }
 
 impl Default for CnQuantifierSegmenter {
@@ -51,9 +56,16 @@ impl CnQuantifierSegmenter {
                 '叁', '肆', '伍', '陆', '柒', '捌', '玖', '拾', '百', '千', '万', '亿', '拾', '佰',
                 '仟', '萬', '億', '兆', '卅', '廿',
             ]),
+            dict: DictRef::Global,
         }
     }
 
+    // 使用指定的词典
+    pub(crate) fn with_dict(mut self, dict: DictRef) -> Self {
+        self.dict = dict;
+        self
+    }
+
     // 处理数词
     pub fn process_cnumber(&mut self, input: &[char]) -> Vec<Lexeme> {
         let mut new_lexemes = Vec::new();
@@ -120,20 +132,17 @@ impl CnQuantifierSegmenter {
             if self.need_count_scan(cnumber_list, cursor) {
                 let curr_char_type = char_type_of(curr_char);
                 if CharType::CHINESE == curr_char_type {
-                    let hit_options = GLOBAL_DICT.lock().unwrap().match_in_quantifier_dict(
-                        chars.iter().copied(),
-                        cursor,
-                        char_count - cursor,
-                    );
+                    let hit_options = self.dict.with(|dict| {
+                        dict.match_in_quantifier_dict(
+                            chars.iter().copied(),
+                            cursor,
+                            char_count - cursor,
+                        )
+                    });
                     for hit in hit_options.iter() {
                         if hit.is_match() {
                             // 输出当前的词
-                            let new_lexeme = Lexeme::new(
-                                0,
-                                hit.begin,
-                                hit.end - hit.begin + 1,
-                                LexemeType::COUNT,
-                            );
+                            let new_lexeme = Lexeme::from_range(0, hit.range(), LexemeType::COUNT);
                             new_lexemes.push(new_lexeme);
                         }
                     }
@@ -0,0 +1,88 @@
+use crate::core::lexeme::Lexeme;
+
+/// 在窗口内共现的词元对, 见 cooccurrences
+pub struct Cooccurrences<'a> {
+    lexemes: &'a [Lexeme],
+    window: usize,
+    // 当前词元及其候选搭配词元的下标
+    i: usize,
+    j: usize,
+}
+
+/// 遍历 lexemes 中在窗口内共现的词元对 (前, 后)
+/// window 为两个词元之间允许间隔的字符数, 0 表示只取相邻的词元
+/// 相互重叠的词元(如 INDEX 模式下的 北京大学 与 北京)不算共现
+/// lexemes 需按起始位置排列, 即 tokenize 的输出顺序
+pub fn cooccurrences(lexemes: &[Lexeme], window: usize) -> Cooccurrences<'_> {
+    Cooccurrences {
+        lexemes,
+        window,
+        i: 0,
+        j: 1,
+    }
+}
+
+impl<'a> Iterator for Cooccurrences<'a> {
+    type Item = (&'a Lexeme, &'a Lexeme);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.i < self.lexemes.len() {
+            let a = &self.lexemes[self.i];
+            let limit = a.get_end_position() + self.window;
+            while self.j < self.lexemes.len() {
+                let b = &self.lexemes[self.j];
+                if b.get_begin_position() > limit {
+                    break;
+                }
+                self.j += 1;
+                if b.get_begin_position() >= a.get_end_position() {
+                    return Some((a, b));
+                }
+            }
+            self.i += 1;
+            self.j = self.i + 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::lexeme::LexemeType;
+
+    #[test]
+    fn test_cooccurrences() {
+        // 我 在 北京大学 北京 大学 读书
+        let mut lexemes = vec![
+            Lexeme::new(0, 0, 1, LexemeType::CNCHAR),
+            Lexeme::new(0, 1, 1, LexemeType::CNCHAR),
+            Lexeme::new(0, 2, 4, LexemeType::CNWORD),
+            Lexeme::new(0, 2, 2, LexemeType::CNWORD),
+            Lexeme::new(0, 4, 2, LexemeType::CNWORD),
+            Lexeme::new(0, 6, 2, LexemeType::CNWORD),
+        ];
+        let text = "我在北京大学读书";
+        for lexeme in lexemes.iter_mut() {
+            lexeme.parse_lexeme_text(text);
+        }
+        let pairs = |window| {
+            cooccurrences(&lexemes, window)
+                .map(|(a, b)| format!("{}-{}", a.get_lexeme_text(), b.get_lexeme_text()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            pairs(0),
+            vec![
+                "我-在",
+                "在-北京大学",
+                "在-北京",
+                "北京大学-读书",
+                "北京-大学",
+                "大学-读书"
+            ]
+        );
+        assert!(pairs(2).contains(&"北京-读书".to_string()));
+        assert!(!pairs(2).contains(&"北京大学-北京".to_string()));
+    }
+}
@@ -1,44 +1,102 @@
-use std::collections::{BTreeSet, HashMap};
-use std::ptr::NonNull;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use crate::core::ik_segmenter::TokenMode;
+use crate::core::lattice::Lattice;
 use crate::core::lexeme::Lexeme;
 use crate::core::lexeme_path::LexemePath;
-use crate::core::ordered_linked_list::{Node, OrderedLinkedList};
+
+// 处理过的 crossPath 数
+static CROSS_PATHS: AtomicU64 = AtomicU64::new(0);
+// 实际进行了歧义裁决的 crossPath 数
+static ARBITRATED_PATHS: AtomicU64 = AtomicU64::new(0);
+
+/// 歧义裁决的统计, 进程内所有分词器累计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArbitrationStats {
+    pub cross_paths: u64,
+    pub arbitrated: u64,
+}
+
+pub fn arbitration_stats() -> ArbitrationStats {
+    ArbitrationStats {
+        cross_paths: CROSS_PATHS.load(Ordering::Relaxed),
+        arbitrated: ARBITRATED_PATHS.load(Ordering::Relaxed),
+    }
+}
+
+/// 歧义裁决策略, 从候选路径中选出最优的一条
+pub trait ArbitrationStrategy: Send + Sync {
+    /// 比较两条候选路径, Less 表示 a 更优
+    /// 默认先按 IK 的规则比较, 规则全部相同时按词频权重比较, 词频也相同时由 tie_break 决定
+    fn compare(&self, a: &LexemePath, b: &LexemePath) -> CmpOrdering {
+        a.cmp_by_rules(b)
+            .then_with(|| a.cmp_by_freq(b))
+            .then_with(|| self.tie_break(a, b))
+    }
+
+    /// 最终裁决, 必须是全序, 以保证结果与候选路径的生成顺序无关
+    /// 默认依次比较两条路径的词元, 起始位置靠前者更优, 起始位置相同时较长者更优
+    fn tie_break(&self, a: &LexemePath, b: &LexemePath) -> CmpOrdering {
+        a.cmp_lexemes(b)
+    }
+}
+
+/// 默认的歧义裁决策略, 与 LexemePath 的 Ord 一致(规则、词频、词元依次比较)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultStrategy;
+
+impl ArbitrationStrategy for DefaultStrategy {}
 
 // IK分词歧义裁决器
-#[derive(Clone, Default)]
-pub struct IKArbitrator {}
+#[derive(Clone)]
+pub struct IKArbitrator {
+    strategy: Arc<dyn ArbitrationStrategy>,
+}
+
+impl Default for IKArbitrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl IKArbitrator {
     pub fn new() -> Self {
-        IKArbitrator {}
+        Self::with_strategy(Arc::new(DefaultStrategy))
+    }
+
+    pub fn with_strategy(strategy: Arc<dyn ArbitrationStrategy>) -> Self {
+        IKArbitrator { strategy }
     }
 
     // 分词歧义处理
-    pub fn process(
-        &mut self,
-        org_lexemes: &mut OrderedLinkedList<Lexeme>,
-        mode: TokenMode,
-    ) -> HashMap<usize, LexemePath> {
-        let mut path_map = HashMap::<usize, LexemePath>::new();
+    pub fn process(&mut self, lattice: &Lattice, mode: TokenMode) -> HashMap<usize, LexemePath> {
+        self.process_ordered(lattice, mode)
+            .into_iter()
+            .map(|path| (path.get_path_begin() as usize, path))
+            .collect()
+    }
+
+    // 分词歧义处理, 结果按起始位置排列
+    pub fn process_ordered(&mut self, lattice: &Lattice, mode: TokenMode) -> Vec<LexemePath> {
+        let mut paths = Vec::new();
         let mut cross_path = LexemePath::new();
-        let mut cur_node = org_lexemes.head_node();
+        let mut cur_node = lattice.head_node();
 
-        let mut handle_once = |path_map: &mut HashMap<usize, LexemePath>,
-                           cross_path: LexemePath| {
+        let mut handle_once = |paths: &mut Vec<LexemePath>, cross_path: LexemePath| {
+            CROSS_PATHS.fetch_add(1, Ordering::Relaxed);
             if cross_path.size() == 1 || !(mode == TokenMode::SEARCH) {
                 // crossPath没有歧义 或者 不做歧义处理
                 // 直接输出当前crossPath
-                path_map.insert(cross_path.get_path_begin() as usize, cross_path);
+                paths.push(cross_path);
             } else {
+                ARBITRATED_PATHS.fetch_add(1, Ordering::Relaxed);
                 // 对当前的crossPath进行歧义处理
-                let judge_result = self.judge(cross_path.get_head());
+                let judge_result = self.judge(&cross_path);
                 // 输出歧义处理结果judgeResult
-                path_map.insert(
-                    judge_result.as_ref().unwrap().get_path_begin() as usize,
-                    judge_result.unwrap(),
-                );
+                paths.push(judge_result.unwrap());
             }
         };
 
@@ -47,7 +105,7 @@ impl IKArbitrator {
             let org_lexeme = unsafe { &(inner.as_ref().val) };
             if !cross_path.add_cross_lexeme(org_lexeme) {
                 // 找到与crossPath不相交的下一个crossPath
-                handle_once(&mut path_map, cross_path);
+                handle_once(&mut paths, cross_path);
                 // 把orgLexeme加入新的crossPath中
                 cross_path = LexemePath::new();
                 cross_path.add_cross_lexeme(org_lexeme);
@@ -59,69 +117,110 @@ impl IKArbitrator {
         }
 
         // 处理最后的path
-        handle_once(&mut path_map, cross_path);
-        path_map
+        handle_once(&mut paths, cross_path);
+        paths
     }
 
     /// 歧义识别
     ///
-    /// @param lexeme_cell     歧义路径链表头
-    /// @param fullTextLength 歧义路径文本长度
-    pub fn judge(&mut self, cur_node: Option<&NonNull<Node<Lexeme>>>) -> Option<LexemePath> {
-        // 候选路径集合
-        let mut path_options = BTreeSet::new();
+    /// @param cross_path 有歧义的路径
+    pub fn judge(&mut self, cross_path: &LexemePath) -> Option<LexemePath> {
+        let lexemes = cross_path.iter().collect::<Vec<_>>();
         // 候选结果路径
         let mut option_path = LexemePath::new();
         // 对crossPath进行一次遍历,同时返回本次遍历中有冲突的Lexeme栈
-        let mut lexeme_stack = self.forward_path(cur_node, &mut option_path);
-        // 当前词元链并非最理想的，加入候选路径集合
-        path_options.insert(option_path.clone());
+        let mut lexeme_stack = self.forward_path(&lexemes, 0, &mut option_path);
+        if lexeme_stack.is_empty() {
+            // 没有冲突, 唯一的候选路径即为结果
+            return Some(option_path);
+        }
+        // 当前词元链并非最理想的, 作为首个候选方案
+        // 路径的克隆共享词元链, 为 O(1)
+        let mut best_path = option_path.clone();
         while let Some(c) = lexeme_stack.pop() {
             // rollback path
-            self.backward_path(c, &mut option_path);
+            self.backward_path(lexemes[c], &mut option_path);
             // forward path
-            self.forward_path(c, &mut option_path);
-            path_options.insert(option_path.clone());
+            self.forward_path(&lexemes, c, &mut option_path);
+            // 只保留最优方案
+            if self.strategy.compare(&option_path, &best_path) == CmpOrdering::Less {
+                best_path = option_path.clone();
+            }
         }
-        // 返回集合中的最优方案
-        path_options.iter().next().cloned()
+        Some(best_path)
     }
 
     // 向前遍历，添加词元，构造一个无歧义词元组合
     // option_path: 无歧义的路径
-    // ret: 歧义，待裁决的路径
-    pub fn forward_path<'a>(
-        &'a self,
-        cur_node: Option<&'a NonNull<Node<Lexeme>>>,
+    // ret: 歧义，待裁决的词元下标
+    pub fn forward_path(
+        &self,
+        lexemes: &[&Lexeme],
+        start: usize,
         option_path: &mut LexemePath,
-    ) -> Vec<Option<&NonNull<Node<Lexeme>>>> {
+    ) -> Vec<usize> {
         // 发生冲突的Lexeme栈
-        let mut conflict_stack: Vec<Option<&NonNull<Node<Lexeme>>>> = Vec::new();
-        // 迭代遍历Lexeme链表
-        let mut cur = cur_node;
-        // safety: cur is Some
-        while let Some(inner) = cur.as_ref() {
-            unsafe {
-                let c = &(inner.as_ref().val);
-                if !option_path.add_not_cross_lexeme(c) {
-                    // 词元交叉，添加失败则加入lexemeStack栈
-                    conflict_stack.push(cur);
-                }
-                cur = inner.as_ref().next.as_ref();
+        let mut conflict_stack = Vec::new();
+        for (i, c) in lexemes.iter().enumerate().skip(start) {
+            if !option_path.add_not_cross_lexeme(c) {
+                // 词元交叉，添加失败则加入lexemeStack栈
+                conflict_stack.push(i);
             }
         }
         conflict_stack
     }
 
     // 回滚词元链，直到它能够接受指定的词元
-    pub fn backward_path(&self, l: Option<&NonNull<Node<Lexeme>>>, option: &mut LexemePath) {
-        if let Some(lexeme) = l {
-            unsafe {
-                let lexeme = &lexeme.as_ref().val;
-                while option.check_cross(lexeme) {
-                    option.remove_tail();
-                }
-            }
+    pub fn backward_path(&self, lexeme: &Lexeme, option: &mut LexemePath) {
+        while option.check_cross(lexeme) {
+            option.remove_tail();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::lexeme::LexemeType;
+    use crate::core::test_util::{path, tie_paths};
+
+    #[test]
+    fn test_process() {
+        // 北京大学 / 北京 / 大学 与 生 不相交
+        let mut lattice = Lattice::new(5);
+        for (begin, length) in [(0, 4), (0, 2), (2, 2), (4, 1)] {
+            lattice.insert(Lexeme::new(0, begin, length, LexemeType::CNWORD));
         }
+        let before = arbitration_stats();
+        let paths = IKArbitrator::new().process_ordered(&lattice, TokenMode::SEARCH);
+        let after = arbitration_stats();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].get_path_begin(), 0);
+        assert_eq!(paths[0].size(), 1);
+        assert_eq!(paths[1].get_path_begin(), 4);
+        // 其他测试可能并发运行, 统计只增不减
+        assert!(after.cross_paths >= before.cross_paths + 2);
+        assert!(after.arbitrated > before.arbitrated);
+    }
+
+    // 规则相同时反转默认的最终裁决
+    struct ReversedTieBreak;
+
+    impl ArbitrationStrategy for ReversedTieBreak {
+        fn tie_break(&self, a: &LexemePath, b: &LexemePath) -> CmpOrdering {
+            b.cmp_lexemes(a)
+        }
+    }
+
+    #[test]
+    fn test_strategy_tie_break() {
+        // 两条候选路径的各项规则都相同, LexemePath 本身的比较见 lexeme_path 的 test_tie_break
+        let (a, b) = tie_paths();
+        assert_eq!(DefaultStrategy.compare(&a, &b), CmpOrdering::Less);
+        assert_eq!(DefaultStrategy.compare(&b, &a), CmpOrdering::Greater);
+        assert_eq!(ReversedTieBreak.compare(&a, &b), CmpOrdering::Greater);
+        // 规则不同时不受最终裁决影响
+        let c = path(&[(0, 1), (1, 2), (3, 2)]);
+        assert_eq!(ReversedTieBreak.compare(&c, &a), CmpOrdering::Less);
     }
 }
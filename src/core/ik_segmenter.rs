@@ -1,25 +1,103 @@
-use std::collections::{HashMap, LinkedList};
+use std::collections::LinkedList;
+use std::sync::Arc;
 
-use crate::core::char_util::{char_type_of, CharType};
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+
+use crate::config::configuration::{global_config, Configuration};
+use crate::core::alignment::{align, Alignment};
+use crate::core::char_util::{
+    char_type_of, detect_script_with, is_useless_char, match_emoji, to_halfwidth, to_halfwidth_str,
+    CharType, Script, DEFAULT_AUTO_SCRIPT_THRESHOLD,
+};
+use crate::core::cjk_connector_segmenter::CjkConnectorSegmenter;
 use crate::core::cjk_segmenter::CJKSegmenter;
 use crate::core::cn_quantifier_segmenter::CnQuantifierSegmenter;
-use crate::core::ik_arbitrator::IKArbitrator;
+use crate::core::ik_arbitrator::{ArbitrationStrategy, IKArbitrator};
+use crate::core::ik_token::{fill_tokens, IkToken};
+use crate::core::interner::SharedInterner;
+use crate::core::lattice::Lattice;
 use crate::core::letter_segmentor::LetterSegmenter;
-use crate::core::lexeme::{Lexeme, LexemeType};
+use crate::core::lexeme::{Lexeme, LexemeType, DEFAULT_TYPE_PRIORITY};
 use crate::core::lexeme_path::LexemePath;
-use crate::core::ordered_linked_list::OrderedLinkedList;
+use crate::core::reduplication_segmenter::ReduplicationSegmenter;
+use crate::core::segmentation_result::{Section, SegmentationResult, SegmentationStats};
 use crate::core::segmentor::Segmenter;
-use crate::dict::dictionary::GLOBAL_DICT;
+use crate::core::special_segmenter::SpecialSegmenter;
+use crate::core::suffix_entity_segmenter::SuffixEntitySegmenter;
+use crate::core::token_batch::TokenBatch;
+use crate::core::token_record::TokenRecord;
+use crate::core::tokenization_result::TokenizationResult;
+use crate::dict::dictionary::{DictRef, Dictionary};
+use crate::dict::oov::{flush_pending, global_oov_collector, OovCollector, SharedOovCollector};
+
+// 短输入的字符数上限, 短输入的字符缓冲区分配在栈上
+const SHORT_INPUT_LEN: usize = 8;
+
+// tokenize_iter 每个片段的最小字符数, 达到后在下一个句子边界处切分
+const ITER_CHUNK_LEN: usize = 64;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum TokenMode {
+    #[default]
     INDEX,
     SEARCH,
 }
 
-impl Default for TokenMode {
+/// 单次分词的选项, 用于分别控制原先由 TokenMode 统一决定的行为
+///
+/// ```
+/// use ik_rs::core::ik_segmenter::{IKSegmenter, TokenMode, TokenizeOptions};
+///
+/// let mut ik = IKSegmenter::new();
+/// // SEARCH 模式的歧义裁决, 但不合并数量词、不输出单字
+/// let options = TokenizeOptions {
+///     merge_quantifiers: false,
+///     emit_single_cjk: false,
+///     ..TokenizeOptions::new(TokenMode::SEARCH)
+/// };
+/// let tokens = ik.tokenize_with("他花了五十元", options);
+/// let texts = tokens.iter().map(|t| t.get_lexeme_text()).collect::<Vec<_>>();
+/// assert_eq!(texts, vec!["花了", "五十", "元"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenizeOptions {
+    // 歧义裁决方式: INDEX 输出所有候选词元, SEARCH 只输出最优切分
+    pub mode: TokenMode,
+    // 是否保留停止词
+    pub keep_stop_words: bool,
+    // 是否以单字输出未被任何词元覆盖的中日韩文字
+    pub emit_single_cjk: bool,
+    // 是否合并相邻的中文数词和量词, 如 五十 + 元 => 五十元
+    // SEARCH 模式下以合并后的词元替换组成词元, INDEX 模式下两者都输出
+    pub merge_quantifiers: bool,
+    // 是否将英文字母转为小写
+    pub lowercase: bool,
+}
+
+impl TokenizeOptions {
+    /// 与 IKSegmenter::tokenize 在 mode 下的行为相同: 过滤停止词、输出单字,
+    /// 只在 SEARCH 模式下合并数量词, 不转换大小写
+    pub fn new(mode: TokenMode) -> Self {
+        TokenizeOptions {
+            mode,
+            keep_stop_words: false,
+            emit_single_cjk: true,
+            merge_quantifiers: mode == TokenMode::SEARCH,
+            lowercase: false,
+        }
+    }
+}
+
+impl Default for TokenizeOptions {
     fn default() -> Self {
-        Self::INDEX
+        TokenizeOptions::new(TokenMode::default())
+    }
+}
+
+impl From<TokenMode> for TokenizeOptions {
+    fn from(mode: TokenMode) -> Self {
+        TokenizeOptions::new(mode)
     }
 }
 
@@ -42,10 +120,35 @@ impl TryFrom<&str> for TokenMode {
 pub struct IKSegmenter {
     segmenters: Vec<Box<dyn Segmenter>>,
     arbitrator: IKArbitrator,
+    // 是否输出标点及特殊符号
+    full_coverage: bool,
+    // 同一位置、相同长度的候选词元的类型优先级
+    type_priority: Vec<LexemeType>,
+    // 过滤停止词所使用的词典
+    dict: DictRef,
+    // 词元文本驻留池, 为 None 时每个词元单独分配文本
+    interner: Option<SharedInterner>,
+    // 是否在以字母数字为主的文本上跳过中文子分词器
+    auto_script: bool,
+    // 字母数字的占比不低于该值时视为以字母数字为主
+    auto_script_threshold: f32,
+    // 是否保留停止词
+    keep_stop_words: bool,
+    // 未登录词候选收集器, 为 None 时使用全局收集器, 均未设置时不收集
+    oov_collector: Option<SharedOovCollector>,
+    // 尚未并入收集器的计数, 收集器的锁被占用时暂存于此
+    oov_pending: OovCollector,
+    // INDEX 模式下是否合并数量词
+    index_merge_quantifiers: bool,
 }
 
-unsafe impl Sync for IKSegmenter {}
-unsafe impl Send for IKSegmenter {}
+impl Drop for IKSegmenter {
+    fn drop(&mut self) {
+        if let Some(collector) = self.oov_collector.as_ref().or(global_oov_collector()) {
+            flush_pending(collector, &mut self.oov_pending, true);
+        }
+    }
+}
 
 impl Default for IKSegmenter {
     fn default() -> Self {
@@ -53,158 +156,706 @@ impl Default for IKSegmenter {
     }
 }
 
+/// 构造 IKSegmenter, 在内置的子分词器之外注册自定义的子分词器
+///
+/// ```
+/// use ik_rs::core::ik_segmenter::{IKSegmenter, TokenMode};
+/// use ik_rs::core::lexeme::{Lexeme, LexemeType};
+/// use ik_rs::core::segmentor::Segmenter;
+///
+/// // 将 #话题# 整体输出
+/// struct HashtagSegmenter;
+///
+/// impl Segmenter for HashtagSegmenter {
+///     fn analyze(&mut self, input: &[char]) -> Vec<Lexeme> {
+///         let marks = (0..input.len()).filter(|&i| input[i] == '#').collect::<Vec<_>>();
+///         marks
+///             .chunks_exact(2)
+///             .map(|pair| Lexeme::new(0, pair[0], pair[1] - pair[0] + 1, LexemeType::CNWORD))
+///             .collect()
+///     }
+///
+///     fn name(&self) -> &str {
+///         "HASHTAG_SEGMENTER"
+///     }
+/// }
+///
+/// let mut ik = IKSegmenter::builder()
+///     .with_segmenter(Box::new(HashtagSegmenter))
+///     .build();
+/// let tokens = ik.tokenize("去#北京#玩", TokenMode::SEARCH);
+/// let texts = tokens.iter().map(|t| t.get_lexeme_text()).collect::<Vec<_>>();
+/// assert_eq!(texts, vec!["去", "#北京#", "玩"]);
+/// ```
+pub struct IKSegmenterBuilder {
+    cfg: Option<Arc<dyn Configuration>>,
+    dict: DictRef,
+    segmenters: Vec<Box<dyn Segmenter>>,
+}
+
+impl IKSegmenterBuilder {
+    /// 内置子分词器的选项取自 cfg, 未指定时使用全局配置
+    pub fn with_configuration(mut self, cfg: Arc<dyn Configuration>) -> Self {
+        self.cfg = Some(cfg);
+        self
+    }
+
+    /// 使用独立的词典, 未指定时使用 GLOBAL_DICT
+    pub fn with_dictionary(mut self, dict: Arc<Dictionary>) -> Self {
+        self.dict = DictRef::Local(dict);
+        self
+    }
+
+    /// 注册自定义的子分词器, 其输出的候选词元与内置子分词器的一同参与歧义裁决
+    pub fn with_segmenter(mut self, segmenter: Box<dyn Segmenter>) -> Self {
+        self.segmenters.push(segmenter);
+        self
+    }
+
+    pub fn build(self) -> IKSegmenter {
+        let cfg = self.cfg.unwrap_or_else(global_config);
+        let mut ik = IKSegmenter::build(cfg.as_ref(), self.dict);
+        ik.segmenters.extend(self.segmenters);
+        ik
+    }
+}
+
 impl IKSegmenter {
     pub fn new() -> Self {
+        Self::bound_to(DictRef::Global)
+    }
+
+    pub fn builder() -> IKSegmenterBuilder {
+        IKSegmenterBuilder {
+            cfg: None,
+            dict: DictRef::Global,
+            segmenters: Vec::new(),
+        }
+    }
+
+    /// 按全局配置构造, 但使用独立的词典而不是 GLOBAL_DICT
+    /// 同一进程中不同的索引可以使用不同的词表
+    pub fn with_dictionary(dict: Arc<Dictionary>) -> Self {
+        Self::bound_to(DictRef::Local(dict))
+    }
+
+    /// 按指定的配置构造, 使用独立的词典, 不读取全局配置
+    pub fn with_configuration(cfg: &dyn Configuration, dict: Arc<Dictionary>) -> Self {
+        Self::build(cfg, DictRef::Local(dict))
+    }
+
+    // 按全局配置构造, 所有子分词器使用指定的词典
+    pub(crate) fn bound_to(dict: DictRef) -> Self {
+        Self::build(global_config().as_ref(), dict)
+    }
+
+    pub(crate) fn build(cfg: &dyn Configuration, dict: DictRef) -> Self {
+        let mut segmenters: Vec<Box<dyn Segmenter>> = vec![
+            Box::new(
+                LetterSegmenter::new()
+                    .with_connector_policy(cfg.get_connector_policy())
+                    .with_numeric_range(cfg.get_numeric_range())
+                    .with_identifier_rules(cfg.get_identifier_rules())
+                    .with_path_segments(cfg.get_path_segments()),
+            ),
+            Box::new(CnQuantifierSegmenter::new().with_dict(dict.clone())),
+            Box::new(CJKSegmenter::new().with_dict(dict.clone())),
+        ];
+        let cjk_connectors = cfg.get_cjk_connectors();
+        if !cjk_connectors.is_empty() {
+            segmenters.push(Box::new(CjkConnectorSegmenter::new(cjk_connectors)));
+        }
+        if cfg.get_reduplication() {
+            segmenters.push(Box::new(ReduplicationSegmenter::new()));
+        }
+        if cfg.get_suffix_entities() {
+            segmenters.push(Box::new(
+                SuffixEntitySegmenter::new().with_dict(dict.clone()),
+            ));
+        }
+        let full_coverage = cfg.get_full_coverage();
+        if full_coverage {
+            // 符号词典加载失败时使用默认的符号集合, 不影响分词
+            let special_segmenter = match cfg.get_special_chars_dictionary() {
+                Some(path) => SpecialSegmenter::load(&path).unwrap_or_else(|error| {
+                    log::error!("{}", error);
+                    SpecialSegmenter::new()
+                }),
+                None => SpecialSegmenter::new(),
+            };
+            segmenters.push(Box::new(special_segmenter));
+        }
+        IKSegmenter {
+            arbitrator: IKArbitrator::new(),
+            segmenters,
+            full_coverage,
+            type_priority: cfg.get_type_priority(),
+            dict,
+            interner: None,
+            auto_script: cfg.get_auto_script(),
+            auto_script_threshold: cfg.get_auto_script_threshold(),
+            keep_stop_words: cfg.get_keep_stop_words(),
+            oov_collector: None,
+            oov_pending: OovCollector::new(),
+            index_merge_quantifiers: cfg.get_index_merge_quantifiers(),
+        }
+    }
+
+    /// 使用指定的子分词器构造, 是否输出特殊符号取决于是否包含 SpecialSegmenter
+    pub fn with_segmenters(segmenters: Vec<Box<dyn Segmenter>>, full_coverage: bool) -> Self {
         IKSegmenter {
             arbitrator: IKArbitrator::new(),
-            segmenters: vec![
-                Box::new(LetterSegmenter::new()),
-                Box::new(CnQuantifierSegmenter::new()),
-                Box::new(CJKSegmenter::new()),
-            ],
+            segmenters,
+            full_coverage,
+            type_priority: DEFAULT_TYPE_PRIORITY.to_vec(),
+            dict: DictRef::Global,
+            interner: None,
+            auto_script: false,
+            auto_script_threshold: DEFAULT_AUTO_SCRIPT_THRESHOLD,
+            keep_stop_words: false,
+            oov_collector: None,
+            oov_pending: OovCollector::new(),
+            index_merge_quantifiers: false,
         }
     }
 
+    /// 设置候选词元的类型优先级, 未列出的类型优先级最低
+    pub fn with_type_priority(mut self, type_priority: Vec<LexemeType>) -> Self {
+        self.type_priority = type_priority;
+        self
+    }
+
+    /// 设置 SEARCH 模式下的歧义裁决策略
+    pub fn with_arbitration_strategy(mut self, strategy: Arc<dyn ArbitrationStrategy>) -> Self {
+        self.arbitrator = IKArbitrator::with_strategy(strategy);
+        self
+    }
+
+    /// 用 detect_script_with 检测文本的书写系统, 以字母数字为主时只运行字母数字等子分词器
+    /// 适合以英文为主的语料; 其中夹杂的汉字不再查词典, 逐字输出
+    pub fn with_auto_script(mut self, auto_script: bool) -> Self {
+        self.auto_script = auto_script;
+        self
+    }
+
+    /// 设置 auto_script 的阈值(0.5~1.0), 为 1.0 时只跳过不含中日韩文字的文本, 结果与完整流程相同
+    pub fn with_auto_script_threshold(mut self, threshold: f32) -> Self {
+        self.auto_script_threshold = threshold;
+        self
+    }
+
+    /// 使用词元文本驻留池, 重复出现的词元文本共享同一份内存
+    /// 传入 Interner::shared() 的克隆即可在多个分词器间共享
+    /// 注意 tantivy 0.18 的 Token::text 为 String, 输出到 tantivy 时仍会复制文本
+    pub fn with_interner(mut self, interner: SharedInterner) -> Self {
+        self.interner = Some(interner);
+        self
+    }
+
+    /// 是否在结果中保留停止词, 默认取自配置的 keep_stop_words(默认过滤停止词)
+    /// 高亮、精确短语匹配等需要完整词元序列的场景可以保留
+    pub fn with_keep_stop_words(mut self, keep_stop_words: bool) -> Self {
+        self.keep_stop_words = keep_stop_words;
+        self
+    }
+
+    /// INDEX 模式下是否也合并数量词, 合并后的词元(如 一两天)与其组成词元(一两、天)一同输出
+    /// 默认取自配置的 index_merge_quantifiers
+    pub fn with_index_merge_quantifiers(mut self, index_merge_quantifiers: bool) -> Self {
+        self.index_merge_quantifiers = index_merge_quantifiers;
+        self
+    }
+
+    /// 将每次分词结果中的连续单字交给 collector 统计, 用于发现词典中缺少的词
+    /// 传入 OovCollector::shared() 的克隆即可汇总多个分词器的结果; 未设置时使用
+    /// init_global_oov_collector 设置的全局收集器
+    /// 锁被其他分词器占用时计数暂存在本分词器中, 下次分词或分词器销毁时合并
+    pub fn with_oov_collector(mut self, collector: SharedOovCollector) -> Self {
+        self.oov_collector = Some(collector);
+        self
+    }
+
+    // 是否输出标点及特殊符号
+    pub fn is_full_coverage(&self) -> bool {
+        self.full_coverage
+    }
+
+    /// 按 mode 分词, 不做全角转半角, 词元的位置及字节范围对应 input_str
+    /// 需要与服务、CLI、FFI 相同的切分时使用 tokenize_halfwidth 或 tokenize_records
     pub fn tokenize(&mut self, input_str: &str, mode: TokenMode) -> Vec<Lexeme> {
-        let chars = input_str.chars().collect::<Vec<_>>();
-        // 遍历子分词器
-        let mut origin_lexemes = OrderedLinkedList::new();
-        for segmenter in self.segmenters.iter_mut() {
-            log::debug!("sub segmenter->{}", segmenter.name());
-            let lexemes = segmenter.analyze(&chars);
-            for lexeme in lexemes {
-                origin_lexemes.insert(lexeme).expect("error!");
-            }
+        let mut tokens = Vec::new();
+        self.tokenize_into(input_str, mode, &mut tokens);
+        tokens
+    }
+
+    /// 与 tokenize 相同, 但全角字符先转为半角再分词, ＡＢＣ１２３ 与 ABC123 的切分相同;
+    /// 词元文本及字节范围取自原文
+    pub fn tokenize_halfwidth(&mut self, input_str: &str, mode: TokenMode) -> Vec<Lexeme> {
+        if input_str.chars().all(|c| to_halfwidth(c) == c) {
+            return self.tokenize(input_str, mode);
+        }
+        // 转换前后字符数不变, 词元的字符位置同样适用于原文
+        let mut lexemes = self.tokenize(&to_halfwidth_str(input_str), mode);
+        for lexeme in lexemes.iter_mut() {
+            lexeme.parse_lexeme_text(input_str);
+        }
+        lexemes
+    }
+
+    /// 按 options 分词, 停止词的处理只取决于 options.keep_stop_words
+    /// lowercase 为 true 时先将输入中的英文字母转为小写, 词元的位置及字节范围不变
+    pub fn tokenize_with(&mut self, input_str: &str, options: TokenizeOptions) -> Vec<Lexeme> {
+        let mut tokens = Vec::new();
+        if options.lowercase && input_str.bytes().any(|b| b.is_ascii_uppercase()) {
+            let lowercase = input_str.to_ascii_lowercase();
+            self.segment_into(&lowercase, options, &mut tokens, None);
+        } else {
+            self.segment_into(input_str, options, &mut tokens, None);
+        }
+        tokens
+    }
+
+    /// 分词并将结果写入 out, out 原有内容会被清空
+    /// 复用同一个 out 可避免处理大量文档时反复分配结果缓冲区
+    pub fn tokenize_into(&mut self, input_str: &str, mode: TokenMode, out: &mut Vec<Lexeme>) {
+        out.clear();
+        self.segment_into(input_str, self.options(mode), out, None);
+    }
+
+    /// 分词并将布局固定的词元写入调用方提供的 buf, 返回词元总数, 见 fill_tokens
+    /// 用于 FFI 调用方复用缓冲区, 跨边界时不为每个词元分配内存
+    /// 切分同 tokenize_halfwidth, 偏移对应原文
+    pub fn tokenize_to_buffer(
+        &mut self,
+        input_str: &str,
+        mode: TokenMode,
+        buf: &mut [IkToken],
+    ) -> usize {
+        let lexemes = self.tokenize_halfwidth(input_str, mode);
+        fill_tokens(&lexemes, buf)
+    }
+
+    /// 分词并输出词元、歧义裁决区间及统计
+    pub fn segment(&mut self, input_str: &str, mode: TokenMode) -> SegmentationResult {
+        let mut tokens = Vec::new();
+        let mut sections = Vec::new();
+        let options = self.options(mode);
+        let stats = self.segment_into(input_str, options, &mut tokens, Some(&mut sections));
+        SegmentationResult::new(tokens, sections, stats)
+    }
+
+    // 按 TokenMode 分词时的选项, 停止词的处理取决于 with_keep_stop_words,
+    // INDEX 模式下是否合并数量词取决于 with_index_merge_quantifiers
+    fn options(&self, mode: TokenMode) -> TokenizeOptions {
+        let options = TokenizeOptions::new(mode);
+        TokenizeOptions {
+            keep_stop_words: self.keep_stop_words,
+            merge_quantifiers: options.merge_quantifiers
+                || (mode == TokenMode::INDEX && self.index_merge_quantifiers),
+            ..options
         }
+    }
+
+    // 分词结果追加到 out, sections 不为 None 时同时输出歧义裁决区间
+    fn segment_into(
+        &mut self,
+        input_str: &str,
+        options: TokenizeOptions,
+        out: &mut Vec<Lexeme>,
+        sections: Option<&mut Vec<Section>>,
+    ) -> SegmentationStats {
+        // 空串、纯空白、纯标点等不含有效字符的输入, 直接返回空结果
+        if !self.full_coverage && is_useless_str(input_str) {
+            return SegmentationStats::default();
+        }
+        let chars = input_str
+            .chars()
+            .collect::<SmallVec<[char; SHORT_INPUT_LEN]>>();
+        if chars.len() < SHORT_INPUT_LEN {
+            // 短输入的耗时主要在反复加锁上, 整个分词过程只对词典加一次锁
+            let dict = self.dict.clone();
+            return dict.pinned(|| self.segment_chars(input_str, &chars, options, out, sections));
+        }
+        self.segment_chars(input_str, &chars, options, out, sections)
+    }
+
+    fn segment_chars(
+        &mut self,
+        input_str: &str,
+        chars: &[char],
+        options: TokenizeOptions,
+        out: &mut Vec<Lexeme>,
+        sections: Option<&mut Vec<Section>>,
+    ) -> SegmentationStats {
+        // 遍历子分词器
+        let lattice = self.collect_lattice(chars);
+        let mut stats = SegmentationStats {
+            char_count: chars.len(),
+            candidates: lattice.len(),
+            ..SegmentationStats::default()
+        };
         // 对分词进行歧义处理
-        let mut path_map = self.arbitrator.process(&mut origin_lexemes, mode);
+        let paths = self.arbitrator.process_ordered(&lattice, options.mode);
+        if let Some(sections) = sections {
+            sections.extend(
+                paths
+                    .iter()
+                    .filter_map(|path| path.range())
+                    .map(|range| Section { range }),
+            );
+        }
         // 将分词结果输出到结果集，并处理未切分的单个CJK字符
-        let mut results = self.output_to_result(&mut path_map, &chars);
-        let mut final_results = Vec::new();
+        let mut results = output_paths(paths, chars, options.emit_single_cjk);
+        let interner = self.interner.clone();
+        let mut interner = interner.as_ref().map(|i| i.lock().unwrap());
+        // 每个字符的起始字节偏移, 只计算一次, 所有词元的字节范围及文本都取自它
+        let mut offsets = Vec::with_capacity(chars.len() + 1);
+        let mut byte_offset = 0;
+        for c in chars.iter() {
+            offsets.push(byte_offset);
+            byte_offset += c.len_utf8();
+        }
+        offsets.push(byte_offset);
+        let output_start = out.len();
         // remove stop word
         while let Some(mut result_value) = results.pop_front() {
             // 数量词合并
-            if mode == TokenMode::SEARCH {
-                self.compound(&mut results, &mut result_value);
-            }
-            if !GLOBAL_DICT.lock().unwrap().is_stop_word(
-                input_str.chars(),
-                result_value.get_begin(),
-                result_value.get_length(),
-            ) {
+            if options.merge_quantifiers && options.mode == TokenMode::SEARCH {
+                stats.compounds += compound(&mut results, &mut result_value);
+            } else if options.merge_quantifiers {
+                // 合并后的词元排在组成词元之后, 同样经过停止词过滤再输出
+                if let Some(merged) = index_compound(&results, &result_value) {
+                    stats.compounds += merged.parts().len() - 1;
+                    results.push_front(merged);
+                }
+            }
+            let is_stop_word = !options.keep_stop_words
+                && self.dict.is_stop_word(
+                    input_str,
+                    result_value.get_begin(),
+                    result_value.get_length(),
+                );
+            if is_stop_word {
+                stats.stop_words += 1;
+            } else {
                 // 不是停止词, 生成lexeme的词元文本,输出
-                result_value.parse_lexeme_text(input_str);
-                final_results.push(result_value.clone())
+                result_value.parse_lexeme_text_with(input_str, &offsets, interner.as_deref_mut());
+                out.push(result_value)
             }
         }
-        final_results
+        if let Some(collector) = self.oov_collector.as_ref().or(global_oov_collector()) {
+            self.oov_pending.observe(&out[output_start..]);
+            flush_pending(collector, &mut self.oov_pending, false);
+        }
+        stats
     }
 
-    /// 推送分词结果到结果集合
-    /// 1. 从buff头部遍历到 self.cursor 已处理位置
-    /// 2. 将map中存在的分词结果推入 results
-    /// 3. 将map中不存在的 CJDK 字符以单字方式推入 results
-    pub fn output_to_result(
+    /// 按需分词的迭代器, 输入在换行及句末标点(。！？)处切分为若干片段, 每次只对一个片段分词
+    /// 只需要前几个词元时(如标题截断、关键词预览)不必切分整个字符串
+    /// 词元的位置及字节范围相对于整个输入; 没有词元跨越这些边界, 结果与 tokenize 相同
+    pub fn tokenize_iter<'a>(
+        &'a mut self,
+        input_str: &'a str,
+        mode: TokenMode,
+    ) -> impl Iterator<Item = Lexeme> + 'a {
+        let mut rest = input_str;
+        let mut char_start = 0;
+        let mut tokens = Vec::new().into_iter();
+        std::iter::from_fn(move || loop {
+            if let Some(token) = tokens.next() {
+                return Some(token);
+            }
+            if rest.is_empty() {
+                return None;
+            }
+            let (chunk, char_count) = next_chunk(rest);
+            let byte_start = input_str.len() - rest.len();
+            let mut chunk_tokens = self.tokenize(chunk, mode);
+            for token in chunk_tokens.iter_mut() {
+                token.shift(char_start, byte_start);
+            }
+            rest = &rest[chunk.len()..];
+            char_start += char_count;
+            tokens = chunk_tokens.into_iter();
+        })
+    }
+
+    /// 分词并输出带有字符、字节、UTF-16 偏移的结果
+    /// 与 IkTokenizer 相同, 全角字符先转为半角再分词, ＡＢＣ１２３ 与 ABC123 的切分相同;
+    /// 词元文本及偏移取自原文
+    pub fn tokenize_records(&mut self, input_str: &str, mode: TokenMode) -> Vec<TokenRecord> {
+        let options = self.options(mode);
+        self.tokenize_records_with(input_str, options)
+    }
+
+    /// 按 options 分词并输出 TokenRecord, 见 tokenize_records
+    pub fn tokenize_records_with(
         &mut self,
-        path_map: &mut HashMap<usize, LexemePath>,
-        input: &[char],
-    ) -> LinkedList<Lexeme> {
-        let mut results = LinkedList::new();
-        let mut index = 0usize;
-        let char_count = input.len();
-        while index < char_count {
-            let curr_char = input[index];
-            let cur_char_type = char_type_of(&curr_char);
-            // 跳过非CJK字符
-            if CharType::USELESS == cur_char_type {
-                index += 1;
+        input_str: &str,
+        options: TokenizeOptions,
+    ) -> Vec<TokenRecord> {
+        let lexemes = self.tokenize_with(&to_halfwidth_str(input_str), options);
+        TokenRecord::from_lexemes(input_str, &lexemes)
+    }
+
+    /// 分词并连同原文及模式一起返回, 便于序列化输出
+    pub fn tokenize_result(&mut self, input_str: &str, mode: TokenMode) -> TokenizationResult {
+        let tokens = self.tokenize(input_str, mode);
+        TokenizationResult::new(input_str, mode, tokens)
+    }
+
+    /// 依次对 texts 分词, 文档编号为其在 texts 中的下标
+    pub fn tokenize_batch<I, S>(&mut self, texts: I, mode: TokenMode) -> TokenBatch
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut batch = TokenBatch::new();
+        let mut lexemes = Vec::new();
+        for (doc_id, text) in texts.into_iter().enumerate() {
+            self.tokenize_into(text.as_ref(), mode, &mut lexemes);
+            batch.push(doc_id as u64, &lexemes);
+        }
+        batch
+    }
+
+    /// 分别以 SEARCH 和 INDEX 模式分词, 并将两者的词元按区间对齐
+    pub fn tokenize_aligned(&mut self, input_str: &str) -> Vec<Alignment> {
+        let search = self.tokenize(input_str, TokenMode::SEARCH);
+        let index = self.tokenize(input_str, TokenMode::INDEX);
+        align(&search, &index)
+    }
+
+    /// 只做候选词生成, 不做歧义裁决
+    pub fn build_lattice(&mut self, input_str: &str) -> Lattice {
+        let chars = input_str.chars().collect::<Vec<_>>();
+        self.collect_lattice(&chars)
+    }
+
+    /// 遍历子分词器, 收集候选词元
+    /// 同一位置、相同长度的词元只保留一个, 按 type_priority 决定保留哪个类型,
+    /// 优先级相同时保留先注册的子分词器输出的词元
+    fn collect_lattice(&mut self, chars: &[char]) -> Lattice {
+        let mut candidates = Vec::new();
+        let skip_cjk = self.auto_script
+            && matches!(
+                detect_script_with(chars.iter().copied(), self.auto_script_threshold),
+                Script::Latin | Script::Unknown
+            );
+        for segmenter in self.segmenters.iter_mut() {
+            if skip_cjk && segmenter.is_cjk_only() {
                 continue;
             }
-            // 从pathMap找出对应index位置的LexemePath
-            let mut path = path_map.get_mut(&index);
-            if path.is_some() {
-                // 输出LexemePath中的lexeme到results集合
-                let mut l = path.as_mut().unwrap().poll_first();
-                while l.is_some() {
-                    let l_value = l.as_ref().unwrap();
-                    results.push_back(l_value.clone());
-                    // 将index移至lexeme后
-                    index = l_value.get_begin() + l_value.get_length();
-                    l = path.as_mut().unwrap().poll_first();
-                    if l.is_some() {
-                        let new_l_value = l.as_ref().unwrap();
-                        // 输出path内部，词元间遗漏的单字
-                        while index < new_l_value.get_begin() {
-                            let curr_char = input[index];
-                            let cur_char_type = char_type_of(&curr_char);
-                            if CharType::CHINESE == cur_char_type {
-                                let single_char_lexeme =
-                                    Lexeme::new(0, index, 1, LexemeType::CNCHAR);
-                                results.push_back(single_char_lexeme);
-                            } else if CharType::OtherCjk == cur_char_type {
-                                let single_char_lexeme =
-                                    Lexeme::new(0, index, 1, LexemeType::OtherCJK);
-                                results.push_back(single_char_lexeme);
-                            }
-                            index += 1;
-                        }
-                    }
-                }
-            } else {
-                // pathMap中找不到index对应的LexemePath, 单字输出
-                let curr_char = input[index];
-                let cur_char_type = char_type_of(&curr_char);
-                if CharType::CHINESE == cur_char_type {
-                    let single_char_lexeme = Lexeme::new(0, index, 1, LexemeType::CNCHAR);
-                    results.push_back(single_char_lexeme);
-                } else if CharType::OtherCjk == cur_char_type {
-                    let single_char_lexeme = Lexeme::new(0, index, 1, LexemeType::OtherCJK);
-                    results.push_back(single_char_lexeme);
-                }
-                index += 1;
+            log::debug!("sub segmenter->{}", segmenter.name());
+            candidates.extend(segmenter.analyze(chars));
+        }
+        let rank = |lexeme: &Lexeme| {
+            self.type_priority
+                .iter()
+                .position(|t| *t == lexeme.lexeme_type)
+                .unwrap_or(self.type_priority.len())
+        };
+        // 稳定排序: 位置优先, 其次类型优先级
+        candidates.sort_by(|a, b| a.cmp(b).then_with(|| rank(a).cmp(&rank(b))));
+        let mut lattice = Lattice::new(chars.len());
+        for lexeme in candidates {
+            // 重复的词元不会被插入, 因此排在前面的胜出
+            lattice.insert(lexeme);
+        }
+        lattice
+    }
+}
+
+// 从 input 开头取出一个片段, 至少 ITER_CHUNK_LEN 个字符, 在其后的第一个句子边界处结束
+// 返回片段及其字符数
+fn next_chunk(input: &str) -> (&str, usize) {
+    let mut char_count = 0;
+    for (i, c) in input.char_indices() {
+        char_count += 1;
+        if char_count >= ITER_CHUNK_LEN && matches!(c, '\n' | '\r' | '。' | '！' | '？') {
+            return (&input[..i + c.len_utf8()], char_count);
+        }
+    }
+    (input, char_count)
+}
+
+// 组合词元, 返回合并的词元数
+fn compound(results: &mut LinkedList<Lexeme>, result: &mut Lexeme) -> usize {
+    let mut merged = 0;
+    // 数量词合并处理
+    if !results.is_empty() {
+        if LexemeType::ARABIC == result.lexeme_type {
+            let next_lexeme = results.front();
+            let mut append_ok = false;
+            if LexemeType::CNUM == next_lexeme.unwrap().lexeme_type {
+                // 合并英文数词+中文数词
+                append_ok = result.append(next_lexeme.unwrap(), LexemeType::CNUM);
+            } else if LexemeType::COUNT == next_lexeme.unwrap().lexeme_type {
+                // 合并英文数词+中文量词
+                append_ok = result.append(next_lexeme.unwrap(), LexemeType::CQUAN);
+            }
+            if append_ok {
+                // 弹出
+                results.pop_front();
+                merged += 1;
             }
         }
+        // 可能存在第二轮合并
+        if LexemeType::CNUM == result.lexeme_type && !results.is_empty() {
+            let next_lexeme = results.front(); // p peekFirst();
+            let mut append_ok = false;
+            if LexemeType::COUNT == next_lexeme.unwrap().lexeme_type {
+                // 合并中文数词+中文量词
+                append_ok = result.append(next_lexeme.unwrap(), LexemeType::CQUAN);
+            }
+            if append_ok {
+                results.pop_front();
+                merged += 1;
+            }
+        }
+    }
+    merged
+}
+
+// INDEX 模式下的数量词合并, 规则与 compound 相同, 但按位置查找紧邻的词元, 且不移除组成词元
+// 没有可合并的词元时返回 None
+fn index_compound(results: &LinkedList<Lexeme>, result: &Lexeme) -> Option<Lexeme> {
+    // results 按起始位置排列, 查找从 position 开始的 lexeme_type 类型的词元
+    let next = |position: usize, lexeme_type: LexemeType| {
         results
+            .iter()
+            .take_while(|l| l.get_begin() <= position)
+            .find(|l| l.get_begin() == position && l.lexeme_type == lexeme_type)
+    };
+    let mut merged = result.clone();
+    let mut append_ok = false;
+    if LexemeType::ARABIC == merged.lexeme_type {
+        let end = merged.get_end_position();
+        if let Some(cnum) = next(end, LexemeType::CNUM) {
+            // 合并英文数词+中文数词
+            append_ok = merged.append(cnum, LexemeType::CNUM);
+        } else if let Some(count) = next(end, LexemeType::COUNT) {
+            // 合并英文数词+中文量词
+            append_ok = merged.append(count, LexemeType::CQUAN);
+        }
     }
+    // 可能存在第二轮合并
+    if LexemeType::CNUM == merged.lexeme_type {
+        if let Some(count) = next(merged.get_end_position(), LexemeType::COUNT) {
+            // 合并中文数词+中文量词
+            append_ok |= merged.append(count, LexemeType::CQUAN);
+        }
+    }
+    if append_ok {
+        Some(merged)
+    } else {
+        None
+    }
+}
 
-    // 组合词元
-    pub fn compound(&mut self, results: &mut LinkedList<Lexeme>, result: &mut Lexeme) {
-        // 数量词合并处理
-        if !results.is_empty() {
-            if LexemeType::ARABIC == result.lexeme_type {
-                let next_lexeme = results.front();
-                let mut append_ok = false;
-                if LexemeType::CNUM == next_lexeme.unwrap().lexeme_type {
-                    // 合并英文数词+中文数词
-                    append_ok = result.append(next_lexeme.unwrap(), LexemeType::CNUM);
-                } else if LexemeType::COUNT == next_lexeme.unwrap().lexeme_type {
-                    // 合并英文数词+中文量词
-                    append_ok = result.append(next_lexeme.unwrap(), LexemeType::CQUAN);
-                }
-                if append_ok {
-                    // 弹出
-                    results.pop_front();
-                }
-            }
-            // 可能存在第二轮合并
-            if LexemeType::CNUM == result.lexeme_type && !results.is_empty() {
-                let next_lexeme = results.front(); // p peekFirst();
-                let mut append_ok = false;
-                if LexemeType::COUNT == next_lexeme.unwrap().lexeme_type {
-                    // 合并中文数词+中文量词
-                    append_ok = result.append(next_lexeme.unwrap(), LexemeType::CQUAN);
-                }
-                if append_ok {
-                    results.pop_front();
+// 按起始位置依次输出各条 LexemePath 中的词元, 不在任何 path 中的 CJK 字符以单字输出
+// emit_single_cjk 为 false 时不输出单字, 表情符号不受影响
+fn output_paths(
+    paths: Vec<LexemePath>,
+    input: &[char],
+    emit_single_cjk: bool,
+) -> LinkedList<Lexeme> {
+    let mut results = LinkedList::new();
+    let mut paths = paths.into_iter().peekable();
+    let mut index = 0usize;
+    let char_count = input.len();
+    while index < char_count {
+        // 跳过起始位置已被越过的path
+        while paths
+            .next_if(|path| (path.get_path_begin() as usize) < index)
+            .is_some()
+        {}
+        // 找出对应index位置的LexemePath
+        if let Some(path) = paths.next_if(|path| path.get_path_begin() as usize == index) {
+            // 输出LexemePath中的lexeme到results集合
+            let mut lexemes = path.into_lexemes().into_iter();
+            let mut l = lexemes.next();
+            while let Some(l_value) = l {
+                // 将index移至lexeme后
+                index = l_value.get_begin() + l_value.get_length();
+                results.push_back(l_value);
+                l = lexemes.next();
+                if let Some(new_l_value) = l.as_ref() {
+                    // 输出path内部，词元间遗漏的单字
+                    while index < new_l_value.get_begin() {
+                        index = output_single(
+                            input,
+                            index,
+                            new_l_value.get_begin(),
+                            emit_single_cjk,
+                            &mut results,
+                        );
+                    }
                 }
             }
+        } else {
+            // pathMap中找不到index对应的LexemePath, 单字输出
+            let limit = paths
+                .peek()
+                .map(|path| path.get_path_begin() as usize)
+                .unwrap_or(char_count);
+            index = output_single(input, index, limit, emit_single_cjk, &mut results);
         }
     }
+    results
+}
+
+// 输出 index 处未被词元覆盖的单字, 表情符号序列整体输出且不越过 limit, 返回下一个位置
+fn output_single(
+    input: &[char],
+    index: usize,
+    limit: usize,
+    emit_single_cjk: bool,
+    results: &mut LinkedList<Lexeme>,
+) -> usize {
+    let curr_char = input[index];
+    let cur_char_type = char_type_of(&curr_char);
+    if !emit_single_cjk && matches!(cur_char_type, CharType::CHINESE | CharType::OtherCjk) {
+        return index + 1;
+    }
+    if CharType::CHINESE == cur_char_type {
+        let single_char_lexeme = Lexeme::new(0, index, 1, LexemeType::CNCHAR);
+        results.push_back(single_char_lexeme);
+    } else if CharType::OtherCjk == cur_char_type {
+        let single_char_lexeme = Lexeme::new(0, index, 1, LexemeType::OtherCJK);
+        results.push_back(single_char_lexeme);
+    } else if CharType::EMOJI == cur_char_type {
+        if let Some(end) = match_emoji(input, index) {
+            let end = end.min(limit).max(index + 1);
+            results.push_back(Lexeme::new(0, index, end - index, LexemeType::EMOJI));
+            return end;
+        }
+    }
+    index + 1
+}
+
+// 输入中是否不含任何可切分的字符
+pub(crate) fn is_useless_str(input_str: &str) -> bool {
+    input_str.chars().all(|c| is_useless_char(&c))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::core::interner::Interner;
+    use crate::core::test_util::base_segmenters;
+
+    #[test]
+    fn test_send() {
+        // 子分词器均为 Send, IKSegmenter 无需 unsafe impl 即可移动到其他线程
+        fn assert_send<T: Send>() {}
+        assert_send::<IKSegmenter>();
+        let mut ik = IKSegmenter::new();
+        let tokens = std::thread::spawn(move || ik.tokenize("中华人民共和国", TokenMode::SEARCH))
+            .join()
+            .unwrap();
+        assert_eq!(tokens.len(), 1);
+    }
 
     #[test]
     fn test_index_segment() {
@@ -232,6 +883,586 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_tokenize_records() {
+        let mut ik = IKSegmenter::new();
+        let text = "😀中华人民共和国";
+        let records = ik.tokenize_records(text, TokenMode::SEARCH);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].text, "😀");
+        assert_eq!(records[0].offsets.utf16, 0..2);
+        assert_eq!(records[1].text, "中华人民共和国");
+        assert_eq!(records[1].offsets.chars, 1..8);
+        assert_eq!(records[1].offsets.bytes, 4..25);
+        assert_eq!(records[1].offsets.utf16, 2..9);
+    }
+
+    #[test]
+    fn test_reduplication() {
+        let text = "大家干干净净, 讨论讨论再瞧瞧";
+        let segmenters = |reduplication: bool| {
+            let mut segmenters = base_segmenters();
+            if reduplication {
+                segmenters.push(Box::new(ReduplicationSegmenter::new()));
+            }
+            segmenters
+        };
+        let texts = |ik: &mut IKSegmenter| {
+            ik.tokenize(text, TokenMode::SEARCH)
+                .iter()
+                .map(|t| t.get_lexeme_text().to_string())
+                .collect::<Vec<_>>()
+        };
+        let plain = texts(&mut IKSegmenter::with_segmenters(segmenters(false), false));
+        assert!(!plain.contains(&"讨论讨论".to_string()), "{:?}", plain);
+        let tokens = texts(&mut IKSegmenter::with_segmenters(segmenters(true), false));
+        assert_eq!(tokens, vec!["大家", "干干净净", "讨论讨论", "再", "瞧瞧"],);
+        let mut ik = IKSegmenter::with_segmenters(segmenters(true), false);
+        let tokens = ik
+            .tokenize("你是不是不想去", TokenMode::SEARCH)
+            .iter()
+            .map(|t| t.get_lexeme_text().to_string())
+            .collect::<Vec<_>>();
+        assert!(!tokens.contains(&"是不是不".to_string()), "{:?}", tokens);
+    }
+
+    #[test]
+    fn test_suffix_entities() {
+        let text = "他在嵘峰公司上班, 住在沧澜路";
+        let tokens = |suffix_entities: bool| {
+            let mut segmenters = base_segmenters();
+            if suffix_entities {
+                segmenters.push(Box::new(SuffixEntitySegmenter::new()));
+            }
+            IKSegmenter::with_segmenters(segmenters, false)
+                .tokenize(text, TokenMode::SEARCH)
+                .iter()
+                .map(|t| {
+                    (
+                        t.get_lexeme_text().to_string(),
+                        t.get_lexeme_type_string().to_string(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let plain = tokens(false);
+        assert!(
+            plain.iter().all(|(text, _)| text != "嵘峰公司"),
+            "{:?}",
+            plain
+        );
+        let entities = tokens(true)
+            .into_iter()
+            .filter(|(_, lexeme_type)| lexeme_type == "ORG" || lexeme_type == "LOC")
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entities,
+            vec![
+                ("嵘峰公司".to_string(), "ORG".to_string()),
+                ("沧澜路".to_string(), "LOC".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_iter() {
+        let sentence =
+            "张华考上了北京大学；李萍进了中等技术学校。我在百货公司当售货员, 花了五十元！\n";
+        let text = sentence.repeat(8);
+        let mut ik = IKSegmenter::new();
+        let summary = |tokens: &[Lexeme]| {
+            tokens
+                .iter()
+                .map(|t| {
+                    (
+                        t.get_lexeme_text().to_string(),
+                        t.get_begin(),
+                        t.byte_range(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+            let expected = ik.tokenize(&text, mode);
+            let actual = ik.tokenize_iter(&text, mode).collect::<Vec<_>>();
+            assert_eq!(summary(&actual), summary(&expected));
+            for token in actual.iter() {
+                assert_eq!(&text[token.byte_range()], token.get_lexeme_text());
+            }
+        }
+        let first = ik
+            .tokenize_iter(&text, TokenMode::SEARCH)
+            .take(3)
+            .map(|t| t.get_lexeme_text().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(first, vec!["张华", "考", "上了"]);
+        assert_eq!(next_chunk("短文本。"), ("短文本。", 4));
+        assert_eq!(ik.tokenize_iter("", TokenMode::SEARCH).count(), 0);
+    }
+
+    #[test]
+    fn test_byte_range() {
+        let text = "😀张华考上了北京大学, iPhone 12售价5999元";
+        let mut ik = IKSegmenter::new();
+        for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+            for token in ik.tokenize(text, mode) {
+                assert_eq!(&text[token.byte_range()], token.get_lexeme_text());
+                for part in token.parts() {
+                    assert_eq!(&text[part.byte_range()], part.get_lexeme_text());
+                }
+            }
+        }
+        let tokens = ik.tokenize("价格五十元", TokenMode::SEARCH);
+        let last = tokens.last().unwrap();
+        assert_eq!(last.byte_range(), 6..15);
+        assert_eq!(last.parts()[1].byte_range(), 12..15);
+    }
+
+    #[test]
+    fn test_emoji() {
+        let text = "今天😀很开心👍🏻, 去𠀀北京🇨🇳";
+        let mut ik = IKSegmenter::new();
+        for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+            let tokens = ik.tokenize(text, mode);
+            for token in tokens.iter() {
+                assert_eq!(&text[token.byte_range()], token.get_lexeme_text());
+            }
+            let emojis = tokens
+                .iter()
+                .filter(|t| t.lexeme_type == LexemeType::EMOJI)
+                .map(|t| t.get_lexeme_text())
+                .collect::<Vec<_>>();
+            assert_eq!(emojis, vec!["😀", "👍🏻", "🇨🇳"]);
+            assert!(tokens.iter().any(|t| t.get_lexeme_text() == "𠀀"));
+        }
+        assert_eq!(ik.tokenize("😀", TokenMode::SEARCH).len(), 1);
+    }
+
+    #[test]
+    fn test_tokenize_to_buffer() {
+        let text = "iPhone卖五千块";
+        let mut ik = IKSegmenter::new();
+        let lexemes = ik.tokenize(text, TokenMode::SEARCH);
+        let mut buf = vec![IkToken::default(); 8];
+        let count = ik.tokenize_to_buffer(text, TokenMode::SEARCH, &mut buf);
+        assert_eq!(count, lexemes.len());
+        for (token, lexeme) in buf[..count].iter().zip(lexemes.iter()) {
+            let bytes = token.begin_byte as usize..token.end_byte as usize;
+            assert_eq!(&text[bytes], lexeme.get_lexeme_text());
+            assert_eq!(LexemeType::from_id(token.type_id), Some(lexeme.lexeme_type));
+        }
+        assert_eq!(
+            ik.tokenize_to_buffer(text, TokenMode::SEARCH, &mut []),
+            count
+        );
+    }
+
+    #[test]
+    fn test_tokenize_halfwidth() {
+        let mut ik = IKSegmenter::new();
+        let text = "ＡＢＣ１２３块";
+        let halfwidth = ik.tokenize("ABC123块", TokenMode::INDEX);
+        let lexemes = ik.tokenize_halfwidth(text, TokenMode::INDEX);
+        assert_eq!(
+            lexemes.iter().map(Lexeme::range).collect::<Vec<_>>(),
+            halfwidth.iter().map(Lexeme::range).collect::<Vec<_>>()
+        );
+        for lexeme in lexemes.iter() {
+            assert_eq!(&text[lexeme.byte_range()], lexeme.get_lexeme_text());
+        }
+        assert_eq!(lexemes[0].get_lexeme_text(), "ＡＢＣ１２３");
+        // 没有全角字符时与 tokenize 相同
+        assert_eq!(
+            ik.tokenize_halfwidth("ABC123块", TokenMode::INDEX),
+            halfwidth
+        );
+    }
+
+    #[test]
+    fn test_tokenize_with() {
+        let text = "IKAnalyzer的作者花了五十元";
+        let mut ik = IKSegmenter::new();
+        let texts = |tokens: Vec<Lexeme>| {
+            tokens
+                .iter()
+                .map(|t| t.get_lexeme_text().to_string())
+                .collect::<Vec<_>>()
+        };
+        for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+            assert_eq!(
+                texts(ik.tokenize_with(text, mode.into())),
+                texts(ik.tokenize(text, mode))
+            );
+        }
+        let options = TokenizeOptions::new(TokenMode::SEARCH);
+        assert!(texts(ik.tokenize_with(text, options)).contains(&"五十元".to_string()));
+        let tokens = texts(ik.tokenize_with(
+            text,
+            TokenizeOptions {
+                merge_quantifiers: false,
+                keep_stop_words: true,
+                lowercase: true,
+                ..options
+            },
+        ));
+        assert_eq!(
+            tokens,
+            vec!["ikanalyzer", "的", "作者", "花了", "五十", "元"]
+        );
+        let tokens = ik.tokenize_with(
+            "的",
+            TokenizeOptions {
+                keep_stop_words: true,
+                emit_single_cjk: false,
+                ..options
+            },
+        );
+        assert!(tokens.is_empty());
+    }
+
+    #[test]
+    fn test_segment() {
+        let mut ik = IKSegmenter::new();
+        let result = ik.segment("五十元", TokenMode::SEARCH);
+        let texts = result
+            .tokens()
+            .iter()
+            .map(|t| t.get_lexeme_text())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["五十元"]);
+        let parts = result.tokens()[0]
+            .parts()
+            .iter()
+            .map(|p| (p.get_lexeme_text(), p.position_range(), p.lexeme_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parts,
+            vec![
+                ("五十", 0..2, LexemeType::CNUM),
+                ("元", 2..3, LexemeType::COUNT)
+            ]
+        );
+        assert_eq!(result.stats().char_count, 3);
+        assert_eq!(result.stats().compounds, 1);
+        assert!(result.stats().candidates >= result.tokens().len());
+        let sections = result.sections();
+        assert!(!sections.is_empty());
+        assert!(sections
+            .windows(2)
+            .all(|w| w[0].range.end <= w[1].range.start));
+        assert!(ik.segment("  ", TokenMode::SEARCH).tokens().is_empty());
+    }
+
+    #[test]
+    fn test_index_merge_quantifiers() {
+        let texts = |ik: &mut IKSegmenter, text: &str| {
+            ik.tokenize(text, TokenMode::INDEX)
+                .iter()
+                .map(|t| t.get_lexeme_text().to_string())
+                .collect::<Vec<_>>()
+        };
+        let mut ik = IKSegmenter::new();
+        assert_eq!(texts(&mut ik, "一两天"), vec!["一两", "两天", "天"]);
+        let mut ik = IKSegmenter::new().with_index_merge_quantifiers(true);
+        assert_eq!(
+            texts(&mut ik, "一两天"),
+            vec!["一两", "一两天", "两天", "天"]
+        );
+        assert_eq!(
+            texts(&mut ik, "五十元钱"),
+            vec!["五十", "五十元", "十元", "元", "钱"]
+        );
+        let result = ik.segment("五十元钱", TokenMode::INDEX);
+        assert_eq!(result.stats().compounds, 1);
+        let merged = &result.tokens()[1];
+        assert_eq!(merged.lexeme_type, LexemeType::CQUAN);
+        assert_eq!(merged.parts()[1].get_lexeme_text(), "元");
+        // SEARCH 模式不受影响
+        assert_eq!(
+            ik.tokenize("五十元钱", TokenMode::SEARCH)
+                .iter()
+                .map(|t| t.get_lexeme_text())
+                .collect::<Vec<_>>(),
+            vec!["五十元", "钱"]
+        );
+    }
+
+    #[test]
+    fn test_with_dictionary() {
+        use crate::dict::trie::Trie;
+
+        let mut main_dict = Trie::default();
+        main_dict.insert("区块链".chars());
+        main_dict.insert("钱包".chars());
+        let mut quantifier_dict = Trie::default();
+        quantifier_dict.insert("个".chars());
+        let dict = Dictionary::with_dicts(main_dict, Trie::default(), quantifier_dict);
+        let mut ik = IKSegmenter::with_dictionary(Arc::new(dict));
+        let texts = ik
+            .tokenize("区块链钱包北京", TokenMode::SEARCH)
+            .iter()
+            .map(|t| t.get_lexeme_text().to_string())
+            .collect::<Vec<_>>();
+        // 独立词典中没有"北京", 以单字输出
+        assert_eq!(texts, vec!["区块链", "钱包", "北", "京"]);
+        let texts = IKSegmenter::new()
+            .tokenize("区块链钱包北京", TokenMode::SEARCH)
+            .iter()
+            .map(|t| t.get_lexeme_text().to_string())
+            .collect::<Vec<_>>();
+        assert!(texts.contains(&"北京".to_string()));
+    }
+
+    #[test]
+    fn test_freq_arbitration() {
+        use crate::dict::trie::Trie;
+
+        // 甲/乙丙丁/戊己 与 甲乙/丙/丁戊己 的各项规则都相同, 由词频决定
+        let text = "甲乙丙丁戊己";
+        let words = ["甲", "乙丙丁", "戊己", "甲乙", "丙", "丁戊己"];
+        let tokenize = |freqs: [u32; 6]| {
+            let mut main_dict = Trie::default();
+            for (word, freq) in words.iter().zip(freqs) {
+                main_dict.insert_with_freq(word.chars(), freq);
+            }
+            let dict = Dictionary::with_dicts(main_dict, Trie::default(), Trie::default());
+            IKSegmenter::with_dictionary(Arc::new(dict))
+                .tokenize(text, TokenMode::SEARCH)
+                .iter()
+                .map(|t| t.get_lexeme_text().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tokenize([1, 1, 1, 93, 43, 1]), vec!["甲乙", "丙", "丁戊己"]);
+        assert_eq!(tokenize([1, 93, 43, 1, 1, 1]), vec!["甲", "乙丙丁", "戊己"]);
+        // 没有词频时由最终裁决决定: 首个词元起始位置相同, 较长者更优
+        assert_eq!(tokenize([0; 6]), vec!["甲乙", "丙", "丁戊己"]);
+    }
+
+    #[test]
+    fn test_pos() {
+        use crate::dict::trie::Trie;
+
+        let mut main_dict = Trie::default();
+        main_dict.insert_with_pos("北京".chars(), 10, Some("ns"));
+        main_dict.insert_with_pos("欢迎".chars(), 5, Some("v"));
+        let dict = Dictionary::with_dicts(main_dict, Trie::default(), Trie::default());
+        let mut ik = IKSegmenter::with_dictionary(Arc::new(dict));
+        let tokens = ik.tokenize("北京欢迎你", TokenMode::SEARCH);
+        let tags = tokens
+            .iter()
+            .map(|t| (t.get_lexeme_text(), t.get_pos(), t.get_freq()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            vec![
+                ("北京", Some("ns"), 10),
+                ("欢迎", Some("v"), 5),
+                ("你", None, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_into() {
+        let mut ik = IKSegmenter::new();
+        let mut out = Vec::with_capacity(64);
+        ik.tokenize_into("中华人民共和国", TokenMode::SEARCH, &mut out);
+        assert_eq!(out.len(), 1);
+        let capacity = out.capacity();
+        ik.tokenize_into("北京大学生活", TokenMode::INDEX, &mut out);
+        let expected = ik.tokenize("北京大学生活", TokenMode::INDEX);
+        assert_eq!(out, expected);
+        assert_eq!(out.capacity(), capacity);
+        ik.tokenize_into("   ", TokenMode::INDEX, &mut out);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_numeral() {
+        let mut ik = IKSegmenter::new();
+        let tokens = ik.tokenize("第Ⅷ章第⑫条", TokenMode::SEARCH);
+        let numbers = tokens
+            .iter()
+            .filter(|t| t.lexeme_type == LexemeType::ARABIC)
+            .map(|t| (t.get_lexeme_text(), t.get_numeric_value()))
+            .collect::<Vec<_>>();
+        assert_eq!(numbers, vec![("Ⅷ", Some(8)), ("⑫", Some(12))]);
+
+        // 相邻的数字各自输出, 均有数值
+        let tokens = ik.tokenize("步骤①②③及1①", TokenMode::SEARCH);
+        let numbers = tokens
+            .iter()
+            .filter(|t| t.lexeme_type == LexemeType::ARABIC)
+            .map(|t| (t.get_lexeme_text(), t.get_numeric_value()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            numbers,
+            vec![
+                ("①", Some(1)),
+                ("②", Some(2)),
+                ("③", Some(3)),
+                ("1", Some(1)),
+                ("①", Some(1))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_full_coverage() {
+        let mut segmenters = base_segmenters();
+        segmenters.push(Box::new(SpecialSegmenter::new()));
+        let mut ik = IKSegmenter::with_segmenters(segmenters, true);
+        let text = "他说：“好的——明天见…”、《书》/usr/local";
+        for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+            let tokens = ik.tokenize(text, mode);
+            let specials = tokens
+                .iter()
+                .filter(|t| t.lexeme_type == LexemeType::SPECIAL)
+                .map(|t| t.get_lexeme_text())
+                .collect::<String>();
+            assert_eq!(specials, "：“——…”、《》//");
+        }
+        let tokens = ik.tokenize("，。", TokenMode::SEARCH);
+        assert_eq!(tokens.len(), 2);
+    }
+
+    #[test]
+    fn test_cjk_connectors() {
+        let mut segmenters = base_segmenters();
+        segmenters.push(Box::new(CjkConnectorSegmenter::new(vec!['_'])));
+        let mut ik = IKSegmenter::with_segmenters(segmenters, false);
+        let texts = |tokens: Vec<Lexeme>| {
+            tokens
+                .iter()
+                .map(|t| t.get_lexeme_text().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            texts(ik.tokenize("你好_世界", TokenMode::SEARCH)),
+            vec!["你好_世界"]
+        );
+        let index = texts(ik.tokenize("你好_世界", TokenMode::INDEX));
+        assert!(index.contains(&"你好_世界".to_string()));
+        assert!(index.contains(&"世界".to_string()));
+    }
+
+    #[test]
+    fn test_type_priority() {
+        let type_of = |ik: &mut IKSegmenter| {
+            ik.tokenize("十八日", TokenMode::INDEX)
+                .iter()
+                .find(|t| t.get_lexeme_text() == "十八")
+                .map(|t| t.lexeme_type)
+        };
+        let mut ik = IKSegmenter::new();
+        assert_eq!(type_of(&mut ik), Some(LexemeType::CNUM));
+        let mut ik = IKSegmenter::new().with_type_priority(vec![LexemeType::CNWORD]);
+        assert_eq!(type_of(&mut ik), Some(LexemeType::CNWORD));
+    }
+
+    #[test]
+    fn test_interner() {
+        let interner = Interner::shared();
+        let mut a = IKSegmenter::new().with_interner(interner.clone());
+        let mut b = IKSegmenter::new().with_interner(interner.clone());
+        let x = a.tokenize("北京大学", TokenMode::SEARCH);
+        let y = b.tokenize("我在北京大学", TokenMode::SEARCH);
+        let x = x[0].get_shared_text().unwrap();
+        let y = y.last().unwrap().get_shared_text().unwrap();
+        assert_eq!(x.as_ref(), "北京大学");
+        assert!(Arc::ptr_eq(x, y));
+    }
+
+    #[test]
+    fn test_auto_script_threshold() {
+        let text = "the quick brown fox jumps over the lazy dog 狐狸";
+        let texts = |ik: &mut IKSegmenter| {
+            ik.tokenize(text, TokenMode::INDEX)
+                .iter()
+                .map(|l| l.get_lexeme_text().to_string())
+                .collect::<Vec<_>>()
+        };
+        // 以英文为主的文本跳过中文子分词器, 汉字逐字输出
+        let mut auto = IKSegmenter::new().with_auto_script(true);
+        let tokens = texts(&mut auto);
+        assert!(!tokens.contains(&"狐狸".to_string()), "{:?}", tokens);
+        assert!(tokens.ends_with(&["狐".to_string(), "狸".to_string()]));
+        // 阈值为 1.0 时只跳过不含中日韩文字的文本
+        let mut exact = IKSegmenter::new()
+            .with_auto_script(true)
+            .with_auto_script_threshold(1.0);
+        let full = texts(&mut IKSegmenter::new());
+        assert!(full.contains(&"狐狸".to_string()));
+        assert_eq!(texts(&mut exact), full);
+    }
+
+    #[test]
+    fn test_auto_script() {
+        let mut auto = IKSegmenter::new().with_auto_script(true);
+        let mut full = IKSegmenter::new();
+        for text in ["hello world 2022", "iphone14 手机", "北京大学"] {
+            for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+                let a = auto.tokenize(text, mode);
+                let b = full.tokenize(text, mode);
+                assert_eq!(a.len(), b.len());
+                for (x, y) in a.iter().zip(b.iter()) {
+                    assert_eq!(x.get_lexeme_text(), y.get_lexeme_text());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokenize_aligned() {
+        let mut ik = IKSegmenter::new();
+        let alignments = ik.tokenize_aligned("张华考上了北京大学");
+        let parent = alignments
+            .iter()
+            .find(|a| a.parent.get_lexeme_text() == "北京大学")
+            .unwrap();
+        let children = parent
+            .children
+            .iter()
+            .map(|l| l.get_lexeme_text())
+            .collect::<Vec<_>>();
+        assert!(children.contains(&"北京大学"));
+        assert!(children.contains(&"北京"));
+        assert!(children.contains(&"大学"));
+    }
+
+    #[test]
+    fn test_build_lattice() {
+        let mut ik = IKSegmenter::new();
+        let lattice = ik.build_lattice("中华人民共和国");
+        assert_eq!(lattice.char_count(), 7);
+        let words = lattice
+            .starting_at(0)
+            .iter()
+            .map(|l| l.get_length())
+            .collect::<Vec<_>>();
+        assert_eq!(words.first(), Some(&7));
+        assert!(words.contains(&2));
+    }
+
+    #[test]
+    fn test_useless_input() {
+        let mut ik = IKSegmenter::new();
+        for text in ["", " ", " \t\r\n", "，。！？", "!@#$%^&*()"] {
+            for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+                let tokens = ik.tokenize(text, mode);
+                assert!(tokens.is_empty());
+                assert_eq!(tokens.capacity(), 0);
+            }
+        }
+        // 全角标点只在整个输入都是无效字符时才被忽略, 其余情况与原有的切分结果一致
+        let tokens = ik.tokenize("你好！世界", TokenMode::INDEX);
+        let texts = tokens
+            .iter()
+            .map(|t| t.get_lexeme_text())
+            .collect::<Vec<_>>();
+        assert!(texts.contains(&"！"));
+    }
+
     fn _get_input_texts() -> Vec<&'static str> {
         let texts = vec![
             "张三说的确实在理",
@@ -0,0 +1,85 @@
+use crate::core::lexeme::Lexeme;
+
+/// 布局固定的词元, 用于 FFI 调用方提供缓冲区、零拷贝地读取分词结果
+/// 与 C 中的 struct { uint32_t begin_byte, end_byte, type_id, position; } 相同, 共 16 字节
+/// 偏移以 UTF-8 字节为单位, 文本长度需小于 4GiB
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct IkToken {
+    // 词元在原文中的起始字节偏移
+    pub begin_byte: u32,
+    // 词元在原文中的结束字节偏移(不包含)
+    pub end_byte: u32,
+    // 词元类型, 见 LexemeType::id
+    pub type_id: u32,
+    // 词元在输出中的序号
+    pub position: u32,
+}
+
+impl IkToken {
+    pub fn from_lexeme(lexeme: &Lexeme, position: usize) -> Self {
+        let bytes = lexeme.byte_range();
+        IkToken {
+            begin_byte: bytes.start as u32,
+            end_byte: bytes.end as u32,
+            type_id: lexeme.lexeme_type.id(),
+            position: position as u32,
+        }
+    }
+}
+
+/// 将 lexemes 依次写入 buf, 返回词元总数
+/// 总数大于 buf.len() 时只写入前 buf.len() 个, 调用方可按返回值扩大缓冲区后重试
+pub fn fill_tokens(lexemes: &[Lexeme], buf: &mut [IkToken]) -> usize {
+    for (position, (lexeme, slot)) in lexemes.iter().zip(buf.iter_mut()).enumerate() {
+        *slot = IkToken::from_lexeme(lexeme, position);
+    }
+    lexemes.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::lexeme::LexemeType;
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(std::mem::size_of::<IkToken>(), 16);
+        assert_eq!(std::mem::align_of::<IkToken>(), 4);
+    }
+
+    #[test]
+    fn test_fill_tokens() {
+        let text = "a中文";
+        let mut lexemes = vec![
+            Lexeme::new(0, 0, 1, LexemeType::ENGLISH),
+            Lexeme::new(0, 1, 2, LexemeType::CNWORD),
+        ];
+        for lexeme in lexemes.iter_mut() {
+            lexeme.parse_lexeme_text(text);
+        }
+        let mut buf = [IkToken::default(); 1];
+        assert_eq!(fill_tokens(&lexemes, &mut buf), 2);
+        assert_eq!(
+            buf[0],
+            IkToken {
+                begin_byte: 0,
+                end_byte: 1,
+                type_id: 1,
+                position: 0
+            }
+        );
+        let mut buf = [IkToken::default(); 4];
+        assert_eq!(fill_tokens(&lexemes, &mut buf), 2);
+        assert_eq!(
+            buf[1],
+            IkToken {
+                begin_byte: 1,
+                end_byte: 7,
+                type_id: 4,
+                position: 1
+            }
+        );
+        assert_eq!(buf[2], IkToken::default());
+    }
+}
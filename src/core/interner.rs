@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+// 默认最多驻留的文本数
+const DEFAULT_LIMIT: usize = 100_000;
+
+/// 词元文本驻留池, 相同的词元文本共享同一份 Arc<str>
+/// 达到上限后不再驻留新文本, 避免长期运行时无限增长
+#[derive(Debug)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+    limit: usize,
+}
+
+/// 多个分词器共享的驻留池
+pub type SharedInterner = Arc<Mutex<Interner>>;
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::with_limit(DEFAULT_LIMIT)
+    }
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        Interner {
+            strings: HashSet::new(),
+            limit,
+        }
+    }
+
+    /// 构造可在多个分词器间共享的驻留池
+    pub fn shared() -> SharedInterner {
+        Arc::new(Mutex::new(Interner::new()))
+    }
+
+    pub fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(text) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = Arc::from(text);
+        if self.strings.len() < self.limit {
+            self.strings.insert(interned.clone());
+        }
+        interned
+    }
+
+    // 已驻留的文本数
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.strings.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let mut interner = Interner::with_limit(1);
+        let a = interner.intern("北京");
+        let b = interner.intern("北京");
+        assert!(Arc::ptr_eq(&a, &b));
+        // 超过上限后不再驻留
+        let c = interner.intern("大学");
+        let d = interner.intern("大学");
+        assert_eq!(c, d);
+        assert!(!Arc::ptr_eq(&c, &d));
+        assert_eq!(interner.len(), 1);
+    }
+}
@@ -0,0 +1,96 @@
+use std::ptr::NonNull;
+
+use crate::core::lexeme::Lexeme;
+use crate::core::ordered_linked_list::{Iter, Node, OrderedLinkedList};
+
+/// 候选词图
+/// 子分词器输出的全部候选词元, 按起始位置、长度(长的在前)排序, 相同位置与长度的词元只保留一个
+/// 由 IKSegmenter::build_lattice 生成, 交给歧义裁决器处理;
+/// 外部也可以只复用 IK 的候选词生成, 自行做路径选择
+pub struct Lattice {
+    // 输入的字符数
+    char_count: usize,
+    lexemes: OrderedLinkedList<Lexeme>,
+}
+
+impl Lattice {
+    pub fn new(char_count: usize) -> Self {
+        Lattice {
+            char_count,
+            lexemes: OrderedLinkedList::new(),
+        }
+    }
+
+    // 添加候选词元, 已存在相同位置与长度的词元时返回 false
+    pub(crate) fn insert(&mut self, lexeme: Lexeme) -> bool {
+        let size = self.lexemes.length();
+        self.lexemes.insert(lexeme).expect("lattice insert error!");
+        self.lexemes.length() > size
+    }
+
+    pub(crate) fn head_node(&self) -> Option<&NonNull<Node<Lexeme>>> {
+        self.lexemes.head_node()
+    }
+
+    // 输入的字符数
+    pub fn char_count(&self) -> usize {
+        self.char_count
+    }
+
+    // 候选词元个数
+    pub fn len(&self) -> usize {
+        self.lexemes.length()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lexemes.is_empty()
+    }
+
+    // 按起始位置、长度(长的在前)遍历候选词元
+    pub fn iter(&self) -> Iter<'_, Lexeme> {
+        self.lexemes.iter()
+    }
+
+    // 从 position 开始的候选词元, 长的在前
+    pub fn starting_at(&self, position: usize) -> Vec<&Lexeme> {
+        self.iter()
+            .skip_while(|l| l.get_begin() < position)
+            .take_while(|l| l.get_begin() == position)
+            .collect()
+    }
+
+    // 覆盖 position 处字符的候选词元
+    pub fn covering(&self, position: usize) -> Vec<&Lexeme> {
+        self.iter()
+            .take_while(|l| l.get_begin() <= position)
+            .filter(|l| l.get_begin() + l.get_length() > position)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::lexeme::LexemeType;
+
+    #[test]
+    fn test_lattice() {
+        let mut lattice = Lattice::new(4);
+        assert!(lattice.insert(Lexeme::new(0, 2, 2, LexemeType::CNWORD)));
+        assert!(lattice.insert(Lexeme::new(0, 0, 2, LexemeType::CNWORD)));
+        assert!(lattice.insert(Lexeme::new(0, 0, 4, LexemeType::CNWORD)));
+        assert!(lattice.insert(Lexeme::new(0, 1, 2, LexemeType::CNWORD)));
+        assert!(!lattice.insert(Lexeme::new(0, 0, 2, LexemeType::CNUM)));
+        assert_eq!(lattice.len(), 4);
+
+        let spans = |lexemes: Vec<&Lexeme>| {
+            lexemes
+                .iter()
+                .map(|l| (l.get_begin(), l.get_length()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spans(lattice.starting_at(0)), vec![(0, 4), (0, 2)]);
+        assert_eq!(spans(lattice.starting_at(3)), vec![]);
+        assert_eq!(spans(lattice.covering(2)), vec![(0, 4), (1, 2), (2, 2)]);
+    }
+}
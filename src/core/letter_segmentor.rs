@@ -1,4 +1,8 @@
-use crate::core::char_util::{char_type_of, CharType};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::char_util::{char_type_of, numeral_value, CharType};
 use crate::core::lexeme::{Lexeme, LexemeType};
 use crate::core::segmentor::Segmenter;
 
@@ -10,6 +14,56 @@ const LETTER_CONNECTOR: [char; 7] = ['#', '&', '+', '-', '.', '@', '_'];
 // 数字符号
 const NUM_CONNECTOR: [char; 2] = [',', '.'];
 
+// 数字范围及分数符号, 如 3-5, 1/2
+const NUM_RANGE_CONNECTOR: [char; 2] = ['-', '/'];
+
+/// 连接符处理策略
+/// 作用于混合字母词元与阿拉伯数字词元中出现的连接符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectorPolicy {
+    /// 保留连接符, 混合字母词元保留结尾的连接符(如 c++), 数字词元不含结尾的连接符
+    #[default]
+    Keep,
+    /// 去掉词元结尾的连接符, 中间的连接符保留
+    Trim,
+    /// 在连接符处拆分词元
+    Split,
+}
+
+/// 可选的标识符识别规则, 命中的片段整体输出为一个 LETTER 词元
+/// 词元的词性(Lexeme::get_pos)为规则名称, 如 url、email, 以便与普通的字母词元区分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifierRule {
+    /// 版本号, 如 v1.2.3, Python3.11, 1.2.3
+    Version,
+    /// 标准编号, 如 GB/T 7714-2015, ISO 9001
+    StandardCode,
+    /// 文件路径, 如 /usr/local/bin, C:\\Windows\\System32
+    Path,
+    /// 域名, 如 example.com.cn
+    Domain,
+    /// URL, 如 https://example.com/a?b=1, www.example.com/index.html
+    Url,
+    /// 邮箱地址, 如 zhiyi.shen@gmail.com
+    Email,
+}
+
+impl IdentifierRule {
+    /// 规则名称, 与配置文件中的写法相同
+    pub fn tag(&self) -> &'static str {
+        match self {
+            IdentifierRule::Version => "version",
+            IdentifierRule::StandardCode => "standard_code",
+            IdentifierRule::Path => "path",
+            IdentifierRule::Domain => "domain",
+            IdentifierRule::Url => "url",
+            IdentifierRule::Email => "email",
+        }
+    }
+}
+
 // 英文字符及阿拉伯数字子分词器
 pub struct LetterSegmenter {
     /// 词元的开始位置，
@@ -29,6 +83,15 @@ pub struct LetterSegmenter {
     arabic_start: i32,
     // 阿拉伯数字结束位置
     arabic_end: i32,
+
+    // 连接符处理策略
+    connector_policy: ConnectorPolicy,
+    // 是否将数字范围及分数(3-5, 1/2, 2023/24)作为一个数字词元输出
+    numeric_range: bool,
+    // 启用的标识符识别规则
+    identifier_rules: Vec<IdentifierRule>,
+    // 文件路径是否额外输出各级路径片段
+    path_segments: bool,
 }
 
 impl Segmenter for LetterSegmenter {
@@ -43,6 +106,10 @@ impl Segmenter for LetterSegmenter {
         new_lexemes.extend(a);
         new_lexemes.extend(b);
         new_lexemes.extend(c);
+        // 处理标识符
+        if !self.identifier_rules.is_empty() {
+            new_lexemes.extend(self.process_identifier(input));
+        }
         new_lexemes
     }
     fn name(&self) -> &str {
@@ -65,31 +132,111 @@ impl LetterSegmenter {
             english_end: -1,
             arabic_start: -1,
             arabic_end: -1,
+            connector_policy: ConnectorPolicy::default(),
+            numeric_range: false,
+            identifier_rules: Vec::new(),
+            path_segments: false,
         }
     }
 
+    pub fn with_connector_policy(mut self, connector_policy: ConnectorPolicy) -> Self {
+        self.connector_policy = connector_policy;
+        self
+    }
+
+    pub fn with_numeric_range(mut self, numeric_range: bool) -> Self {
+        self.numeric_range = numeric_range;
+        self
+    }
+
+    pub fn with_identifier_rules(mut self, identifier_rules: Vec<IdentifierRule>) -> Self {
+        self.identifier_rules = identifier_rules;
+        self
+    }
+
+    pub fn with_path_segments(mut self, path_segments: bool) -> Self {
+        self.path_segments = path_segments;
+        self
+    }
+
+    /// 处理标识符输出
+    /// 如：v1.2.3 | python3.11 | GB/T 7714-2015 | /usr/local/bin | example.com.cn
+    ///     https://example.com/a?b=1 | zhiyi.shen@gmail.com
+    pub fn process_identifier(&self, chars: &[char]) -> Vec<Lexeme> {
+        let mut new_lexemes = Vec::new();
+        let mut cursor = 0;
+        while cursor < chars.len() {
+            // 只在字母数字串的起始位置尝试匹配
+            let at_boundary = cursor == 0 || !is_letter_or_digit(&chars[cursor - 1]);
+            let mut best: Option<(usize, IdentifierRule)> = None;
+            if at_boundary && (is_letter_or_digit(&chars[cursor]) || chars[cursor] == '/') {
+                for rule in self.identifier_rules.iter() {
+                    let matched = match rule {
+                        IdentifierRule::Version => match_version(chars, cursor),
+                        IdentifierRule::StandardCode => match_standard_code(chars, cursor),
+                        IdentifierRule::Path => match_path(chars, cursor),
+                        IdentifierRule::Domain => match_domain(chars, cursor),
+                        IdentifierRule::Url => match_url(chars, cursor),
+                        IdentifierRule::Email => match_email(chars, cursor),
+                    };
+                    // 取最长的匹配
+                    if let Some(end) = matched {
+                        let longer = match best {
+                            Some((best_end, _)) => end > best_end,
+                            None => true,
+                        };
+                        if longer {
+                            best = Some((end, *rule));
+                        }
+                    }
+                }
+            }
+            match best {
+                Some((end, rule)) => {
+                    new_lexemes.push(
+                        Lexeme::new(0, cursor, end - cursor, LexemeType::LETTER)
+                            .with_pos(Some(Arc::from(rule.tag()))),
+                    );
+                    if rule == IdentifierRule::Path && self.path_segments {
+                        new_lexemes.extend(path_segment_lexemes(chars, cursor, end));
+                    }
+                    cursor = end;
+                }
+                None => cursor += 1,
+            }
+        }
+        new_lexemes
+    }
+
     /// 处理数字字母混合输出
     /// 如：windos2000 | zhiyi.shen@gmail.com
     pub fn process_mix_letter(&mut self, chars: &[char]) -> Vec<Lexeme> {
         let mut new_lexemes = Vec::new();
-        let char_count = chars.len();
         for (cursor, curr_char) in chars.iter().enumerate() {
             let curr_char_type = char_type_of(curr_char);
+            // 罗马数字、带圈数字等不参与混合字母词元
+            let is_letter = !is_numeral(curr_char)
+                && (CharType::ARABIC == curr_char_type || CharType::ENGLISH == curr_char_type);
             if self.start == -1 {
                 // 当前的分词器尚未开始处理字符
-                if CharType::ARABIC == curr_char_type || CharType::ENGLISH == curr_char_type {
+                if is_letter {
                     // 记录起始指针的位置,标明分词器进入处理状态
                     self.start = cursor as i32;
                     self.end = self.start;
                 }
             } else {
                 // 当前的分词器正在处理字符
-                if CharType::ARABIC == curr_char_type
-                    || CharType::ENGLISH == curr_char_type
-                    || (CharType::USELESS == curr_char_type && self.is_letter_connector(curr_char))
-                {
+                if is_letter {
                     // 记录下可能的结束位置
                     self.end = cursor as i32;
+                } else if CharType::USELESS == curr_char_type
+                    && self.is_letter_connector(curr_char)
+                    && self.connector_policy != ConnectorPolicy::Split
+                {
+                    // Trim 策略下, 连接符只有后面还有字母时才会被包含
+                    if self.connector_policy == ConnectorPolicy::Keep {
+                        self.end = cursor as i32;
+                    }
                 } else {
                     // 遇到非Letter字符，输出词元
                     let new_lexeme = Lexeme::new(
@@ -105,7 +252,8 @@ impl LetterSegmenter {
             }
         }
 
-        if self.end == (char_count - 1) as i32 {
+        // 输入结束, 输出尚未输出的词元
+        if self.start != -1 {
             let new_lexeme = Lexeme::new(
                 0,
                 self.start as usize,
@@ -122,7 +270,6 @@ impl LetterSegmenter {
     // 处理纯英文字母输出
     fn process_english_letter(&mut self, input: &[char]) -> Vec<Lexeme> {
         let mut new_lexemes = Vec::new();
-        let char_count = input.len();
         for (cursor, curr_char) in input.iter().enumerate() {
             let curr_char_type = char_type_of(curr_char);
             if self.english_start == -1 {
@@ -151,8 +298,8 @@ impl LetterSegmenter {
                 }
             }
         }
-        // 结束了
-        if self.english_end == (char_count - 1) as i32 {
+        // 输入结束, 输出尚未输出的词元
+        if self.english_start != -1 {
             let new_lexeme = Lexeme::new(
                 0,
                 self.english_start as usize,
@@ -169,9 +316,23 @@ impl LetterSegmenter {
     /// 处理阿拉伯数字输出
     fn process_arabic_letter(&mut self, chars: &[char]) -> Vec<Lexeme> {
         let mut new_lexemes = Vec::new();
-        let char_count = chars.len();
         for (cursor, curr_char) in chars.iter().enumerate() {
             let curr_char_type = char_type_of(curr_char);
+            if is_numeral(curr_char) {
+                // 罗马数字、带圈数字等各自输出为一个词元, 不与相邻数字合并
+                if self.arabic_start != -1 {
+                    new_lexemes.push(Lexeme::new(
+                        0,
+                        self.arabic_start as usize,
+                        (self.arabic_end - self.arabic_start + 1) as usize,
+                        LexemeType::ARABIC,
+                    ));
+                    self.arabic_start = -1;
+                    self.arabic_end = -1;
+                }
+                new_lexemes.push(Lexeme::new(0, cursor, 1, LexemeType::ARABIC));
+                continue;
+            }
             if self.arabic_start == -1 {
                 // 当前的分词器尚未开始处理数字字符
                 if CharType::ARABIC == curr_char_type {
@@ -184,7 +345,10 @@ impl LetterSegmenter {
                 if CharType::ARABIC == curr_char_type {
                     // 记录当前指针位置为结束位置
                     self.arabic_end = cursor as i32;
-                } else if CharType::USELESS == curr_char_type && self.is_num_connector(curr_char) {
+                } else if CharType::USELESS == curr_char_type
+                    && self.is_num_connector(curr_char)
+                    && self.connector_policy != ConnectorPolicy::Split
+                {
                     // 不输出数字，但不标记结束
                 } else {
                     // 遇到非Arabic字符,输出词元
@@ -200,7 +364,8 @@ impl LetterSegmenter {
                 }
             }
         }
-        if self.arabic_end == (char_count - 1) as i32 {
+        // 输入结束, 输出尚未输出的词元
+        if self.arabic_start != -1 {
             let new_lexeme = Lexeme::new(
                 0,
                 self.arabic_start as usize,
@@ -221,6 +386,483 @@ impl LetterSegmenter {
 
     // 判断是否是数字连接符号
     pub fn is_num_connector(&self, c: &char) -> bool {
-        NUM_CONNECTOR.contains(c)
+        NUM_CONNECTOR.contains(c) || (self.numeric_range && NUM_RANGE_CONNECTOR.contains(c))
+    }
+}
+
+fn is_letter_or_digit(c: &char) -> bool {
+    c.is_ascii_alphanumeric()
+}
+
+// 从 start 开始连续的满足 f 的字符的结束位置
+fn skip_while(chars: &[char], start: usize, f: fn(&char) -> bool) -> usize {
+    let mut i = start;
+    while i < chars.len() && f(&chars[i]) {
+        i += 1;
+    }
+    i
+}
+
+// 版本号: 可选的字母前缀 + 数字 + (. 数字)+
+// 没有字母前缀时至少需要两段小数点, 以免把小数当作版本号
+fn match_version(chars: &[char], start: usize) -> Option<usize> {
+    let digits_start = skip_while(chars, start, char::is_ascii_alphabetic);
+    let mut i = skip_while(chars, digits_start, char::is_ascii_digit);
+    if i == digits_start {
+        return None;
+    }
+    let mut dots = 0;
+    while i + 1 < chars.len() && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+        i = skip_while(chars, i + 1, char::is_ascii_digit);
+        dots += 1;
+    }
+    if dots == 0 || (digits_start == start && dots < 2) {
+        return None;
+    }
+    Some(i)
+}
+
+// 标准编号: 1~5个字母 + 可选的(/ 1~3个字母) + 可选的空格 + 数字 + (. 数字)* + 可选的(- 数字)
+fn match_standard_code(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = skip_while(chars, start, char::is_ascii_alphabetic);
+    if i == start || i - start > 5 {
+        return None;
+    }
+    // 前缀须全为大写 (如 GB, ISO), 或为常见标准前缀 (lowercase 选项下输入已转为小写),
+    // 避免 "top 10", "Win 10" 之类被识别为标准号
+    let prefix = &chars[start..i];
+    let uppercase = prefix.iter().all(char::is_ascii_uppercase);
+    if !uppercase && !is_known_standard_prefix(prefix) {
+        return None;
+    }
+    if i + 1 < chars.len() && chars[i] == '/' {
+        let sub_end = skip_while(chars, i + 1, char::is_ascii_alphabetic);
+        let sub = &chars[i + 1..sub_end];
+        if !sub.is_empty()
+            && sub.len() <= 3
+            && (!uppercase || sub.iter().all(char::is_ascii_uppercase))
+        {
+            i = sub_end;
+        }
+    }
+    if i < chars.len() && chars[i] == ' ' {
+        i += 1;
+    }
+    let digits_start = i;
+    i = skip_while(chars, i, char::is_ascii_digit);
+    if i == digits_start {
+        return None;
+    }
+    while i + 1 < chars.len() && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+        i = skip_while(chars, i + 1, char::is_ascii_digit);
+    }
+    if i + 1 < chars.len() && chars[i] == '-' && chars[i + 1].is_ascii_digit() {
+        i = skip_while(chars, i + 1, char::is_ascii_digit);
+    }
+    Some(i)
+}
+
+// 常见标准号前缀, 不区分大小写
+const KNOWN_STANDARD_PREFIXES: [&str; 10] = [
+    "gb", "gbt", "iso", "iec", "ieee", "rfc", "din", "jis", "ansi", "astm",
+];
+
+fn is_known_standard_prefix(prefix: &[char]) -> bool {
+    KNOWN_STANDARD_PREFIXES.iter().any(|known| {
+        known.len() == prefix.len()
+            && known
+                .chars()
+                .zip(prefix)
+                .all(|(k, c)| k == c.to_ascii_lowercase())
+    })
+}
+
+// 罗马数字、带圈数字等有独立数值的单个字符
+fn is_numeral(c: &char) -> bool {
+    numeral_value(*c).is_some()
+}
+
+// 路径片段字符
+fn is_path_char(c: &char) -> bool {
+    c.is_ascii_alphanumeric() || ['.', '_', '-'].contains(c)
+}
+
+// 去掉结尾的 '.', 如句末的 "/usr/local/bin."
+fn trim_trailing_dot(chars: &[char], start: usize, mut end: usize) -> usize {
+    while end > start && chars[end - 1] == '.' {
+        end -= 1;
+    }
+    end
+}
+
+// 文件路径: /seg/seg(/seg)* 或 C:\\seg(\\seg)*
+fn match_path(chars: &[char], start: usize) -> Option<usize> {
+    let (mut i, separator, min_segments) = if chars[start] == '/' {
+        (start, '/', 2)
+    } else if start + 2 < chars.len()
+        && chars[start].is_ascii_alphabetic()
+        && chars[start + 1] == ':'
+        && chars[start + 2] == '\\'
+    {
+        (start + 2, '\\', 1)
+    } else {
+        return None;
+    };
+    let mut segments = 0;
+    while i + 1 < chars.len() && chars[i] == separator && is_path_char(&chars[i + 1]) {
+        i = skip_while(chars, i + 1, is_path_char);
+        segments += 1;
+    }
+    if segments < min_segments {
+        return None;
+    }
+    Some(trim_trailing_dot(chars, start, i))
+}
+
+// 域名: label(.label)+, 顶级域名为至少两个字母, 后面紧跟 @ 的视为邮箱用户名
+fn match_domain(chars: &[char], start: usize) -> Option<usize> {
+    let is_label_char = |c: &char| c.is_ascii_alphanumeric() || *c == '-';
+    let mut i = skip_while(chars, start, is_label_char);
+    let mut last_label = start..i;
+    let mut labels = 1;
+    while i + 1 < chars.len() && chars[i] == '.' && is_label_char(&chars[i + 1]) {
+        let label_start = i + 1;
+        i = skip_while(chars, label_start, is_label_char);
+        last_label = label_start..i;
+        labels += 1;
+    }
+    let tld_ok = last_label.len() >= 2 && chars[last_label].iter().all(char::is_ascii_alphabetic);
+    if labels < 2 || !tld_ok || (i < chars.len() && chars[i] == '@') {
+        return None;
+    }
+    Some(i)
+}
+
+// URL 中可以出现的字符, 见 RFC 3986
+fn is_url_char(c: &char) -> bool {
+    c.is_ascii_alphanumeric() || "-._~:/?#[]@!$&'()*+,;=%".contains(*c)
+}
+
+// 整段文本是否以 prefix 开头, 不区分大小写
+fn starts_with_ignore_case(chars: &[char], start: usize, prefix: &str) -> bool {
+    let len = prefix.chars().count();
+    start + len <= chars.len()
+        && chars[start..start + len]
+            .iter()
+            .zip(prefix.chars())
+            .all(|(a, b)| a.eq_ignore_ascii_case(&b))
+}
+
+// URL: (http|https|ftp):// 或 www. 开头, 到第一个不能出现在 URL 中的字符为止
+// 去掉结尾的标点, 如句末的 "https://example.com." 及括号中的 "(https://example.com)"
+fn match_url(chars: &[char], start: usize) -> Option<usize> {
+    let host_start = ["http://", "https://", "ftp://"]
+        .iter()
+        .find(|scheme| starts_with_ignore_case(chars, start, scheme))
+        .map(|scheme| start + scheme.len())
+        .or_else(|| starts_with_ignore_case(chars, start, "www.").then_some(start))?;
+    // 主机名必须是域名
+    let host_end = match_domain(chars, host_start)?;
+    let mut end = host_end;
+    if end < chars.len() && [':', '/', '?', '#'].contains(&chars[end]) {
+        end = skip_while(chars, end, is_url_char);
+    }
+    let has_open_paren = chars[start..end].contains(&'(');
+    while end > host_end {
+        match chars[end - 1] {
+            '.' | ',' | ';' | ':' | '!' | '?' | '\'' => end -= 1,
+            ')' if !has_open_paren => end -= 1,
+            _ => break,
+        }
+    }
+    Some(end)
+}
+
+// 邮箱地址: 用户名 @ 域名, 用户名由字母数字及 . _ % + - 组成, 不以 . 开头或结尾
+fn match_email(chars: &[char], start: usize) -> Option<usize> {
+    let is_local_char = |c: &char| c.is_ascii_alphanumeric() || "._%+-".contains(*c);
+    let at = skip_while(chars, start, is_local_char);
+    if at == start || at >= chars.len() || chars[at] != '@' {
+        return None;
+    }
+    if chars[start] == '.' || chars[at - 1] == '.' {
+        return None;
+    }
+    let end = match_domain(chars, at + 1)?;
+    Some(trim_trailing_dot(chars, at + 1, end))
+}
+
+// 路径中的各级片段
+fn path_segment_lexemes(chars: &[char], start: usize, end: usize) -> Vec<Lexeme> {
+    let mut lexemes = Vec::new();
+    let mut i = start;
+    while i < end {
+        if is_path_char(&chars[i]) {
+            let segment_end = skip_while(&chars[..end], i, is_path_char);
+            // 跳过盘符
+            if !(segment_end < end && chars[segment_end] == ':') {
+                lexemes.push(Lexeme::new(0, i, segment_end - i, LexemeType::LETTER));
+            }
+            i = segment_end;
+        } else {
+            i += 1;
+        }
+    }
+    lexemes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::test_util::lexeme_texts;
+
+    fn spans(lexemes: &[Lexeme], lexeme_type: LexemeType) -> Vec<(usize, usize)> {
+        lexemes
+            .iter()
+            .filter(|l| l.lexeme_type == lexeme_type)
+            .map(|l| (l.get_begin(), l.get_length()))
+            .collect()
+    }
+
+    fn analyze(text: &str) -> Vec<Lexeme> {
+        let chars = text.chars().collect::<Vec<_>>();
+        LetterSegmenter::new().analyze(&chars)
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert!(analyze("").is_empty());
+        assert!(analyze("-").is_empty());
+    }
+
+    #[test]
+    fn test_trailing_connector() {
+        let lexemes = analyze("abc-");
+        assert_eq!(spans(&lexemes, LexemeType::ENGLISH), vec![(0, 3)]);
+        assert_eq!(spans(&lexemes, LexemeType::LETTER), vec![(0, 4)]);
+
+        let lexemes = analyze("3.");
+        assert_eq!(spans(&lexemes, LexemeType::ARABIC), vec![(0, 1)]);
+        assert_eq!(spans(&lexemes, LexemeType::LETTER), vec![(0, 2)]);
+    }
+
+    #[test]
+    fn test_end_of_input() {
+        let lexemes = analyze("中文abc");
+        assert_eq!(spans(&lexemes, LexemeType::ENGLISH), vec![(2, 3)]);
+        let lexemes = analyze("3.14");
+        assert_eq!(spans(&lexemes, LexemeType::ARABIC), vec![(0, 4)]);
+        let lexemes = analyze("a");
+        assert_eq!(spans(&lexemes, LexemeType::LETTER), vec![(0, 1)]);
+    }
+
+    fn texts(text: &str, policy: ConnectorPolicy, lexeme_type: LexemeType) -> Vec<String> {
+        let chars = text.chars().collect::<Vec<_>>();
+        let lexemes = LetterSegmenter::new()
+            .with_connector_policy(policy)
+            .analyze(&chars);
+        lexeme_texts(
+            &chars,
+            lexemes.iter().filter(|l| l.lexeme_type == lexeme_type),
+        )
+    }
+
+    // process_identifier 输出的词元文本
+    fn identifiers(segmenter: &LetterSegmenter, text: &str) -> Vec<String> {
+        let chars = text.chars().collect::<Vec<_>>();
+        lexeme_texts(&chars, &segmenter.process_identifier(&chars))
+    }
+
+    #[test]
+    fn test_connector_policy() {
+        use ConnectorPolicy::*;
+        // url
+        let url = "www.example.com.";
+        assert_eq!(
+            texts(url, Keep, LexemeType::LETTER),
+            vec!["www.example.com."]
+        );
+        assert_eq!(
+            texts(url, Trim, LexemeType::LETTER),
+            vec!["www.example.com"]
+        );
+        assert_eq!(
+            texts(url, Split, LexemeType::LETTER),
+            vec!["www", "example", "com"]
+        );
+        // file name
+        let file = "foo--bar_v2.txt-";
+        assert_eq!(
+            texts(file, Keep, LexemeType::LETTER),
+            vec!["foo--bar_v2.txt-"]
+        );
+        assert_eq!(
+            texts(file, Trim, LexemeType::LETTER),
+            vec!["foo--bar_v2.txt"]
+        );
+        assert_eq!(
+            texts(file, Split, LexemeType::LETTER),
+            vec!["foo", "bar", "v2", "txt"]
+        );
+        // range
+        let range = "10-20,";
+        assert_eq!(texts(range, Keep, LexemeType::LETTER), vec!["10-20"]);
+        assert_eq!(texts(range, Trim, LexemeType::LETTER), vec!["10-20"]);
+        assert_eq!(texts(range, Split, LexemeType::LETTER), vec!["10", "20"]);
+        // number
+        let number = "1,000.5.";
+        assert_eq!(texts(number, Keep, LexemeType::ARABIC), vec!["1,000.5"]);
+        assert_eq!(texts(number, Trim, LexemeType::ARABIC), vec!["1,000.5"]);
+        assert_eq!(
+            texts(number, Split, LexemeType::ARABIC),
+            vec!["1", "000", "5"]
+        );
+    }
+
+    #[test]
+    fn test_numeric_range() {
+        let arabic = |text: &str, numeric_range: bool| -> Vec<String> {
+            let chars = text.chars().collect::<Vec<_>>();
+            let lexemes = LetterSegmenter::new()
+                .with_numeric_range(numeric_range)
+                .analyze(&chars);
+            lexeme_texts(
+                &chars,
+                lexemes
+                    .iter()
+                    .filter(|l| l.lexeme_type == LexemeType::ARABIC),
+            )
+        };
+        assert_eq!(arabic("3-5天", false), vec!["3", "5"]);
+        assert_eq!(arabic("3-5天", true), vec!["3-5"]);
+        assert_eq!(arabic("1/2杯", true), vec!["1/2"]);
+        assert_eq!(arabic("2023/24赛季", true), vec!["2023/24"]);
+        assert_eq!(arabic("共3-", true), vec!["3"]);
+    }
+
+    #[test]
+    fn test_numerals() {
+        let texts = |text: &str, lexeme_type| texts(text, ConnectorPolicy::default(), lexeme_type);
+        // 相邻的带圈数字、罗马数字各自输出
+        assert_eq!(texts("①②③", LexemeType::ARABIC), vec!["①", "②", "③"]);
+        assert_eq!(texts("1①", LexemeType::ARABIC), vec!["1", "①"]);
+        assert_eq!(texts("ⅫⅧ", LexemeType::ARABIC), vec!["Ⅻ", "Ⅷ"]);
+        assert_eq!(texts("12,5⑶", LexemeType::ARABIC), vec!["12,5", "⑶"]);
+        // 不并入混合字母词元
+        assert_eq!(texts("a1①", LexemeType::LETTER), vec!["a1"]);
+        assert!(texts("①②", LexemeType::LETTER).is_empty());
+    }
+
+    #[test]
+    fn test_identifier_rules() {
+        let segmenter = LetterSegmenter::new()
+            .with_connector_policy(ConnectorPolicy::Split)
+            .with_identifier_rules(vec![IdentifierRule::Version, IdentifierRule::StandardCode]);
+        let identifiers = |text: &str| identifiers(&segmenter, text);
+        assert_eq!(identifiers("升级到v1.2.3了"), vec!["v1.2.3"]);
+        assert_eq!(identifiers("Python3.11发布"), vec!["Python3.11"]);
+        assert_eq!(identifiers("版本1.2.3"), vec!["1.2.3"]);
+        assert_eq!(identifiers("参照GB/T 7714-2015。"), vec!["GB/T 7714-2015"]);
+        assert_eq!(identifiers("ISO 9001认证"), vec!["ISO 9001"]);
+        assert!(identifiers("圆周率3.14").is_empty());
+        assert!(identifiers("hello world").is_empty());
+        // 前缀须为大写或常见标准前缀
+        assert_eq!(identifiers("iso 9001认证"), vec!["iso 9001"]);
+        assert_eq!(identifiers("gb/t 7714-2015"), vec!["gb/t 7714-2015"]);
+        assert!(identifiers("top 10榜单").is_empty());
+        assert!(identifiers("Win 10系统").is_empty());
+        assert!(identifiers("page 3").is_empty());
+    }
+
+    #[test]
+    fn test_path_and_domain() {
+        let identifiers = |text: &str, path_segments: bool| {
+            let segmenter = LetterSegmenter::new()
+                .with_identifier_rules(vec![IdentifierRule::Path, IdentifierRule::Domain])
+                .with_path_segments(path_segments);
+            identifiers(&segmenter, text)
+        };
+        assert_eq!(
+            identifiers("安装在/usr/local/bin。", false),
+            vec!["/usr/local/bin"]
+        );
+        assert_eq!(
+            identifiers("安装在/usr/local/bin.", true),
+            vec!["/usr/local/bin", "usr", "local", "bin"]
+        );
+        assert_eq!(
+            identifiers("打开C:\\Windows\\System32", false),
+            vec!["C:\\Windows\\System32"]
+        );
+        assert_eq!(
+            identifiers("打开C:\\Windows\\System32", true),
+            vec!["C:\\Windows\\System32", "Windows", "System32"]
+        );
+        assert_eq!(
+            identifiers("访问example.com.cn首页", false),
+            vec!["example.com.cn"]
+        );
+        assert_eq!(
+            identifiers("zhiyi.shen@gmail.com", false),
+            vec!["gmail.com"]
+        );
+        assert!(identifiers("and /or v1.2", false).is_empty());
+    }
+
+    #[test]
+    fn test_url_and_email() {
+        let identifiers = |text: &str| -> Vec<(String, String)> {
+            let chars = text.chars().collect::<Vec<_>>();
+            let lexemes = LetterSegmenter::new()
+                .with_identifier_rules(vec![
+                    IdentifierRule::Path,
+                    IdentifierRule::Domain,
+                    IdentifierRule::Url,
+                    IdentifierRule::Email,
+                ])
+                .process_identifier(&chars);
+            let tags = lexemes.iter().map(|l| l.get_pos().unwrap().to_string());
+            lexeme_texts(&chars, &lexemes)
+                .into_iter()
+                .zip(tags)
+                .collect()
+        };
+        let pair = |text: &str, tag: &str| (text.to_string(), tag.to_string());
+        assert_eq!(
+            identifiers("打开https://example.com/a?b=1&c=%E4#top。"),
+            vec![pair("https://example.com/a?b=1&c=%E4#top", "url")]
+        );
+        assert_eq!(
+            identifiers("见(HTTP://Example.com:8080/index.html)."),
+            vec![pair("HTTP://Example.com:8080/index.html", "url")]
+        );
+        assert_eq!(
+            identifiers("访问www.example.com/wiki/Rust_(language),谢谢"),
+            vec![pair("www.example.com/wiki/Rust_(language)", "url")]
+        );
+        assert_eq!(
+            identifiers("发邮件到zhiyi.shen+ik@gmail.com."),
+            vec![pair("zhiyi.shen+ik@gmail.com", "email")]
+        );
+        assert_eq!(
+            identifiers("访问example.com.cn首页"),
+            vec![pair("example.com.cn", "domain")]
+        );
+        assert!(identifiers("https://").is_empty());
+        assert_eq!(
+            identifiers("a.@gmail.com"),
+            vec![pair("gmail.com", "domain")]
+        );
+    }
+
+    #[test]
+    fn test_state_reset_between_calls() {
+        let mut segmenter = LetterSegmenter::new();
+        let chars = "abc-".chars().collect::<Vec<_>>();
+        segmenter.analyze(&chars);
+        let chars = "中x".chars().collect::<Vec<_>>();
+        let lexemes = segmenter.analyze(&chars);
+        assert_eq!(spans(&lexemes, LexemeType::ENGLISH), vec![(1, 1)]);
+        assert_eq!(spans(&lexemes, LexemeType::LETTER), vec![(1, 1)]);
     }
 }
@@ -1,9 +1,16 @@
 use std::cmp::Ordering;
+use std::ops::Range;
+use std::sync::Arc;
 
-use crate::core::char_util::utf8_slice;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::core::char_util::{byte_range, numeral_value};
+use crate::core::interner::Interner;
 
 // lexemeType常量
-#[derive(Debug, PartialEq, Clone)]
+// 序列化为 as_str 的类型名, 反序列化时也接受 Lucene 类型名及枚举名(如 CNWORD)
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum LexemeType {
     // 未知 0
     UNKNOWN,
@@ -25,9 +32,144 @@ pub enum LexemeType {
     COUNT,
     // 中文数量词48
     CQUAN,
+    // 标点及特殊符号
+    SPECIAL,
+    // 表情符号, 含修饰符及零宽连接符连接的整个序列
+    EMOJI,
+    // 按后缀识别的机构名, 如 某某公司
+    ORG,
+    // 按后缀识别的地名, 如 某某路
+    LOC,
+}
+
+const ALL_TYPES: [LexemeType; 14] = [
+    LexemeType::UNKNOWN,
+    LexemeType::ENGLISH,
+    LexemeType::ARABIC,
+    LexemeType::LETTER,
+    LexemeType::CNWORD,
+    LexemeType::CNCHAR,
+    LexemeType::OtherCJK,
+    LexemeType::CNUM,
+    LexemeType::COUNT,
+    LexemeType::CQUAN,
+    LexemeType::SPECIAL,
+    LexemeType::EMOJI,
+    LexemeType::ORG,
+    LexemeType::LOC,
+];
+
+impl LexemeType {
+    /// 稳定的数值编号, 用于 FFI 等跨语言场景, 与 IK Analyzer 的类型常量一致
+    /// IK Analyzer 中没有的类型从 128 开始编号
+    pub fn id(&self) -> u32 {
+        match self {
+            LexemeType::UNKNOWN => 0,
+            LexemeType::ENGLISH => 1,
+            LexemeType::ARABIC => 2,
+            LexemeType::LETTER => 3,
+            LexemeType::CNWORD => 4,
+            LexemeType::CNCHAR => 64,
+            LexemeType::OtherCJK => 8,
+            LexemeType::CNUM => 16,
+            LexemeType::COUNT => 32,
+            LexemeType::CQUAN => 48,
+            LexemeType::SPECIAL => 128,
+            LexemeType::EMOJI => 129,
+            LexemeType::ORG => 130,
+            LexemeType::LOC => 131,
+        }
+    }
+
+    /// 类型标示字符串, 与 Lexeme::get_lexeme_type_string 相同
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LexemeType::ENGLISH => "ENGLISH",
+            LexemeType::ARABIC => "ARABIC",
+            LexemeType::LETTER => "LETTER",
+            LexemeType::CNWORD => "CN_WORD",
+            LexemeType::CNCHAR => "CN_CHAR",
+            LexemeType::OtherCJK => "OtherCjk",
+            LexemeType::COUNT => "COUNT",
+            LexemeType::CNUM => "TYPE_CNUM",
+            LexemeType::CQUAN => "TYPE_CQUAN",
+            LexemeType::SPECIAL => "SPECIAL",
+            LexemeType::EMOJI => "EMOJI",
+            LexemeType::ORG => "ORG",
+            LexemeType::LOC => "LOC",
+            _ => "UNKNOW",
+        }
+    }
+
+    /// IK Analyzer(Lucene) 中的类型名, 与 as_str 只在日韩文字及未知类型上不同
+    pub fn lucene_name(&self) -> &'static str {
+        match self {
+            LexemeType::OtherCJK => "OTHER_CJK",
+            LexemeType::UNKNOWN => "UNKONW",
+            _ => self.as_str(),
+        }
+    }
+
+    /// 由类型名查找类型, 接受 as_str、lucene_name 及枚举名, 未知的类型名返回 None
+    pub fn from_name(name: &str) -> Option<LexemeType> {
+        ALL_TYPES
+            .iter()
+            .copied()
+            .find(|t| t.as_str() == name || t.lucene_name() == name || format!("{:?}", t) == name)
+    }
+
+    /// id 的逆映射, 未知的编号返回 None
+    pub fn from_id(id: u32) -> Option<LexemeType> {
+        let lexeme_type = match id {
+            0 => LexemeType::UNKNOWN,
+            1 => LexemeType::ENGLISH,
+            2 => LexemeType::ARABIC,
+            3 => LexemeType::LETTER,
+            4 => LexemeType::CNWORD,
+            64 => LexemeType::CNCHAR,
+            8 => LexemeType::OtherCJK,
+            16 => LexemeType::CNUM,
+            32 => LexemeType::COUNT,
+            48 => LexemeType::CQUAN,
+            128 => LexemeType::SPECIAL,
+            129 => LexemeType::EMOJI,
+            130 => LexemeType::ORG,
+            131 => LexemeType::LOC,
+            _ => return None,
+        };
+        Some(lexeme_type)
+    }
+}
+
+impl Serialize for LexemeType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
 }
 
+impl<'de> Deserialize<'de> for LexemeType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        LexemeType::from_name(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown lexeme type: {}", name)))
+    }
+}
+
+/// 默认的词元类型优先级, 多个子分词器在同一位置输出相同长度的词元时, 靠前的类型胜出
+/// 与子分词器的注册顺序一致: 字母数字 > 数量词 > 中文词 > 特殊符号
+pub const DEFAULT_TYPE_PRIORITY: [LexemeType; 7] = [
+    LexemeType::ENGLISH,
+    LexemeType::ARABIC,
+    LexemeType::LETTER,
+    LexemeType::CNUM,
+    LexemeType::COUNT,
+    LexemeType::CNWORD,
+    LexemeType::SPECIAL,
+];
+
 /// IK词元对象
+/// 序列化为 text、type、chars(在文本中的字符范围)、bytes(字节范围),
+/// 以及非空的 freq、pos、parts(组成词元); 不输出内部的相对位置
 #[derive(Debug, Clone)]
 pub struct Lexeme {
     // 词元的起始位移
@@ -36,10 +178,95 @@ pub struct Lexeme {
     begin: usize,
     // 词元的长度
     length: usize,
-    // 词元文本
-    lexeme_text: String,
+    // 词元文本, 解析前为 None; 使用 Arc<str> 以便多个词元共享驻留的文本
+    lexeme_text: Option<Arc<str>>,
     // 词元类型
     pub(crate) lexeme_type: LexemeType,
+    // 词典中的词频, 未指定时为 0
+    freq: u32,
+    // 词典中的词性, 未指定时为 None
+    pos: Option<Arc<str>>,
+    // 合并前的组成词元, 如 五十元 由 五十(CNUM) 和 元(COUNT) 组成; 未合并时为空
+    parts: Vec<Lexeme>,
+    // 词元在输入文本中的字节范围, 与词元文本一同解析
+    bytes: Range<usize>,
+}
+
+// Lexeme 的序列化形式
+#[derive(Serialize)]
+struct LexemeRef<'a> {
+    text: &'a str,
+    #[serde(rename = "type")]
+    lexeme_type: LexemeType,
+    chars: Range<usize>,
+    bytes: Range<usize>,
+    #[serde(skip_serializing_if = "is_zero")]
+    freq: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pos: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    parts: &'a [Lexeme],
+}
+
+// Lexeme 的反序列化形式, 字符范围换算为起始位移为 0 的词元
+#[derive(Deserialize)]
+struct LexemeData {
+    #[serde(default)]
+    text: Option<Arc<str>>,
+    #[serde(rename = "type")]
+    lexeme_type: LexemeType,
+    chars: Range<usize>,
+    #[serde(default = "empty_range")]
+    bytes: Range<usize>,
+    #[serde(default)]
+    freq: u32,
+    #[serde(default)]
+    pos: Option<Arc<str>>,
+    #[serde(default)]
+    parts: Vec<Lexeme>,
+}
+
+fn is_zero(freq: &u32) -> bool {
+    *freq == 0
+}
+
+fn empty_range() -> Range<usize> {
+    0..0
+}
+
+impl Serialize for Lexeme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LexemeRef {
+            text: self.get_lexeme_text(),
+            lexeme_type: self.lexeme_type,
+            chars: self.position_range(),
+            bytes: self.byte_range(),
+            freq: self.freq,
+            pos: self.get_pos(),
+            parts: &self.parts,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Lexeme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = LexemeData::deserialize(deserializer)?;
+        if data.chars.start > data.chars.end {
+            return Err(D::Error::custom("invalid chars range"));
+        }
+        Ok(Lexeme {
+            offset: 0,
+            begin: data.chars.start,
+            length: data.chars.len(),
+            lexeme_text: data.text,
+            lexeme_type: data.lexeme_type,
+            freq: data.freq,
+            pos: data.pos,
+            parts: data.parts,
+            bytes: data.bytes,
+        })
+    }
 }
 
 impl PartialEq for Lexeme {
@@ -76,10 +303,51 @@ impl Lexeme {
             begin,
             length,
             lexeme_type,
-            lexeme_text: String::from(""),
+            lexeme_text: None,
+            freq: 0,
+            pos: None,
+            parts: Vec::new(),
+            bytes: 0..0,
         }
     }
 
+    /// 由左闭右开的相对字符范围构造词元
+    pub fn from_range(offset: usize, range: Range<usize>, lexeme_type: LexemeType) -> Self {
+        Lexeme::new(offset, range.start, range.len(), lexeme_type)
+    }
+
+    /// 设置词频, 用于歧义裁决
+    pub fn with_freq(mut self, freq: u32) -> Self {
+        self.freq = freq;
+        self
+    }
+
+    /// 词典中的词频, 未指定时为 0
+    pub fn get_freq(&self) -> u32 {
+        self.freq
+    }
+
+    /// 设置词性
+    pub fn with_pos(mut self, pos: Option<Arc<str>>) -> Self {
+        self.pos = pos;
+        self
+    }
+
+    /// 词典中的词性, 如 n、v、ns, 词典未指定时为 None
+    pub fn get_pos(&self) -> Option<&str> {
+        self.pos.as_deref()
+    }
+
+    /// 词元的相对字符范围, 左闭右开
+    pub fn range(&self) -> Range<usize> {
+        self.begin..self.begin + self.length
+    }
+
+    /// 词元在文本中的字符范围, 左闭右开
+    pub fn position_range(&self) -> Range<usize> {
+        self.get_begin_position()..self.get_end_position()
+    }
+
     pub fn get_begin(&self) -> usize {
         self.begin
     }
@@ -105,33 +373,97 @@ impl Lexeme {
 
     // 获取词元的文本内容
     pub fn get_lexeme_text(&self) -> &str {
-        &self.lexeme_text
+        self.lexeme_text.as_deref().unwrap_or("")
     }
 
+    /// 从输入文本中解析词元文本及字节范围
     pub fn parse_lexeme_text(&mut self, input: &str) {
-        let sub_text = utf8_slice(input, self.begin, self.begin + self.length);
-        self.lexeme_text = sub_text.to_string();
+        self.bytes = byte_range(input, self.range());
+        self.lexeme_text = Some(Arc::from(&input[self.bytes.clone()]));
+        for part in self.parts.iter_mut() {
+            part.parse_lexeme_text(input);
+        }
+    }
+
+    // 与 parse_lexeme_text 相同, 字节范围取自预先计算的 offsets(每个字符的起始字节偏移,
+    // 末尾附加文本的字节长度), interner 不为 None 时文本从 interner 中取得
+    pub(crate) fn parse_lexeme_text_with(
+        &mut self,
+        input: &str,
+        offsets: &[usize],
+        mut interner: Option<&mut Interner>,
+    ) {
+        let range = self.range();
+        let last = offsets.len() - 1;
+        self.bytes = offsets[range.start.min(last)]..offsets[range.end.min(last)];
+        let sub_text = &input[self.bytes.clone()];
+        self.lexeme_text = Some(match interner.as_mut() {
+            Some(interner) => interner.intern(sub_text),
+            None => Arc::from(sub_text),
+        });
+        for part in self.parts.iter_mut() {
+            part.parse_lexeme_text_with(input, offsets, interner.as_deref_mut());
+        }
+    }
+
+    // 将词元(及组成词元)的位置后移 chars 个字符、bytes 个字节
+    // 用于把片段内的词元换算到整个文本
+    pub(crate) fn shift(&mut self, chars: usize, bytes: usize) {
+        self.begin += chars;
+        self.bytes = self.bytes.start + bytes..self.bytes.end + bytes;
+        for part in self.parts.iter_mut() {
+            part.shift(chars, bytes);
+        }
+    }
+
+    /// 词元在输入文本中的字节范围, 左闭右开, 可直接用于切片
+    /// 由 parse_lexeme_text 计算, IKSegmenter 输出的词元均已解析; 解析前为 0..0
+    pub fn byte_range(&self) -> Range<usize> {
+        self.bytes.clone()
+    }
+
+    // 共享的词元文本, 解析前为 None
+    pub fn get_shared_text(&self) -> Option<&Arc<str>> {
+        self.lexeme_text.as_ref()
+    }
+
+    // 获取数字词元的数值, 支持阿拉伯数字及单个罗马数字、带圈数字
+    // 需要在 parse_lexeme_text 之后调用
+    pub fn get_numeric_value(&self) -> Option<u64> {
+        if let Ok(value) = self.get_lexeme_text().parse::<u64>() {
+            return Some(value);
+        }
+        let mut chars = self.get_lexeme_text().chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => numeral_value(c).map(u64::from),
+            _ => None,
+        }
     }
 
     // 获取词元类型标示字符串
     pub fn get_lexeme_type_string(&self) -> &str {
-        match &self.lexeme_type {
-            LexemeType::ENGLISH => "ENGLISH",
-            LexemeType::ARABIC => "ARABIC",
-            LexemeType::LETTER => "LETTER",
-            LexemeType::CNWORD => "CN_WORD",
-            LexemeType::CNCHAR => "CN_CHAR",
-            LexemeType::OtherCJK => "OtherCjk",
-            LexemeType::COUNT => "COUNT",
-            LexemeType::CNUM => "TYPE_CNUM",
-            LexemeType::CQUAN => "TYPE_CQUAN",
-            _ => "UNKNOW",
-        }
+        self.lexeme_type.as_str()
+    }
+
+    /// 合并而成的词元(如数量词)的组成词元, 按位置排列, 偏移与本词元一致
+    /// 可用于同时索引合并词及其组成部分; 未经合并的词元返回空
+    pub fn parts(&self) -> &[Lexeme] {
+        &self.parts
     }
 
     // 合并两个相邻的词元, 返回 词元是否成功合并
+    // 合并后记录双方的组成词元, 多轮合并时组成词元保持平铺
     pub fn append(&mut self, l: &Lexeme, lexeme_type: LexemeType) -> bool {
         if self.get_end_position() == l.get_begin_position() {
+            if self.parts.is_empty() {
+                let part = self.clone();
+                self.parts.push(part);
+            }
+            if l.parts.is_empty() {
+                self.parts.push(l.clone());
+            } else {
+                self.parts.extend(l.parts.iter().cloned());
+            }
             self.length += l.get_length();
             self.lexeme_type = lexeme_type;
             return true;
@@ -1,11 +1,18 @@
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
-use std::ptr::NonNull;
+use std::ops::Range;
+use std::rc::Rc;
 
 use crate::core::lexeme::Lexeme;
-use crate::core::ordered_linked_list::{Node, OrderedLinkedList};
+
+// 词元链的节点, 由尾部指向头部, 克隆路径时共享已有的节点
+struct ChainNode {
+    lexeme: Lexeme,
+    prev: Option<Rc<ChainNode>>,
+}
 
 // Lexeme链（路径）
+// 词元链是持久化的单链表, clone 为 O(1), 歧义裁决时生成候选路径不必复制整条链
 pub struct LexemePath {
     // 起始位置
     pub path_begin: i32,
@@ -13,7 +20,10 @@ pub struct LexemePath {
     pub path_end: i32,
     // 词元链的有效字符长度
     pub payload_length: usize,
-    pub lexeme_list: OrderedLinkedList<Lexeme>,
+    // 词元链的尾节点
+    tail: Option<Rc<ChainNode>>,
+    // 词元个数
+    size: usize,
 }
 
 impl Default for LexemePath {
@@ -28,7 +38,8 @@ impl LexemePath {
             path_begin: -1,
             path_end: -1,
             payload_length: 0,
-            lexeme_list: OrderedLinkedList::new(),
+            tail: None,
+            size: 0,
         }
     }
 
@@ -37,69 +48,88 @@ impl LexemePath {
     // 如果 lexeme 和 lexeme_list 没有冲突，则不添加到 lexeme_list 中， 否则将更新 lexeme_list
     pub fn add_cross_lexeme(&mut self, lexeme: &Lexeme) -> bool {
         // lexeme_list 为空
-        if self.lexeme_list.is_empty() {
-            self.lexeme_list
-                .insert(lexeme.clone())
-                .expect("add cross lexeme error!");
+        if self.is_empty() {
+            self.insert(lexeme.clone());
             self.path_begin = lexeme.get_begin() as i32;
-            self.path_end = (lexeme.get_begin() + lexeme.get_length()) as i32;
+            self.path_end = lexeme.range().end as i32;
             self.payload_length += lexeme.get_length();
             true
         } else if self.check_cross(lexeme) {
             // 当前 lexeme 和 lexeme_list 冲突
-            self.lexeme_list
-                .insert(lexeme.clone())
-                .expect("add cross lexeme error!");
-            if (lexeme.get_begin() + lexeme.get_length()) as i32 > self.path_end {
-                self.path_end = (lexeme.get_begin() + lexeme.get_length()) as i32;
+            self.insert(lexeme.clone());
+            if lexeme.range().end as i32 > self.path_end {
+                self.path_end = lexeme.range().end as i32;
             }
             self.payload_length = (self.path_end - self.path_begin) as usize;
-            return true;
+            true
         } else {
-            return false;
+            false
         }
     }
 
     //  向LexemePath追加不相交的Lexeme
     pub fn add_not_cross_lexeme(&mut self, lexeme: &Lexeme) -> bool {
-        if self.lexeme_list.is_empty() {
-            self.lexeme_list
-                .insert(lexeme.clone())
-                .expect("add not cross lexeme error");
+        if self.is_empty() {
+            self.insert(lexeme.clone());
             self.path_begin = lexeme.get_begin() as i32;
-            self.path_end = (lexeme.get_begin() + lexeme.get_length()) as i32;
+            self.path_end = lexeme.range().end as i32;
             self.payload_length += lexeme.get_length();
             true
         } else if self.check_cross(lexeme) {
-            return false;
+            false
         } else {
-            self.lexeme_list
-                .insert(lexeme.clone())
-                .expect("add no cross lexeme error");
+            self.insert(lexeme.clone());
             self.payload_length += lexeme.get_length();
-            let head = self.lexeme_list.peek_front(); //  peekFirst();
-            self.path_begin = head.unwrap().get_begin() as i32;
-            let tail = self.lexeme_list.peek_back(); //  peekLast();
-            self.path_end =
-                (tail.unwrap().get_begin() as i32) + (tail.unwrap().get_length() as i32);
-            return true;
+            self.path_begin = self.path_begin.min(lexeme.get_begin() as i32);
+            let tail = self.peek_back().unwrap();
+            self.path_end = tail.range().end as i32;
+            true
         }
     }
 
+    // 按顺序插入词元, 重复的词元不插入
+    // 插入位置之后的节点被复制, 之前的节点与其他路径共享; 通常词元插入在尾部
+    fn insert(&mut self, lexeme: Lexeme) {
+        let mut after = Vec::new();
+        let mut node = self.tail.clone();
+        while let Some(n) = node.as_ref() {
+            match n.lexeme.cmp(&lexeme) {
+                Ordering::Greater => {
+                    after.push(n.lexeme.clone());
+                    node = n.prev.clone();
+                }
+                Ordering::Equal => return,
+                Ordering::Less => break,
+            }
+        }
+        node = Some(Rc::new(ChainNode { lexeme, prev: node }));
+        while let Some(lexeme) = after.pop() {
+            node = Some(Rc::new(ChainNode { lexeme, prev: node }));
+        }
+        self.tail = node;
+        self.size += 1;
+    }
+
+    fn peek_back(&self) -> Option<&Lexeme> {
+        self.tail.as_ref().map(|node| &node.lexeme)
+    }
+
     /// 移除尾部的Lexeme
     pub fn remove_tail(&mut self) -> Option<Lexeme> {
-        let tail = self.lexeme_list.pop_back();
-        if self.lexeme_list.is_empty() {
+        let node = self.tail.take()?;
+        self.tail = node.prev.clone();
+        self.size -= 1;
+        let tail = node.lexeme.clone();
+        if self.is_empty() {
             self.path_begin = -1;
             self.path_end = -1;
             self.payload_length = 0;
         } else {
-            self.payload_length -= tail.as_ref().unwrap().get_length();
-            let new_tail = self.lexeme_list.peek_back();
-            self.path_end = (new_tail.as_ref().unwrap().get_begin() as i32)
-                + (new_tail.as_ref().unwrap().get_length() as i32);
+            self.payload_length -= tail.get_length();
+            let new_tail = self.peek_back().unwrap();
+            self.path_end = new_tail.range().end as i32;
         }
-        tail
+        Some(tail)
     }
 
     // 检测词元位置交叉（有歧义的切分）
@@ -111,6 +141,15 @@ impl LexemePath {
             || (self.path_begin >= l_begin && self.path_begin < l_begin + l_length)
     }
 
+    /// 路径覆盖的字符范围, 左闭右开, 空路径返回 None
+    pub fn range(&self) -> Option<Range<usize>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.path_begin as usize..self.path_end as usize)
+        }
+    }
+
     pub fn get_path_begin(&self) -> i32 {
         self.path_begin
     }
@@ -130,7 +169,7 @@ impl LexemePath {
     // X权重（词元长度积）
     pub fn get_xweight(&self) -> i32 {
         let mut product = 1;
-        for lexeme in self.lexeme_list.iter() {
+        for lexeme in self.iter() {
             product *= lexeme.get_length();
         }
         product as i32
@@ -140,23 +179,69 @@ impl LexemePath {
     pub fn get_pweight(&self) -> i32 {
         let mut p_weight = 0;
         let mut p = 0;
-        for lexeme in self.lexeme_list.iter() {
+        for lexeme in self.iter() {
             p += 1;
             p_weight += p * lexeme.get_length();
         }
         p_weight as i32
     }
 
+    // 词频权重（词元词频之和）
+    pub fn get_freq_weight(&self) -> u64 {
+        self.iter().map(|lexeme| u64::from(lexeme.get_freq())).sum()
+    }
+
     pub fn size(&self) -> usize {
-        self.lexeme_list.length()
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
     }
 
-    pub fn poll_first(&mut self) -> Option<Lexeme> {
-        self.lexeme_list.pop_front()
+    // 从头到尾遍历词元
+    pub fn iter(&self) -> impl Iterator<Item = &Lexeme> {
+        let mut lexemes = Vec::with_capacity(self.size);
+        let mut node = self.tail.as_deref();
+        while let Some(n) = node {
+            lexemes.push(&n.lexeme);
+            node = n.prev.as_deref();
+        }
+        lexemes.into_iter().rev()
     }
 
-    pub fn get_head(&self) -> Option<&NonNull<Node<Lexeme>>> {
-        self.lexeme_list.head_node()
+    // 按从头到尾的顺序取出全部词元
+    pub fn into_lexemes(mut self) -> Vec<Lexeme> {
+        let mut lexemes = Vec::with_capacity(self.size);
+        let mut node = self.tail.take();
+        while let Some(n) = node {
+            node = match Rc::try_unwrap(n) {
+                Ok(n) => {
+                    lexemes.push(n.lexeme);
+                    n.prev
+                }
+                // 与其他路径共享的节点, 复制其中的词元
+                Err(n) => {
+                    lexemes.push(n.lexeme.clone());
+                    n.prev.clone()
+                }
+            };
+        }
+        lexemes.reverse();
+        lexemes
+    }
+}
+
+impl Drop for LexemePath {
+    // 逐个释放不再共享的节点, 避免长链递归析构导致栈溢出
+    fn drop(&mut self) {
+        let mut node = self.tail.take();
+        while let Some(n) = node {
+            node = match Rc::try_unwrap(n) {
+                Ok(mut n) => n.prev.take(),
+                Err(_) => None,
+            };
+        }
     }
 }
 
@@ -164,79 +249,158 @@ impl Display for LexemePath {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "path_begin:{}, path_end:{}, payload_length:{}, lexeme_list:{}",
-            self.path_begin, self.path_end, self.payload_length, self.lexeme_list
-        )
+            "path_begin:{}, path_end:{}, payload_length:{}, lexeme_list:[",
+            self.path_begin, self.path_end, self.payload_length
+        )?;
+        for (i, lexeme) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:?}", lexeme)?;
+        }
+        write!(f, "]")
     }
 }
 
 impl Clone for LexemePath {
     fn clone(&self) -> Self {
-        let mut the_copy = LexemePath::new();
-        the_copy.path_begin = self.path_begin;
-        the_copy.path_end = self.path_end;
-        the_copy.payload_length = self.payload_length;
-        for lexeme in self.lexeme_list.iter() {
-            the_copy
-                .lexeme_list
-                .insert(lexeme.clone())
-                .expect("clone insert error");
+        LexemePath {
+            path_begin: self.path_begin,
+            path_end: self.path_end,
+            payload_length: self.payload_length,
+            tail: self.tail.clone(),
+            size: self.size,
         }
-        the_copy
+    }
+}
+
+impl LexemePath {
+    /// 按 IK 的歧义裁决规则比较, Less 表示 self 更优
+    /// 有效文本长度越长越好, 词元数越少越好, 路径跨度越长越好, 结束位置越靠后越好,
+    /// 词元长度越平均越好, 位置权重越大越好
+    pub fn cmp_by_rules(&self, other: &Self) -> Ordering {
+        other
+            .payload_length
+            .cmp(&self.payload_length)
+            .then_with(|| self.size().cmp(&other.size()))
+            .then_with(|| other.get_path_length().cmp(&self.get_path_length()))
+            .then_with(|| other.path_end.cmp(&self.path_end))
+            .then_with(|| other.get_xweight().cmp(&self.get_xweight()))
+            .then_with(|| other.get_pweight().cmp(&self.get_pweight()))
+    }
+
+    /// 规则全部相同时按词频权重比较, 词频之和越大越好
+    /// 词典未指定词频时各路径的权重均为 0, 不影响裁决结果
+    pub fn cmp_by_freq(&self, other: &Self) -> Ordering {
+        other.get_freq_weight().cmp(&self.get_freq_weight())
+    }
+
+    /// 词频也相同时的最终裁决: 依次比较两条路径的词元,
+    /// 起始位置靠前者更优, 起始位置相同时较长者更优
+    pub fn cmp_lexemes(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
     }
 }
 
 impl Ord for LexemePath {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.cmp_by_rules(other)
+            .then_with(|| self.cmp_by_freq(other))
+            .then_with(|| self.cmp_lexemes(other))
     }
 }
 
 impl PartialOrd<Self> for LexemePath {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        // 比较有效文本长度
-        Some(match self.payload_length.cmp(&other.payload_length) {
-            Ordering::Less => Ordering::Greater,
-            Ordering::Greater => Ordering::Less,
-            Ordering::Equal => match self.size().cmp(&other.size()) {
-                Ordering::Less => Ordering::Less,
-                Ordering::Greater => Ordering::Greater,
-                Ordering::Equal => match self.get_path_length().cmp(&other.get_path_length()) {
-                    Ordering::Less => Ordering::Greater,
-                    Ordering::Greater => Ordering::Less,
-                    Ordering::Equal => match self.path_end.cmp(&other.path_end) {
-                        Ordering::Less => Ordering::Greater,
-                        Ordering::Greater => Ordering::Less,
-                        Ordering::Equal => match self.get_xweight().cmp(&other.get_xweight()) {
-                            Ordering::Less => Ordering::Greater,
-                            Ordering::Greater => Ordering::Less,
-                            Ordering::Equal => other.get_pweight().cmp(&self.get_pweight()),
-                        },
-                    },
-                },
-            },
-        })
+        Some(self.cmp(other))
     }
 }
 
 impl Eq for LexemePath {}
 impl PartialEq for LexemePath {
     fn eq(&self, other: &Self) -> bool {
-        if self.path_begin == other.path_begin
+        self.path_begin == other.path_begin
             && self.path_end == other.path_end
             && self.payload_length == other.payload_length
-            && self.lexeme_list.length() == other.lexeme_list.length()
-        {
-            for _ in 0..self.lexeme_list.length() {
-                let a = self.lexeme_list.iter().next().unwrap();
-                let b = other.lexeme_list.iter().next().unwrap();
-                if !a.eq(b) {
-                    return false;
-                }
+            && self.size == other.size
+            && self.iter().eq(other.iter())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::lexeme::LexemeType;
+    use crate::core::test_util::{path, tie_paths};
+
+    #[test]
+    fn test_tie_break() {
+        // 两条路径的各项规则都相同
+        let (a, b) = tie_paths();
+        assert_eq!(a.cmp_by_rules(&b), Ordering::Equal);
+        assert_eq!(a.cmp(&b), Ordering::Less);
+        assert_eq!(b.cmp(&a), Ordering::Greater);
+        assert_eq!(a.cmp(&a.clone()), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_freq_tie_break() {
+        let freq_path = |lexemes: &[(usize, usize, u32)]| {
+            let mut path = LexemePath::new();
+            for (begin, length, freq) in lexemes {
+                let lexeme = Lexeme::new(0, *begin, *length, LexemeType::CNWORD).with_freq(*freq);
+                path.add_not_cross_lexeme(&lexeme);
             }
-            true
-        } else {
-            false
-        }
+            path
+        };
+        // 规则相同, 不考虑词频时 a 更优
+        let a = freq_path(&[(0, 1), (1, 2), (4, 1)].map(|(b, l)| (b, l, 1)));
+        let b = freq_path(&[(0, 1, 1), (2, 2, 50), (4, 1, 1)]);
+        assert_eq!(a.cmp_by_rules(&b), Ordering::Equal);
+        assert_eq!(b.get_freq_weight(), 52);
+        assert_eq!(b.cmp(&a), Ordering::Less);
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(LexemePath::new().range(), None);
+        let path = path(&[(1, 2), (4, 1)]);
+        assert_eq!(path.range(), Some(1..5));
+        let lexeme = Lexeme::from_range(3, 1..3, LexemeType::CNWORD);
+        assert_eq!(lexeme.get_length(), 2);
+        assert_eq!(lexeme.range(), 1..3);
+        assert_eq!(lexeme.position_range(), 4..6);
+    }
+
+    #[test]
+    fn test_clone_on_write() {
+        let mut path = LexemePath::new();
+        path.add_not_cross_lexeme(&Lexeme::new(0, 0, 2, LexemeType::CNWORD));
+        path.add_not_cross_lexeme(&Lexeme::new(0, 2, 2, LexemeType::CNWORD));
+        let snapshot = path.clone();
+
+        path.remove_tail();
+        path.add_not_cross_lexeme(&Lexeme::new(0, 2, 3, LexemeType::CNWORD));
+        assert_eq!(path.get_path_end(), 5);
+        // 插入到头部之前
+        path.remove_tail();
+        path.remove_tail();
+        path.add_not_cross_lexeme(&Lexeme::new(0, 4, 1, LexemeType::CNWORD));
+        path.add_not_cross_lexeme(&Lexeme::new(0, 1, 2, LexemeType::CNWORD));
+        assert_eq!(
+            path.iter().map(|l| l.get_begin()).collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+        assert_eq!((path.get_path_begin(), path.get_path_end()), (1, 5));
+        assert_eq!(snapshot.get_path_end(), 4);
+        assert_eq!(snapshot.size(), 2);
+        assert!(path != snapshot);
+
+        let lengths = snapshot
+            .into_lexemes()
+            .iter()
+            .map(|l| l.get_length())
+            .collect::<Vec<_>>();
+        assert_eq!(lengths, vec![2, 2]);
     }
 }
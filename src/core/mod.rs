@@ -1,10 +1,25 @@
+pub mod alignment;
 pub mod char_util;
+pub mod cjk_connector_segmenter;
 pub mod cjk_segmenter;
 pub mod cn_quantifier_segmenter;
+pub mod cooccurrence;
 pub mod ik_arbitrator;
 pub mod ik_segmenter;
+pub mod ik_token;
+pub mod interner;
+pub mod lattice;
 pub mod letter_segmentor;
 pub mod lexeme;
 pub mod lexeme_path;
 pub mod ordered_linked_list;
+pub mod reduplication_segmenter;
+pub mod segmentation_result;
 pub mod segmentor;
+pub mod special_segmenter;
+pub mod suffix_entity_segmenter;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod token_batch;
+pub mod token_record;
+pub mod tokenization_result;
@@ -282,7 +282,7 @@ impl<T: PartialOrd> OrderedLinkedList<T> {
         unsafe { Ok(cur.as_ref().map(|node| &node.as_ref().val)) }
     }
 
-    pub fn get_mut(&self, idx: usize) -> Result<Option<&mut T>, Box<dyn Error>> {
+    pub fn get_mut(&mut self, idx: usize) -> Result<Option<&mut T>, Box<dyn Error>> {
         let mut cur = self._get_by_idx_mut(idx)?;
         unsafe { Ok(cur.as_mut().map(|node| &mut node.as_mut().val)) }
     }
@@ -352,7 +352,9 @@ impl<T: PartialOrd> OrderedLinkedList<T> {
     }
 
     pub fn contains(&self, elem: &T) -> bool
-    where T: PartialEq<T> {
+    where
+        T: PartialEq<T>,
+    {
         self.iter().any(|x| x == elem)
     }
 
@@ -0,0 +1,78 @@
+use crate::core::char_util::{char_type_of, CharType};
+use crate::core::lexeme::{Lexeme, LexemeType};
+use crate::core::segmentor::Segmenter;
+
+// 子分词器标签
+const SEGMENTER_NAME: &str = "REDUPLICATION_SEGMENTER";
+
+// 否定词, ABAB 中的 B 为否定词时是正反问(是不是不想去), 不是叠词
+const NEGATIONS: [char; 2] = ['不', '没'];
+
+// 叠词子分词器
+// 按 AABB(高高兴兴)、ABAB(研究研究, B 不是否定词)、AA(看看) 规则输出 CNWORD 候选词元, 由歧义裁决决定是否采用
+// 用于词典中没有收录的叠词, 避免被切成单字
+#[derive(Debug, Default)]
+pub struct ReduplicationSegmenter;
+
+impl Segmenter for ReduplicationSegmenter {
+    fn analyze(&mut self, input: &[char]) -> Vec<Lexeme> {
+        let mut new_lexemes = Vec::new();
+        for cursor in 0..input.len() {
+            let window = &input[cursor..];
+            if window.len() >= 4 && window[..4].iter().all(is_chinese) {
+                let (a, b, c, d) = (window[0], window[1], window[2], window[3]);
+                // AABB 或 ABAB
+                let abab = a == c && b == d && a != b && !NEGATIONS.contains(&b);
+                if (a == b && c == d && a != c) || abab {
+                    new_lexemes.push(Lexeme::new(0, cursor, 4, LexemeType::CNWORD));
+                }
+            }
+            // AA
+            if window.len() >= 2 && window[0] == window[1] && is_chinese(&window[0]) {
+                new_lexemes.push(Lexeme::new(0, cursor, 2, LexemeType::CNWORD));
+            }
+        }
+        new_lexemes
+    }
+
+    fn name(&self) -> &str {
+        SEGMENTER_NAME
+    }
+
+    fn is_cjk_only(&self) -> bool {
+        true
+    }
+}
+
+impl ReduplicationSegmenter {
+    pub fn new() -> Self {
+        ReduplicationSegmenter
+    }
+}
+
+fn is_chinese(c: &char) -> bool {
+    char_type_of(c) == CharType::CHINESE
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reduplication() {
+        let mut segmenter = ReduplicationSegmenter::new();
+        let input = "高高兴兴地研究研究, 看看aa".chars().collect::<Vec<_>>();
+        let spans = segmenter
+            .analyze(&input)
+            .iter()
+            .map(|l| (l.get_begin(), l.get_length()))
+            .collect::<Vec<_>>();
+        assert_eq!(spans, vec![(0, 4), (0, 2), (2, 2), (5, 4), (11, 2)]);
+
+        // 正反问不是 ABAB 叠词
+        for text in ["你是不是不想去", "有没有没去过"] {
+            let input = text.chars().collect::<Vec<_>>();
+            assert!(segmenter.analyze(&input).is_empty(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::ops::Range;
+
+use crate::core::lexeme::Lexeme;
+
+/// 一个歧义裁决区间, 即一组相互交叉的候选词元覆盖的字符范围(左闭右开)
+/// 区间内的切分由歧义裁决决定, 区间之间互不影响
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub range: Range<usize>,
+}
+
+/// 分词过程的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SegmentationStats {
+    // 输入的字符数
+    pub char_count: usize,
+    // 子分词器输出的候选词元数
+    pub candidates: usize,
+    // 被过滤的停止词数
+    pub stop_words: usize,
+    // SEARCH 模式下合并的数量词数
+    pub compounds: usize,
+}
+
+/// IKSegmenter::segment 的结果
+/// 内部的路径、链表等结构不对外暴露, 以便后续调整实现
+#[derive(Debug, Clone, Default)]
+pub struct SegmentationResult {
+    tokens: Vec<Lexeme>,
+    sections: Vec<Section>,
+    // 与 tokens 一一对应, 词元所在歧义裁决区间的下标
+    token_sections: Vec<Option<usize>>,
+    stats: SegmentationStats,
+}
+
+impl SegmentationResult {
+    pub(crate) fn new(
+        tokens: Vec<Lexeme>,
+        sections: Vec<Section>,
+        stats: SegmentationStats,
+    ) -> Self {
+        let token_sections = tokens
+            .iter()
+            .map(|token| section_index(&sections, token.get_begin_position()))
+            .collect();
+        SegmentationResult {
+            tokens,
+            sections,
+            token_sections,
+            stats,
+        }
+    }
+
+    /// 输出的词元, 按起始位置排列
+    pub fn tokens(&self) -> &[Lexeme] {
+        &self.tokens
+    }
+
+    pub fn into_tokens(self) -> Vec<Lexeme> {
+        self.tokens
+    }
+
+    /// 歧义裁决区间, 按起始位置排列
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// 与 tokens 一一对应, 每个词元所在歧义裁决区间在 sections 中的下标
+    /// 不属于任何区间的词元(如未切分的单字)为 None; 合并的数量词按起始位置归属
+    pub fn token_sections(&self) -> &[Option<usize>] {
+        &self.token_sections
+    }
+
+    /// 第 index 个词元所在的歧义裁决区间
+    pub fn section_of(&self, index: usize) -> Option<&Section> {
+        let section = (*self.token_sections.get(index)?)?;
+        self.sections.get(section)
+    }
+
+    /// 字符位置 position 是否可以安全截断, 即不落在任何歧义裁决区间的内部
+    /// 用于生成摘要时避免截断在歧义区域中间
+    pub fn is_boundary(&self, position: usize) -> bool {
+        match section_index(&self.sections, position) {
+            Some(section) => self.sections[section].range.start == position,
+            None => true,
+        }
+    }
+
+    pub fn stats(&self) -> &SegmentationStats {
+        &self.stats
+    }
+}
+
+// 二分查找包含 position 的区间, sections 按起始位置排列且互不交叉
+fn section_index(sections: &[Section], position: usize) -> Option<usize> {
+    let index = sections.partition_point(|section| section.range.start <= position);
+    let section = index.checked_sub(1)?;
+    if sections[section].range.contains(&position) {
+        Some(section)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::lexeme::LexemeType;
+
+    #[test]
+    fn test_token_sections() {
+        let tokens = vec![
+            Lexeme::new(0, 0, 2, LexemeType::CNWORD),
+            Lexeme::new(0, 2, 2, LexemeType::CNWORD),
+            Lexeme::new(0, 4, 1, LexemeType::CNCHAR),
+            Lexeme::new(0, 5, 3, LexemeType::CNWORD),
+        ];
+        let sections = vec![Section { range: 0..4 }, Section { range: 5..8 }];
+        let result = SegmentationResult::new(tokens, sections, SegmentationStats::default());
+        assert_eq!(result.token_sections(), &[Some(0), Some(0), None, Some(1)]);
+        assert_eq!(result.section_of(3).unwrap().range, 5..8);
+        assert!(result.section_of(2).is_none());
+        assert!(result.section_of(9).is_none());
+        let boundaries = (0..=8)
+            .filter(|&position| result.is_boundary(position))
+            .collect::<Vec<_>>();
+        assert_eq!(boundaries, vec![0, 4, 5, 8]);
+    }
+}
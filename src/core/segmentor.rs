@@ -1,6 +1,11 @@
 use crate::core::lexeme::Lexeme;
 
-pub trait Segmenter {
+// 子分词器须为 Send, 使 IKSegmenter 可以移动到其他线程(如 THREAD_IK、worker)
+pub trait Segmenter: Send {
     fn analyze(&mut self, input: &[char]) -> Vec<Lexeme>;
     fn name(&self) -> &str;
+    // 是否只切分中日韩文字, 检测到文本不含中日韩文字时可以跳过
+    fn is_cjk_only(&self) -> bool {
+        false
+    }
 }
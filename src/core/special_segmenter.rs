@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::core::lexeme::{Lexeme, LexemeType};
+use crate::core::segmentor::Segmenter;
+
+// 子分词器标签
+const SEGMENTER_NAME: &str = "SPECIAL_SEGMENTER";
+
+// 默认的标点及特殊符号集合(全角及半角)
+pub const DEFAULT_SPECIAL_CHARS: &str = concat!(
+    "，。、；：？！…—–·‘’“”「」『』《》〈〉【】〔〕〖〗（）［］｛｝～＂＇｀｜＼／＠＃＄％＾＆＊＋＝＜＞＿－",
+    "〃々〜‖∶¨ˇˉ•※§№°℃￥￡￠",
+    ",.;:?!'\"()[]{}<>-_/\\|@#$%^&*+=~`",
+);
+
+// 标点及特殊符号子分词器, 仅在 full coverage 模式下启用
+// 将每个特殊符号输出为单字的 SPECIAL 词元, 保证标点不会被静默丢弃
+#[derive(Debug)]
+pub struct SpecialSegmenter {
+    special_chars: HashSet<char>,
+}
+
+impl Segmenter for SpecialSegmenter {
+    fn analyze(&mut self, input: &[char]) -> Vec<Lexeme> {
+        let mut new_lexemes = Vec::new();
+        for (cursor, curr_char) in input.iter().enumerate() {
+            if self.is_special(curr_char) {
+                new_lexemes.push(Lexeme::new(0, cursor, 1, LexemeType::SPECIAL));
+            }
+        }
+        new_lexemes
+    }
+
+    fn name(&self) -> &str {
+        SEGMENTER_NAME
+    }
+}
+
+impl Default for SpecialSegmenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpecialSegmenter {
+    pub fn new() -> Self {
+        Self::with_chars(DEFAULT_SPECIAL_CHARS.chars())
+    }
+
+    pub fn with_chars<C: IntoIterator<Item = char>>(chars: C) -> Self {
+        SpecialSegmenter {
+            special_chars: chars.into_iter().filter(|c| !c.is_whitespace()).collect(),
+        }
+    }
+
+    // 从符号词典加载, 每行可以包含一个或多个符号
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| format!("open special chars dict {} error: {}", path.display(), e))?;
+        let mut chars = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line
+                .map_err(|e| format!("read special chars dict {} error: {}", path.display(), e))?;
+            chars.extend(line.trim_start_matches('\u{feff}').chars());
+        }
+        Ok(Self::with_chars(chars))
+    }
+
+    // 判断是否是特殊符号
+    pub fn is_special(&self, c: &char) -> bool {
+        self.special_chars.contains(c)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_special_chars() {
+        let segmenter = SpecialSegmenter::new();
+        for c in "：、‘’“”——…《》【】（）".chars() {
+            assert!(segmenter.is_special(&c), "{:?}", c);
+        }
+        assert!(!segmenter.is_special(&'中'));
+        assert!(!segmenter.is_special(&' '));
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join(format!("ik-special-{}.dic", std::process::id()));
+        std::fs::write(&path, "\u{feff}★☆\n→\n").unwrap();
+        let segmenter = SpecialSegmenter::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(segmenter.is_special(&'☆'));
+        assert!(segmenter.is_special(&'→'));
+        assert!(!segmenter.is_special(&'\u{feff}'));
+
+        let error = SpecialSegmenter::load(&path).unwrap_err();
+        assert!(error.starts_with("open special chars dict"), "{}", error);
+    }
+}
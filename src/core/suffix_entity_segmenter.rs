@@ -0,0 +1,178 @@
+use crate::core::char_util::{char_type_of, CharType};
+use crate::core::lexeme::{Lexeme, LexemeType};
+use crate::core::segmentor::Segmenter;
+use crate::dict::dictionary::DictRef;
+
+// 子分词器标签
+const SEGMENTER_NAME: &str = "SUFFIX_ENTITY_SEGMENTER";
+
+// 默认的机构名后缀
+pub const DEFAULT_ORG_SUFFIXES: [&str; 6] = ["公司", "集团", "银行", "大学", "学院", "医院"];
+
+// 默认的地名后缀
+pub const DEFAULT_LOC_SUFFIXES: [&str; 4] = ["路", "街", "区", "县"];
+
+// 常见的虚词及代词, 不作为实体名的一部分, 如 他在嵘峰公司 中的 他在
+const BOUNDARY_CHARS: &str = "的地得了着过在是和与及或把被从向往到于为对给他她它我你这那就也都很又";
+
+// 后缀前最少、最多连接的字数
+const MIN_PREFIX_LEN: usize = 2;
+const MAX_PREFIX_LEN: usize = 4;
+
+// 后缀实体子分词器
+// 后缀前紧邻 2~4 个未被词典中的词覆盖的汉字时, 连同后缀输出一个 ORG/LOC 候选词元
+// 用于词典中没有收录的机构名、地名, 由歧义裁决决定是否采用
+#[derive(Debug)]
+pub struct SuffixEntitySegmenter {
+    // (后缀, 词元类型), 按后缀长度降序排列
+    suffixes: Vec<(Vec<char>, LexemeType)>,
+    dict: DictRef,
+}
+
+impl Segmenter for SuffixEntitySegmenter {
+    fn analyze(&mut self, input: &[char]) -> Vec<Lexeme> {
+        let mut new_lexemes = Vec::new();
+        let matches = (0..input.len())
+            .filter_map(|cursor| self.match_suffix(input, cursor).map(|m| (cursor, m)))
+            .collect::<Vec<_>>();
+        if matches.is_empty() {
+            return new_lexemes;
+        }
+        let covered = self.covered(input);
+        for (cursor, (length, lexeme_type)) in matches {
+            // 向前数紧邻后缀的未覆盖汉字
+            let prefix_len = (0..cursor)
+                .rev()
+                .take_while(|&i| {
+                    !covered[i] && is_chinese(&input[i]) && !BOUNDARY_CHARS.contains(input[i])
+                })
+                .count();
+            if (MIN_PREFIX_LEN..=MAX_PREFIX_LEN).contains(&prefix_len) {
+                let begin = cursor - prefix_len;
+                new_lexemes.push(Lexeme::new(0, begin, prefix_len + length, lexeme_type));
+            }
+        }
+        new_lexemes
+    }
+
+    fn name(&self) -> &str {
+        SEGMENTER_NAME
+    }
+
+    fn is_cjk_only(&self) -> bool {
+        true
+    }
+}
+
+impl Default for SuffixEntitySegmenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SuffixEntitySegmenter {
+    pub fn new() -> Self {
+        SuffixEntitySegmenter {
+            suffixes: Vec::new(),
+            dict: DictRef::Global,
+        }
+        .with_suffixes(DEFAULT_ORG_SUFFIXES, LexemeType::ORG)
+        .with_suffixes(DEFAULT_LOC_SUFFIXES, LexemeType::LOC)
+    }
+
+    /// 追加一组后缀, 命中时输出 lexeme_type 类型的词元
+    pub fn with_suffixes<I, S>(mut self, suffixes: I, lexeme_type: LexemeType) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for suffix in suffixes {
+            let suffix = suffix.as_ref().chars().collect::<Vec<_>>();
+            if !suffix.is_empty() {
+                self.suffixes.push((suffix, lexeme_type));
+            }
+        }
+        // 优先匹配长后缀
+        self.suffixes
+            .sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+        self
+    }
+
+    // 使用指定的词典
+    pub(crate) fn with_dict(mut self, dict: DictRef) -> Self {
+        self.dict = dict;
+        self
+    }
+
+    // cursor 处的后缀, 返回后缀长度及词元类型
+    fn match_suffix(&self, input: &[char], cursor: usize) -> Option<(usize, LexemeType)> {
+        self.suffixes
+            .iter()
+            .find(|(suffix, _)| input[cursor..].starts_with(suffix))
+            .map(|(suffix, lexeme_type)| (suffix.len(), *lexeme_type))
+    }
+
+    // 被词典中的多字词覆盖的字符
+    fn covered(&self, input: &[char]) -> Vec<bool> {
+        let mut covered = vec![false; input.len()];
+        self.dict.pinned(|| {
+            for cursor in 0..input.len() {
+                if !is_chinese(&input[cursor]) {
+                    continue;
+                }
+                let hits =
+                    self.dict
+                        .match_in_main_dict_with_offset(input, cursor, input.len() - cursor);
+                for hit in hits.iter() {
+                    let range = hit.range();
+                    if hit.is_match() && range.len() > 1 {
+                        covered[range].iter_mut().for_each(|c| *c = true);
+                    }
+                }
+            }
+        });
+        covered
+    }
+}
+
+fn is_chinese(c: &char) -> bool {
+    char_type_of(c) == CharType::CHINESE
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::dict::dictionary::Dictionary;
+    use crate::dict::trie::Trie;
+
+    #[test]
+    fn test_suffix_entities() {
+        let mut main = Trie::default();
+        for word in ["公司", "大学", "技术", "前往", "位于"] {
+            main.insert(word.chars());
+        }
+        let dict = Dictionary::with_dicts(main, Trie::default(), Trie::default());
+        let mut segmenter = SuffixEntitySegmenter::new().with_dict(DictRef::Local(Arc::new(dict)));
+        let text = "前往嵘峰技术公司, 嵘峰公司位于沧澜路, 在鹤大学";
+        let input = text.chars().collect::<Vec<_>>();
+        let entities = segmenter
+            .analyze(&input)
+            .iter()
+            .map(|l| {
+                let word = input[l.get_begin()..l.get_begin() + l.get_length()]
+                    .iter()
+                    .collect::<String>();
+                (word, l.get_lexeme_type_string().to_string())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entities,
+            vec![
+                ("嵘峰公司".to_string(), "ORG".to_string()),
+                ("沧澜路".to_string(), "LOC".to_string()),
+            ]
+        );
+    }
+}
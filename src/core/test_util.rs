@@ -0,0 +1,48 @@
+//! core 各模块测试共用的辅助函数
+use crate::core::cjk_segmenter::CJKSegmenter;
+use crate::core::cn_quantifier_segmenter::CnQuantifierSegmenter;
+use crate::core::letter_segmentor::LetterSegmenter;
+use crate::core::lexeme::{Lexeme, LexemeType};
+use crate::core::lexeme_path::LexemePath;
+use crate::core::segmentor::Segmenter;
+
+// 默认的三个子分词器, 测试可以在其后追加其他子分词器
+pub(crate) fn base_segmenters() -> Vec<Box<dyn Segmenter>> {
+    vec![
+        Box::new(LetterSegmenter::new()),
+        Box::new(CnQuantifierSegmenter::new()),
+        Box::new(CJKSegmenter::new()),
+    ]
+}
+
+// 子分词器输出的词元在输入中对应的文本
+pub(crate) fn lexeme_texts<'a>(
+    chars: &[char],
+    lexemes: impl IntoIterator<Item = &'a Lexeme>,
+) -> Vec<String> {
+    lexemes
+        .into_iter()
+        .map(|l| {
+            chars[l.get_begin()..l.get_begin() + l.get_length()]
+                .iter()
+                .collect()
+        })
+        .collect()
+}
+
+// 由 (begin, length) 构造不相交的 CNWORD 词元路径
+pub(crate) fn path(lexemes: &[(usize, usize)]) -> LexemePath {
+    let mut path = LexemePath::new();
+    for (begin, length) in lexemes {
+        path.add_not_cross_lexeme(&Lexeme::new(0, *begin, *length, LexemeType::CNWORD));
+    }
+    path
+}
+
+// 各项规则都相同、只能由最终裁决区分的两条路径, 默认裁决下前者更优
+pub(crate) fn tie_paths() -> (LexemePath, LexemePath) {
+    (
+        path(&[(0, 1), (1, 2), (4, 1)]),
+        path(&[(0, 1), (2, 2), (4, 1)]),
+    )
+}
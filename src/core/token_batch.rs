@@ -0,0 +1,76 @@
+use crate::core::lexeme::{Lexeme, LexemeType};
+
+/// 按列存放的批量分词结果, 每个词元一行
+/// 词元文本的布局与 Arrow 的 LargeUtf8 数组相同, 可以不逐行转换直接构造列式数据
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenBatch {
+    // 词元所属文档在输入中的下标
+    pub doc_ids: Vec<u64>,
+    // 所有词元文本依次拼接
+    pub token_values: String,
+    // 第 i 个词元的文本为 token_values[token_offsets[i]..token_offsets[i + 1]], 首元素为 0
+    pub token_offsets: Vec<i64>,
+    // 词元在所属文档中的字节偏移, end 不包含
+    pub starts: Vec<u32>,
+    pub ends: Vec<u32>,
+    pub types: Vec<LexemeType>,
+}
+
+impl TokenBatch {
+    pub fn new() -> Self {
+        TokenBatch {
+            token_offsets: vec![0],
+            ..TokenBatch::default()
+        }
+    }
+
+    /// 追加文档 doc_id 的词元, lexemes 需已解析文本及字节范围(IKSegmenter 的输出均已解析)
+    pub fn push(&mut self, doc_id: u64, lexemes: &[Lexeme]) {
+        if self.token_offsets.is_empty() {
+            self.token_offsets.push(0);
+        }
+        for lexeme in lexemes {
+            let bytes = lexeme.byte_range();
+            self.doc_ids.push(doc_id);
+            self.token_values.push_str(lexeme.get_lexeme_text());
+            self.token_offsets.push(self.token_values.len() as i64);
+            self.starts.push(bytes.start as u32);
+            self.ends.push(bytes.end as u32);
+            self.types.push(lexeme.lexeme_type);
+        }
+    }
+
+    /// 词元数
+    pub fn len(&self) -> usize {
+        self.doc_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.doc_ids.is_empty()
+    }
+
+    /// 第 i 个词元的文本
+    pub fn token(&self, i: usize) -> &str {
+        &self.token_values[self.token_offsets[i] as usize..self.token_offsets[i + 1] as usize]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::ik_segmenter::{IKSegmenter, TokenMode};
+
+    #[test]
+    fn test_tokenize_batch() {
+        let mut ik = IKSegmenter::new();
+        let batch = ik.tokenize_batch(["中华人民共和国", "", "iPhone手机"], TokenMode::SEARCH);
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch.doc_ids, vec![0, 2, 2]);
+        assert_eq!(batch.token(0), "中华人民共和国");
+        assert_eq!(batch.token(2), "手机");
+        assert_eq!(batch.token_offsets.len(), batch.len() + 1);
+        assert_eq!((batch.starts[2], batch.ends[2]), (6, 12));
+        assert_eq!(batch.types[1], LexemeType::ENGLISH);
+        assert!(TokenBatch::new().is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::core::lexeme::{Lexeme, LexemeType};
+
+// 词元在原文中的位置，分别以字符、UTF-8字节、UTF-16码元为单位
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Offsets {
+    pub chars: Range<usize>,
+    pub bytes: Range<usize>,
+    pub utf16: Range<usize>,
+}
+
+/// 面向调用方的分词结果
+/// 同时携带字符、字节、UTF-16 三种偏移，方便 JS 前端等场景做高亮
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenRecord {
+    // 词元文本
+    pub text: String,
+    // 词元类型
+    pub lexeme_type: LexemeType,
+    // 词元的相对起始位置
+    pub position: usize,
+    // 词元偏移
+    pub offsets: Offsets,
+}
+
+impl TokenRecord {
+    /// 将 lexemes 转换为 TokenRecord
+    /// 只遍历一次 text，建立字符下标到字节/UTF-16 偏移的映射表
+    pub fn from_lexemes(text: &str, lexemes: &[Lexeme]) -> Vec<TokenRecord> {
+        let char_count = text.chars().count();
+        // table[i] = (第i个字符的字节偏移, 第i个字符的UTF-16偏移)，末尾追加总长度
+        let mut table = Vec::with_capacity(char_count + 1);
+        let mut utf16_offset = 0usize;
+        for (byte_offset, c) in text.char_indices() {
+            table.push((byte_offset, utf16_offset));
+            utf16_offset += c.len_utf16();
+        }
+        table.push((text.len(), utf16_offset));
+
+        let mut records = Vec::with_capacity(lexemes.len());
+        for lexeme in lexemes {
+            let begin = lexeme.get_begin_position().min(char_count);
+            let end = lexeme.get_end_position().min(char_count);
+            let (byte_begin, utf16_begin) = table[begin];
+            let (byte_end, utf16_end) = table[end];
+            records.push(TokenRecord {
+                text: String::from(&text[byte_begin..byte_end]),
+                lexeme_type: lexeme.lexeme_type,
+                position: lexeme.get_begin(),
+                offsets: Offsets {
+                    chars: begin..end,
+                    bytes: byte_begin..byte_end,
+                    utf16: utf16_begin..utf16_end,
+                },
+            });
+        }
+        records
+    }
+}
+
+/// 按 Lucene/Elasticsearch _analyze 的命名序列化的词元, 字段顺序与 ES 的输出相同
+/// 偏移以 UTF-16 码元为单位(与 Java 的 String 一致), 类型名与 IK Analyzer 相同
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LuceneToken<'a> {
+    pub token: &'a str,
+    pub start_offset: usize,
+    pub end_offset: usize,
+    #[serde(rename = "type")]
+    pub token_type: &'static str,
+    pub position: usize,
+}
+
+impl TokenRecord {
+    /// 转为 Lucene 命名的词元, position 为词元在输出中的序号
+    pub fn to_lucene(&self, position: usize) -> LuceneToken<'_> {
+        LuceneToken {
+            token: &self.text,
+            start_offset: self.offsets.utf16.start,
+            end_offset: self.offsets.utf16.end,
+            token_type: self.lexeme_type.lucene_name(),
+            position,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_offsets() {
+        let text = "a😀中文b";
+        let lexemes = vec![
+            Lexeme::new(0, 0, 1, LexemeType::ENGLISH),
+            Lexeme::new(0, 2, 2, LexemeType::CNWORD),
+            Lexeme::new(0, 4, 1, LexemeType::ENGLISH),
+        ];
+        let records = TokenRecord::from_lexemes(text, &lexemes);
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(records[1].text, "中文");
+        assert_eq!(records[1].offsets.chars, 2..4);
+        assert_eq!(records[1].offsets.bytes, 5..11);
+        assert_eq!(records[1].offsets.utf16, 3..5);
+
+        assert_eq!(records[2].text, "b");
+        assert_eq!(records[2].offsets.chars, 4..5);
+        assert_eq!(records[2].offsets.bytes, 11..12);
+        assert_eq!(records[2].offsets.utf16, 5..6);
+    }
+
+    #[test]
+    fn test_lucene_token() {
+        let text = "😀中文";
+        let lexemes = vec![Lexeme::new(0, 1, 2, LexemeType::CNWORD)];
+        let records = TokenRecord::from_lexemes(text, &lexemes);
+        let json = serde_json::to_string(&records[0].to_lucene(0)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"token":"中文","start_offset":2,"end_offset":4,"type":"CN_WORD","position":0}"#
+        );
+        assert_eq!(LexemeType::OtherCJK.lucene_name(), "OTHER_CJK");
+    }
+}
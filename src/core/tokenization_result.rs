@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::ik_segmenter::TokenMode;
+use crate::core::lexeme::Lexeme;
+use crate::core::token_record::{LuceneToken, TokenRecord};
+
+/// 一次分词的输入及输出, 可直接序列化为 JSON 等格式, 也可反序列化后与新的结果比对
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenizationResult {
+    // 原文
+    pub text: String,
+    pub mode: TokenMode,
+    // 输出的词元, 与 IKSegmenter::tokenize 的结果相同
+    pub tokens: Vec<Lexeme>,
+}
+
+impl TokenizationResult {
+    pub fn new(text: &str, mode: TokenMode, tokens: Vec<Lexeme>) -> Self {
+        TokenizationResult {
+            text: text.to_string(),
+            mode,
+            tokens,
+        }
+    }
+
+    /// 词元文本
+    pub fn texts(&self) -> Vec<&str> {
+        self.tokens.iter().map(Lexeme::get_lexeme_text).collect()
+    }
+
+    /// 按 Lucene/Elasticsearch _analyze 的命名输出词元, 序列化 {"tokens": ..} 即与 ES 的输出相同
+    pub fn to_lucene(&self) -> Vec<LuceneToken<'_>> {
+        TokenRecord::from_lexemes(&self.text, &self.tokens)
+            .iter()
+            .enumerate()
+            .map(|(position, record)| LuceneToken {
+                token: &self.text[record.offsets.bytes.clone()],
+                start_offset: record.offsets.utf16.start,
+                end_offset: record.offsets.utf16.end,
+                token_type: record.lexeme_type.lucene_name(),
+                position,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::ik_segmenter::IKSegmenter;
+    use crate::core::lexeme::LexemeType;
+
+    #[test]
+    fn test_serde() {
+        let mut ik = IKSegmenter::new();
+        let result = ik.tokenize_result("五十元", TokenMode::SEARCH);
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["mode"], "SEARCH");
+        let token = &json["tokens"][0];
+        assert_eq!(token["text"], "五十元");
+        assert_eq!(token["type"], "TYPE_CQUAN");
+        assert_eq!(token["chars"], serde_json::json!({"start": 0, "end": 3}));
+        assert_eq!(token["bytes"], serde_json::json!({"start": 0, "end": 9}));
+        assert_eq!(token["parts"][0]["type"], "TYPE_CNUM");
+        assert_eq!(token["parts"][1]["text"], "元");
+        assert!(token.get("pos").is_none());
+        assert!(token.get("offset").is_none());
+        assert!(token.get("begin").is_none());
+        assert!(token.get("length").is_none());
+
+        let decoded: TokenizationResult = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.text, result.text);
+        assert_eq!(decoded.mode, result.mode);
+        assert_eq!(decoded.texts(), vec!["五十元"]);
+        let (lexeme, expected) = (&decoded.tokens[0], &result.tokens[0]);
+        assert_eq!(lexeme.lexeme_type, LexemeType::CQUAN);
+        assert_eq!(lexeme.position_range(), expected.position_range());
+        assert_eq!(lexeme.byte_range(), 0..9);
+        assert_eq!(lexeme.get_pos(), None);
+        assert_eq!(lexeme.parts()[0].get_lexeme_text(), "五十");
+        assert_eq!(lexeme.parts()[0].lexeme_type, LexemeType::CNUM);
+        assert_eq!(lexeme.parts()[1].position_range(), 2..3);
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let lexeme: Lexeme = serde_json::from_str(
+            r#"{"text":"北京","type":"CN_WORD","chars":{"start":1,"end":3},"pos":"ns","freq":5}"#,
+        )
+        .unwrap();
+        assert_eq!(lexeme.get_lexeme_text(), "北京");
+        assert_eq!(lexeme.lexeme_type, LexemeType::CNWORD);
+        assert_eq!(lexeme.position_range(), 1..3);
+        assert_eq!(lexeme.get_pos(), Some("ns"));
+        assert_eq!(lexeme.get_freq(), 5);
+        assert_eq!(lexeme.byte_range(), 0..0);
+
+        // 也接受 Lucene 类型名及枚举名
+        let types = ["OTHER_CJK", "OtherCjk", "CNWORD"]
+            .iter()
+            .map(|name| serde_json::from_value(serde_json::json!(name)).unwrap())
+            .collect::<Vec<LexemeType>>();
+        assert_eq!(
+            types,
+            vec![
+                LexemeType::OtherCJK,
+                LexemeType::OtherCJK,
+                LexemeType::CNWORD
+            ]
+        );
+        assert!(serde_json::from_str::<LexemeType>("\"WORD\"").is_err());
+    }
+
+    #[test]
+    fn test_lucene() {
+        let mut ik = IKSegmenter::new();
+        let result = ik.tokenize_result("😀中华人民共和国", TokenMode::INDEX);
+        let tokens = result.to_lucene();
+        assert_eq!(tokens[0].token, "😀");
+        assert_eq!(tokens[1].token, "中华人民共和国");
+        assert_eq!(tokens[1].token_type, "CN_WORD");
+        assert_eq!((tokens[1].start_offset, tokens[1].end_offset), (2, 9));
+        assert_eq!(tokens[1].position, 1);
+    }
+}
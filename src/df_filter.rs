@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+
+use tantivy::tokenizer::{BoxTokenStream, Token, TokenFilter, TokenStream};
+
+/// 词元的文档频率表
+#[derive(Debug, Clone, Default)]
+pub struct DfTable {
+    df: HashMap<String, u64>,
+}
+
+impl DfTable {
+    /// 从文件加载, 每行一个 "词<TAB>文档频率", 忽略空行
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("open df table {} error: {}", path, e))?;
+        let mut table = DfTable::default();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("read df table {} error: {}", path, e))?;
+            let line = line.trim_start_matches('\u{feff}').trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = line
+                .rsplit_once('\t')
+                .and_then(|(word, df)| Some((word, df.trim().parse::<u64>().ok()?)));
+            match parsed {
+                Some((word, df)) => table.insert(word, df),
+                None => return Err(format!("{}:{} invalid df entry: {:?}", path, i + 1, line)),
+            }
+        }
+        Ok(table)
+    }
+
+    pub fn insert(&mut self, word: &str, df: u64) {
+        self.df.insert(word.to_string(), df);
+    }
+
+    // 不在表中的词文档频率为 0
+    pub fn df(&self, word: &str) -> u64 {
+        self.df.get(word).copied().unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.df.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.df.is_empty()
+    }
+}
+
+impl<'a> FromIterator<(&'a str, u64)> for DfTable {
+    fn from_iter<T: IntoIterator<Item = (&'a str, u64)>>(iter: T) -> Self {
+        let mut table = DfTable::default();
+        for (word, df) in iter {
+            table.insert(word, df);
+        }
+        table
+    }
+}
+
+/// 按文档频率过滤词元的 tantivy TokenFilter
+/// 丢弃文档频率低于 min_df 或高于 max_df 的词元
+#[derive(Debug, Clone)]
+pub struct DfPruningFilter {
+    table: Arc<DfTable>,
+    min_df: u64,
+    max_df: u64,
+}
+
+impl DfPruningFilter {
+    pub fn new(table: Arc<DfTable>) -> Self {
+        DfPruningFilter {
+            table,
+            min_df: 0,
+            max_df: u64::MAX,
+        }
+    }
+
+    /// 文档频率下限, 注意不在表中的词文档频率为 0, 设置下限后会被丢弃
+    pub fn min_df(mut self, min_df: u64) -> Self {
+        self.min_df = min_df;
+        self
+    }
+
+    /// 文档频率上限
+    pub fn max_df(mut self, max_df: u64) -> Self {
+        self.max_df = max_df;
+        self
+    }
+
+    fn keep(&self, token: &Token) -> bool {
+        let df = self.table.df(&token.text);
+        df >= self.min_df && df <= self.max_df
+    }
+}
+
+impl TokenFilter for DfPruningFilter {
+    fn transform<'a>(&self, token_stream: BoxTokenStream<'a>) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(DfPruningStream {
+            filter: self.clone(),
+            tail: token_stream,
+        })
+    }
+}
+
+pub struct DfPruningStream<'a> {
+    filter: DfPruningFilter,
+    tail: BoxTokenStream<'a>,
+}
+
+impl<'a> TokenStream for DfPruningStream<'a> {
+    fn advance(&mut self) -> bool {
+        while self.tail.advance() {
+            if self.filter.keep(self.tail.token()) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tantivy::tokenizer::TextAnalyzer;
+
+    use super::*;
+    use crate::core::ik_segmenter::TokenMode;
+    use crate::IkTokenizer;
+
+    #[test]
+    fn test_df_pruning() {
+        let table = [("北京大学", 10), ("的", 100000), ("学生", 500)]
+            .into_iter()
+            .collect::<DfTable>();
+        let filter = DfPruningFilter::new(Arc::new(table)).min_df(5).max_df(1000);
+        let analyzer = TextAnalyzer::from(IkTokenizer::new(TokenMode::SEARCH)).filter(filter);
+        let mut stream = analyzer.token_stream("北京大学的学生张三");
+        let mut tokens = Vec::new();
+        while let Some(token) = stream.next() {
+            tokens.push(token.text.clone());
+        }
+        assert_eq!(tokens, vec!["北京大学", "学生"]);
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join("ik-rs-df-table.txt");
+        std::fs::write(&path, "\u{feff}北京\t12\n\n大学\t7\n").unwrap();
+        let table = DfTable::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.df("北京"), 12);
+        assert_eq!(table.df("上海"), 0);
+
+        std::fs::write(&path, "北京 12\n").unwrap();
+        assert!(DfTable::load(path.to_str().unwrap()).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
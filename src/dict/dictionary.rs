@@ -1,48 +1,404 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::marker::Sync;
-use std::path::Path;
-use std::rc::Rc;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::time::Instant;
 
 #[warn(unused_imports)]
 use once_cell;
 use once_cell::sync::Lazy;
+use regex::Regex;
 
-use crate::config::configuration::Configuration;
-use crate::config::default_config::{DefaultConfig, IK_CONFIG_NAME};
+use crate::config::configuration::{global_config, Configuration, DictSource};
+use crate::core::char_util::{
+    char_type_of, is_useless_char, regularize as regularize_char, regularize_str, CharType,
+};
+use crate::core::ik_segmenter::IKSegmenter;
 use crate::dict::hit::Hit;
+use crate::dict::lint::{LintIssue, LintKind, MAX_WORD_LENGTH};
+use crate::dict::reload::{
+    dict_sources, diff_words, notify_reload, source_checksums, ReloadReport,
+};
 use crate::dict::trie::Trie;
+use crate::dict::updater::WordOp;
+use crate::dict::word_dict::WordDict;
+use crate::pinyin::{PinyinIndex, PinyinTable};
 
-pub static GLOBAL_DICT: Lazy<Mutex<Dictionary>> = Lazy::new(|| {
+/// 全局词典, 分词只持有读锁, 多个线程可以同时分词
+/// 只有重新加载、增删词条时才持有写锁
+pub static GLOBAL_DICT: Lazy<RwLock<Dictionary>> = Lazy::new(|| {
+    *DICT_LOAD_STATE.lock().unwrap() = LoadState::Loading;
     let mut dict = Dictionary::default();
-    dict.load();
-    Mutex::new(dict)
+    let state = match load_global(&mut dict) {
+        Ok(()) => LoadState::Ready,
+        Err(error) => {
+            log::error!("load dictionary failed: {}", error);
+            LoadState::Degraded(error)
+        }
+    };
+    *DICT_LOAD_STATE.lock().unwrap() = state;
+    RwLock::new(dict)
 });
 
+/// GLOBAL_DICT 的加载状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LoadState {
+    Uninitialized,
+    Loading,
+    Ready,
+    Degraded(String),
+}
+
+pub(crate) static DICT_LOAD_STATE: Mutex<LoadState> = Mutex::new(LoadState::Uninitialized);
+
+// 配置了编译词典缓存时优先从缓存加载, 缓存缺失或过期时加载词典文件并重新生成缓存
+fn load_global(dict: &mut Dictionary) -> Result<(), String> {
+    // 使用内置词典时不读写缓存
+    let cache = dict
+        .config()
+        .filter(|cfg| cfg.get_dict_source() != DictSource::Embedded)
+        .and_then(|cfg| cfg.get_compiled_dictionary());
+    if let Some(path) = cache.as_deref() {
+        match Dictionary::load_compiled(path) {
+            Ok(compiled) => {
+                *dict = compiled;
+                return Ok(());
+            }
+            Err(error) => log::info!("{}, rebuilding compiled dictionary", error),
+        }
+    }
+    // dict_source 为 auto 时, 主词典文件不存在则使用内置词典
+    #[cfg(feature = "embedded-dict")]
+    if let Some(cfg) = dict
+        .config()
+        .filter(|cfg| cfg.get_dict_source() == DictSource::Auto)
+    {
+        let main_dict = cfg.get_main_dictionary();
+        if !main_dict.is_file() {
+            log::info!(
+                "main dict {} not found, using embedded dictionaries",
+                main_dict.display()
+            );
+            dict.regularize_entries = cfg.get_regularize_dict();
+            return dict.load_embedded();
+        }
+    }
+    dict.try_load()?;
+    if let Some(path) = cache.as_deref() {
+        if let Err(error) = dict.save_compiled(path) {
+            log::warn!("{}", error);
+        }
+    }
+    Ok(())
+}
+
 type Dict = Trie;
 
+/// 重新加载 GLOBAL_DICT, 并调用 set_reload_callback 设置的回调
+/// 新词典在锁外加载, 与旧词典的比较只持有读锁, 写锁只在替换时持有
+/// 加载失败时继续使用旧词典, 并将状态标记为 Degraded
+pub fn reload_global_dict() -> ReloadReport {
+    let start = Instant::now();
+    let (cfg, base_dict) = {
+        let dict = GLOBAL_DICT.read().unwrap();
+        (dict.config(), dict.base_dict.clone())
+    };
+    let mut report = ReloadReport::default();
+    match Dictionary::load_with(cfg, base_dict, &mut report) {
+        #[allow(unused_mut)]
+        Ok(mut fresh) => {
+            // 重新合并已拉取的远程词, 否则直到远程词典再次变化之前都会丢失
+            #[cfg(feature = "remote-dict")]
+            crate::dict::remote::reapply_remote_words(&mut fresh);
+            // 比较时不阻塞分词, 其间通过 add_words 等做的修改不计入报告
+            GLOBAL_DICT.read().unwrap().diff_dicts(&fresh, &mut report);
+            let mut dict = GLOBAL_DICT.write().unwrap();
+            dict.swap_dicts(fresh, &mut report);
+            *DICT_LOAD_STATE.lock().unwrap() = LoadState::Ready;
+            log::info!("global dictionary reloaded, version = {}", dict.version);
+        }
+        Err(error) => {
+            log::error!("reload dictionary failed: {}", error);
+            *DICT_LOAD_STATE.lock().unwrap() = LoadState::Degraded(error.clone());
+            report.errors.push(error);
+            report.version = GLOBAL_DICT.read().unwrap().version;
+        }
+    }
+    report.duration = start.elapsed();
+    notify_reload(&report);
+    report
+}
+
+/// 分词器所使用的词典
+#[derive(Clone, Default)]
+pub(crate) enum DictRef {
+    // 使用 GLOBAL_DICT, 每次查询时加读锁
+    #[default]
+    Global,
+    // 使用独立的词典
+    Local(Arc<Dictionary>),
+    // 在另一个词典之上叠加少量的词及停止词
+    Overlay(Arc<DictOverlay>),
+}
+
+/// 叠加在共享词典之上的少量词条, 只对使用它的分词器生效, 共享词典不受影响
+/// 匹配主词典及判断停止词时先查叠加层, 量词等其他查询直接使用共享词典
+#[derive(Clone, Debug)]
+pub(crate) struct DictOverlay {
+    base: DictRef,
+    words: Trie,
+    stop_words: Trie,
+    // 在叠加层中屏蔽的主词典词条, 优先于 words
+    removed: HashSet<String>,
+}
+
+impl DictOverlay {
+    pub(crate) fn new(base: DictRef) -> Self {
+        // 在叠加层之上再叠加时合并为一层
+        match base {
+            DictRef::Overlay(overlay) => (*overlay).clone(),
+            base => DictOverlay {
+                base,
+                words: Trie::default(),
+                stop_words: Trie::default(),
+                removed: HashSet::new(),
+            },
+        }
+    }
+
+    pub(crate) fn add_words(&mut self, words: &[&str]) {
+        for word in words {
+            let word = regularize_str(word.trim());
+            if !word.is_empty() {
+                self.words.insert(word.chars());
+            }
+        }
+    }
+
+    pub(crate) fn remove_words(&mut self, words: &[&str]) {
+        for word in words {
+            let word = regularize_str(word.trim());
+            if !word.is_empty() {
+                self.removed.insert(word);
+            }
+        }
+    }
+
+    pub(crate) fn add_stop_words(&mut self, words: &[&str]) {
+        for word in words {
+            let word = regularize_str(word.trim());
+            if !word.is_empty() {
+                self.stop_words.insert(word.chars());
+            }
+        }
+    }
+}
+
+thread_local! {
+    // 本线程持有的 GLOBAL_DICT 读锁, 见 DictRef::pinned
+    static PINNED_DICT: RefCell<Option<RwLockReadGuard<'static, Dictionary>>> =
+        const { RefCell::new(None) };
+}
+
+// 离开 pinned 作用域时释放读锁, 发生 panic 时同样生效
+struct PinGuard;
+
+impl Drop for PinGuard {
+    fn drop(&mut self) {
+        PINNED_DICT.with(|pinned| pinned.borrow_mut().take());
+    }
+}
+
+fn is_pinned() -> bool {
+    PINNED_DICT.with(|pinned| pinned.borrow().is_some())
+}
+
+impl DictRef {
+    /// 对 GLOBAL_DICT 的访问经由 pinned, 同一线程内嵌套调用不会重复加读锁
+    pub(crate) fn with<R, F: FnOnce(&Dictionary) -> R>(&self, f: F) -> R {
+        match self {
+            DictRef::Global => self.pinned(|| {
+                PINNED_DICT.with(|pinned| {
+                    let pinned = pinned.borrow();
+                    f(pinned.as_ref().expect("GLOBAL_DICT is pinned"))
+                })
+            }),
+            DictRef::Local(dict) => f(dict),
+            DictRef::Overlay(overlay) => overlay.base.with(f),
+        }
+    }
+
+    /// 检索匹配主词典, 叠加层中的词与共享词典中的词一同输出
+    pub(crate) fn match_in_main_dict_with_offset(
+        &self,
+        chars: &[char],
+        offset: usize,
+        length: usize,
+    ) -> Vec<Hit> {
+        let mut hits = self.with(|dict| {
+            dict.match_in_main_dict_with_offset(chars.iter().copied(), offset, length)
+        });
+        if let DictRef::Overlay(overlay) = self {
+            let extra = overlay
+                .words
+                .match_word_with_offset(chars.iter().copied(), offset, length);
+            for hit in extra.into_iter().filter(Hit::is_match) {
+                match hits.iter_mut().find(|h| h.range() == hit.range()) {
+                    Some(existing) => existing.set_match(),
+                    None => hits.push(hit),
+                }
+            }
+            if !overlay.removed.is_empty() {
+                hits.retain_mut(|hit| {
+                    let word = chars[hit.range()].iter().collect::<String>();
+                    if !hit.is_match() || !overlay.removed.contains(&word) {
+                        return true;
+                    }
+                    // 被屏蔽的词仍可能是更长的词的前缀
+                    let prefix = hit.is_prefix();
+                    hit.set_unmatch();
+                    hit.freq = 0;
+                    hit.pos = None;
+                    if prefix {
+                        hit.set_prefix();
+                    }
+                    prefix
+                });
+            }
+        }
+        hits
+    }
+
+    /// 判断是否是停止词, 叠加层中的停止词优先
+    pub(crate) fn is_stop_word(&self, input: &str, offset: usize, length: usize) -> bool {
+        if let DictRef::Overlay(overlay) = self {
+            let hits = overlay
+                .stop_words
+                .match_word_with_offset(input.chars(), offset, length);
+            if hits
+                .iter()
+                .any(|hit| hit.is_match() && hit.range() == (offset..offset + length))
+            {
+                return true;
+            }
+        }
+        self.with(|dict| dict.is_stop_word(input.chars(), offset, length))
+    }
+
+    /// f 执行期间只对 GLOBAL_DICT 加一次读锁, 本线程内的 with 调用都复用这把锁
+    /// 同一线程重复加读锁时, 若有写者在等待可能死锁, 因此每个线程最多持有一把
+    pub(crate) fn pinned<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        if let DictRef::Overlay(overlay) = self {
+            return overlay.base.pinned(f);
+        }
+        if !matches!(self, DictRef::Global) || is_pinned() {
+            return f();
+        }
+        let dict = GLOBAL_DICT.read().unwrap();
+        PINNED_DICT.with(|pinned| *pinned.borrow_mut() = Some(dict));
+        let _guard = PinGuard;
+        f()
+    }
+}
+
+impl fmt::Debug for DictRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DictRef::Global => write!(f, "Global"),
+            DictRef::Local(dict) => write!(f, "Local(version={})", dict.version),
+            DictRef::Overlay(overlay) => write!(f, "Overlay({:?})", overlay.base),
+        }
+    }
+}
+
+/// 被屏蔽的词条由谁、在哪个词典版本屏蔽
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisabledWord {
+    // 屏蔽的来源, 如 disable_words、word_op
+    pub by: String,
+    pub version: u64,
+}
+
+/// Dictionary::diagnose 的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordStatus {
+    // 主词典中的词
+    Present,
+    // 曾在主词典中, 已被屏蔽
+    Disabled(DisabledWord),
+    // 主词典中没有的词
+    Unknown,
+}
+
+impl fmt::Display for WordStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WordStatus::Present => write!(f, "present"),
+            WordStatus::Disabled(disabled) => write!(
+                f,
+                "present but disabled (by {} at version {})",
+                disabled.by, disabled.version
+            ),
+            WordStatus::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Dictionary::prefix_state 的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixState {
+    // 是词典中的词, 且不是其他词的前缀
+    Exact,
+    // 是其他词的前缀, 但本身不是词
+    Prefix,
+    // 是词典中的词, 同时也是其他词的前缀, 如 "北京" 与 "北京大学"
+    ExactAndPrefix,
+    // 既不是词也不是前缀
+    None,
+}
+
 /// Dictionary Manager
 pub struct Dictionary {
-    // 主词典对象
+    // 主词典对象, 包括扩展词典及运行时添加的词条
     main_dict: Dict,
+    // 只读的基础主词典, 设置后不再从文件加载主词典, 见 with_base_dict
+    base_dict: Option<Arc<dyn WordDict>>,
     // 停止词词典
     stop_word_dict: Dict,
+    // 停止词来自哪些停止词词典文件, 运行时通过 add_stop_words 添加的停止词没有来源
+    stop_word_sources: HashMap<String, Vec<PathBuf>>,
+    // 通过 disable_words 等接口屏蔽的词, 用于诊断
+    disabled_words: HashMap<String, DisabledWord>,
+    // 插入词条时是否正规化(全角转半角、英文转小写)
+    regularize_entries: bool,
     // 量词词典
     quantifier_dict: Dict,
     // 配置文件
-    cfg: Option<Rc<dyn Configuration>>,
+    cfg: Option<Arc<dyn Configuration>>,
+    // 未关联配置时是否使用全局配置, 见 config; Default 构造的词典为 true
+    global_cfg: bool,
+    // 词典版本号, 每次批量更新后加一
+    version: u64,
 }
 
 impl Default for Dictionary {
     fn default() -> Self {
-        let root_path = env!("CARGO_MANIFEST_DIR");
-        let conf_file_path = Path::new(root_path).join(IK_CONFIG_NAME);
         Self {
             main_dict: Dict::default(),
+            base_dict: None,
             stop_word_dict: Dict::default(),
+            stop_word_sources: HashMap::new(),
+            disabled_words: HashMap::new(),
+            regularize_entries: true,
             quantifier_dict: Dict::default(),
-            cfg: Some(Rc::new(DefaultConfig::new(conf_file_path))),
+            cfg: None,
+            global_cfg: true,
+            version: 0,
         }
     }
 }
@@ -51,43 +407,582 @@ unsafe impl Sync for Dictionary {}
 unsafe impl Send for Dictionary {}
 
 impl Dictionary {
+    /// 使用已构建好的词典, 不关联配置文件
+    pub fn with_dicts(main_dict: Dict, stop_word_dict: Dict, quantifier_dict: Dict) -> Self {
+        Self {
+            main_dict,
+            base_dict: None,
+            stop_word_dict,
+            stop_word_sources: HashMap::new(),
+            disabled_words: HashMap::new(),
+            regularize_entries: true,
+            quantifier_dict,
+            cfg: None,
+            global_cfg: false,
+            version: 0,
+        }
+    }
+
+    /// 按配置读取主词典、量词词典、停止词词典及所有扩展词典
+    /// 词典文件不存在或无法读取时返回包含文件路径的错误
+    pub fn new(cfg: impl Configuration + 'static) -> Result<Self, String> {
+        Dictionary::from_configuration(Arc::new(cfg))
+    }
+
+    /// 与 new 相同, 接受任意的配置实现, 如从配置中心读取的配置
+    /// 词典保留配置的引用, reload 时重新读取其中的词典路径
+    pub fn from_configuration(cfg: Arc<dyn Configuration>) -> Result<Self, String> {
+        let mut dict = Dictionary::with_dicts(Dict::default(), Dict::default(), Dict::default());
+        dict.cfg = Some(cfg);
+        dict.try_load()?;
+        Ok(dict)
+    }
+
+    /// 与 from_configuration 相同, 但以 base 作为主词典, 不读取配置中的主词典文件
+    /// 如内存映射的 FstDict, 多个进程共享同一份主词典; 扩展词典及运行时添加的词条仍存于 Trie,
+    /// disable_words 对 base 中的词同样生效. find_entries、pinyin_index 及 save_compiled
+    /// 只包含 Trie 中的词条
+    pub fn with_base_dict(
+        cfg: Arc<dyn Configuration>,
+        base: Arc<dyn WordDict>,
+    ) -> Result<Self, String> {
+        let mut dict = Dictionary::with_dicts(Dict::default(), Dict::default(), Dict::default());
+        dict.cfg = Some(cfg);
+        dict.base_dict = Some(base);
+        dict.try_load()?;
+        Ok(dict)
+    }
+
+    /// 编译时嵌入的 dict/ 下的主词典、量词词典及停止词词典, 不读取任何文件, 不关联配置
+    /// 用于 wasm 等没有文件系统的环境; 启用 embedded-dict 时, GLOBAL_DICT 在配置的主词典文件
+    /// 不存在时也使用它
+    #[cfg(feature = "embedded-dict")]
+    pub fn embedded() -> Dictionary {
+        let mut dict = Dictionary::with_dicts(Dict::default(), Dict::default(), Dict::default());
+        // 内容在编译时确定, 由测试保证可以解析
+        dict.load_embedded().unwrap();
+        dict
+    }
+
+    // 加载内置的主词典、量词词典及停止词词典, 配置的 dict_source 为 embedded 时使用
+    #[cfg(feature = "embedded-dict")]
+    fn load_embedded(&mut self) -> Result<(), String> {
+        const MAIN: &str = include_str!("../../dict/main2012.dic");
+        const QUANTIFIER: &str = include_str!("../../dict/quantifier.dic");
+        const STOP_WORDS: &str = include_str!("../../dict/stopword.dic");
+        let regularize = self.regularize_entries;
+        let embedded = |name: &str| PathBuf::from(format!("<embedded>/{}", name));
+        if self.base_dict.is_none() {
+            load_words_from(
+                &mut self.main_dict,
+                MAIN.as_bytes(),
+                &embedded("main2012.dic"),
+                "main dict",
+                regularize,
+            )?;
+        }
+        load_words_from(
+            &mut self.quantifier_dict,
+            QUANTIFIER.as_bytes(),
+            &embedded("quantifier.dic"),
+            "quantifier dict",
+            regularize,
+        )?;
+        self.load_stop_words_from(STOP_WORDS.as_bytes(), &embedded("stopword.dic"))?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "embedded-dict"))]
+    fn load_embedded(&mut self) -> Result<(), String> {
+        Err("dict_source embedded requires the embedded-dict feature".to_string())
+    }
+
+    /// 按配置加载词典, 失败时记录错误并返回 false
     pub fn load(&mut self) -> bool {
-        self.load_main_dict() && self.load_stop_word_dict() && self.load_quantifier_dict()
+        match self.try_load() {
+            Ok(()) => true,
+            Err(error) => {
+                log::error!("{}", error);
+                false
+            }
+        }
+    }
+
+    /// 按配置重新加载全部词典, 成功后版本号加一
+    /// 运行时通过 add_words 等接口做的修改会被丢弃
+    pub fn reload(&mut self) -> ReloadReport {
+        let start = Instant::now();
+        let mut report = ReloadReport::default();
+        match Dictionary::load_with(self.config(), self.base_dict.clone(), &mut report) {
+            Ok(fresh) => self.replace_dicts(fresh, &mut report),
+            Err(error) => {
+                report.errors.push(error);
+                report.version = self.version;
+            }
+        }
+        report.duration = start.elapsed();
+        report
+    }
+
+    // 按配置加载一份新的词典, 并记录词典文件的校验和
+    fn load_with(
+        cfg: Option<Arc<dyn Configuration>>,
+        base_dict: Option<Arc<dyn WordDict>>,
+        report: &mut ReloadReport,
+    ) -> Result<Dictionary, String> {
+        let cfg = cfg.ok_or_else(|| "dictionary has no configuration".to_string())?;
+        report.source_checksums = source_checksums(cfg.as_ref(), &mut report.errors);
+        let mut fresh = Dictionary::with_dicts(Dict::default(), Dict::default(), Dict::default());
+        fresh.cfg = Some(cfg);
+        fresh.base_dict = base_dict;
+        fresh.try_load()?;
+        Ok(fresh)
+    }
+
+    // 用 fresh 的词典内容替换当前词典, 版本号加一
+    fn replace_dicts(&mut self, fresh: Dictionary, report: &mut ReloadReport) {
+        self.diff_dicts(&fresh, report);
+        self.swap_dicts(fresh, report);
+    }
+
+    // 在报告中记录 fresh 相对于当前词典的变化
+    fn diff_dicts(&self, fresh: &Dictionary, report: &mut ReloadReport) {
+        report.main_dict = diff_words(&self.main_dict, &fresh.main_dict);
+        report.stop_word_dict = diff_words(&self.stop_word_dict, &fresh.stop_word_dict);
+        report.quantifier_dict = diff_words(&self.quantifier_dict, &fresh.quantifier_dict);
+    }
+
+    // 替换词典内容, 不做比较
+    fn swap_dicts(&mut self, fresh: Dictionary, report: &mut ReloadReport) {
+        self.main_dict = fresh.main_dict;
+        self.stop_word_dict = fresh.stop_word_dict;
+        self.stop_word_sources = fresh.stop_word_sources;
+        self.disabled_words.clear();
+        self.quantifier_dict = fresh.quantifier_dict;
+        self.version += 1;
+        report.version = self.version;
+        report.success = true;
+    }
+
+    /// 将词典(包括运行时添加的词条)写出为二进制缓存, 缓存中记录词典文件的校验和
+    pub fn save_compiled<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let mut errors = Vec::new();
+        let checksums = match self.config() {
+            Some(cfg) => source_checksums(cfg.as_ref(), &mut errors),
+            None => Vec::new(),
+        };
+        if let Some(error) = errors.into_iter().next() {
+            return Err(error);
+        }
+        let mut out = Vec::new();
+        out.extend_from_slice(COMPILED_MAGIC);
+        out.extend_from_slice(&COMPILED_FORMAT.to_le_bytes());
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.push(self.regularize_entries as u8);
+        out.extend_from_slice(&(checksums.len() as u32).to_le_bytes());
+        for (source, checksum) in checksums {
+            let source = source.to_string_lossy();
+            out.extend_from_slice(&(source.len() as u32).to_le_bytes());
+            out.extend_from_slice(source.as_bytes());
+            out.extend_from_slice(&checksum.to_le_bytes());
+        }
+        self.main_dict.write_to(&mut out);
+        self.stop_word_dict.write_to(&mut out);
+        self.quantifier_dict.write_to(&mut out);
+        // 停止词的来源
+        out.extend_from_slice(&(self.stop_word_sources.len() as u32).to_le_bytes());
+        for (word, files) in self.stop_word_sources.iter() {
+            write_str(&mut out, word);
+            out.extend_from_slice(&(files.len() as u32).to_le_bytes());
+            for file in files {
+                write_str(&mut out, &file.to_string_lossy());
+            }
+        }
+        // 屏蔽的词
+        out.extend_from_slice(&(self.disabled_words.len() as u32).to_le_bytes());
+        for (word, disabled) in self.disabled_words.iter() {
+            write_str(&mut out, word);
+            write_str(&mut out, &disabled.by);
+            out.extend_from_slice(&disabled.version.to_le_bytes());
+        }
+        std::fs::write(path, out)
+            .map_err(|e| format!("write compiled dictionary {} error: {}", path.display(), e))
+    }
+
+    /// 读取 save_compiled 写出的缓存, 免去逐行解析词典文件
+    /// 词典文件与生成缓存时不一致(路径或内容变化)时返回错误
+    pub fn load_compiled<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("read compiled dictionary {} error: {}", path.display(), e))?;
+        let invalid = |e: String| format!("invalid compiled dictionary {}: {}", path.display(), e);
+        let mut input = &bytes[..];
+        if !input.starts_with(COMPILED_MAGIC) {
+            return Err(invalid("bad magic".to_string()));
+        }
+        input = &input[COMPILED_MAGIC.len()..];
+        let format = u32::from_le_bytes(take(&mut input).map_err(invalid)?);
+        if format != COMPILED_FORMAT {
+            return Err(invalid(format!("unsupported format {}", format)));
+        }
+        let version = u64::from_le_bytes(take(&mut input).map_err(invalid)?);
+        let [regularize] = take(&mut input).map_err(invalid)?;
+        let count = u32::from_le_bytes(take(&mut input).map_err(invalid)?);
+        let mut checksums = Vec::new();
+        for _ in 0..count {
+            let len = u32::from_le_bytes(take(&mut input).map_err(invalid)?) as usize;
+            if input.len() < len {
+                return Err(invalid("truncated source path".to_string()));
+            }
+            let (source, rest) = input.split_at(len);
+            input = rest;
+            let checksum = u64::from_le_bytes(take(&mut input).map_err(invalid)?);
+            checksums.push((String::from_utf8_lossy(source).to_string(), checksum));
+        }
+
+        let mut dict = Dictionary {
+            main_dict: Trie::read_from(&mut input).map_err(invalid)?,
+            stop_word_dict: Trie::read_from(&mut input).map_err(invalid)?,
+            quantifier_dict: Trie::read_from(&mut input).map_err(invalid)?,
+            ..Dictionary::default()
+        };
+        let count = u32::from_le_bytes(take(&mut input).map_err(invalid)?);
+        for _ in 0..count {
+            let word = read_str(&mut input).map_err(invalid)?;
+            let files = u32::from_le_bytes(take(&mut input).map_err(invalid)?);
+            let files = (0..files)
+                .map(|_| read_str(&mut input).map(PathBuf::from))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(invalid)?;
+            dict.stop_word_sources.insert(word, files);
+        }
+        let count = u32::from_le_bytes(take(&mut input).map_err(invalid)?);
+        for _ in 0..count {
+            let word = read_str(&mut input).map_err(invalid)?;
+            let by = read_str(&mut input).map_err(invalid)?;
+            let version = u64::from_le_bytes(take(&mut input).map_err(invalid)?);
+            dict.disabled_words
+                .insert(word, DisabledWord { by, version });
+        }
+        dict.version = version;
+        dict.regularize_entries = regularize != 0;
+        let mut errors = Vec::new();
+        let current = match dict.config() {
+            Some(cfg) => source_checksums(cfg.as_ref(), &mut errors),
+            None => Vec::new(),
+        };
+        // 缓存中的路径为 UTF-8 文本, 按相同的方式转换后比较
+        let current = current
+            .into_iter()
+            .map(|(source, checksum)| (source.to_string_lossy().to_string(), checksum))
+            .collect::<Vec<_>>();
+        let expect_regularize = match dict.config() {
+            Some(cfg) => cfg.get_regularize_dict(),
+            None => true,
+        };
+        if !errors.is_empty()
+            || current != checksums
+            || expect_regularize != dict.regularize_entries
+        {
+            return Err(format!("compiled dictionary {} is stale", path.display()));
+        }
+        Ok(dict)
+    }
+
+    // 关联的配置; Default 构造的词典在首次加载时才读取全局配置,
+    // 因此在此之前仍可以调用 init_with_config
+    fn config(&self) -> Option<Arc<dyn Configuration>> {
+        match &self.cfg {
+            Some(cfg) => Some(cfg.clone()),
+            None if self.global_cfg => Some(global_config()),
+            None => None,
+        }
+    }
+
+    // 配置中所有词典文件的路径, 未关联配置时为空
+    pub(crate) fn sources(&self) -> Vec<PathBuf> {
+        self.config()
+            .map(|cfg| dict_sources(cfg.as_ref()))
+            .unwrap_or_default()
+    }
+
+    // 主词典与量词词典是否已加载
+    pub fn is_loaded(&self) -> bool {
+        (!self.main_dict.is_empty() || self.base_dict.is_some()) && !self.quantifier_dict.is_empty()
+    }
+
+    /// 插入、移除词条时是否与分词的输入一样正规化(全角转半角、英文转小写), 默认为 true
+    /// 从配置加载时取自配置的 regularize_dict; 关闭后词条按原样存储,
+    /// 含大写或全角字符的词条将无法匹配正规化后的输入
+    pub fn with_regularize(mut self, regularize: bool) -> Self {
+        self.regularize_entries = regularize;
+        self
+    }
+
+    // 按设置正规化词条
+    fn normalize<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        normalize_entry(word, self.regularize_entries)
     }
 
     // 批量加载新词条
     pub fn add_words(&mut self, words: Vec<&str>) {
         for word in words {
+            let word = self.normalize(word);
             self.main_dict.insert(word.chars());
+            self.disabled_words.remove(word.as_ref());
         }
     }
 
     // 批量移除（屏蔽）词条
     pub fn disable_words(&mut self, words: Vec<&str>) {
+        self.disable_words_by(words, "disable_words");
+    }
+
+    /// 与 disable_words 相同, 并记录屏蔽的来源(如审核系统的名称), 可通过 diagnose 查询
+    pub fn disable_words_by(&mut self, words: Vec<&str>, by: &str) {
         for word in words {
+            self.disable_word(word, by, self.version);
+        }
+    }
+
+    // 屏蔽词条, 词条在主词典中时记录屏蔽的来源及版本
+    // 基础主词典只读, 其中的词只记录屏蔽, 匹配时滤除
+    fn disable_word(&mut self, word: &str, by: &str, version: u64) {
+        let word = normalize_entry(word, self.regularize_entries);
+        let in_base = self.in_base_dict(&word);
+        if self.main_dict.exist(word.chars()) || in_base {
             self.main_dict.delete(word.chars());
+            let disabled = DisabledWord {
+                by: by.to_string(),
+                version,
+            };
+            self.disabled_words.insert(word.into_owned(), disabled);
         }
     }
 
-    // 检索匹配主词典
-    pub fn match_in_main_dict<C: IntoIterator<Item = char>>(&mut self, word: C) -> Vec<Hit> {
-        self.main_dict.match_word(word.into_iter())
+    /// 词在主词典中的状态, 区分被屏蔽的词与词典中从未有过的词
+    /// reload 会恢复词典文件中被屏蔽的词, 同时清除屏蔽记录
+    pub fn diagnose(&self, word: &str) -> WordStatus {
+        let word = self.normalize(word);
+        if self.main_dict.exist(word.chars()) {
+            return WordStatus::Present;
+        }
+        match self.disabled_words.get(word.as_ref()) {
+            None if self.in_base_dict(&word) => WordStatus::Present,
+            Some(disabled) => WordStatus::Disabled(disabled.clone()),
+            None => WordStatus::Unknown,
+        }
+    }
+
+    // 批量添加停止词
+    pub fn add_stop_words(&mut self, words: Vec<&str>) {
+        for word in words {
+            let word = self.normalize(word);
+            self.stop_word_dict.insert(word.chars());
+        }
+    }
+
+    // 批量移除停止词
+    pub fn remove_stop_words(&mut self, words: Vec<&str>) {
+        for word in words {
+            let word = self.normalize(word);
+            self.stop_word_dict.delete(word.chars());
+            self.stop_word_sources.remove(word.as_ref());
+        }
+    }
+
+    // 清空停止词词典(包括配置中的停止词), 之后可通过 add_stop_words 维护自己的停止词
+    // reload 会按配置重新加载停止词
+    pub fn clear_stop_words(&mut self) {
+        self.stop_word_dict = Dict::default();
+        self.stop_word_sources.clear();
+    }
+
+    /// 加载一个停止词词典文件, 并记录其中每个停止词的来源, 返回读取的行数
+    /// 配置中的停止词词典也通过它加载, 可在运行时加载租户自己的停止词
+    pub fn load_stop_words<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, String> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| format!("open stop word dict {} error: {}", path.display(), e))?;
+        self.load_stop_words_from(BufReader::new(file), path)
+    }
+
+    // 从 reader 中读取停止词, path 作为停止词的来源
+    fn load_stop_words_from<R: BufRead>(
+        &mut self,
+        reader: R,
+        path: &Path,
+    ) -> Result<usize, String> {
+        let regularize = self.regularize_entries;
+        let trie = &mut self.stop_word_dict;
+        let sources = &mut self.stop_word_sources;
+        read_entries_from(reader, path, "stop word dict", |entry| {
+            if entry.word.is_empty() {
+                return;
+            }
+            let word = normalize_entry(entry.word, regularize);
+            trie.insert(word.chars());
+            let files = sources.entry(word.into_owned()).or_default();
+            if !files.iter().any(|file| file == path) {
+                files.push(path.to_path_buf());
+            }
+        })
+    }
+
+    /// 停止词来自的第一个停止词词典文件, 运行时添加的停止词或不是停止词时为 None
+    pub fn stop_word_source(&self, word: &str) -> Option<&Path> {
+        self.stop_word_sources(word).first().map(PathBuf::as_path)
+    }
+
+    /// 包含该停止词的所有停止词词典文件, 按加载顺序排列
+    pub fn stop_word_sources(&self, word: &str) -> &[PathBuf] {
+        self.stop_word_sources
+            .get(word)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// 移除来自 path 的停止词, path 与加载时使用的路径相同
+    /// 同时出现在其他停止词词典中的停止词会保留, 返回移除的停止词数
+    pub fn remove_stop_word_source<P: AsRef<Path>>(&mut self, path: P) -> usize {
+        let path = path.as_ref();
+        let mut removed = Vec::new();
+        for (word, files) in self.stop_word_sources.iter_mut() {
+            let count = files.len();
+            files.retain(|file| file != path);
+            if files.is_empty() && count > 0 {
+                removed.push(word.clone());
+            }
+        }
+        for word in removed.iter() {
+            self.stop_word_dict.delete(word.chars());
+            self.stop_word_sources.remove(word);
+        }
+        removed.len()
+    }
+
+    // 停止词词典中的所有停止词, 按字典序排列
+    pub fn stop_words(&self) -> Vec<String> {
+        let mut words = Vec::new();
+        self.stop_word_dict
+            .for_each_word(|word| words.push(word.to_string()));
+        words.sort();
+        words
+    }
+
+    // 词典版本号
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    // 批量应用词典更新, 返回更新后的版本号
+    pub fn apply_word_ops(&mut self, ops: Vec<WordOp>) -> u64 {
+        for op in ops.iter() {
+            match op {
+                WordOp::Add(word) => self.add_words(vec![word]),
+                WordOp::Remove(word) => self.disable_word(word, "word_op", self.version + 1),
+                WordOp::AddStop(word) => self.add_stop_words(vec![word]),
+                WordOp::RemoveStop(word) => self.remove_stop_words(vec![word]),
+                WordOp::ClearStop => self.clear_stop_words(),
+            }
+        }
+        self.version += 1;
+        self.version
+    }
+
+    /// 词在主词典中的匹配状态, 用于自动补全判断是否值得继续等待用户输入
+    /// 词按插入词条时的规则正规化
+    pub fn prefix_state(&self, word: &str) -> PrefixState {
+        let word = self.normalize(word);
+        let length = word.chars().count();
+        let hit = self
+            .match_in_main_dict(word.chars())
+            .into_iter()
+            .find(|hit| hit.range() == (0..length));
+        match hit {
+            Some(hit) if length > 0 => match (hit.is_match(), hit.is_prefix()) {
+                (true, true) => PrefixState::ExactAndPrefix,
+                (true, false) => PrefixState::Exact,
+                (false, true) => PrefixState::Prefix,
+                (false, false) => PrefixState::None,
+            },
+            _ => PrefixState::None,
+        }
     }
 
     // 检索匹配主词典
+    pub fn match_in_main_dict<C: IntoIterator<Item = char>>(&self, word: C) -> Vec<Hit> {
+        let chars = word.into_iter().collect::<Vec<_>>();
+        self.match_in_main_dict_with_offset(chars.iter().copied(), 0, chars.len())
+    }
+
+    // 检索匹配主词典, 设置了基础主词典时合并两者的命中
     pub fn match_in_main_dict_with_offset<C: IntoIterator<Item = char>>(
-        &mut self,
+        &self,
         word: C,
         offset: usize,
         length: usize,
     ) -> Vec<Hit> {
-        self.main_dict
-            .match_word_with_offset(word.into_iter(), offset, length)
+        let base = match self.base_dict.as_ref() {
+            Some(base) => base,
+            None => {
+                return self
+                    .main_dict
+                    .match_word_with_offset(word.into_iter(), offset, length)
+            }
+        };
+        let chars = word.into_iter().collect::<Vec<_>>();
+        let mut hits = self.main_dict.match_chars(&chars, offset, length);
+        for mut hit in base.match_chars(&chars, offset, length) {
+            // 被屏蔽的词仍可能是其他词的前缀
+            if hit.is_match() && self.is_disabled(&chars[hit.range()]) {
+                let prefix = hit.is_prefix();
+                hit.set_unmatch();
+                if prefix {
+                    hit.set_prefix();
+                }
+            }
+            match hits.iter_mut().find(|h| h.range() == hit.range()) {
+                Some(existing) => existing.hit_state |= hit.hit_state,
+                None => hits.push(hit),
+            }
+        }
+        // 两个词典沿同一串字符匹配, 比最深的命中短的命中都是前缀, 被屏蔽的词不算在最深的命中内
+        let reach = hits
+            .iter()
+            .filter(|hit| !hit.is_unmatch())
+            .map(|hit| hit.range().end)
+            .max()
+            .unwrap_or(0);
+        for hit in hits.iter_mut() {
+            if hit.range().end < reach {
+                hit.set_prefix();
+            }
+        }
+        hits.retain(|hit| !hit.is_unmatch());
+        hits.sort_by_key(|hit| hit.range().end);
+        hits
+    }
+
+    // 词是否在基础主词典中且未被屏蔽
+    fn in_base_dict(&self, word: &str) -> bool {
+        match self.base_dict.as_ref() {
+            Some(base) => base.contains(word) && !self.disabled_words.contains_key(word),
+            None => false,
+        }
+    }
+
+    fn is_disabled(&self, chars: &[char]) -> bool {
+        !self.disabled_words.is_empty()
+            && self
+                .disabled_words
+                .contains_key(&chars.iter().collect::<String>())
     }
 
     // 检索匹配量词词典
     pub fn match_in_quantifier_dict<C: IntoIterator<Item = char>>(
-        &mut self,
+        &self,
         word: C,
         offset: usize,
         length: usize,
@@ -96,9 +991,23 @@ impl Dictionary {
             .match_word_with_offset(word.into_iter(), offset, length)
     }
 
+    // 判断是否是量词, 与加载时一致先规范化
+    pub fn is_quantifier(&self, word: &str) -> bool {
+        self.quantifier_dict.exist(self.normalize(word).chars())
+    }
+
+    // 量词词典中的所有量词, 按字典序排列
+    pub fn quantifiers(&self) -> Vec<String> {
+        let mut words = Vec::new();
+        self.quantifier_dict
+            .for_each_word(|word| words.push(word.to_string()));
+        words.sort();
+        words
+    }
+
     // 判断是否是停止词
     pub fn is_stop_word<C: IntoIterator<Item = char>>(
-        &mut self,
+        &self,
         word: C,
         offset: usize,
         length: usize,
@@ -107,120 +1016,322 @@ impl Dictionary {
             .stop_word_dict
             .match_word_with_offset(word.into_iter(), offset, length);
         for hit in hits.iter() {
-            if hit.is_match() && hit.begin == offset && hit.end == offset + length - 1 {
+            if hit.is_match() && hit.range() == (offset..offset + length) {
                 return true;
             }
         }
         false
     }
 
-    // 加载主词典及扩展词典
-    fn load_main_dict(&mut self) -> bool {
-        let main_dict_path = self.cfg.as_ref().unwrap().as_ref().get_main_dictionary();
-        // 读取主词典文件
-        let file = File::open(main_dict_path).expect("Open main_dict error!");
-        let reader = BufReader::new(file);
-        let mut total: usize = 0;
-        for line in reader.lines() {
-            match line {
-                Ok(word) => {
-                    self.main_dict.insert(word.trim().chars());
-                    total += 1;
-                }
-                Err(e) => {
-                    panic!("main dict read error:{}", e);
-                }
+    /// 查找主词典中匹配正则表达式的词条(包括运行时添加的词条), 结果按字典序排列
+    /// 如 find_entries("和尚") 返回所有包含"和尚"的词
+    pub fn find_entries(&self, pattern: &str) -> Result<Vec<String>, regex::Error> {
+        let re = Regex::new(pattern)?;
+        let mut entries = Vec::new();
+        self.main_dict.for_each_word(|word| {
+            if re.is_match(word) {
+                entries.push(word.to_string());
             }
-        }
-        log::debug!("load main_dict size = {}", total);
-        // 加载扩展词典
-        self.load_ext_dict()
+        });
+        entries.sort();
+        Ok(entries)
     }
 
-    // 加载用户配置的扩展词典到主词库表
-    fn load_ext_dict(&mut self) -> bool {
-        let ext_dict_files = self.cfg.as_ref().unwrap().get_ext_dictionaries();
-        let mut total = 0;
-        for ext_dict_file in ext_dict_files {
-            let file = File::open(ext_dict_file).expect("open error");
-            let reader = BufReader::new(file);
-            for line in reader.lines() {
-                match line {
-                    Ok(word) => {
-                        self.main_dict.insert(word.trim().chars());
-                        total += 1;
-                    }
-                    Err(e) => {
-                        panic!("ext dict read error:{}", e);
-                    }
-                }
+    /// 将主词典(包括运行时添加的词条)编译为 FST
+    #[cfg(feature = "fst")]
+    pub fn main_dict_fst(&self) -> Result<crate::dict::fst_dict::FstDict, String> {
+        crate::dict::fst_dict::FstDict::from_trie(&self.main_dict)
+    }
+
+    /// 按拼音对照表为主词典(包括运行时添加的词条)建立拼音索引
+    pub fn pinyin_index(&self, table: &PinyinTable) -> PinyinIndex {
+        let mut index = PinyinIndex::default();
+        self.main_dict
+            .for_each_word(|word| index.insert(table, word));
+        index
+    }
+
+    /// 在 GLOBAL_DICT 之上叠加增删的词条, 并用绑定到该叠加层的分词器执行 f
+    /// 用于在正式修改词典之前预览变更对分词结果的影响, GLOBAL_DICT 不受影响
+    /// 不复制词典, 也不在 f 执行期间持有锁
+    pub fn preview_with<R, F>(words_added: Vec<&str>, words_removed: Vec<&str>, f: F) -> R
+    where
+        F: FnOnce(&mut IKSegmenter) -> R,
+    {
+        let mut overlay = DictOverlay::new(DictRef::Global);
+        overlay.add_words(&words_added);
+        overlay.remove_words(&words_removed);
+        let mut segmenter = IKSegmenter::bound_to(DictRef::Overlay(Arc::new(overlay)));
+        f(&mut segmenter)
+    }
+
+    /// 检查待加载的词条, 报告可疑的词条
+    /// 可在推送扩展词典前调用, 避免错误词条影响分词效果
+    pub fn lint<I, S>(&self, lines: I) -> Vec<LintIssue>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut issues = Vec::new();
+        let mut seen = HashSet::new();
+        for (index, line) in lines.into_iter().enumerate() {
+            let word = parse_dict_line(line.as_ref()).word;
+            let mut report = |kind| {
+                issues.push(LintIssue {
+                    line: index + 1,
+                    word: word.to_string(),
+                    kind,
+                })
+            };
+            if word.is_empty() {
+                report(LintKind::Empty);
+                continue;
+            }
+            // 与加载时一致, 按规范化后的词条检查, 报告中仍是原始词条
+            let normalized = self.normalize(word);
+            let length = normalized.chars().count();
+            if normalized.chars().any(char::is_whitespace) {
+                report(LintKind::Whitespace);
+            } else if normalized.chars().any(|c| is_useless_char(&c)) {
+                report(LintKind::Symbol);
+            }
+            let has_type = |t: CharType| {
+                normalized
+                    .chars()
+                    .any(|c| !is_useless_char(&c) && char_type_of(&c) == t)
+            };
+            let has_other_cjk = has_type(CharType::OtherCjk);
+            let has_latin = has_type(CharType::ENGLISH) || has_type(CharType::ARABIC);
+            if has_other_cjk && (has_type(CharType::CHINESE) || has_latin) {
+                report(LintKind::MixedScript);
+            }
+            if length > MAX_WORD_LENGTH {
+                report(LintKind::TooLong);
+            }
+            if !seen.insert(normalized.to_string()) {
+                report(LintKind::Duplicate);
+            }
+            if self.is_stop_word(normalized.chars(), 0, length) {
+                report(LintKind::StopWord);
             }
         }
-        log::debug!("ext dict total size = {}", total);
-        true
+        issues
     }
 
-    // 加载用户扩展的停止词词典
-    fn load_stop_word_dict(&mut self) -> bool {
-        // 加载扩展停止词典
-        let ext_stop_word_dict_files = self
-            .cfg
-            .as_ref()
-            .unwrap()
-            .as_ref()
-            .get_ext_stop_word_dictionaries();
-        let mut total = 0_usize;
-        for stop_file in ext_stop_word_dict_files {
-            log::debug!("{}", stop_file);
-            let file = File::open(stop_file).expect("open error");
-            let reader = BufReader::new(file);
-            for line in reader.lines() {
-                match line {
-                    Ok(word) => {
-                        self.stop_word_dict.insert(word.trim().chars());
-                        total += 1;
-                    }
-                    Err(e) => {
-                        panic!("stop dict read error:{}", e);
-                    }
-                }
-            }
+    // 按配置加载全部词典, 返回第一个错误
+    fn try_load(&mut self) -> Result<(), String> {
+        let cfg = self
+            .config()
+            .ok_or_else(|| "dictionary has no configuration".to_string())?;
+        self.regularize_entries = cfg.get_regularize_dict();
+        if cfg.get_dict_source() == DictSource::Embedded {
+            return self.load_embedded();
+        }
+        let regularize = self.regularize_entries;
+        // 主词典及扩展词典, 设置了基础主词典时不读取主词典文件
+        if self.base_dict.is_none() {
+            let total = load_words(
+                &mut self.main_dict,
+                &cfg.get_main_dictionary(),
+                "main dict",
+                regularize,
+            )?;
+            log::debug!("load main_dict size = {}", total);
+        }
+        let mut total = 0;
+        for ext_dict_file in cfg.get_ext_dictionaries() {
+            total += load_words(&mut self.main_dict, &ext_dict_file, "ext dict", regularize)?;
+        }
+        log::debug!("ext dict total size = {}", total);
+        // 停止词词典及扩展停止词词典
+        let mut total = 0;
+        for stop_file in cfg.get_ext_stop_word_dictionaries() {
+            total += self.load_stop_words(&stop_file)?;
         }
         log::debug!("stop dict total size = {}", total);
-        true
+        // 量词词典
+        let total = load_words(
+            &mut self.quantifier_dict,
+            &cfg.get_quantifier_dictionary(),
+            "quantifier dict",
+            regularize,
+        )?;
+        log::debug!("quantifier_dict total size = {}", total);
+        Ok(())
     }
+}
 
-    // 加载量词词典
-    fn load_quantifier_dict(&mut self) -> bool {
-        // 建立一个量词典实例
-        let file_path = self
-            .cfg
-            .as_ref()
-            .unwrap()
-            .as_ref()
-            .get_quantifier_dictionary();
-        let file = File::open(&file_path[..]).expect("open error");
-        let reader = BufReader::new(file);
-        let mut total = 0_usize;
-        for line in reader.lines() {
-            match line {
-                Ok(word) => {
-                    self.quantifier_dict.insert(word.trim().chars());
-                    total += 1;
-                }
-                Err(e) => {
-                    panic!("quantifier dict read error:{}", e);
-                }
-            }
+// 编译词典缓存的文件头及格式版本
+const COMPILED_MAGIC: &[u8] = b"IKDC";
+const COMPILED_FORMAT: u32 = 6;
+
+// 读取定长字节
+fn take<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], String> {
+    if input.len() < N {
+        return Err("truncated data".to_string());
+    }
+    let (bytes, rest) = input.split_at(N);
+    *input = rest;
+    let mut buf = [0u8; N];
+    buf.copy_from_slice(bytes);
+    Ok(buf)
+}
+
+// 写出带长度前缀的字符串
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+// 读取 write_str 写出的字符串
+fn read_str(input: &mut &[u8]) -> Result<String, String> {
+    let len = u32::from_le_bytes(take(input)?) as usize;
+    if input.len() < len {
+        return Err("truncated string".to_string());
+    }
+    let (s, rest) = input.split_at(len);
+    *input = rest;
+    String::from_utf8(s.to_vec()).map_err(|e| e.to_string())
+}
+
+// 将词典文件中的词逐行加入 trie, 返回读取的行数
+// regularize 为 true 时词条与分词的输入一样正规化, 如 "Ｕ盘" 与 "U盘" 都存为 "u盘"
+fn load_words(trie: &mut Trie, path: &Path, kind: &str, regularize: bool) -> Result<usize, String> {
+    read_entries(path, kind, |entry| {
+        let word = normalize_entry(entry.word, regularize);
+        trie.insert_with_pos(word.chars(), entry.freq, entry.pos)
+    })
+}
+
+// 与 load_words 相同, 从 reader 中读取, path 只用于错误信息
+#[cfg(feature = "embedded-dict")]
+fn load_words_from<R: BufRead>(
+    trie: &mut Trie,
+    reader: R,
+    path: &Path,
+    kind: &str,
+    regularize: bool,
+) -> Result<usize, String> {
+    read_entries_from(reader, path, kind, |entry| {
+        let word = normalize_entry(entry.word, regularize);
+        trie.insert_with_pos(word.chars(), entry.freq, entry.pos)
+    })
+}
+
+// 正规化词条, 不需要转换时不复制
+fn normalize_entry(word: &str, regularize: bool) -> Cow<'_, str> {
+    if regularize && word.chars().any(|c| regularize_char(c) != c) {
+        Cow::Owned(regularize_str(word))
+    } else {
+        Cow::Borrowed(word)
+    }
+}
+
+// 逐行解析词典文件, 返回读取的行数
+// 超过 MAX_WORD_LENGTH 的词条记录日志后跳过
+fn read_entries<F: FnMut(DictEntry)>(path: &Path, kind: &str, f: F) -> Result<usize, String> {
+    let file =
+        File::open(path).map_err(|e| format!("open {} {} error: {}", kind, path.display(), e))?;
+    read_entries_from(BufReader::new(file), path, kind, f)
+}
+
+// 与 read_entries 相同, 从 reader 中读取, path 只用于错误信息
+fn read_entries_from<R: BufRead, F: FnMut(DictEntry)>(
+    reader: R,
+    path: &Path,
+    kind: &str,
+    mut f: F,
+) -> Result<usize, String> {
+    let mut total = 0;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("read {} {} error: {}", kind, path.display(), e))?;
+        total += 1;
+        let entry = parse_dict_line(&line);
+        let length = entry.word.chars().count();
+        if length > MAX_WORD_LENGTH {
+            log::warn!(
+                "{}:{}: skip {} word of {} chars, max length is {}",
+                path.display(),
+                index + 1,
+                kind,
+                length,
+                MAX_WORD_LENGTH
+            );
+            continue;
         }
-        log::debug!("quantifier_dict total size = {}", total);
-        true
+        f(entry);
+    }
+    Ok(total)
+}
+
+// 词典文件中的一行
+#[derive(Debug, PartialEq, Eq)]
+struct DictEntry<'a> {
+    word: &'a str,
+    freq: u32,
+    pos: Option<&'a str>,
+}
+
+// 解析词典文件的一行, 格式为 "词"、"词 词频" 或 "词 词频 词性"(jieba 的 dict.txt)
+// 含制表符的行只按制表符分隔, 词中的空格保留, 如 "iphone 14\t0" 整体是一个词条;
+// 不含制表符的行按空白分隔, 末尾能解析为 u32 的字段为词频, 词频后的字母字段为词性
+// 不符合以上格式时整行视为词条, 词频为 0
+fn parse_dict_line(line: &str) -> DictEntry<'_> {
+    let line = line.trim();
+    let entry = if line.contains('\t') {
+        parse_tab_fields(line)
+    } else {
+        parse_whitespace_fields(line)
+    };
+    entry.unwrap_or(DictEntry {
+        word: line,
+        freq: 0,
+        pos: None,
+    })
+}
+
+fn parse_tab_fields(line: &str) -> Option<DictEntry<'_>> {
+    let mut fields = line.split('\t');
+    let word = fields.next().unwrap_or_default().trim_end();
+    let (freq, pos) = match (fields.next(), fields.next(), fields.next()) {
+        (Some(freq), pos, None) => (freq.trim().parse::<u32>().ok()?, pos),
+        _ => return None,
+    };
+    (!word.is_empty()).then_some(DictEntry {
+        word,
+        freq,
+        pos: pos.map(str::trim).filter(|pos| !pos.is_empty()),
+    })
+}
+
+fn parse_whitespace_fields(line: &str) -> Option<DictEntry<'_>> {
+    let (rest, last) = line.rsplit_once(char::is_whitespace)?;
+    let rest = rest.trim_end();
+    if let Ok(freq) = last.parse::<u32>() {
+        return (!rest.is_empty()).then_some(DictEntry {
+            word: rest,
+            freq,
+            pos: None,
+        });
+    }
+    if !last.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
     }
+    let (word, freq) = rest.rsplit_once(char::is_whitespace)?;
+    let word = word.trim_end();
+    let freq = freq.parse::<u32>().ok()?;
+    (!word.is_empty()).then_some(DictEntry {
+        word,
+        freq,
+        pos: Some(last),
+    })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::core::ik_segmenter::TokenMode;
+    use std::panic::{self, AssertUnwindSafe};
     #[test]
     fn test_dictionary() {
         let mut dictionary = Dictionary::default();
@@ -236,4 +1347,637 @@ mod test {
             assert!(!hits.is_empty());
         }
     }
+
+    #[test]
+    fn test_parse_dict_line() {
+        let parse = |line| {
+            let entry = parse_dict_line(line);
+            (entry.word, entry.freq, entry.pos)
+        };
+        assert_eq!(parse(" 北京大学 "), ("北京大学", 0, None));
+        assert_eq!(parse("北京大学\t120"), ("北京大学", 120, None));
+        assert_eq!(parse("北京大学 \t 120"), ("北京大学", 120, None));
+        assert_eq!(parse("北京大学\t120\tnt"), ("北京大学", 120, Some("nt")));
+        assert_eq!(parse("new york\t3\tns"), ("new york", 3, Some("ns")));
+        assert_eq!(parse("new york"), ("new york", 0, None));
+        // 不含制表符时末尾的数字是词频
+        assert_eq!(parse("北京大学 120"), ("北京大学", 120, None));
+        assert_eq!(parse(" 北京大学  120 \r"), ("北京大学", 120, None));
+        assert_eq!(parse("new york 3"), ("new york", 3, None));
+        assert_eq!(parse("iphone 14"), ("iphone", 14, None));
+        assert_eq!(parse("北京大学 -1"), ("北京大学 -1", 0, None));
+        // 含制表符时空格是词的一部分
+        assert_eq!(parse("iphone 14\t0"), ("iphone 14", 0, None));
+        assert_eq!(parse("windows 7\t5"), ("windows 7", 5, None));
+        // jieba 的 "词 词频 词性"
+        assert_eq!(parse("北京大学 120 nt"), ("北京大学", 120, Some("nt")));
+        assert_eq!(parse("new york 3 ns"), ("new york", 3, Some("ns")));
+        assert_eq!(parse("北京大学 nt"), ("北京大学 nt", 0, None));
+        assert_eq!(parse("北京大学 120 n1"), ("北京大学 120 n1", 0, None));
+        assert_eq!(parse("120"), ("120", 0, None));
+        assert_eq!(parse("120\tn"), ("120\tn", 0, None));
+        assert_eq!(parse("北京\t1\tns\tx"), ("北京\t1\tns\tx", 0, None));
+        assert_eq!(parse(""), ("", 0, None));
+    }
+
+    struct PathConfig {
+        ext_dicts: Vec<PathBuf>,
+    }
+
+    fn dict_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("dict")
+            .join(name)
+    }
+
+    impl Configuration for PathConfig {
+        fn get_main_dictionary(&self) -> PathBuf {
+            dict_path("main2012.dic")
+        }
+        fn get_quantifier_dictionary(&self) -> PathBuf {
+            dict_path("quantifier.dic")
+        }
+        fn get_ext_dictionaries(&self) -> Vec<PathBuf> {
+            self.ext_dicts.clone()
+        }
+        fn get_ext_stop_word_dictionaries(&self) -> Vec<PathBuf> {
+            vec![dict_path("stopword.dic")]
+        }
+    }
+
+    #[test]
+    fn test_add_prefix_word() {
+        // 运行时添加的词是已有词的前缀时同样可以匹配, 见 trie_insert_prefix_of_existing_word
+        let mut main = Trie::default();
+        main.insert("北京大学".chars());
+        let mut dictionary = Dictionary::with_dicts(main, Trie::default(), Trie::default());
+        assert!(!dictionary.match_in_main_dict("北京大".chars())[0].is_match());
+        dictionary.add_words(vec!["北京大"]);
+        let hit = &dictionary.match_in_main_dict("北京大".chars())[0];
+        assert!(hit.is_match() && hit.is_prefix());
+    }
+
+    #[test]
+    fn test_base_dict() {
+        let mut base = Trie::default();
+        for word in ["北京", "北京大学", "大学"] {
+            base.insert(word.chars());
+        }
+        let mut dictionary =
+            Dictionary::with_base_dict(Arc::new(PathConfig { ext_dicts: vec![] }), Arc::new(base))
+                .unwrap();
+        assert!(dictionary.is_loaded());
+        // 主词典文件不再加载, 运行时添加的词与基础主词典一同匹配
+        assert!(dictionary
+            .find_entries("^中华人民共和国$")
+            .unwrap()
+            .is_empty());
+        dictionary.add_words(vec!["北京大学生"]);
+        let summary = |dictionary: &Dictionary| {
+            dictionary
+                .match_in_main_dict("北京大学生".chars())
+                .iter()
+                .map(|hit| (hit.range(), hit.is_match(), hit.is_prefix()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            summary(&dictionary),
+            vec![(0..2, true, true), (0..4, true, true), (0..5, true, false)]
+        );
+        assert_eq!(dictionary.prefix_state("北京"), PrefixState::ExactAndPrefix);
+
+        // 屏蔽基础主词典中的词, 前缀仍然保留
+        dictionary.disable_words(vec!["北京大学"]);
+        assert!(matches!(
+            dictionary.diagnose("北京大学"),
+            WordStatus::Disabled(_)
+        ));
+        assert_eq!(dictionary.diagnose("大学"), WordStatus::Present);
+        assert_eq!(
+            summary(&dictionary),
+            vec![(0..2, true, true), (0..4, false, true), (0..5, true, false)]
+        );
+
+        let mut ik = IKSegmenter::with_configuration(
+            &PathConfig { ext_dicts: vec![] },
+            Arc::new(dictionary),
+        );
+        let tokens = ik.tokenize("北京大学生", TokenMode::INDEX);
+        let texts = tokens
+            .iter()
+            .map(|lexeme| lexeme.get_lexeme_text())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["北京大学生", "北京", "大学", "生"]);
+    }
+
+    #[test]
+    fn test_disable_longest_base_word() {
+        let mut base = Trie::default();
+        base.insert("北京大学".chars());
+        let mut dictionary =
+            Dictionary::with_base_dict(Arc::new(PathConfig { ext_dicts: vec![] }), Arc::new(base))
+                .unwrap();
+        dictionary.add_words(vec!["北京"]);
+        dictionary.disable_words(vec!["北京大学"]);
+        // 最长的词被屏蔽后, 较短的命中不再是前缀
+        let hits = dictionary.match_in_main_dict("北京大学".chars());
+        assert_eq!(
+            hits.iter()
+                .map(|hit| (hit.range(), hit.is_match(), hit.is_prefix()))
+                .collect::<Vec<_>>(),
+            vec![(0..2, true, false)]
+        );
+    }
+
+    #[test]
+    fn test_new_with_config() {
+        let dictionary = Dictionary::new(PathConfig { ext_dicts: vec![] }).unwrap();
+        assert!(dictionary.is_loaded());
+        assert!(!dictionary.match_in_main_dict("北京大学".chars()).is_empty());
+
+        let error = Dictionary::new(PathConfig {
+            ext_dicts: vec![PathBuf::from("/nonexistent/ext.dic")],
+        })
+        .err()
+        .unwrap();
+        assert!(
+            error.starts_with("open ext dict /nonexistent/ext.dic error"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_jieba_dict() {
+        // 摘自 jieba 的 dict.txt, 每行 "词 词频 词性"
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("jieba_dict.txt");
+        let content = std::fs::read_to_string(&fixture).unwrap();
+        for line in content.lines() {
+            let entry = parse_dict_line(line);
+            assert!(entry.freq > 0 && entry.pos.is_some(), "{}", line);
+            assert!(!entry.word.contains(' '), "{}", line);
+        }
+        let dictionary = Dictionary::new(PathConfig {
+            ext_dicts: vec![fixture],
+        })
+        .unwrap();
+        let mut ik = IKSegmenter::with_dictionary(Arc::new(dictionary));
+        let tokens = ik.tokenize(&regularize_str("去做B超"), TokenMode::SEARCH);
+        let tags = tokens
+            .iter()
+            .map(|t| (t.get_lexeme_text(), t.get_pos(), t.get_freq()))
+            .collect::<Vec<_>>();
+        assert!(tags.contains(&("b超", Some("n"), 3)), "{:?}", tags);
+    }
+
+    #[test]
+    fn test_fullwidth_entries() {
+        let ext_dict =
+            std::env::temp_dir().join(format!("ik-fullwidth-{}.dic", std::process::id()));
+        std::fs::write(&ext_dict, "U盘\n４Ｓ店\n").unwrap();
+        let dictionary = Dictionary::new(PathConfig {
+            ext_dicts: vec![ext_dict.clone()],
+        })
+        .unwrap();
+        std::fs::remove_file(&ext_dict).unwrap();
+        let mut ik = IKSegmenter::with_dictionary(Arc::new(dictionary));
+        for input in ["买了Ｕ盘去4S店", "买了u盘去４ｓ店", "买了U盘去４Ｓ店"] {
+            let text = regularize_str(input);
+            let tokens = ik.tokenize(&text, TokenMode::SEARCH);
+            let texts = tokens
+                .iter()
+                .map(|t| t.get_lexeme_text())
+                .collect::<Vec<_>>();
+            assert!(texts.contains(&"u盘"), "{} {:?}", input, texts);
+            assert!(texts.contains(&"4s店"), "{} {:?}", input, texts);
+        }
+    }
+
+    #[test]
+    fn test_regularize_entries() {
+        let mut dictionary =
+            Dictionary::with_dicts(Trie::default(), Trie::default(), Trie::default());
+        dictionary.add_words(vec!["ＶＩＰ会员", "iPhone手机"]);
+        dictionary.add_stop_words(vec!["ＴＨＥ"]);
+        assert!(dictionary.main_dict.exist("vip会员".chars()));
+        assert!(dictionary.main_dict.exist("iphone手机".chars()));
+        assert!(dictionary.is_stop_word("the".chars(), 0, 3));
+        dictionary.disable_words(vec!["IPHONE手机"]);
+        assert!(!dictionary.main_dict.exist("iphone手机".chars()));
+
+        // 关闭后按原样存储
+        let mut raw = Dictionary::with_dicts(Trie::default(), Trie::default(), Trie::default())
+            .with_regularize(false);
+        raw.add_words(vec!["ＶＩＰ会员"]);
+        assert!(raw.main_dict.exist("ＶＩＰ会员".chars()));
+        assert!(!raw.main_dict.exist("vip会员".chars()));
+
+        // 从配置加载
+        struct RawConfig(PathConfig);
+        impl Configuration for RawConfig {
+            fn get_main_dictionary(&self) -> PathBuf {
+                self.0.get_main_dictionary()
+            }
+            fn get_quantifier_dictionary(&self) -> PathBuf {
+                self.0.get_quantifier_dictionary()
+            }
+            fn get_ext_dictionaries(&self) -> Vec<PathBuf> {
+                self.0.get_ext_dictionaries()
+            }
+            fn get_ext_stop_word_dictionaries(&self) -> Vec<PathBuf> {
+                self.0.get_ext_stop_word_dictionaries()
+            }
+            fn get_regularize_dict(&self) -> bool {
+                false
+            }
+        }
+        let ext_dict = std::env::temp_dir().join(format!("ik-raw-{}.dic", std::process::id()));
+        std::fs::write(&ext_dict, "ＶＩＰ会员\n").unwrap();
+        let ext_dicts = vec![ext_dict.clone()];
+        let raw = Dictionary::new(RawConfig(PathConfig { ext_dicts })).unwrap();
+        std::fs::remove_file(&ext_dict).unwrap();
+        assert!(raw.main_dict.exist("ＶＩＰ会员".chars()));
+        assert!(!raw.main_dict.exist("vip会员".chars()));
+    }
+
+    #[test]
+    fn test_prefix_state() {
+        let mut dictionary =
+            Dictionary::with_dicts(Trie::default(), Trie::default(), Trie::default());
+        dictionary.add_words(vec!["北京", "北京大学", "iphone手机"]);
+        assert_eq!(dictionary.prefix_state("北京"), PrefixState::ExactAndPrefix);
+        assert_eq!(dictionary.prefix_state("北京大"), PrefixState::Prefix);
+        assert_eq!(dictionary.prefix_state("北京大学"), PrefixState::Exact);
+        assert_eq!(dictionary.prefix_state("北京大学生"), PrefixState::None);
+        assert_eq!(dictionary.prefix_state("上海"), PrefixState::None);
+        assert_eq!(dictionary.prefix_state("iPhone"), PrefixState::Prefix);
+        assert_eq!(dictionary.prefix_state(""), PrefixState::None);
+    }
+
+    #[test]
+    fn test_adversarial_dict() {
+        let ext_dict =
+            std::env::temp_dir().join(format!("ik-adversarial-{}.dic", std::process::id()));
+        let huge = "北".repeat(10_000);
+        std::fs::write(&ext_dict, format!("{}\n北京欢迎你\n{} 100 n\n", huge, huge)).unwrap();
+        let mut dictionary = Dictionary::new(PathConfig {
+            ext_dicts: vec![ext_dict.clone()],
+        })
+        .unwrap();
+        std::fs::remove_file(&ext_dict).unwrap();
+        assert!(dictionary
+            .match_in_main_dict("北京欢迎你".chars())
+            .iter()
+            .any(|hit| hit.is_match()));
+        // 超长的词条没有进入词典, 在其前缀上匹配时不会沿着超长的链走下去
+        let hits = dictionary.match_in_main_dict(huge.chars());
+        assert!(hits.iter().all(|hit| hit.range().len() <= 2), "{:?}", hits);
+        dictionary.add_words(vec![&huge]);
+        assert!(!dictionary.main_dict.exist(huge.chars()));
+    }
+
+    #[test]
+    fn test_stop_word_sources() {
+        let mut dictionary = Dictionary::new(PathConfig { ext_dicts: vec![] }).unwrap();
+        let base = dict_path("stopword.dic");
+        assert_eq!(dictionary.stop_word_source("the"), Some(base.as_path()));
+        let tenant =
+            std::env::temp_dir().join(format!("ik-tenant-stop-{}.dic", std::process::id()));
+        std::fs::write(&tenant, "the\n的\n\n了\n").unwrap();
+        assert_eq!(dictionary.load_stop_words(&tenant).unwrap(), 4);
+        std::fs::remove_file(&tenant).unwrap();
+        assert_eq!(
+            dictionary.stop_word_sources("the"),
+            &[base.clone(), tenant.clone()]
+        );
+        assert_eq!(dictionary.stop_word_source("的"), Some(tenant.as_path()));
+        dictionary.add_stop_words(vec!["啊"]);
+        assert_eq!(dictionary.stop_word_source("啊"), None);
+
+        // 只移除租户独有的停止词
+        assert_eq!(dictionary.remove_stop_word_source(&tenant), 2);
+        assert!(!dictionary.is_stop_word("的".chars(), 0, 1));
+        assert!(dictionary.is_stop_word("the".chars(), 0, 3));
+        assert!(dictionary.is_stop_word("啊".chars(), 0, 1));
+        assert_eq!(dictionary.stop_word_sources("the"), &[base]);
+        assert_eq!(dictionary.remove_stop_word_source(&tenant), 0);
+    }
+
+    #[test]
+    fn test_compiled() {
+        let mut dictionary = Dictionary::default();
+        dictionary.load();
+        dictionary.add_words(vec!["区块链钱包"]);
+        dictionary.disable_words_by(vec!["清华大学"], "moderation");
+        let path = std::env::temp_dir().join(format!("ik-compiled-{}.bin", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        dictionary.save_compiled(&path).unwrap();
+        let compiled = Dictionary::load_compiled(&path).unwrap();
+        for word in ["北京大学", "区块链钱包"] {
+            assert!(!compiled.match_in_main_dict(word.chars()).is_empty());
+        }
+        assert!(compiled.is_stop_word("the".chars(), 0, 3));
+        assert_eq!(
+            compiled.stop_word_source("the"),
+            dictionary.stop_word_source("the")
+        );
+        assert!(compiled.is_quantifier("个"));
+        assert_eq!(
+            compiled.diagnose("清华大学"),
+            dictionary.diagnose("清华大学")
+        );
+        assert_eq!(
+            compiled.find_entries("^北京").unwrap(),
+            dictionary.find_entries("^北京").unwrap()
+        );
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(Dictionary::load_compiled(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "embedded-dict")]
+    #[test]
+    fn test_embedded() {
+        let dict = Dictionary::embedded();
+        assert!(dict.cfg.is_none());
+        assert!(dict.match_in_main_dict("中华人民共和国".chars())[0].is_match());
+        assert!(dict.is_quantifier("元"));
+        assert!(dict.is_stop_word("the".chars(), 0, 3));
+        assert_eq!(
+            dict.stop_word_source("the"),
+            Some(Path::new("<embedded>/stopword.dic"))
+        );
+    }
+
+    #[test]
+    fn test_dict_source() {
+        struct EmbeddedConfig;
+        impl Configuration for EmbeddedConfig {
+            fn get_main_dictionary(&self) -> PathBuf {
+                PathBuf::from("missing/main.dic")
+            }
+            fn get_quantifier_dictionary(&self) -> PathBuf {
+                PathBuf::from("missing/quantifier.dic")
+            }
+            fn get_ext_dictionaries(&self) -> Vec<PathBuf> {
+                vec![PathBuf::from("missing/ext.dic")]
+            }
+            fn get_ext_stop_word_dictionaries(&self) -> Vec<PathBuf> {
+                vec![PathBuf::from("missing/stop.dic")]
+            }
+            fn get_dict_source(&self) -> DictSource {
+                DictSource::Embedded
+            }
+        }
+        // 不读取配置中的任何词典文件
+        let result = Dictionary::from_configuration(Arc::new(EmbeddedConfig));
+        #[cfg(feature = "embedded-dict")]
+        {
+            let dict = result.unwrap();
+            assert!(dict.match_in_main_dict("中华人民共和国".chars())[0].is_match());
+            assert!(dict.is_quantifier("元"));
+            assert!(dict.is_stop_word("the".chars(), 0, 3));
+        }
+        #[cfg(not(feature = "embedded-dict"))]
+        assert!(matches!(result, Err(error) if error.contains("embedded-dict")));
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let mut main = Trie::default();
+        for word in ["北京大学", "清华大学", "赌博"] {
+            main.insert(word.chars());
+        }
+        let mut dictionary = Dictionary::with_dicts(main, Trie::default(), Trie::default());
+        dictionary.disable_words_by(vec!["赌博", "从未收录"], "moderation");
+        dictionary.apply_word_ops(vec![WordOp::Remove("清华大学".to_string())]);
+        assert_eq!(dictionary.diagnose("北京大学"), WordStatus::Present);
+        assert_eq!(dictionary.diagnose("从未收录"), WordStatus::Unknown);
+        assert_eq!(
+            dictionary.diagnose("赌博").to_string(),
+            "present but disabled (by moderation at version 0)"
+        );
+        assert_eq!(
+            dictionary.diagnose("清华大学"),
+            WordStatus::Disabled(DisabledWord {
+                by: "word_op".to_string(),
+                version: 1,
+            })
+        );
+        dictionary.add_words(vec!["赌博"]);
+        assert_eq!(dictionary.diagnose("赌博"), WordStatus::Present);
+    }
+
+    #[test]
+    fn test_find_entries() {
+        let mut dictionary = Dictionary::default();
+        dictionary.load();
+        dictionary.add_words(vec!["小和尚念经"]);
+        let entries = dictionary.find_entries("和尚").unwrap();
+        assert!(entries.contains(&"和尚".to_string()));
+        assert!(entries.contains(&"小和尚念经".to_string()));
+        assert!(entries.iter().all(|e| e.contains("和尚")));
+        let entries = dictionary.find_entries("^北京大学$").unwrap();
+        assert_eq!(entries, vec!["北京大学"]);
+        assert!(dictionary.find_entries("(").is_err());
+    }
+
+    #[cfg(feature = "fst")]
+    #[test]
+    fn test_main_dict_fst() {
+        let mut dictionary = Dictionary::default();
+        dictionary.load();
+        let fst = dictionary.main_dict_fst().unwrap();
+        let chars = "张三说的确实在理中华人民共和国".chars().collect::<Vec<_>>();
+        for offset in 0..chars.len() {
+            let length = chars.len() - offset;
+            let summary = |hits: Vec<Hit>| {
+                hits.iter()
+                    .filter(|hit| hit.is_match())
+                    .map(|hit| hit.range())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                summary(fst.match_word_with_offset(chars.iter().copied(), offset, length)),
+                summary(dictionary.match_in_main_dict_with_offset(
+                    chars.iter().copied(),
+                    offset,
+                    length
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_pinyin_index() {
+        let mut dictionary = Dictionary::default();
+        dictionary.load();
+        let mut table = PinyinTable::default();
+        for (c, reading) in [('北', "bei"), ('京', "jing"), ('大', "da"), ('学', "xue")] {
+            table.insert(c, reading);
+        }
+        let index = dictionary.pinyin_index(&table);
+        assert!(index
+            .candidates("beijingdaxue")
+            .contains(&"北京大学".to_string()));
+        assert!(index.candidates("daxue").contains(&"大学".to_string()));
+    }
+
+    #[test]
+    fn test_quantifier() {
+        let mut dictionary = Dictionary::default();
+        dictionary.load();
+        assert!(dictionary.is_quantifier("块"));
+        assert!(dictionary.is_quantifier("个"));
+        assert!(!dictionary.is_quantifier("北京"));
+        assert!(!dictionary.is_quantifier(""));
+        let quantifiers = dictionary.quantifiers();
+        assert!(quantifiers.contains(&"块".to_string()));
+        assert!(quantifiers.iter().all(|q| dictionary.is_quantifier(q)));
+
+        let mut quantifier_dict = Trie::default();
+        quantifier_dict.insert("kg".chars());
+        let dictionary = Dictionary::with_dicts(Trie::default(), Trie::default(), quantifier_dict);
+        for word in ["kg", "KG", "Ｋｇ"] {
+            assert!(dictionary.is_quantifier(word), "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_stop_words() {
+        let mut dictionary = Dictionary::default();
+        dictionary.load();
+        let is_stop = |dictionary: &Dictionary, word: &str| {
+            dictionary.is_stop_word(word.chars(), 0, word.chars().count())
+        };
+        assert!(is_stop(&dictionary, "the"));
+        dictionary.clear_stop_words();
+        assert!(!is_stop(&dictionary, "the"));
+        assert!(dictionary.stop_words().is_empty());
+        dictionary.add_stop_words(vec!["的", "了", "the"]);
+        assert_eq!(dictionary.stop_words(), vec!["the", "了", "的"]);
+        dictionary.remove_stop_words(vec!["了"]);
+        assert!(is_stop(&dictionary, "的"));
+        assert!(!is_stop(&dictionary, "了"));
+        assert_eq!(
+            dictionary.apply_word_ops(vec![WordOp::ClearStop, WordOp::AddStop("啊".to_string())]),
+            1
+        );
+        assert_eq!(dictionary.stop_words(), vec!["啊"]);
+    }
+
+    #[test]
+    fn test_reload() {
+        let mut dictionary = Dictionary::default();
+        dictionary.load();
+        dictionary.add_words(vec!["不存在的词"]);
+        let report = dictionary.reload();
+        assert!(report.is_ok(), "{:?}", report);
+        assert_eq!(report.version, 1);
+        assert_eq!(report.main_dict.removed, 1);
+        assert_eq!(report.main_dict.added, 0);
+        assert_eq!(report.source_checksums.len(), 5);
+        assert_eq!(dictionary.version(), 1);
+        assert!(dictionary.find_entries("^不存在的词$").unwrap().is_empty());
+        assert!(!dictionary.match_in_main_dict("北京大学".chars()).is_empty());
+
+        let mut detached =
+            Dictionary::with_dicts(Dict::default(), Dict::default(), Dict::default());
+        let report = detached.reload();
+        assert!(!report.success);
+        assert_eq!(report.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_pinned() {
+        let dict = DictRef::Global;
+        let hits = dict.pinned(|| {
+            // 嵌套调用复用已持有的锁, 不会死锁
+            dict.pinned(|| dict.with(|d| d.match_in_main_dict("北京".chars()).len()))
+        });
+        assert!(hits > 0);
+        assert!(!is_pinned());
+        // with 中嵌套 with 同样只持有一把读锁
+        let nested = dict.with(|_| {
+            assert!(is_pinned());
+            dict.with(|d| d.match_in_main_dict("北京".chars()).len())
+        });
+        assert_eq!(nested, hits);
+        assert!(!is_pinned());
+        // panic 后释放读锁
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| dict.with(|_| panic!("in with"))));
+        assert!(!is_pinned());
+    }
+
+    #[test]
+    fn test_preview_with() {
+        let texts = ["张三在区块链钱包公司上班", "北京大学生"];
+        let previews =
+            Dictionary::preview_with(vec!["区块链钱包"], vec!["大学生"], |segmenter| {
+                // 预览期间不持有 GLOBAL_DICT 的读锁
+                assert!(!is_pinned());
+                texts
+                    .iter()
+                    .map(|text| {
+                        segmenter
+                            .tokenize(text, TokenMode::INDEX)
+                            .into_iter()
+                            .map(|lexeme| lexeme.get_lexeme_text().to_string())
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+            });
+        assert!(previews[0].contains(&"区块链钱包".to_string()));
+        assert!(!previews[1].contains(&"大学生".to_string()));
+        assert!(previews[1].contains(&"大学".to_string()));
+        // 当前词典不受影响
+        let dictionary = GLOBAL_DICT.read().unwrap();
+        assert!(dictionary.find_entries("^区块链钱包$").unwrap().is_empty());
+        assert_eq!(dictionary.find_entries("^大学生$").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_lint() {
+        let mut dictionary = Dictionary::default();
+        dictionary.load();
+        let long_word = "长".repeat(MAX_WORD_LENGTH + 1);
+        let lines = vec![
+            "北京大学",
+            "",
+            "北京 大学",
+            "北京大学",
+            "中文かな",
+            "U盘",
+            "the",
+            "逗号，",
+            long_word.as_str(),
+            "ｕ盘",
+            "ＡＮＤ",
+        ];
+        let issues = dictionary
+            .lint(lines)
+            .into_iter()
+            .map(|issue| (issue.line, issue.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            vec![
+                (2, LintKind::Empty),
+                (3, LintKind::Whitespace),
+                (4, LintKind::Duplicate),
+                (5, LintKind::MixedScript),
+                (7, LintKind::StopWord),
+                (8, LintKind::Symbol),
+                (9, LintKind::TooLong),
+                (10, LintKind::Duplicate),
+                (11, LintKind::StopWord),
+            ]
+        );
+    }
 }
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::core::char_util::{char_type_of, CharType};
+use crate::core::ik_segmenter::{IKSegmenter, TokenMode, TokenizeOptions};
+use crate::core::lexeme::LexemeType;
+use crate::dict::dictionary::Dictionary;
+
+/// 从语料中统计词典词条的词频, 输出 "词\t词频" 格式的词典文件, 可直接作为带词频的主词典或扩展词典
+/// 只统计词典中的词, 未登录的单字、英文、数词等不会写入词典
+#[derive(Debug, Clone, Default)]
+pub struct FreqCounter {
+    counts: HashMap<String, u64>,
+}
+
+impl FreqCounter {
+    pub fn new() -> Self {
+        FreqCounter::default()
+    }
+
+    /// 用分词器切分 text, 统计其中的词典词(CNWORD), 保留停止词
+    pub fn count_tokens(&mut self, segmenter: &mut IKSegmenter, text: &str, mode: TokenMode) {
+        let options = TokenizeOptions {
+            keep_stop_words: true,
+            ..TokenizeOptions::new(mode)
+        };
+        for lexeme in segmenter.tokenize_with(text, options) {
+            if lexeme.lexeme_type == LexemeType::CNWORD {
+                self.add(lexeme.get_lexeme_text(), 1);
+            }
+        }
+    }
+
+    /// 用主词典做正向最大匹配并统计, 不经过歧义裁决, 速度较快但长词的词频偏高
+    pub fn count_max_match(&mut self, dict: &Dictionary, text: &str) {
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut cursor = 0;
+        while cursor < chars.len() {
+            if char_type_of(&chars[cursor]) != CharType::CHINESE {
+                cursor += 1;
+                continue;
+            }
+            let longest = dict
+                .match_in_main_dict_with_offset(chars.iter().copied(), cursor, chars.len() - cursor)
+                .into_iter()
+                .filter(|hit| hit.is_match())
+                .map(|hit| hit.range().end)
+                .max();
+            match longest {
+                Some(end) => {
+                    let word = chars[cursor..end].iter().collect::<String>();
+                    self.add(&word, 1);
+                    cursor = end;
+                }
+                None => cursor += 1,
+            }
+        }
+    }
+
+    pub fn add(&mut self, word: &str, count: u64) {
+        match self.counts.get_mut(word) {
+            Some(total) => *total += count,
+            None => {
+                self.counts.insert(word.to_string(), count);
+            }
+        }
+    }
+
+    pub fn get(&self, word: &str) -> u64 {
+        self.counts.get(word).copied().unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// 出现不少于 min_count 次的词及词频, 按词频降序, 词频相同时按词排序
+    /// 超出 u32 的词频截断为 u32::MAX
+    pub fn entries(&self, min_count: u64) -> Vec<(&str, u32)> {
+        let mut entries = self
+            .counts
+            .iter()
+            .filter(|(_, &count)| count >= min_count)
+            .map(|(word, &count)| (word.as_str(), count.min(u32::MAX as u64) as u32))
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        entries
+    }
+
+    /// 按 "词\t词频" 格式每行写出一个词条
+    pub fn write_dict<W: Write>(&self, out: &mut W, min_count: u64) -> Result<(), String> {
+        for (word, freq) in self.entries(min_count) {
+            writeln!(out, "{}\t{}", word, freq)
+                .map_err(|e| format!("write freq dict error: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dict::trie::Trie;
+
+    #[test]
+    fn test_count_tokens() {
+        let mut ik = IKSegmenter::new();
+        let mut counter = FreqCounter::new();
+        for text in ["南京市长江大桥", "长江大桥的桥墩", "iPhone 12"] {
+            counter.count_tokens(&mut ik, text, TokenMode::SEARCH);
+        }
+        assert_eq!(counter.get("长江大桥"), 2);
+        assert_eq!(counter.get("南京市"), 1);
+        assert_eq!(counter.get("iphone"), 0);
+        assert_eq!(counter.entries(2), vec![("长江大桥", 2)]);
+    }
+
+    #[test]
+    fn test_count_max_match() {
+        let mut main = Trie::default();
+        for word in ["北京", "北京大学", "大学", "大学生", "学生"] {
+            main.insert(word.chars());
+        }
+        let dict = Dictionary::with_dicts(main, Trie::default(), Trie::default());
+        let mut counter = FreqCounter::new();
+        counter.count_max_match(&dict, "北京大学生, 大学生在北京");
+        assert_eq!(
+            counter.entries(1),
+            vec![("北京", 1), ("北京大学", 1), ("大学生", 1)]
+        );
+        counter.add("北京", u64::MAX - 1);
+        let mut out = Vec::new();
+        counter.write_dict(&mut out, 2).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("北京\t{}\n", u32::MAX)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::fs;
+use std::ops::Deref;
+
+use fst::raw::{Builder, Fst, Node};
+
+use crate::dict::hit::Hit;
+use crate::dict::trie::Trie;
+use crate::dict::word_dict::WordDict;
+
+/// 基于 FST 的只读词典, 匹配语义与 Trie::match_word_with_offset 相同
+/// 数据可以是 Vec<u8>, 也可以是内存映射的文件, 便于多进程共享同一份词典
+pub struct FstDict<D = Vec<u8>> {
+    fst: Fst<D>,
+}
+
+impl FstDict<Vec<u8>> {
+    /// 由词条构造, 词条无需有序, 重复和空词条会被忽略
+    pub fn build<I, S>(words: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut words = words
+            .into_iter()
+            .map(|word| word.as_ref().to_string())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>();
+        words.sort_unstable();
+        words.dedup();
+        let mut builder = Builder::memory();
+        for word in words.iter() {
+            builder
+                .add(word)
+                .map_err(|e| format!("build fst dict error: {}", e))?;
+        }
+        let bytes = builder
+            .into_inner()
+            .map_err(|e| format!("build fst dict error: {}", e))?;
+        FstDict::new(bytes)
+    }
+
+    /// 由 Trie 中的全部词条构造
+    pub fn from_trie(trie: &Trie) -> Result<Self, String> {
+        let mut words = Vec::new();
+        trie.for_each_word(|word| words.push(word.to_string()));
+        FstDict::build(words)
+    }
+
+    /// 从 save 写出的文件加载
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("read fst dict {} error: {}", path, e))?;
+        FstDict::new(bytes)
+    }
+}
+
+impl<D: Deref<Target = [u8]>> FstDict<D> {
+    /// 由 FST 数据构造, 如 memmap2::Mmap
+    pub fn new(data: D) -> Result<Self, String> {
+        let fst = Fst::new(data).map_err(|e| format!("invalid fst dict: {}", e))?;
+        Ok(FstDict { fst })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.fst.to_vec())
+            .map_err(|e| format!("write fst dict {} error: {}", path, e))
+    }
+
+    pub fn len(&self) -> usize {
+        self.fst.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fst.is_empty()
+    }
+
+    pub fn exist<C: Iterator<Item = char>>(&self, chars: C) -> bool {
+        self.fst.contains_key(chars.collect::<String>())
+    }
+
+    pub fn match_word<C: Iterator<Item = char>>(&self, chars: C) -> Vec<Hit> {
+        let char_list = chars.collect::<Vec<_>>();
+        let length = char_list.len();
+        self.match_with_offset(&char_list, 0, length)
+    }
+
+    pub fn match_word_with_offset<C: Iterator<Item = char>>(
+        &self,
+        chars: C,
+        offset: usize,
+        length: usize,
+    ) -> Vec<Hit> {
+        let char_list = chars.collect::<Vec<_>>();
+        self.match_with_offset(&char_list, offset, length)
+    }
+
+    // 从 offset 开始逐字匹配, 途经的每个词输出一个命中, 最后到达的节点输出一个命中(可能只是前缀)
+    fn match_with_offset(&self, char_list: &[char], offset: usize, length: usize) -> Vec<Hit> {
+        let mut hits = Vec::new();
+        if offset + length > char_list.len() {
+            return hits;
+        }
+        let mut node = self.fst.root();
+        let mut matched = 0;
+        for c in char_list[offset..offset + length].iter() {
+            let next = match self.step(node, *c) {
+                Some(next) => next,
+                None => break,
+            };
+            if matched > 0 && node.is_final() {
+                let mut hit = Hit::new();
+                hit.set_range(offset..offset + matched);
+                hit.set_match();
+                hit.set_prefix();
+                hits.push(hit);
+            }
+            node = next;
+            matched += 1;
+        }
+        if matched > 0 {
+            let mut hit = Hit::new();
+            hit.set_range(offset..offset + matched);
+            if node.is_final() {
+                hit.set_match();
+            }
+            if !node.is_empty() {
+                hit.set_prefix();
+            }
+            hits.push(hit);
+        }
+        hits
+    }
+
+    // 沿字符的 UTF-8 字节转移
+    fn step<'f>(&'f self, mut node: Node<'f>, c: char) -> Option<Node<'f>> {
+        let mut buf = [0u8; 4];
+        for b in c.encode_utf8(&mut buf).bytes() {
+            let i = node.find_input(b)?;
+            node = self.fst.node(node.transition_addr(i));
+        }
+        Some(node)
+    }
+}
+
+impl<D: Deref<Target = [u8]> + Send + Sync> WordDict for FstDict<D> {
+    fn match_chars(&self, chars: &[char], offset: usize, length: usize) -> Vec<Hit> {
+        self.match_with_offset(chars, offset, length)
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.fst.contains_key(word)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn summary(hits: &[Hit]) -> Vec<(std::ops::Range<usize>, bool, bool)> {
+        hits.iter()
+            .map(|hit| (hit.range(), hit.is_match(), hit.is_prefix()))
+            .collect()
+    }
+
+    #[test]
+    fn test_same_as_trie() {
+        let words = [
+            "北京",
+            "北京大学",
+            "大学",
+            "大学生",
+            "学生",
+            "生活",
+            "中华人民共和国",
+        ];
+        let mut trie = Trie::default();
+        for word in words {
+            trie.insert(word.chars());
+        }
+        let dict = FstDict::build(words).unwrap();
+        assert_eq!(dict.len(), words.len());
+        let chars = "北京大学生活在中华人民共和国".chars().collect::<Vec<_>>();
+        for offset in 0..chars.len() {
+            for length in 1..=chars.len() - offset {
+                let expected = trie.match_word_with_offset(chars.iter().copied(), offset, length);
+                let actual = dict.match_word_with_offset(chars.iter().copied(), offset, length);
+                assert_eq!(
+                    summary(&actual),
+                    summary(&expected),
+                    "{} {}",
+                    offset,
+                    length
+                );
+            }
+        }
+        assert!(dict.exist("大学生".chars()));
+        assert!(!dict.exist("大".chars()));
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = std::env::temp_dir().join(format!("ik-fst-{}.fst", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        FstDict::build(["北京", "上海"])
+            .unwrap()
+            .save(&path)
+            .unwrap();
+        let dict = FstDict::load(&path).unwrap();
+        assert_eq!(
+            summary(&dict.match_word("上海".chars())),
+            vec![(0..2, true, false)]
+        );
+        fs::remove_file(&path).unwrap();
+        assert!(FstDict::new(vec![0u8; 8]).is_err());
+    }
+
+    #[test]
+    fn test_base_dict() {
+        use std::sync::Arc;
+
+        use crate::config::configuration::global_config;
+        use crate::core::ik_segmenter::{IKSegmenter, TokenMode};
+        use crate::dict::dictionary::Dictionary;
+
+        // 以 FST 作为主词典时的分词结果与 Trie 相同
+        let cfg = global_config();
+        let trie_dict = Dictionary::from_configuration(cfg.clone()).unwrap();
+        let fst = trie_dict.main_dict_fst().unwrap();
+        let fst_dict = Dictionary::with_base_dict(cfg.clone(), Arc::new(fst)).unwrap();
+        let mut segmenters = vec![trie_dict, fst_dict]
+            .into_iter()
+            .map(|dict| IKSegmenter::with_configuration(cfg.as_ref(), Arc::new(dict)))
+            .collect::<Vec<_>>();
+        for text in [
+            "张华考上了北京大学",
+            "中华人民共和国成立了",
+            "乒乓球拍卖完了",
+        ] {
+            for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+                let outputs = segmenters
+                    .iter_mut()
+                    .map(|ik| {
+                        ik.tokenize(text, mode)
+                            .iter()
+                            .map(|l| l.get_lexeme_text().to_string())
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(outputs[1], outputs[0], "{} {:?}", text, mode);
+            }
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::ops::Range;
+use std::sync::Arc;
+
 const UNMATCH: u32 = 0x00000000;
 const MATCH: u32 = 0x00000001;
 const PREFIX: u32 = 0x00000010;
@@ -7,17 +10,38 @@ const PREFIX: u32 = 0x00000010;
 pub struct Hit {
     pub hit_state: u32,
     pub begin: usize,
+    // 闭区间的结束位置, 即命中的最后一个字符
+    #[deprecated(note = "end 为闭区间, 请使用 range()/set_range()")]
     pub end: usize,
+    // 命中词条的词频, 词典中未指定词频或未完整匹配时为 0
+    pub freq: u32,
+    // 命中词条的词性, 未指定或未完整匹配时为 None
+    pub pos: Option<Arc<str>>,
 }
 
+#[allow(deprecated)]
 impl Hit {
     pub fn new() -> Self {
         Hit {
             hit_state: UNMATCH,
             begin: 0,
             end: 0,
+            freq: 0,
+            pos: None,
         }
     }
+
+    /// 命中的字符范围, 左闭右开, 与 Lexeme::range 一致
+    pub fn range(&self) -> Range<usize> {
+        self.begin..self.end + 1
+    }
+
+    /// 设置命中的字符范围, 左闭右开, range 不能为空
+    pub fn set_range(&mut self, range: Range<usize>) {
+        debug_assert!(range.start < range.end);
+        self.begin = range.start;
+        self.end = range.end - 1;
+    }
     pub fn set_match(&mut self) {
         self.hit_state |= MATCH;
     }
@@ -39,3 +63,19 @@ impl Hit {
         self.hit_state == UNMATCH
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_range() {
+        let mut hit = Hit::new();
+        hit.set_range(2..5);
+        assert_eq!(hit.begin, 2);
+        assert_eq!(hit.end, 4);
+        assert_eq!(hit.range(), 2..5);
+        assert_eq!(hit.range().len(), 3);
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt::{Display, Formatter};
+
+// 词条最大字符长度, 超出视为可疑词条, 插入词典时被跳过
+pub const MAX_WORD_LENGTH: usize = 32;
+
+/// 可疑词条类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    // 空行
+    Empty,
+    // 词条内部包含空白字符
+    Whitespace,
+    // 包含标点或控制符等无法被切分的字符
+    Symbol,
+    // 混合了中文与日韩文字, 或拉丁字母与日韩文字
+    MixedScript,
+    // 超过 MAX_WORD_LENGTH
+    TooLong,
+    // 与前面的词条重复
+    Duplicate,
+    // 是停止词, 加入主词典后仍会被过滤
+    StopWord,
+}
+
+/// 一条词典检查结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    // 行号, 从1开始
+    pub line: usize,
+    // 去掉首尾空白后的词条
+    pub word: String,
+    pub kind: LintKind,
+}
+
+impl Display for LintIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {:?} {:?}", self.line, self.word, self.kind)
+    }
+}
@@ -1,3 +1,15 @@
 pub mod dictionary;
+pub mod freq;
+#[cfg(feature = "fst")]
+pub mod fst_dict;
 pub mod hit;
+pub mod lint;
+pub mod oov;
+pub mod reload;
+#[cfg(feature = "remote-dict")]
+pub mod remote;
+pub mod synonym;
 pub mod trie;
+pub mod updater;
+pub mod watcher;
+pub mod word_dict;
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex, PoisonError, TryLockError};
+
+use once_cell::sync::OnceCell;
+
+use crate::core::lexeme::{Lexeme, LexemeType};
+
+// 默认最多记录的片段数
+const DEFAULT_LIMIT: usize = 100_000;
+
+// 候选词的字数范围, 更长的单字串通常不是一个词
+const MIN_CANDIDATE_LEN: usize = 2;
+const MAX_CANDIDATE_LEN: usize = 8;
+
+/// 未登录词候选收集器, 记录分词结果中由连续单字(CNCHAR)组成的片段及出现次数
+/// 这些片段往往是词典中缺少的词, 导出后供人工审核, 确认的词追加到扩展词典
+/// 达到上限后不再记录新片段, 已有片段继续计数, 避免长期运行时无限增长
+#[derive(Debug)]
+pub struct OovCollector {
+    counts: HashMap<String, u64>,
+    limit: usize,
+}
+
+/// 多个分词器共享的收集器, 见 IKSegmenter::with_oov_collector
+pub type SharedOovCollector = Arc<Mutex<OovCollector>>;
+
+// 未指定收集器的分词器所使用的全局收集器
+static GLOBAL_OOV_COLLECTOR: OnceCell<SharedOovCollector> = OnceCell::new();
+
+/// 设置全局收集器, 未调用 with_oov_collector 的分词器(含 with_thread_segmenter、IkTokenizer、
+/// worker 及 server 使用的分词器)均向它汇总; 只能设置一次, 重复设置返回错误
+pub fn init_global_oov_collector(collector: SharedOovCollector) -> Result<(), String> {
+    GLOBAL_OOV_COLLECTOR
+        .set(collector)
+        .map_err(|_| "global oov collector already initialized".to_string())
+}
+
+/// 全局收集器, 未设置时为 None
+pub fn global_oov_collector() -> Option<&'static SharedOovCollector> {
+    GLOBAL_OOV_COLLECTOR.get()
+}
+
+impl Default for OovCollector {
+    fn default() -> Self {
+        Self::with_limit(DEFAULT_LIMIT)
+    }
+}
+
+impl OovCollector {
+    pub fn new() -> Self {
+        OovCollector::default()
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        OovCollector {
+            counts: HashMap::new(),
+            limit,
+        }
+    }
+
+    /// 构造可在多个分词器间共享的收集器
+    pub fn shared() -> SharedOovCollector {
+        Arc::new(Mutex::new(OovCollector::new()))
+    }
+
+    /// 记录一次分词结果中的单字串, lexemes 须已解析词元文本且按位置排列
+    pub fn observe(&mut self, lexemes: &[Lexeme]) {
+        let mut run: Vec<&Lexeme> = Vec::new();
+        for lexeme in lexemes {
+            let adjacent = run
+                .last()
+                .map(|last| last.get_end_position() == lexeme.get_begin())
+                .unwrap_or(true);
+            if lexeme.lexeme_type == LexemeType::CNCHAR && adjacent {
+                run.push(lexeme);
+                continue;
+            }
+            self.record(&run);
+            run.clear();
+            if lexeme.lexeme_type == LexemeType::CNCHAR {
+                run.push(lexeme);
+            }
+        }
+        self.record(&run);
+    }
+
+    fn record(&mut self, run: &[&Lexeme]) {
+        if !(MIN_CANDIDATE_LEN..=MAX_CANDIDATE_LEN).contains(&run.len()) {
+            return;
+        }
+        let word = run.iter().map(|l| l.get_lexeme_text()).collect::<String>();
+        if let Some(count) = self.counts.get_mut(&word) {
+            *count += 1;
+        } else if self.counts.len() < self.limit {
+            self.counts.insert(word, 1);
+        }
+    }
+
+    /// 将 other 中的计数并入本收集器并清空 other, 新片段同样受上限约束
+    pub fn merge(&mut self, other: &mut OovCollector) {
+        for (word, count) in other.counts.drain() {
+            if let Some(total) = self.counts.get_mut(&word) {
+                *total += count;
+            } else if self.counts.len() < self.limit {
+                self.counts.insert(word, count);
+            }
+        }
+    }
+
+    pub fn get(&self, word: &str) -> u64 {
+        self.counts.get(word).copied().unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+
+    /// 出现次数最多的 n 个片段, 次数相同时按文本排序
+    pub fn top(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut candidates = self
+            .counts
+            .iter()
+            .map(|(word, &count)| (word.as_str(), count))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        candidates.truncate(n);
+        candidates
+    }
+
+    /// 按 "片段\t次数" 格式写出出现次数最多的 n 个片段
+    pub fn export<W: Write>(&self, out: &mut W, n: usize) -> Result<(), String> {
+        for (word, count) in self.top(n) {
+            writeln!(out, "{}\t{}", word, count)
+                .map_err(|e| format!("write oov candidates error: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+// 将分词器本地缓冲的计数并入共享收集器
+// wait 为 false 时锁被占用则保留在缓冲中, 下次再合并; 锁中毒时仍然合并
+pub(crate) fn flush_pending(shared: &SharedOovCollector, pending: &mut OovCollector, wait: bool) {
+    if pending.is_empty() {
+        return;
+    }
+    let mut collector = match shared.try_lock() {
+        Ok(collector) => collector,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) if !wait => return,
+        Err(TryLockError::WouldBlock) => shared.lock().unwrap_or_else(PoisonError::into_inner),
+    };
+    collector.merge(pending);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::ik_segmenter::{IKSegmenter, TokenMode};
+
+    // text 中 begin 处的单字
+    fn cnchar(text: &str, begin: usize) -> Lexeme {
+        let mut lexeme = Lexeme::new(0, begin, 1, LexemeType::CNCHAR);
+        lexeme.parse_lexeme_text(text);
+        lexeme
+    }
+
+    #[test]
+    fn test_observe() {
+        let text = "嵘峰和沧澜嵘, 峰";
+        let mut word = Lexeme::new(0, 2, 1, LexemeType::CNWORD);
+        word.parse_lexeme_text(text);
+        let lexemes = vec![
+            cnchar(text, 0),
+            cnchar(text, 1),
+            word,
+            cnchar(text, 3),
+            cnchar(text, 4),
+            cnchar(text, 5),
+            // 与前一个单字不相邻
+            cnchar(text, 8),
+        ];
+        let mut collector = OovCollector::with_limit(2);
+        collector.observe(&lexemes);
+        collector.observe(&lexemes[..2]);
+        assert_eq!(collector.top(10), vec![("嵘峰", 2), ("沧澜嵘", 1)]);
+
+        // 达到上限后不再记录新片段
+        collector.observe(&[cnchar("云岫", 0), cnchar("云岫", 1)]);
+        assert_eq!(collector.get("云岫"), 0);
+        let mut out = Vec::new();
+        collector.export(&mut out, 1).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "嵘峰\t2\n");
+    }
+
+    #[test]
+    fn test_segmenter_hook() {
+        let collector = OovCollector::shared();
+        let mut ik = IKSegmenter::new().with_oov_collector(collector.clone());
+        for text in ["嵘峰风景", "位于嵘峰"] {
+            ik.tokenize(text, TokenMode::SEARCH);
+        }
+        assert_eq!(collector.lock().unwrap().top(1), vec![("嵘峰", 2)]);
+    }
+
+    #[test]
+    fn test_contended() {
+        let collector = OovCollector::shared();
+        let mut ik = IKSegmenter::new().with_oov_collector(collector.clone());
+        // 锁被占用时先缓冲在分词器中, 不阻塞分词
+        let guard = collector.lock().unwrap();
+        ik.tokenize("嵘峰风景", TokenMode::SEARCH);
+        drop(guard);
+        assert_eq!(collector.lock().unwrap().get("嵘峰"), 0);
+        ik.tokenize("位于嵘峰", TokenMode::SEARCH);
+        assert_eq!(collector.lock().unwrap().get("嵘峰"), 2);
+
+        // 锁中毒后仍然收集, 不会 panic
+        let poisoner = collector.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison");
+        })
+        .join();
+        assert!(collector.is_poisoned());
+        ik.tokenize("嵘峰", TokenMode::SEARCH);
+        assert_eq!(collector.lock().unwrap_err().into_inner().get("嵘峰"), 3);
+    }
+
+    #[test]
+    fn test_flush_on_drop() {
+        let collector = OovCollector::shared();
+        let ik = {
+            let _guard = collector.lock().unwrap();
+            let mut ik = IKSegmenter::new().with_oov_collector(collector.clone());
+            ik.tokenize("嵘峰风景", TokenMode::SEARCH);
+            ik
+        };
+        drop(ik);
+        assert_eq!(collector.lock().unwrap().get("嵘峰"), 1);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::configuration::Configuration;
+use crate::dict::trie::Trie;
+
+type ReloadCallback = Box<dyn Fn(&ReloadReport) + Send>;
+
+// 全局词典重新加载后的回调
+static RELOAD_CALLBACK: Mutex<Option<ReloadCallback>> = Mutex::new(None);
+
+/// 单个词典的变化
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DictChange {
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// 词典重新加载报告
+#[derive(Debug, Clone, Default)]
+pub struct ReloadReport {
+    // 是否加载成功, 失败时继续使用旧词典
+    pub success: bool,
+    // 加载后的词典版本号
+    pub version: u64,
+    pub main_dict: DictChange,
+    pub stop_word_dict: DictChange,
+    pub quantifier_dict: DictChange,
+    // 加载耗时
+    pub duration: Duration,
+    // 各词典文件的校验和 (路径, FNV-1a 64)
+    pub source_checksums: Vec<(PathBuf, u64)>,
+    pub errors: Vec<String>,
+}
+
+impl ReloadReport {
+    pub fn is_ok(&self) -> bool {
+        self.success && self.errors.is_empty()
+    }
+}
+
+/// 设置全局词典重新加载后的回调, 用于记录、审计词典推送
+pub fn set_reload_callback<F: Fn(&ReloadReport) + Send + 'static>(callback: F) {
+    *RELOAD_CALLBACK.lock().unwrap() = Some(Box::new(callback));
+}
+
+pub(crate) fn notify_reload(report: &ReloadReport) {
+    if let Some(callback) = RELOAD_CALLBACK.lock().unwrap().as_ref() {
+        callback(report);
+    }
+}
+
+// 比较新旧词典的词条
+pub(crate) fn diff_words(old: &Trie, new: &Trie) -> DictChange {
+    let mut old_words = HashSet::new();
+    old.for_each_word(|word| {
+        old_words.insert(word.to_string());
+    });
+    let mut change = DictChange::default();
+    new.for_each_word(|word| {
+        if !old_words.remove(word) {
+            change.added += 1;
+        }
+    });
+    change.removed = old_words.len();
+    change
+}
+
+// 配置中所有词典文件的路径
+pub(crate) fn dict_sources(cfg: &dyn Configuration) -> Vec<PathBuf> {
+    let mut sources = vec![cfg.get_main_dictionary()];
+    sources.extend(cfg.get_ext_dictionaries());
+    sources.extend(cfg.get_ext_stop_word_dictionaries());
+    sources.push(cfg.get_quantifier_dictionary());
+    sources
+}
+
+// 配置中所有词典文件的校验和, 读取失败的文件记录错误
+pub(crate) fn source_checksums(
+    cfg: &dyn Configuration,
+    errors: &mut Vec<String>,
+) -> Vec<(PathBuf, u64)> {
+    let sources = dict_sources(cfg);
+    let mut checksums = Vec::with_capacity(sources.len());
+    for source in sources {
+        match fs::read(&source) {
+            Ok(bytes) => checksums.push((source, fnv1a(&bytes))),
+            Err(e) => errors.push(format!("read {} error: {}", source.display(), e)),
+        }
+    }
+    checksums
+}
+
+// FNV-1a 64, 跨平台、跨版本稳定
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_diff_words() {
+        let mut old = Trie::default();
+        old.insert("北京".chars());
+        old.insert("上海".chars());
+        let mut new = Trie::default();
+        new.insert("北京".chars());
+        new.insert("广州".chars());
+        new.insert("深圳".chars());
+        assert_eq!(
+            diff_words(&old, &new),
+            DictChange {
+                added: 2,
+                removed: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -0,0 +1,370 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::configuration::global_config;
+use crate::dict::dictionary::{Dictionary, GLOBAL_DICT};
+use crate::dict::reload::fnv1a;
+use crate::dict::updater::WordOp;
+use crate::dict::watcher::DictWatcher;
+
+// 连接及读取远程词典的超时时间
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+// 响应的最大字节数, 超出时视为错误, 不合并
+const MAX_RESPONSE_SIZE: usize = 32 * 1024 * 1024;
+
+// 已合并到 GLOBAL_DICT 的远程词, 按 URL 记录最近一次拉取的内容, 重新加载词典时再次合并
+static MERGED_WORDS: Mutex<BTreeMap<String, (RemoteKind, Vec<String>)>> =
+    Mutex::new(BTreeMap::new());
+
+/// 远程词典的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteKind {
+    // 扩展词
+    Words,
+    // 扩展停止词
+    StopWords,
+}
+
+/// 通过 HTTP 拉取的远程扩展词典, 与 ES IK 插件的 remote_ext_dict 相同,
+/// 每行一个词, 使用 ETag/Last-Modified 做条件请求, 内容未变化时不重复合并
+/// 使用内置的简单 HTTP/1.1 客户端, 仅支持 http://, 不支持 https 及重定向, 响应不超过 32MB
+#[derive(Debug, Clone)]
+pub struct RemoteDict {
+    url: String,
+    kind: RemoteKind,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    // 上次内容的校验和, 用于不支持条件请求的服务端
+    checksum: Option<u64>,
+}
+
+impl RemoteDict {
+    pub fn new(url: &str, kind: RemoteKind) -> Self {
+        RemoteDict {
+            url: url.to_string(),
+            kind,
+            etag: None,
+            last_modified: None,
+            checksum: None,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn kind(&self) -> RemoteKind {
+        self.kind
+    }
+
+    /// 拉取远程词典, 内容未变化时返回 Ok(None)
+    pub fn fetch(&mut self) -> Result<Option<Vec<String>>, String> {
+        let mut headers = Vec::new();
+        if let Some(etag) = self.etag.as_deref() {
+            headers.push(("If-None-Match", etag));
+        }
+        if let Some(last_modified) = self.last_modified.as_deref() {
+            headers.push(("If-Modified-Since", last_modified));
+        }
+        let response = http_get(&self.url, &headers)?;
+        match response.status {
+            304 => return Ok(None),
+            200 => {}
+            status => return Err(format!("GET {} returned status {}", self.url, status)),
+        }
+        self.etag = response.header("ETag").map(String::from);
+        self.last_modified = response.header("Last-Modified").map(String::from);
+        let checksum = fnv1a(&response.body);
+        if self.checksum == Some(checksum) {
+            return Ok(None);
+        }
+        self.checksum = Some(checksum);
+        let body = String::from_utf8_lossy(&response.body);
+        Ok(Some(
+            body.trim_start_matches('\u{feff}')
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(String::from)
+                .collect(),
+        ))
+    }
+
+    /// 将拉取的词合并到词典中, 返回合并后的词典版本号
+    /// 远程词典中删除的词不会从词典中移除
+    pub fn merge_into(&self, dict: &mut Dictionary, words: Vec<String>) -> u64 {
+        let ops = words
+            .into_iter()
+            .map(|word| match self.kind {
+                RemoteKind::Words => WordOp::Add(word),
+                RemoteKind::StopWords => WordOp::AddStop(word),
+            })
+            .collect();
+        dict.apply_word_ops(ops)
+    }
+}
+
+/// 按全局配置中的 remote_ext_dict/remote_ext_stopwords 启动后台线程,
+/// 立即拉取一次, 之后每隔 interval 轮询, 内容变化时合并到 GLOBAL_DICT
+/// reload_global_dict 重新加载本地词典后, 再次合并各远程词典最近一次拉取的词
+pub fn watch_remote_dicts(interval: Duration) -> DictWatcher {
+    let cfg = global_config();
+    let mut remotes = Vec::new();
+    for url in cfg.get_remote_ext_dictionaries() {
+        remotes.push(RemoteDict::new(&url, RemoteKind::Words));
+    }
+    for url in cfg.get_remote_ext_stop_word_dictionaries() {
+        remotes.push(RemoteDict::new(&url, RemoteKind::StopWords));
+    }
+    DictWatcher::poll(Duration::ZERO, interval, move || {
+        for remote in remotes.iter_mut() {
+            // 在锁外拉取, 只在合并时持有锁
+            match remote.fetch() {
+                Ok(Some(words)) => {
+                    let count = words.len();
+                    MERGED_WORDS
+                        .lock()
+                        .unwrap()
+                        .insert(remote.url().to_string(), (remote.kind(), words.clone()));
+                    let version = remote.merge_into(&mut GLOBAL_DICT.write().unwrap(), words);
+                    log::info!(
+                        "merged {} words from {}, version = {}",
+                        count,
+                        remote.url(),
+                        version
+                    );
+                }
+                Ok(None) => {}
+                Err(error) => log::warn!("fetch remote dictionary failed: {}", error),
+            }
+        }
+    })
+}
+
+// 将已合并过的远程词加入重新加载的词典
+pub(crate) fn reapply_remote_words(dict: &mut Dictionary) {
+    for (kind, words) in MERGED_WORDS.lock().unwrap().values() {
+        let words = words.iter().map(String::as_str).collect::<Vec<_>>();
+        match kind {
+            RemoteKind::Words => dict.add_words(words),
+            RemoteKind::StopWords => dict.add_stop_words(words),
+        }
+    }
+}
+
+struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// 解析 http://host[:port]/path
+fn parse_url(url: &str) -> Result<(String, u16, String), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("unsupported url {}, only http:// is supported", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse::<u16>()
+                .map_err(|_| format!("invalid port in url {}", url))?,
+        ),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("invalid url {}", url));
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+fn http_get(url: &str, headers: &[(&str, &str)]) -> Result<HttpResponse, String> {
+    let (host, port, path) = parse_url(url)?;
+    let error = |e: std::io::Error| format!("GET {} error: {}", url, e);
+    let addrs = (host.as_str(), port).to_socket_addrs().map_err(error)?;
+    let mut stream = None;
+    let mut last_error = None;
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, HTTP_TIMEOUT) {
+            Ok(s) => {
+                stream = Some(s);
+                break;
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    let mut stream = match (stream, last_error) {
+        (Some(stream), _) => stream,
+        (None, Some(e)) => return Err(error(e)),
+        (None, None) => return Err(format!("GET {} error: no address for {}", url, host)),
+    };
+    stream.set_read_timeout(Some(HTTP_TIMEOUT)).map_err(error)?;
+    stream
+        .set_write_timeout(Some(HTTP_TIMEOUT))
+        .map_err(error)?;
+
+    let mut request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nUser-Agent: ik-rs\r\n",
+        path, host
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).map_err(error)?;
+    let mut raw = Vec::new();
+    stream
+        .take(MAX_RESPONSE_SIZE as u64 + 1)
+        .read_to_end(&mut raw)
+        .map_err(error)?;
+    if raw.len() > MAX_RESPONSE_SIZE {
+        return Err(format!(
+            "GET {} error: response larger than {} bytes",
+            url, MAX_RESPONSE_SIZE
+        ));
+    }
+    parse_response(&raw).ok_or_else(|| format!("GET {} error: malformed response", url))
+}
+
+fn parse_response(raw: &[u8]) -> Option<HttpResponse> {
+    let head_end = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&raw[..head_end]).ok()?;
+    let mut lines = head.split("\r\n");
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let mut response = HttpResponse {
+        status,
+        headers,
+        body: raw[head_end + 4..].to_vec(),
+    };
+    let chunked = response
+        .header("Transfer-Encoding")
+        .map(|value| value.eq_ignore_ascii_case("chunked"))
+        .unwrap_or(false);
+    if chunked {
+        response.body = decode_chunked(&response.body)?;
+    } else if let Some(length) = response.header("Content-Length") {
+        let length = length.parse::<usize>().ok()?;
+        response.body.truncate(length);
+    }
+    Some(response)
+}
+
+fn decode_chunked(mut raw: &[u8]) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = raw.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&raw[..line_end]).ok()?;
+        let size = size.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        raw = &raw[line_end + 2..];
+        if size == 0 {
+            return Some(body);
+        }
+        body.extend_from_slice(raw.get(..size)?);
+        raw = raw.get(size + 2..)?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::dict::trie::Trie;
+
+    // 依次返回 responses 的本地 HTTP 服务, 返回地址及收到的请求
+    fn serve(responses: Vec<&'static str>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/ext.dic", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                    request.push_str(&line);
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_remote_dict() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 27\r\n\r\n区块链钱包\n\n元宇宙\n",
+            "HTTP/1.1 304 Not Modified\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n7\r\n北京\n\r\n0\r\n\r\n",
+        ]);
+        let mut remote = RemoteDict::new(&url, RemoteKind::Words);
+        let words = remote.fetch().unwrap().unwrap();
+        assert_eq!(words, vec!["区块链钱包", "元宇宙"]);
+        assert_eq!(remote.fetch().unwrap(), None);
+        assert_eq!(remote.fetch().unwrap(), Some(vec!["北京".to_string()]));
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /ext.dic HTTP/1.1\r\n"));
+        assert!(requests[1].contains("If-None-Match: \"v1\"\r\n"));
+
+        let mut dict = Dictionary::with_dicts(Trie::default(), Trie::default(), Trie::default());
+        assert_eq!(remote.merge_into(&mut dict, words), 1);
+        assert!(!dict.match_in_main_dict("元宇宙".chars()).is_empty());
+    }
+
+    #[test]
+    fn test_reapply_remote_words() {
+        MERGED_WORDS.lock().unwrap().insert(
+            "http://localhost/test_reapply.dic".to_string(),
+            (RemoteKind::StopWords, vec!["嵘峰".to_string()]),
+        );
+        let mut dict = Dictionary::with_dicts(Trie::default(), Trie::default(), Trie::default());
+        reapply_remote_words(&mut dict);
+        assert!(dict.is_stop_word("嵘峰".chars(), 0, 2));
+    }
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            parse_url("http://localhost:8080/dict/ext.dic").unwrap(),
+            ("localhost".to_string(), 8080, "/dict/ext.dic".to_string())
+        );
+        assert_eq!(
+            parse_url("http://example.com").unwrap(),
+            ("example.com".to_string(), 80, "/".to_string())
+        );
+        assert!(parse_url("https://example.com/ext.dic").is_err());
+        assert!(parse_url("http://:80/").is_err());
+    }
+}
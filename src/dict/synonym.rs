@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::configuration::Configuration;
+use crate::core::char_util::regularize_str;
+
+/// 同义词词典, 与 Solr 的同义词格式相同, 每行一条规则, # 开头的行为注释:
+///
+/// - `蕃茄,西红柿 => 番茄`: 左侧的词扩展出右侧的词
+/// - `番茄,西红柿,蕃茄`: 互为同义词, 每个词扩展出其余的词
+///
+/// 只匹配单个词元, 词条按分词时的规则正规化(全角转半角、英文转小写)
+#[derive(Debug, Clone, Default)]
+pub struct SynonymMap {
+    synonyms: HashMap<String, Vec<String>>,
+}
+
+impl SynonymMap {
+    pub fn new() -> Self {
+        SynonymMap::default()
+    }
+
+    /// 读取同义词词典文件, 错误信息中带有行号
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SynonymMap, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("read synonym dict {} error: {}", path.display(), e))?;
+        let mut map = SynonymMap::new();
+        for (index, line) in content.trim_start_matches('\u{feff}').lines().enumerate() {
+            map.add_rule(line)
+                .map_err(|e| format!("{}:{}: {}", path.display(), index + 1, e))?;
+        }
+        Ok(map)
+    }
+
+    /// 加载配置中 synonyms_dict 指定的词典, 未配置时返回 Ok(None)
+    pub fn from_config(cfg: &dyn Configuration) -> Result<Option<SynonymMap>, String> {
+        cfg.get_synonym_dictionary()
+            .map(|path| SynonymMap::load(&path))
+            .transpose()
+    }
+
+    /// 添加一条规则, 空行及注释被忽略
+    pub fn add_rule(&mut self, rule: &str) -> Result<(), String> {
+        let rule = rule.trim();
+        if rule.is_empty() || rule.starts_with('#') {
+            return Ok(());
+        }
+        match rule.split_once("=>") {
+            Some((from, to)) => {
+                let from = split_words(from);
+                let to = split_words(to);
+                if from.is_empty() || to.is_empty() {
+                    return Err(format!("invalid synonym rule {:?}", rule));
+                }
+                for word in from.iter() {
+                    self.add(word, &to);
+                }
+            }
+            None => {
+                let words = split_words(rule);
+                for word in words.iter() {
+                    self.add(word, &words);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 为 word 追加同义词, 跳过 word 本身及已有的同义词
+    fn add(&mut self, word: &str, synonyms: &[String]) {
+        let entry = self.synonyms.entry(word.to_string()).or_default();
+        for synonym in synonyms {
+            if synonym != word && !entry.contains(synonym) {
+                entry.push(synonym.clone());
+            }
+        }
+        if entry.is_empty() {
+            self.synonyms.remove(word);
+        }
+    }
+
+    /// 词元的同义词, 不包含词元本身
+    pub fn get(&self, word: &str) -> &[String] {
+        self.synonyms.get(word).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn len(&self) -> usize {
+        self.synonyms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.synonyms.is_empty()
+    }
+}
+
+fn split_words(words: &str) -> Vec<String> {
+    words
+        .split(',')
+        .map(|word| regularize_str(word.trim()))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_synonym_rules() {
+        let mut map = SynonymMap::new();
+        map.add_rule("蕃茄, 西红柿 => 番茄").unwrap();
+        map.add_rule("# 注释").unwrap();
+        map.add_rule("").unwrap();
+        map.add_rule("土豆,马铃薯,洋芋").unwrap();
+        map.add_rule("TV => 电视").unwrap();
+        assert_eq!(map.get("西红柿"), &["番茄"]);
+        assert!(map.get("番茄").is_empty());
+        assert_eq!(map.get("马铃薯"), &["土豆", "洋芋"]);
+        assert_eq!(map.get("tv"), &["电视"]);
+        assert_eq!(map.len(), 6);
+        assert!(map.add_rule("=> 番茄").is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join(format!("ik-synonym-{}.txt", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        fs::write(&path, "蕃茄,西红柿 => 番茄\n, =>\n").unwrap();
+        let error = SynonymMap::load(&path).unwrap_err();
+        assert!(
+            error.ends_with(":2: invalid synonym rule \", =>\""),
+            "{}",
+            error
+        );
+        fs::write(&path, "蕃茄,西红柿 => 番茄\n").unwrap();
+        assert_eq!(SynonymMap::load(&path).unwrap().get("蕃茄"), &["番茄"]);
+        fs::remove_file(&path).unwrap();
+    }
+}
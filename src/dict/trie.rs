@@ -1,12 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 
 use crate::dict::hit::Hit;
+use crate::dict::lint::MAX_WORD_LENGTH;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct TrieNode {
     value: Option<char>,
     final_state: bool,
+    // 词频, 仅对词尾节点有意义, 0 表示未指定
+    freq: u32,
+    // 词性标注, 仅对词尾节点有意义, 相同的词性共享同一份文本
+    pos: Option<Arc<str>>,
     child_nodes: HashMap<char, TrieNode>,
 }
 
@@ -27,6 +33,8 @@ impl TrieNode {
         TrieNode {
             value: Some(c),
             final_state,
+            freq: 0,
+            pos: None,
             child_nodes: HashMap::new(),
         }
     }
@@ -71,9 +79,37 @@ impl TrieNode {
     }
 
     pub fn insert<C: Iterator<Item = char>>(&mut self, chars: C) {
+        self.insert_with_freq(chars, 0)
+    }
+
+    // 插入词条并记录词频, 词条已存在时更新其词频
+    pub fn insert_with_freq<C: Iterator<Item = char>>(&mut self, chars: C, freq: u32) {
+        self.insert_entry(chars, freq, None)
+    }
+
+    // 插入词条并记录词频及词性, 词条已存在时更新指定了的词频、词性
+    // 超过 MAX_WORD_LENGTH 的词条被跳过, 避免超长的词条拖慢其前缀上的每次匹配
+    pub fn insert_entry<C: Iterator<Item = char>>(
+        &mut self,
+        chars: C,
+        freq: u32,
+        pos: Option<Arc<str>>,
+    ) {
         let mut current_node = self;
         let char_list: Vec<char> = chars.collect();
         let length = char_list.len();
+        if length == 0 {
+            return;
+        }
+        if length > MAX_WORD_LENGTH {
+            log::warn!(
+                "skip dictionary word of {} chars starting with {:?}, max length is {}",
+                length,
+                char_list.iter().take(8).collect::<String>(),
+                MAX_WORD_LENGTH
+            );
+            return;
+        }
 
         for c in char_list.iter().take(length - 1) {
             if !current_node.child_nodes.contains_key(c) {
@@ -82,14 +118,103 @@ impl TrieNode {
             current_node = current_node.child_nodes.get_mut(c).unwrap();
         }
         // last char in the list, this should be a final state
-        if !current_node
-            .child_nodes
-            .contains_key(&char_list[length - 1])
-        {
-            current_node.add_child(char_list[length - 1], true);
+        let last = char_list[length - 1];
+        match current_node.child_nodes.get_mut(&last) {
+            // 已是其他词的前缀(或已被屏蔽)的节点标记为词条
+            Some(node) if !node.final_state => {
+                node.final_state = true;
+                node.freq = freq;
+                node.pos = pos;
+            }
+            Some(node) => {
+                if freq > 0 {
+                    node.freq = freq;
+                }
+                if pos.is_some() {
+                    node.pos = pos;
+                }
+            }
+            None => {
+                current_node.add_child(last, true);
+                let node = current_node.child_nodes.get_mut(&last).unwrap();
+                node.freq = freq;
+                node.pos = pos;
+            }
+        }
+    }
+
+    // 深度优先遍历以当前节点为前缀的所有词条
+    fn for_each_word<F: FnMut(&str)>(&self, prefix: &mut String, f: &mut F) {
+        for (c, child) in self.child_nodes.iter() {
+            prefix.push(*c);
+            if child.final_state {
+                f(prefix);
+            }
+            child.for_each_word(prefix, f);
+            prefix.pop();
         }
     }
 
+    // 先序写出节点: 字符(u32) 是否为词尾(u8) 词频(u32) 词性长度(u8) 词性 子节点数(u32),
+    // 之后依次写出子节点
+    fn write_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(self.value.map(u32::from).unwrap_or(0)).to_le_bytes());
+        out.push(self.final_state as u8);
+        out.extend_from_slice(&self.freq.to_le_bytes());
+        let pos = self.pos.as_deref().unwrap_or("").as_bytes();
+        out.push(pos.len() as u8);
+        out.extend_from_slice(pos);
+        out.extend_from_slice(&(self.child_nodes.len() as u32).to_le_bytes());
+        for child in self.child_nodes.values() {
+            child.write_to(out);
+        }
+    }
+
+    fn read_from(
+        input: &mut &[u8],
+        is_root: bool,
+        tags: &mut HashSet<Arc<str>>,
+    ) -> Result<TrieNode, String> {
+        let value = read_u32(input)?;
+        let final_state = read_u8(input)? != 0;
+        let freq = read_u32(input)?;
+        let pos_len = read_u8(input)? as usize;
+        if input.len() < pos_len {
+            return Err(TRUNCATED.to_string());
+        }
+        let (pos, rest) = input.split_at(pos_len);
+        *input = rest;
+        let pos = match pos_len {
+            0 => None,
+            _ => {
+                let pos = std::str::from_utf8(pos).map_err(|e| format!("invalid pos: {}", e))?;
+                Some(intern_tag(tags, pos))
+            }
+        };
+        let child_count = read_u32(input)? as usize;
+        let value = if is_root {
+            None
+        } else {
+            Some(char::from_u32(value).ok_or_else(|| format!("invalid char {:#x}", value))?)
+        };
+        // 每个子节点至少 14 个字节, 避免按损坏的计数预分配
+        if child_count > input.len() / 14 {
+            return Err(TRUNCATED.to_string());
+        }
+        let mut child_nodes = HashMap::with_capacity(child_count);
+        for _ in 0..child_count {
+            let child = TrieNode::read_from(input, false, tags)?;
+            child_nodes.insert(child.value.unwrap(), child);
+        }
+        Ok(TrieNode {
+            value,
+            final_state,
+            freq,
+            pos,
+            child_nodes,
+        })
+    }
+
     pub fn match_with_offset(
         &self,
         char_list: Vec<char>,
@@ -106,9 +231,10 @@ impl TrieNode {
                 }
                 if current_node.final_state {
                     let mut hit = Hit::new();
-                    hit.begin = offset;
-                    hit.end = end;
+                    hit.set_range(offset..end + 1);
                     hit.set_match();
+                    hit.freq = current_node.freq;
+                    hit.pos = current_node.pos.clone();
                     if current_node.has_childs() {
                         hit.set_prefix();
                     }
@@ -119,10 +245,11 @@ impl TrieNode {
             }
             if current_node.value.is_some() {
                 let mut hit = Hit::new();
-                hit.begin = offset;
-                hit.end = end;
+                hit.set_range(offset..end + 1);
                 if current_node.final_state {
                     hit.set_match();
+                    hit.freq = current_node.freq;
+                    hit.pos = current_node.pos.clone();
                 }
                 if current_node.has_childs() {
                     hit.set_prefix();
@@ -134,41 +261,121 @@ impl TrieNode {
     }
 }
 
-#[derive(Debug, Default)]
+const TRUNCATED: &str = "truncated trie data";
+
+// 截断到 255 字节以内的字符边界
+fn truncate_tag(tag: &str) -> &str {
+    let mut end = tag.len().min(u8::MAX as usize);
+    while !tag.is_char_boundary(end) {
+        end -= 1;
+    }
+    &tag[..end]
+}
+
+// 取得 tags 中与 tag 相同的共享文本, 不存在时加入
+fn intern_tag(tags: &mut HashSet<Arc<str>>, tag: &str) -> Arc<str> {
+    match tags.get(tag) {
+        Some(tag) => tag.clone(),
+        None => {
+            let tag: Arc<str> = Arc::from(tag);
+            tags.insert(tag.clone());
+            tag
+        }
+    }
+}
+
+fn read_u8(input: &mut &[u8]) -> Result<u8, String> {
+    let (b, rest) = input.split_first().ok_or_else(|| TRUNCATED.to_string())?;
+    *input = rest;
+    Ok(*b)
+}
+
+fn read_u32(input: &mut &[u8]) -> Result<u32, String> {
+    if input.len() < 4 {
+        return Err(TRUNCATED.to_string());
+    }
+    let (bytes, rest) = input.split_at(4);
+    *input = rest;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct Trie {
     root: TrieNode,
+    // 已出现的词性, 供各节点共享
+    tags: HashSet<Arc<str>>,
 }
 
 impl Trie {
+    pub fn is_empty(&self) -> bool {
+        !self.root.has_childs()
+    }
+
     pub fn insert<C: Iterator<Item = char>>(&mut self, chars: C) {
         let current_node = &mut self.root;
         current_node.insert(chars)
     }
 
+    /// 插入词条并记录词频, 词频用于歧义裁决
+    pub fn insert_with_freq<C: Iterator<Item = char>>(&mut self, chars: C, freq: u32) {
+        self.root.insert_with_freq(chars, freq)
+    }
+
+    /// 插入词条并记录词频及词性, 词性不超过 255 字节, 超出的部分被截断
+    pub fn insert_with_pos<C: Iterator<Item = char>>(
+        &mut self,
+        chars: C,
+        freq: u32,
+        pos: Option<&str>,
+    ) {
+        let pos = pos
+            .map(|pos| truncate_tag(pos.trim()))
+            .filter(|pos| !pos.is_empty())
+            .map(|pos| intern_tag(&mut self.tags, pos));
+        self.root.insert_entry(chars, freq, pos)
+    }
+
     pub fn delete<C: Iterator<Item = char>>(&mut self, chars: C) -> bool {
         let current_node = &mut self.root;
         current_node.delete(chars)
     }
 
-    pub fn exist<C: Iterator<Item = char>>(&mut self, chars: C) -> bool {
-        let current_node = &mut self.root;
-        current_node.exist(chars)
+    pub fn exist<C: Iterator<Item = char>>(&self, chars: C) -> bool {
+        self.root.exist(chars)
+    }
+
+    // 遍历所有词条, 顺序不固定
+    pub fn for_each_word<F: FnMut(&str)>(&self, mut f: F) {
+        let mut prefix = String::new();
+        self.root.for_each_word(&mut prefix, &mut f);
+    }
+
+    /// 以紧凑的二进制格式写出, 用于编译词典缓存
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        self.root.write_to(out);
     }
 
-    pub fn match_word<C: Iterator<Item = char>>(&mut self, chars: C) -> Vec<Hit> {
-        let root_node = &mut self.root;
+    /// 读取 write_to 写出的数据, input 前移到数据之后
+    pub fn read_from(input: &mut &[u8]) -> Result<Trie, String> {
+        let mut tags = HashSet::new();
+        let root = TrieNode::read_from(input, true, &mut tags)?;
+        Ok(Trie { root, tags })
+    }
+
+    pub fn match_word<C: Iterator<Item = char>>(&self, chars: C) -> Vec<Hit> {
+        let root_node = &self.root;
         let char_list: Vec<char> = chars.collect();
         let length = char_list.len();
         root_node.match_with_offset(char_list, 0, length)
     }
 
     pub fn match_word_with_offset<C: Iterator<Item = char>>(
-        &mut self,
+        &self,
         chars: C,
         offset: usize,
         length: usize,
     ) -> Vec<Hit> {
-        let root_node = &mut self.root;
+        let root_node = &self.root;
         let char_list = chars.collect();
         root_node.match_with_offset(char_list, offset, length)
     }
@@ -192,6 +399,36 @@ mod test {
         assert!(trie.exist("Brown".chars()));
         assert!(trie.exist("申艳超".chars()));
         assert!(!trie.exist("申超".chars()));
+        // 先插入的长词的前缀
+        assert!(trie.exist("Back".chars()));
+        assert!(!trie.exist("Backg".chars()));
+    }
+
+    #[test]
+    fn trie_insert_prefix_of_existing_word() {
+        // 回归测试: 已是其他词前缀的节点此前不会被标记为词条,
+        // 如主词典中已有 "北京大学" 时, 扩展词典中的 "北京大" 被忽略
+        let mut trie = Trie::default();
+        trie.insert("北京大学".chars());
+        assert!(!trie.exist("北京大".chars()));
+        trie.insert_with_pos("北京大".chars(), 5, Some("ns"));
+        assert!(trie.exist("北京大".chars()));
+        assert!(trie.exist("北京大学".chars()));
+        let hit = &trie.match_word("北京大".chars())[0];
+        assert!(hit.is_match() && hit.is_prefix());
+        assert_eq!(hit.freq, 5);
+        assert_eq!(hit.pos.as_deref(), Some("ns"));
+    }
+
+    #[test]
+    fn trie_delete_insert() {
+        let mut trie = Trie::default();
+        trie.insert_with_freq("北京".chars(), 10);
+        trie.delete("北京".chars());
+        assert!(!trie.exist("北京".chars()));
+        trie.insert_with_freq("北京".chars(), 3);
+        assert!(trie.exist("北京".chars()));
+        assert_eq!(trie.match_word("北京".chars())[0].freq, 3);
     }
 
     #[test]
@@ -211,4 +448,82 @@ mod test {
             println!("{:?}", hit);
         }
     }
+
+    #[test]
+    fn trie_max_word_length() {
+        let mut trie = Trie::default();
+        trie.insert("长".repeat(MAX_WORD_LENGTH + 1).chars());
+        assert!(trie.is_empty());
+        trie.insert("长".repeat(MAX_WORD_LENGTH).chars());
+        assert!(trie.exist("长".repeat(MAX_WORD_LENGTH).chars()));
+    }
+
+    #[test]
+    fn trie_freq() {
+        let mut trie = Trie::default();
+        trie.insert_with_freq("北京".chars(), 100);
+        trie.insert_with_freq("北京大学".chars(), 20);
+        let hits = trie.match_word("北京大学".chars());
+        let freqs = hits
+            .iter()
+            .map(|hit| (hit.range(), hit.freq))
+            .collect::<Vec<_>>();
+        assert_eq!(freqs, vec![(0..2, 100), (0..4, 20)]);
+        trie.insert_with_freq("北京".chars(), 7);
+        assert_eq!(trie.match_word("北京".chars())[0].freq, 7);
+    }
+
+    #[test]
+    fn trie_pos() {
+        let mut trie = Trie::default();
+        trie.insert_with_pos("北京".chars(), 100, Some("ns"));
+        trie.insert_with_pos("上海".chars(), 0, Some("ns"));
+        trie.insert_with_pos("学习".chars(), 0, None);
+        let pos = |trie: &Trie, word: &str| trie.match_word(word.chars())[0].pos.clone();
+        assert_eq!(pos(&trie, "北京").as_deref(), Some("ns"));
+        assert!(Arc::ptr_eq(
+            &pos(&trie, "北京").unwrap(),
+            &pos(&trie, "上海").unwrap()
+        ));
+        assert_eq!(pos(&trie, "学习"), None);
+        trie.insert_with_pos("学习".chars(), 0, Some("v"));
+        assert_eq!(pos(&trie, "学习").as_deref(), Some("v"));
+        assert_eq!(truncate_tag(&"名".repeat(100)).len(), 255);
+    }
+
+    #[test]
+    fn trie_write_read() {
+        let mut trie = Trie::default();
+        trie.insert_with_pos("Background".chars(), 3, Some("n"));
+        trie.insert("申".chars());
+        trie.insert("申艳超".chars());
+        let mut bytes = Vec::new();
+        trie.write_to(&mut bytes);
+        let mut input = &bytes[..];
+        let restored = Trie::read_from(&mut input).unwrap();
+        assert!(input.is_empty());
+        assert!(restored.exist("Background".chars()));
+        assert_eq!(restored.match_word("Background".chars())[0].freq, 3);
+        assert_eq!(
+            restored.match_word("Background".chars())[0].pos.as_deref(),
+            Some("n")
+        );
+        assert!(restored.exist("申艳超".chars()));
+        assert!(restored.exist("申".chars()));
+        assert!(!restored.exist("申艳".chars()));
+        assert!(Trie::read_from(&mut &bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn trie_for_each_word() {
+        let mut trie = Trie::default();
+        trie.insert("Back".chars());
+        trie.insert("Background".chars());
+        trie.insert("申艳超".chars());
+        trie.delete("Back".chars());
+        let mut words = Vec::new();
+        trie.for_each_word(|word| words.push(word.to_string()));
+        words.sort();
+        assert_eq!(words, vec!["Background", "申艳超"]);
+    }
 }
@@ -0,0 +1,110 @@
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::RwLock;
+use std::thread::JoinHandle;
+
+use crate::dict::dictionary::{Dictionary, GLOBAL_DICT};
+
+// 每批最多应用的更新条数
+const DEFAULT_BATCH_SIZE: usize = 1024;
+
+/// 词典更新操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordOp {
+    // 添加主词典词条
+    Add(String),
+    // 屏蔽主词典词条
+    Remove(String),
+    // 添加停止词
+    AddStop(String),
+    // 移除停止词
+    RemoveStop(String),
+    // 清空停止词词典
+    ClearStop,
+}
+
+/// 从 channel 接收词典更新, 分批应用到词典, 每批应用后词典版本号加一
+/// 控制面可以通过 Sender<WordOp> 向运行中的分词进程推送词条变更
+pub struct DictionaryUpdater {
+    receiver: Receiver<WordOp>,
+    batch_size: usize,
+}
+
+impl DictionaryUpdater {
+    pub fn new(receiver: Receiver<WordOp>) -> Self {
+        DictionaryUpdater {
+            receiver,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// 阻塞等待下一批更新并应用到 dict, 返回应用后的版本号
+    /// 所有 Sender 都已关闭时返回 None
+    pub fn apply_next_batch(&self, dict: &RwLock<Dictionary>) -> Option<u64> {
+        // 阻塞等待第一条, 之后把已经到达的更新凑成一批
+        let first = self.receiver.recv().ok()?;
+        let mut ops = vec![first];
+        while ops.len() < self.batch_size {
+            match self.receiver.try_recv() {
+                Ok(op) => ops.push(op),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        log::debug!("apply {} word ops", ops.len());
+        Some(dict.write().unwrap().apply_word_ops(ops))
+    }
+
+    /// 持续应用更新, 直到所有 Sender 都已关闭
+    pub fn run(&self, dict: &RwLock<Dictionary>) {
+        while self.apply_next_batch(dict).is_some() {}
+    }
+
+    /// 在后台线程中持续将更新应用到 GLOBAL_DICT
+    pub fn spawn(self) -> JoinHandle<()> {
+        std::thread::spawn(move || self.run(&GLOBAL_DICT))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::dict::trie::Trie;
+
+    #[test]
+    fn test_updater() {
+        let dict = RwLock::new(Dictionary::with_dicts(
+            Trie::default(),
+            Trie::default(),
+            Trie::default(),
+        ));
+        let (sender, receiver) = mpsc::channel();
+        let updater = DictionaryUpdater::new(receiver).with_batch_size(2);
+        sender.send(WordOp::Add("北京大学".to_string())).unwrap();
+        sender.send(WordOp::AddStop("的".to_string())).unwrap();
+        sender.send(WordOp::Add("清华大学".to_string())).unwrap();
+        assert_eq!(updater.apply_next_batch(&dict), Some(1));
+        assert_eq!(updater.apply_next_batch(&dict), Some(2));
+        {
+            let dict = dict.read().unwrap();
+            assert!(!dict.match_in_main_dict("清华大学".chars()).is_empty());
+            assert!(dict.is_stop_word("的".chars(), 0, 1));
+        }
+        sender.send(WordOp::Remove("清华大学".to_string())).unwrap();
+        sender.send(WordOp::RemoveStop("的".to_string())).unwrap();
+        drop(sender);
+        updater.run(&dict);
+        let dict = dict.read().unwrap();
+        assert_eq!(dict.version(), 3);
+        assert!(!dict.is_stop_word("的".chars(), 0, 1));
+        assert!(dict
+            .match_in_main_dict("清华大学".chars())
+            .iter()
+            .all(|hit| !hit.is_match()));
+    }
+}
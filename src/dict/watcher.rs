@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::dict::dictionary::{reload_global_dict, GLOBAL_DICT};
+
+// 检查停止标志的间隔, 保证 stop 能及时返回
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+// 文件的修改时间和长度, 文件不存在时为 None
+type FileStamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> FileStamp {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+fn stamps(paths: &[PathBuf]) -> Vec<FileStamp> {
+    paths.iter().map(|path| stamp(path)).collect()
+}
+
+/// 轮询词典来源的后台线程, 文件修改时间或长度变化后调用回调
+/// drop 或调用 stop 时结束线程
+pub struct DictWatcher {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DictWatcher {
+    /// 每隔 interval 检查一次 paths, 有文件变化时调用 on_change
+    pub fn spawn<F>(paths: Vec<PathBuf>, interval: Duration, on_change: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        let mut last = stamps(&paths);
+        DictWatcher::poll(interval, interval, move || {
+            let current = stamps(&paths);
+            if current != last {
+                log::info!("dictionary files changed, reloading");
+                last = current;
+                on_change();
+            }
+        })
+    }
+
+    // 等待 delay 后每隔 interval 调用一次 tick, 直到 stop
+    pub(crate) fn poll<F>(delay: Duration, interval: Duration, mut tick: F) -> Self
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let handle = thread::spawn(move || {
+            let mut next_check = Instant::now() + delay;
+            while !stopped.load(Ordering::Relaxed) {
+                let now = Instant::now();
+                if now < next_check {
+                    thread::sleep((next_check - now).min(STOP_CHECK_INTERVAL));
+                    continue;
+                }
+                next_check = now + interval;
+                tick();
+            }
+        });
+        DictWatcher {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// 结束轮询线程并等待其退出
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for DictWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// 监视 GLOBAL_DICT 配置中的主词典、扩展词典、停止词词典与量词词典文件,
+/// 文件变化后调用 reload_global_dict, 结果通过 set_reload_callback 设置的回调获取
+pub fn watch_global_dict(interval: Duration) -> DictWatcher {
+    let paths = GLOBAL_DICT.read().unwrap().sources();
+    DictWatcher::spawn(paths, interval, || {
+        let report = reload_global_dict();
+        if !report.is_ok() {
+            log::error!("reload dictionaries failed: {:?}", report.errors);
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn test_watcher() {
+        let path = std::env::temp_dir().join(format!("ik-watcher-{}.dic", std::process::id()));
+        fs::write(&path, "北京\n").unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let watcher =
+            DictWatcher::spawn(vec![path.clone()], Duration::from_millis(10), move || {
+                let _ = sender.send(());
+            });
+        assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+        fs::write(&path, "北京\n上海\n").unwrap();
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+        watcher.stop();
+        fs::remove_file(&path).unwrap();
+    }
+}
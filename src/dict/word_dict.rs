@@ -0,0 +1,22 @@
+use crate::dict::hit::Hit;
+use crate::dict::trie::Trie;
+
+/// 只读的主词典匹配接口, Trie 及 FstDict(fst feature) 均实现了它
+/// 通过 Dictionary::with_base_dict 接入, CJKSegmenter 等子分词器经由 Dictionary 查询
+pub trait WordDict: Send + Sync {
+    /// 从 chars[offset] 开始匹配至多 length 个字符, 语义同 Trie::match_word_with_offset
+    fn match_chars(&self, chars: &[char], offset: usize, length: usize) -> Vec<Hit>;
+
+    /// 是否包含词条 word
+    fn contains(&self, word: &str) -> bool;
+}
+
+impl WordDict for Trie {
+    fn match_chars(&self, chars: &[char], offset: usize, length: usize) -> Vec<Hit> {
+        self.match_word_with_offset(chars.iter().copied(), offset, length)
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.exist(word.chars())
+    }
+}
@@ -0,0 +1,187 @@
+use std::fs;
+use std::path::Path;
+
+/// 标准切分语料的格式, 均为 UTF-8 编码, 每行一句
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorpusFormat {
+    // SIGHAN bakeoff 的 PKU/MSR 语料, 词之间以空白分隔, 如 共同  创造  美好  的  新  世纪
+    Segmented,
+    // 北大人民日报标注语料, 词后带有词性, 行首为句子编号, 方括号标出复合词, 如
+    // 19980101-01-001-002/m  [中央/n  人民/n  广播/vn  电台/n]nt  播出/v
+    // 复合词按内部的词切分
+    Tagged,
+}
+
+/// 一句标准切分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldSentence {
+    pub words: Vec<String>,
+}
+
+impl GoldSentence {
+    /// 按 format 解析一行, 空行返回 None
+    pub fn parse(line: &str, format: CorpusFormat) -> Option<GoldSentence> {
+        let line = line.trim_start_matches('\u{feff}');
+        let words = match format {
+            CorpusFormat::Segmented => line.split_whitespace().map(String::from).collect(),
+            CorpusFormat::Tagged => line
+                .split_whitespace()
+                .filter(|item| !is_sentence_id(item))
+                .filter_map(strip_tag)
+                .collect::<Vec<_>>(),
+        };
+        if words.is_empty() {
+            None
+        } else {
+            Some(GoldSentence { words })
+        }
+    }
+
+    /// 原文, 即各词的拼接
+    pub fn text(&self) -> String {
+        self.words.concat()
+    }
+
+    /// 转为回归测试的 golden 文件格式: 原文与空格分隔的期望词元, 以制表符分隔
+    pub fn to_fixture_line(&self) -> String {
+        format!("{}\t{}", self.text(), self.words.join(" "))
+    }
+
+    /// 解析 golden 文件的一行, 期望词元的拼接须与原文相同
+    pub fn from_fixture_line(line: &str) -> Result<GoldSentence, String> {
+        let (text, words) = line
+            .split_once('\t')
+            .ok_or_else(|| format!("missing tab in fixture {:?}", line))?;
+        let sentence = GoldSentence {
+            words: words
+                .split(' ')
+                .filter(|w| !w.is_empty())
+                .map(String::from)
+                .collect(),
+        };
+        if sentence.text() != text {
+            return Err(format!(
+                "words do not add up to the text in fixture {:?}",
+                line
+            ));
+        }
+        Ok(sentence)
+    }
+}
+
+/// 读取标准切分语料, 跳过空行
+pub fn load_corpus<P: AsRef<Path>>(
+    path: P,
+    format: CorpusFormat,
+) -> Result<Vec<GoldSentence>, String> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("read corpus {} error: {}", path.display(), e))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| GoldSentence::parse(line, format))
+        .collect())
+}
+
+/// 读取 golden 文件, 跳过空行及 # 开头的注释行, 错误信息中带有行号
+pub fn load_fixtures<P: AsRef<Path>>(path: P) -> Result<Vec<GoldSentence>, String> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("read fixtures {} error: {}", path.display(), e))?;
+    content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            GoldSentence::from_fixture_line(line)
+                .map_err(|e| format!("{}:{}: {}", path.display(), index + 1, e))
+        })
+        .collect()
+}
+
+/// 将语料转为 golden 文件的内容
+pub fn to_fixtures(sentences: &[GoldSentence]) -> String {
+    sentences
+        .iter()
+        .map(|sentence| sentence.to_fixture_line() + "\n")
+        .collect()
+}
+
+// 句子编号, 如 19980101-01-001-002/m
+fn is_sentence_id(item: &str) -> bool {
+    match item.split_once('/') {
+        Some((id, _)) => id.contains('-') && id.chars().all(|c| c.is_ascii_digit() || c == '-'),
+        None => false,
+    }
+}
+
+// 去掉词性及复合词的方括号, 如 [中央/n -> 中央, 电台/n]nt -> 电台
+fn strip_tag(item: &str) -> Option<String> {
+    let item = item.strip_prefix('[').unwrap_or(item);
+    let word = match item.rfind('/') {
+        Some(i) if i > 0 => &item[..i],
+        _ => item,
+    };
+    if word.is_empty() {
+        None
+    } else {
+        Some(word.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn words(sentence: Option<GoldSentence>) -> Vec<String> {
+        sentence.map(|s| s.words).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            words(GoldSentence::parse(
+                "共同  创造  美好  的  新  世纪  ——",
+                CorpusFormat::Segmented
+            )),
+            vec!["共同", "创造", "美好", "的", "新", "世纪", "——"]
+        );
+        assert_eq!(
+            words(GoldSentence::parse(
+                "19980101-01-001-002/m  [中央/n  人民/n  广播/vn  电台/n]nt  播出/v  １/m  张/q  //w",
+                CorpusFormat::Tagged
+            )),
+            vec!["中央", "人民", "广播", "电台", "播出", "１", "张", "/"]
+        );
+        assert!(GoldSentence::parse("  ", CorpusFormat::Segmented).is_none());
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let sentence = GoldSentence::parse("南京市  长江大桥", CorpusFormat::Segmented).unwrap();
+        assert_eq!(
+            sentence.to_fixture_line(),
+            "南京市长江大桥\t南京市 长江大桥"
+        );
+        assert_eq!(
+            GoldSentence::from_fixture_line("南京市长江大桥\t南京市 长江大桥").unwrap(),
+            sentence
+        );
+        assert!(GoldSentence::from_fixture_line("南京市长江大桥\t南京 长江大桥").is_err());
+        assert!(GoldSentence::from_fixture_line("南京市长江大桥").is_err());
+
+        let path = std::env::temp_dir().join(format!("ik-fixtures-{}.tsv", std::process::id()));
+        let fixtures = to_fixtures(std::slice::from_ref(&sentence));
+        fs::write(&path, fixtures.clone() + "# 注释\n坏行\n").unwrap();
+        let error = load_fixtures(&path).unwrap_err();
+        assert!(
+            error.ends_with(":3: missing tab in fixture \"坏行\""),
+            "{}",
+            error
+        );
+        fs::write(&path, fixtures).unwrap();
+        assert_eq!(load_fixtures(&path).unwrap(), vec![sentence]);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::ops::AddAssign;
+
+use crate::core::char_util::{char_type_of, CharType};
+use crate::core::ik_segmenter::{IKSegmenter, TokenMode, TokenizeOptions};
+use crate::eval::corpus::GoldSentence;
+
+pub mod corpus;
+
+/// 分词评测的统计, 以词为单位
+/// 一个词切分正确, 当且仅当其在原文中的起止位置与标准切分中的某个词相同
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvalReport {
+    // 参与评测的句子数
+    pub sentences: usize,
+    // 标准切分的词数
+    pub gold_words: usize,
+    // 分词器输出的词数
+    pub predicted_words: usize,
+    // 切分正确的词数
+    pub correct_words: usize,
+}
+
+impl EvalReport {
+    pub fn precision(&self) -> f64 {
+        ratio(self.correct_words, self.predicted_words)
+    }
+
+    pub fn recall(&self) -> f64 {
+        ratio(self.correct_words, self.gold_words)
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (p, r) = (self.precision(), self.recall());
+        if p + r == 0.0 {
+            0.0
+        } else {
+            2.0 * p * r / (p + r)
+        }
+    }
+}
+
+impl AddAssign for EvalReport {
+    fn add_assign(&mut self, other: EvalReport) {
+        self.sentences += other.sentences;
+        self.gold_words += other.gold_words;
+        self.predicted_words += other.predicted_words;
+        self.correct_words += other.correct_words;
+    }
+}
+
+impl Display for EvalReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "sentences: {}, gold: {}, predicted: {}, correct: {}, P: {:.4}, R: {:.4}, F1: {:.4}",
+            self.sentences,
+            self.gold_words,
+            self.predicted_words,
+            self.correct_words,
+            self.precision(),
+            self.recall(),
+            self.f1()
+        )
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// 用 SEARCH 模式对标准切分语料评测, 每行一句, 词之间以空白分隔(SIGHAN bakeoff 格式)
+/// 保留停止词; 标点等 IK 不输出的词不计入双方的词数
+pub fn evaluate<S: AsRef<str>>(segmenter: &mut IKSegmenter, gold: &[S]) -> EvalReport {
+    let mut report = EvalReport::default();
+    for line in gold {
+        let words = line.as_ref().split_whitespace().collect::<Vec<_>>();
+        if !words.is_empty() {
+            report += evaluate_sentence(segmenter, &words);
+        }
+    }
+    report
+}
+
+/// 对 corpus::load_corpus 或 corpus::load_fixtures 读取的语料评测
+pub fn evaluate_corpus(segmenter: &mut IKSegmenter, sentences: &[GoldSentence]) -> EvalReport {
+    let mut report = EvalReport::default();
+    for sentence in sentences {
+        let words = sentence
+            .words
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        report += evaluate_sentence(segmenter, &words);
+    }
+    report
+}
+
+/// 评测一句标准切分, 原文为各词的拼接
+pub fn evaluate_sentence(segmenter: &mut IKSegmenter, words: &[&str]) -> EvalReport {
+    let text = words.concat();
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut gold = HashSet::new();
+    let mut begin = 0;
+    for word in words {
+        let end = begin + word.chars().count();
+        if word.chars().any(|c| is_tokenizable_char(&c)) {
+            gold.insert(begin..end);
+        }
+        begin = end;
+    }
+    let options = TokenizeOptions {
+        keep_stop_words: true,
+        ..TokenizeOptions::new(TokenMode::SEARCH)
+    };
+    let predicted = segmenter
+        .tokenize_with(&text, options)
+        .iter()
+        .map(|l| l.get_begin()..l.get_end_position())
+        .filter(|span| chars[span.clone()].iter().any(is_tokenizable_char))
+        .collect::<HashSet<_>>();
+    EvalReport {
+        sentences: 1,
+        gold_words: gold.len(),
+        predicted_words: predicted.len(),
+        correct_words: gold.intersection(&predicted).count(),
+    }
+}
+
+// IK 可以切分的字符, 不含标点、空白等
+fn is_tokenizable_char(c: &char) -> bool {
+    char_type_of(c) != CharType::USELESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let report = EvalReport {
+            sentences: 1,
+            gold_words: 4,
+            predicted_words: 5,
+            correct_words: 3,
+        };
+        assert_eq!(report.precision(), 0.6);
+        assert_eq!(report.recall(), 0.75);
+        assert!((report.f1() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(EvalReport::default().f1(), 0.0);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut ik = IKSegmenter::new();
+        // 当前输出为 他 说 的确 实 在理, 标点不计入
+        let gold = ["他  说  的  确实  在理  。", "", "南京市  长江大桥"];
+        let report = evaluate(&mut ik, &gold);
+        assert_eq!(
+            report,
+            EvalReport {
+                sentences: 2,
+                gold_words: 7,
+                predicted_words: 7,
+                correct_words: 5,
+            }
+        );
+        let sentences = gold
+            .iter()
+            .filter_map(|line| GoldSentence::parse(line, corpus::CorpusFormat::Segmented))
+            .collect::<Vec<_>>();
+        assert_eq!(evaluate_corpus(&mut ik, &sentences), report);
+    }
+}
@@ -0,0 +1,201 @@
+//! C ABI 接口, 对应的头文件为 include/ik.h
+//! 编译动态库: cargo rustc --release --no-default-features --features ffi --crate-type cdylib
+use std::os::raw::c_int;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::core::ik_segmenter::TokenMode;
+use crate::core::ik_token::{fill_tokens, IkToken};
+use crate::with_thread_segmenter;
+
+/// 与 ik.h 中的 IK_MODE_INDEX/IK_MODE_SEARCH 对应
+pub const IK_MODE_INDEX: c_int = 0;
+pub const IK_MODE_SEARCH: c_int = 1;
+
+/// ik_tokenize 返回的词元数组, 需通过 ik_token_array_free 释放
+/// 出错(如文本不是合法的 UTF-8、mode 未知)时 tokens 为 NULL
+#[repr(C)]
+#[derive(Debug)]
+pub struct IkTokenArray {
+    pub tokens: *mut IkToken,
+    pub len: usize,
+}
+
+impl IkTokenArray {
+    fn empty() -> Self {
+        IkTokenArray {
+            tokens: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+/// 对 text 开始的 len 个字节(UTF-8, 无需以 0 结尾)分词
+/// 与服务、CLI 相同, 全角字符先转为半角再分词, 偏移对应原文, 见 IKSegmenter::tokenize_halfwidth
+///
+/// # Safety
+/// text 必须指向至少 len 个可读字节
+#[no_mangle]
+pub unsafe extern "C" fn ik_tokenize(text: *const u8, len: usize, mode: c_int) -> IkTokenArray {
+    let (text, mode) = match parse_args(text, len, mode) {
+        Some(args) => args,
+        None => return IkTokenArray::empty(),
+    };
+    let result = catch_unwind(|| {
+        let lexemes = with_thread_segmenter(|ik| ik.tokenize_halfwidth(text, mode));
+        let mut tokens = vec![IkToken::default(); lexemes.len()];
+        fill_tokens(&lexemes, &mut tokens);
+        tokens.into_boxed_slice()
+    });
+    match result {
+        Ok(tokens) => {
+            let len = tokens.len();
+            IkTokenArray {
+                tokens: Box::into_raw(tokens) as *mut IkToken,
+                len,
+            }
+        }
+        Err(_) => IkTokenArray::empty(),
+    }
+}
+
+/// 释放 ik_tokenize 返回的数组, tokens 为 NULL 时不做任何事
+///
+/// # Safety
+/// array 必须由 ik_tokenize 返回, 且只能释放一次
+#[no_mangle]
+pub unsafe extern "C" fn ik_token_array_free(array: IkTokenArray) {
+    if !array.tokens.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            array.tokens,
+            array.len,
+        )));
+    }
+}
+
+/// 与 ik_tokenize 相同, 但写入调用方提供的 buf(容量为 cap 个词元), 不分配内存
+/// 返回词元总数, 大于 cap 时只写入前 cap 个, 可扩大 buf 后重试; 出错时返回 -1
+///
+/// # Safety
+/// text 必须指向至少 len 个可读字节, buf 必须指向至少 cap 个可写的 IkToken(cap 为 0 时可为 NULL)
+#[no_mangle]
+pub unsafe extern "C" fn ik_tokenize_into(
+    text: *const u8,
+    len: usize,
+    mode: c_int,
+    buf: *mut IkToken,
+    cap: usize,
+) -> isize {
+    let (text, mode) = match parse_args(text, len, mode) {
+        Some(args) => args,
+        None => return -1,
+    };
+    let buf = if cap == 0 || buf.is_null() {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(buf, cap)
+    };
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        with_thread_segmenter(|ik| ik.tokenize_to_buffer(text, mode, buf))
+    }));
+    match result {
+        Ok(count) => count as isize,
+        Err(_) => -1,
+    }
+}
+
+unsafe fn parse_args<'a>(text: *const u8, len: usize, mode: c_int) -> Option<(&'a str, TokenMode)> {
+    let mode = match mode {
+        IK_MODE_INDEX => TokenMode::INDEX,
+        IK_MODE_SEARCH => TokenMode::SEARCH,
+        _ => return None,
+    };
+    if len == 0 {
+        return Some(("", mode));
+    }
+    if text.is_null() {
+        return None;
+    }
+    let bytes = std::slice::from_raw_parts(text, len);
+    std::str::from_utf8(bytes).ok().map(|text| (text, mode))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::lexeme::LexemeType;
+
+    #[test]
+    fn test_ik_tokenize() {
+        let text = "iPhone中华人民共和国";
+        unsafe {
+            let array = ik_tokenize(text.as_ptr(), text.len(), IK_MODE_SEARCH);
+            assert!(!array.tokens.is_null());
+            let tokens = std::slice::from_raw_parts(array.tokens, array.len);
+            let words = tokens
+                .iter()
+                .map(|t| &text[t.begin_byte as usize..t.end_byte as usize])
+                .collect::<Vec<_>>();
+            assert_eq!(words, vec!["iPhone", "中华人民共和国"]);
+            assert_eq!(tokens[1].type_id, LexemeType::CNWORD.id());
+            assert_eq!(tokens[1].position, 1);
+            ik_token_array_free(array);
+
+            let invalid = [0xffu8, 0xfe];
+            let array = ik_tokenize(invalid.as_ptr(), invalid.len(), IK_MODE_SEARCH);
+            assert!(array.tokens.is_null());
+            ik_token_array_free(array);
+            assert!(ik_tokenize(text.as_ptr(), text.len(), 7).tokens.is_null());
+
+            // 全角字符按半角切分, 偏移对应原文
+            let text = "ＡＢＣ１２３中华人民共和国";
+            let array = ik_tokenize(text.as_ptr(), text.len(), IK_MODE_SEARCH);
+            let tokens = std::slice::from_raw_parts(array.tokens, array.len);
+            let words = tokens
+                .iter()
+                .map(|t| &text[t.begin_byte as usize..t.end_byte as usize])
+                .collect::<Vec<_>>();
+            assert_eq!(words, vec!["ＡＢＣ１２３", "中华人民共和国"]);
+            let mut buf = [IkToken::default(); 2];
+            let count = ik_tokenize_into(
+                text.as_ptr(),
+                text.len(),
+                IK_MODE_SEARCH,
+                buf.as_mut_ptr(),
+                2,
+            );
+            assert_eq!(count, 2);
+            assert_eq!(&buf[..], tokens);
+            ik_token_array_free(array);
+        }
+    }
+
+    #[test]
+    fn test_ik_tokenize_into() {
+        let text = "iPhone中华人民共和国";
+        let mut buf = [IkToken::default(); 1];
+        unsafe {
+            let count = ik_tokenize_into(
+                text.as_ptr(),
+                text.len(),
+                IK_MODE_SEARCH,
+                buf.as_mut_ptr(),
+                buf.len(),
+            );
+            assert_eq!(count, 2);
+            assert_eq!(buf[0].end_byte, 6);
+            let count = ik_tokenize_into(
+                text.as_ptr(),
+                text.len(),
+                IK_MODE_SEARCH,
+                ptr::null_mut(),
+                0,
+            );
+            assert_eq!(count, 2);
+            assert_eq!(
+                ik_tokenize_into(ptr::null(), 0, IK_MODE_INDEX, ptr::null_mut(), 0),
+                0
+            );
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use tantivy::tokenizer::TokenStream;
+
+use crate::core::ik_segmenter::TokenMode;
+use crate::dict::reload::fnv1a;
+use crate::IkTokenizer;
+
+/// 对 IkTokenizer 输出的词元序列(文本、位置、偏移)计算稳定的哈希值
+/// 跨平台、跨版本稳定, 索引维护任务可以据此判断已存储的文档是否需要重新分词
+pub fn fingerprint(text: &str, mode: TokenMode) -> u64 {
+    let mut stream = IkTokenizer::new(mode).ik_token_stream(text);
+    let mut bytes = Vec::new();
+    while stream.advance() {
+        let token = stream.token();
+        for n in [
+            token.position,
+            token.position_length,
+            token.offset_from,
+            token.offset_to,
+        ] {
+            bytes.extend_from_slice(&(n as u64).to_le_bytes());
+        }
+        bytes.extend_from_slice(&(token.text.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(token.text.as_bytes());
+    }
+    fnv1a(&bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        let text = "张华考上了北京大学";
+        let a = fingerprint(text, TokenMode::SEARCH);
+        assert_eq!(a, fingerprint(text, TokenMode::SEARCH));
+        assert_ne!(a, fingerprint(text, TokenMode::INDEX));
+        assert_ne!(a, fingerprint("张华考上了清华大学", TokenMode::SEARCH));
+        // 不产生词元的文本
+        assert_eq!(fingerprint("", TokenMode::SEARCH), fnv1a(b""));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::config::configuration::config_error;
+use crate::core::ik_segmenter::TokenMode;
+use crate::dict::dictionary::{LoadState, DICT_LOAD_STATE, GLOBAL_DICT};
+use crate::with_thread_segmenter;
+
+/// 全局分词器的状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IkStatus {
+    // 尚未开始加载词典
+    Uninitialized,
+    // 正在加载词典
+    Loading,
+    // 词典加载完成
+    Ready { dict_version: u64 },
+    // 配置无效(此时使用内置默认配置), 或词典加载、重新加载失败
+    Degraded { error: String },
+}
+
+/// 查询全局分词器的状态, 不会触发词典加载, 也不会因加载中而阻塞
+/// 嵌入分词器的服务可以据此决定是否接收流量
+pub fn status() -> IkStatus {
+    if let Some(error) = config_error() {
+        return IkStatus::Degraded {
+            error: format!("invalid configuration: {}", error),
+        };
+    }
+    let state = DICT_LOAD_STATE.lock().unwrap().clone();
+    match state {
+        LoadState::Uninitialized => IkStatus::Uninitialized,
+        LoadState::Loading => IkStatus::Loading,
+        LoadState::Ready => IkStatus::Ready {
+            dict_version: GLOBAL_DICT.read().unwrap().version(),
+        },
+        LoadState::Degraded(error) => IkStatus::Degraded { error },
+    }
+}
+
+// 自检使用的内置语料
+const SELF_CHECK_CORPUS: [&str; 5] = [
+    "张华考上了北京大学；李萍进了中等技术学校",
+    "中华人民共和国",
+    "zhiyi.shen@gmail.com",
+    "我感觉很happy,并且不悲伤!",
+    "一二三四五六七八九十",
+];
+
+/// 自检报告
+#[derive(Debug, Clone, Default)]
+pub struct SelfCheckReport {
+    // 词典是否已加载
+    pub dict_loaded: bool,
+    // 已检查的语料条数(每种模式各算一次)
+    pub samples_checked: usize,
+    // 输出的词元总数
+    pub tokens_checked: usize,
+    // 发现的问题
+    pub errors: Vec<String>,
+}
+
+impl SelfCheckReport {
+    pub fn is_ok(&self) -> bool {
+        self.dict_loaded && self.errors.is_empty()
+    }
+}
+
+/// 部署自检: 检查词典是否加载, 并用内置语料跑一遍两种分词模式, 校验输出的偏移
+/// 可在推送词典后由健康检查接口调用
+pub fn self_check() -> SelfCheckReport {
+    let mut report = SelfCheckReport {
+        dict_loaded: GLOBAL_DICT.read().unwrap().is_loaded(),
+        ..Default::default()
+    };
+    if !report.dict_loaded {
+        report
+            .errors
+            .push("main or quantifier dictionary is empty".to_string());
+    }
+    for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+        for text in SELF_CHECK_CORPUS {
+            let records = with_thread_segmenter(|ik| ik.tokenize_records(text, mode));
+            if records.is_empty() {
+                report
+                    .errors
+                    .push(format!("{:?}: no tokens for {:?}", mode, text));
+            }
+            let char_count = text.chars().count();
+            for record in records.iter() {
+                let offsets = &record.offsets;
+                if offsets.chars.start >= offsets.chars.end
+                    || offsets.chars.end > char_count
+                    || offsets.bytes.end > text.len()
+                    || text.get(offsets.bytes.clone()) != Some(record.text.as_str())
+                {
+                    report.errors.push(format!(
+                        "{:?}: bad offsets {:?} for token {:?} in {:?}",
+                        mode, offsets, record.text, text
+                    ));
+                }
+            }
+            report.samples_checked += 1;
+            report.tokens_checked += records.len();
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        Lazy::force(&GLOBAL_DICT);
+        assert!(matches!(status(), IkStatus::Ready { .. }));
+    }
+
+    #[test]
+    fn test_self_check() {
+        let report = self_check();
+        assert!(report.is_ok(), "{:?}", report);
+        assert_eq!(report.samples_checked, SELF_CHECK_CORPUS.len() * 2);
+        assert!(report.tokens_checked > 0);
+    }
+}
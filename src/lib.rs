@@ -1,86 +1,233 @@
+#[cfg(feature = "tantivy")]
+mod analyzer;
+mod compare;
+pub mod compat;
 #[allow(dead_code)]
 pub mod config;
 #[allow(dead_code)]
 pub mod core;
+#[cfg(feature = "tantivy")]
+mod df_filter;
 #[allow(dead_code)]
 pub mod dict;
+pub mod eval;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "tantivy")]
+mod fingerprint;
+mod health;
+mod pinyin;
+#[cfg(feature = "server")]
+mod server;
+#[cfg(all(unix, feature = "signal-reload"))]
+pub mod signal;
+#[cfg(feature = "tantivy")]
+mod tantivy_adapter;
+#[cfg(feature = "tantivy")]
+mod tokenizer;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "worker")]
+mod worker;
 
-use std::sync::Mutex;
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
 
 use once_cell::sync::Lazy;
-use tantivy::tokenizer::{BoxTokenStream, Token, TokenStream, Tokenizer};
 
-use crate::core::char_util::regularize_str;
-use crate::core::ik_segmenter::{IKSegmenter, TokenMode};
+#[cfg(feature = "tantivy")]
+pub use crate::analyzer::{IkAnalyzer, IkAnalyzerBuilder};
+pub use crate::compare::{
+    compare, coverage_holes, fallback_tokenize, CompareConfig, CoverageHole, TokenDiff,
+};
+use crate::config::configuration::{init_global_config, Configuration};
+pub use crate::core::char_util::{detect_script, detect_script_with, Script};
+use crate::core::ik_segmenter::IKSegmenter;
+#[cfg(feature = "tantivy")]
+pub use crate::df_filter::{DfPruningFilter, DfTable};
+#[cfg(feature = "tantivy")]
+pub use crate::fingerprint::fingerprint;
+pub use crate::health::{self_check, status, IkStatus, SelfCheckReport};
+#[cfg(feature = "tantivy")]
+pub use crate::pinyin::PinyinAbbrFilter;
+pub use crate::pinyin::{segment_pinyin, PinyinIndex, PinyinTable};
+#[cfg(feature = "server")]
+pub use crate::server::{analyze, AnalyzeServer};
+#[cfg(feature = "tantivy")]
+pub use crate::tantivy_adapter::{register_ik_tokenizers, IK_MAX, IK_SMART};
+#[cfg(feature = "tantivy")]
+pub use crate::tokenizer::{IkTokenStream, IkTokenizer};
+#[cfg(feature = "worker")]
+pub use crate::worker::{Worker, WorkerStats};
 
+/// 全局共享的分词器, 所有调用方串行使用
+/// IkTokenizer 不再使用它, 而是使用 with_thread_segmenter 提供的线程独立分词器
 pub static GLOBAL_IK: Lazy<Mutex<IKSegmenter>> = Lazy::new(|| {
     let ik = IKSegmenter::new();
     Mutex::new(ik)
 });
 
-#[derive(Debug, Clone)]
-pub struct IkTokenizer {
-    mode: TokenMode,
+thread_local! {
+    // 每个线程独立的分词器, 共享 GLOBAL_DICT 的读锁, 线程之间不再争用分词器的锁
+    static THREAD_IK: RefCell<IKSegmenter> = RefCell::new(IKSegmenter::new());
 }
 
-#[derive(Debug, Clone)]
-pub struct IkTokenStream {
-    tokens: Vec<Token>,
-    index: usize,
+/// 用本线程独立的分词器执行 f, 分词器在线程内首次使用时创建
+/// f 中再次调用 with_thread_segmenter 时, 内层使用临时创建的分词器
+pub fn with_thread_segmenter<R>(f: impl FnOnce(&mut IKSegmenter) -> R) -> R {
+    THREAD_IK.with(|ik| match ik.try_borrow_mut() {
+        Ok(mut ik) => f(&mut ik),
+        Err(_) => f(&mut IKSegmenter::new()),
+    })
 }
 
-impl TokenStream for IkTokenStream {
-    fn advance(&mut self) -> bool {
-        if self.index < self.tokens.len() {
-            self.index += 1;
-            true
-        } else {
-            false
+/// 指定 GLOBAL_DICT/GLOBAL_IK 所使用的配置: 词典路径、dict_source(词典文件或内置词典)、
+/// keep_stop_words(是否保留停止词)及各子分词器选项
+/// 必须在首次分词前调用, 否则返回错误; 构造 Dictionary::default() 不会确定全局配置
+/// 使用 ik.yml 及 IK_* 环境变量并在配置有误时报错:
+/// `init_with_config(load_default_config()?)`, 见 config::configuration::load_default_config
+pub fn init_with_config(cfg: impl Configuration + 'static) -> Result<(), String> {
+    init_global_config(Arc::new(cfg))
+}
+
+#[cfg(all(test, feature = "tantivy"))]
+mod tests {
+    use crate::core::ik_segmenter::TokenMode;
+
+    #[test]
+    fn test_synonyms() {
+        use crate::dict::synonym::SynonymMap;
+        use std::sync::Arc;
+        use tantivy::tokenizer::Tokenizer;
+
+        let mut synonyms = SynonymMap::new();
+        synonyms.add_rule("蕃茄,西红柿 => 番茄").unwrap();
+        let tokenizer =
+            crate::IkTokenizer::new(TokenMode::SEARCH).with_synonyms(Arc::new(synonyms));
+        let mut stream = tokenizer.token_stream("我爱吃西红柿");
+        let mut tokens = Vec::new();
+        while let Some(token) = stream.next() {
+            tokens.push((
+                token.text.clone(),
+                token.position,
+                token.offset_from,
+                token.position_length,
+            ));
         }
+        let tail = &tokens[tokens.len() - 2..];
+        assert_eq!(
+            tail,
+            &[
+                ("西红柿".to_string(), 3, 9, 3),
+                ("番茄".to_string(), 3, 9, 3)
+            ]
+        );
     }
 
-    fn token(&self) -> &Token {
-        &self.tokens[self.index - 1]
-    }
+    #[test]
+    fn test_register_ik_tokenizers() {
+        use tantivy::tokenizer::TokenizerManager;
 
-    fn token_mut(&mut self) -> &mut Token {
-        &mut self.tokens[self.index - 1]
+        let manager = TokenizerManager::default();
+        crate::register_ik_tokenizers(&manager);
+        let texts = |name: &str| {
+            let mut stream = manager.get(name).unwrap().token_stream("中华人民共和国");
+            let mut texts = Vec::new();
+            while let Some(token) = stream.next() {
+                texts.push(token.text.clone());
+            }
+            texts
+        };
+        assert_eq!(texts(crate::IK_SMART), vec!["中华人民共和国"]);
+        let max = texts(crate::IK_MAX);
+        assert!(max.len() > 1 && max.contains(&"中华人民共和国".to_string()));
     }
-}
 
-impl IkTokenizer {
-    pub fn new(mode: TokenMode) -> Self {
-        Self { mode }
-    }
-}
+    #[test]
+    fn test_concurrent_tokenize() {
+        use crate::dict::dictionary::GLOBAL_DICT;
+        use std::thread;
 
-impl Tokenizer for IkTokenizer {
-    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
-        let regular_str = regularize_str(text);
-        let text = regular_str.as_str();
-        let mut indices = text.char_indices().collect::<Vec<_>>();
-        indices.push((text.len(), '\0'));
-        let orig_tokens = GLOBAL_IK.lock().unwrap().tokenize(text, self.mode);
-        let mut tokens = Vec::new();
-        for token in orig_tokens.iter() {
-            tokens.push(Token {
-                offset_from: indices[token.get_begin_position()].0,
-                offset_to: indices[token.get_end_position()].0,
-                position: token.get_begin(),
-                text: String::from(
-                    &text[(indices[token.get_begin_position()].0)
-                        ..(indices[token.get_end_position()].0)],
-                ),
-                position_length: token.get_length(),
-            });
+        let handles = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    let tokenizer = crate::IkTokenizer::new(TokenMode::INDEX);
+                    for _ in 0..50 {
+                        let mut stream = tokenizer.ik_token_stream("张华考上了北京大学");
+                        let mut texts = Vec::new();
+                        while tantivy::tokenizer::TokenStream::advance(&mut stream) {
+                            texts
+                                .push(tantivy::tokenizer::TokenStream::token(&stream).text.clone());
+                        }
+                        assert!(texts.contains(&"北京大学".to_string()));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        // 分词期间写入词典
+        GLOBAL_DICT.write().unwrap().add_words(vec!["考上"]);
+        for handle in handles {
+            handle.join().unwrap();
         }
-        BoxTokenStream::from(IkTokenStream { tokens, index: 0 })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::TokenMode;
+    #[test]
+    fn test_with_configuration() {
+        use crate::config::configuration::Configuration;
+        use crate::config::default_config::DefaultConfig;
+        use std::path::PathBuf;
+        use std::sync::Arc;
+
+        // 在代码中提供的配置, 只使用扩展词典中的词
+        struct CodeConfig {
+            ext_dict: PathBuf,
+        }
+        impl Configuration for CodeConfig {
+            fn get_main_dictionary(&self) -> PathBuf {
+                DefaultConfig::default().get_main_dictionary()
+            }
+            fn get_quantifier_dictionary(&self) -> PathBuf {
+                DefaultConfig::default().get_quantifier_dictionary()
+            }
+            fn get_ext_dictionaries(&self) -> Vec<PathBuf> {
+                vec![self.ext_dict.clone()]
+            }
+            fn get_ext_stop_word_dictionaries(&self) -> Vec<PathBuf> {
+                Vec::new()
+            }
+            fn get_default_mode(&self) -> TokenMode {
+                TokenMode::SEARCH
+            }
+        }
+
+        let ext_dict =
+            std::env::temp_dir().join(format!("ik-code-config-{}.dic", std::process::id()));
+        std::fs::write(&ext_dict, "张华考上\n").unwrap();
+        let tokenizer = crate::IkTokenizer::with_configuration(Arc::new(CodeConfig {
+            ext_dict: ext_dict.clone(),
+        }))
+        .unwrap();
+        std::fs::remove_file(&ext_dict).unwrap();
+        let mut stream = tokenizer.ik_token_stream("张华考上了北京大学");
+        let mut texts = Vec::new();
+        while tantivy::tokenizer::TokenStream::advance(&mut stream) {
+            texts.push(tantivy::tokenizer::TokenStream::token(&stream).text.clone());
+        }
+        assert_eq!(texts, vec!["张华考上", "了", "北京大学"]);
+        // 全局词典不受影响
+        let global = crate::IkTokenizer::new(TokenMode::SEARCH);
+        let mut stream = global.ik_token_stream("张华考上了北京大学");
+        assert!(tantivy::tokenizer::TokenStream::advance(&mut stream));
+        assert_ne!(
+            tantivy::tokenizer::TokenStream::token(&stream).text,
+            "张华考上"
+        );
+
+        let missing = CodeConfig {
+            ext_dict: PathBuf::from("no/such/ext.dic"),
+        };
+        assert!(crate::IkTokenizer::with_configuration(Arc::new(missing)).is_err());
+    }
 
     fn test_once(text: &str, mode: TokenMode, expect_tokens: Vec<&str>) {
         use tantivy::tokenizer::*;
@@ -181,7 +328,7 @@ mod tests {
 
     #[test]
     fn test_regularize() {
-        test_once("Ａｄｅ", TokenMode::INDEX, vec!["Ade"])
+        test_once("Ａｄｅ", TokenMode::INDEX, vec!["ade"])
     }
 
     #[test]
@@ -279,8 +426,216 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_init_with_config_after_use() {
+        use crate::config::default_config::{DefaultConfig, IK_CONFIG_NAME};
+        test_once("中华人民共和国", TokenMode::SEARCH, vec!["中华人民共和国"]);
+        let conf_file_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(IK_CONFIG_NAME);
+        assert!(crate::init_with_config(DefaultConfig::new(conf_file_path)).is_err());
+    }
+
+    #[test]
+    fn test_offsets_exclude_whitespace() {
+        use tantivy::tokenizer::*;
+        let text = "\u{3000}北京大学\u{3000}ＡＢＣ ｄｅｆ\u{3000}GB/T 7714 ";
+        for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+            let tokenizer = crate::IkTokenizer::new(mode);
+            let mut token_stream = tokenizer.token_stream(text);
+            let mut count = 0;
+            while let Some(token) = token_stream.next() {
+                let orig = &text[token.offset_from..token.offset_to];
+                assert!(!orig.is_empty());
+                assert_eq!(orig.trim(), orig, "{:?}", token);
+                assert_eq!(crate::core::char_util::regularize_str(orig), token.text);
+                count += 1;
+            }
+            assert!(count >= 3);
+        }
+    }
+
+    #[test]
+    fn test_useless_input() {
+        for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+            test_once("", mode, vec![]);
+            test_once("   ", mode, vec![]);
+            test_once("\u{3000}\u{3000}", mode, vec![]);
+            test_once("，。！——……", mode, vec![]);
+        }
+    }
+
     #[test]
     fn test_stop_word() {
         test_once("is：issue：feed", TokenMode::INDEX, vec!["issue", "feed"]);
     }
+
+    #[test]
+    fn test_sanitize_controls() {
+        use tantivy::tokenizer::TokenStream;
+        let text = "北京\u{200B}大学\u{202E}和\u{FEFF}清华";
+        let collect = |tokenizer: crate::IkTokenizer| {
+            let mut stream = tokenizer.ik_token_stream(text);
+            let mut tokens = Vec::new();
+            while stream.advance() {
+                let token = stream.token();
+                tokens.push((token.text.clone(), token.offset_from..token.offset_to));
+            }
+            tokens
+        };
+        let tokens =
+            collect(crate::IkTokenizer::new(TokenMode::SEARCH).with_sanitize_controls(true));
+        assert_eq!(
+            tokens,
+            vec![
+                ("北京大学".to_string(), 0..15),
+                ("和".to_string(), 18..21),
+                ("清华".to_string(), 24..30),
+            ]
+        );
+        assert_eq!(&text[18..21], "和");
+        let tokens =
+            collect(crate::IkTokenizer::new(TokenMode::SEARCH).with_sanitize_controls(false));
+        assert!(tokens.iter().all(|(text, _)| text != "北京大学"));
+        let only_controls = crate::IkTokenizer::new(TokenMode::SEARCH).with_sanitize_controls(true);
+        assert!(!only_controls.ik_token_stream("\u{200B}\u{200D}").advance());
+    }
+
+    #[test]
+    fn test_emoji_offsets() {
+        use crate::core::char_util::{is_invisible_control, regularize_str};
+        use tantivy::tokenizer::TokenStream;
+        let text = "Ｒｕｓｔ🦀很好用👍🏻\u{200B}😀, 𠀀𪚥北京大学👨\u{200D}👩\u{200D}👧🇨🇳 end😀";
+        for sanitize in [true, false] {
+            for mode in [TokenMode::INDEX, TokenMode::SEARCH] {
+                let tokenizer = crate::IkTokenizer::new(mode).with_sanitize_controls(sanitize);
+                let mut stream = tokenizer.ik_token_stream(text);
+                let mut count = 0;
+                while stream.advance() {
+                    let token = stream.token();
+                    let source = regularize_str(&text[token.offset_from..token.offset_to])
+                        .chars()
+                        .filter(|c| !sanitize || !is_invisible_control(*c))
+                        .collect::<String>();
+                    assert_eq!(source, token.text, "{:?}", token);
+                    count += 1;
+                }
+                assert!(count > 0);
+            }
+        }
+        let tokenizer = crate::IkTokenizer::new(TokenMode::SEARCH);
+        let mut stream = tokenizer.ik_token_stream("北京👨\u{200D}👩\u{200D}👧");
+        while stream.advance() {}
+        assert_eq!(stream.token().text, "👨\u{200D}👩\u{200D}👧");
+    }
+
+    #[test]
+    fn test_extra_words() {
+        use crate::dict::dictionary::GLOBAL_DICT;
+        use tantivy::tokenizer::TokenStream;
+        let texts = |tokenizer: &crate::IkTokenizer, text: &str| {
+            let mut stream = tokenizer.ik_token_stream(text);
+            let mut texts = Vec::new();
+            while stream.advance() {
+                texts.push(stream.token().text.clone());
+            }
+            texts
+        };
+        let text = "嵘峰Ｘ光机在栖梧镇展出";
+        let tokenizer = crate::IkTokenizer::new(TokenMode::SEARCH)
+            .with_extra_words(&["嵘峰x光机"])
+            .with_extra_stop_words(&["展出"])
+            .with_extra_words(&["栖梧镇"]);
+        assert_eq!(texts(&tokenizer, text), vec!["嵘峰x光机", "在", "栖梧镇"]);
+        // 其他分词器及共享词典不受影响
+        let plain = texts(&crate::IkTokenizer::new(TokenMode::SEARCH), text);
+        assert!(!plain.contains(&"栖梧镇".to_string()), "{:?}", plain);
+        assert!(plain.contains(&"展出".to_string()), "{:?}", plain);
+        assert!(!GLOBAL_DICT
+            .read()
+            .unwrap()
+            .match_in_main_dict("嵘峰x光机".chars())
+            .iter()
+            .any(|hit| hit.is_match()));
+    }
+
+    #[test]
+    fn test_thread_segmenter_reentrant() {
+        let (outer, inner) = crate::with_thread_segmenter(|ik| {
+            let inner =
+                crate::with_thread_segmenter(|ik| ik.tokenize("长江大桥", TokenMode::SEARCH));
+            (ik.tokenize("中华人民共和国", TokenMode::SEARCH), inner)
+        });
+        assert_eq!(outer[0].get_lexeme_text(), "中华人民共和国");
+        assert_eq!(inner[0].get_lexeme_text(), "长江大桥");
+    }
+
+    #[test]
+    fn test_lexeme_types() {
+        use crate::core::lexeme::LexemeType;
+        use tantivy::tokenizer::TokenStream;
+        let tokenizer = crate::IkTokenizer::new(TokenMode::SEARCH);
+        let mut stream = tokenizer.ik_token_stream("iPhone卖五千块");
+        assert_eq!(stream.lexeme_type(), None);
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            tokens.push((stream.token().text.clone(), stream.lexeme_type().unwrap()));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                ("iphone".to_string(), LexemeType::ENGLISH),
+                ("卖".to_string(), LexemeType::CNCHAR),
+                ("五千块".to_string(), LexemeType::CQUAN),
+            ]
+        );
+        let stream = tokenizer
+            .ik_token_stream("iPhone卖五千块")
+            .retain(|_, lexeme_type| lexeme_type != LexemeType::CNCHAR);
+        assert_eq!(
+            stream.lexeme_types(),
+            &[LexemeType::ENGLISH, LexemeType::CQUAN]
+        );
+    }
+
+    #[test]
+    fn test_with_options() {
+        use crate::core::ik_segmenter::TokenizeOptions;
+        use tantivy::tokenizer::TokenStream;
+        let tokenizer = crate::IkTokenizer::new(TokenMode::INDEX).with_options(TokenizeOptions {
+            merge_quantifiers: false,
+            ..TokenizeOptions::new(TokenMode::SEARCH)
+        });
+        let mut stream = tokenizer.ik_token_stream("iPhone售价五十元");
+        let mut texts = Vec::new();
+        while stream.advance() {
+            texts.push(stream.token().text.clone());
+        }
+        assert_eq!(texts, vec!["iPhone", "售价", "五十", "元"]);
+    }
+
+    #[test]
+    fn test_keep_stop_words() {
+        use tantivy::tokenizer::TokenStream;
+        let tokenizer = crate::IkTokenizer::new(TokenMode::INDEX).with_keep_stop_words(true);
+        let mut stream = tokenizer.ik_token_stream("is：issue：feed");
+        let mut texts = Vec::new();
+        while stream.advance() {
+            texts.push(stream.token().text.clone());
+        }
+        assert_eq!(texts, vec!["is", "issue", "feed"]);
+        // 同一线程的其他分词器仍过滤停止词
+        test_once("is：issue：feed", TokenMode::INDEX, vec!["issue", "feed"]);
+    }
+
+    #[test]
+    fn test_with_base() {
+        use tantivy::tokenizer::TokenStream;
+        let tokenizer = crate::IkTokenizer::new(TokenMode::SEARCH);
+        let mut stream = tokenizer.ik_token_stream("北京大学").with_base(10, 100);
+        assert!(stream.advance());
+        let token = stream.token();
+        assert_eq!(token.text, "北京大学");
+        assert_eq!(token.position, 10);
+        assert_eq!((token.offset_from, token.offset_to), (100, 112));
+        assert!(!stream.advance());
+    }
 }
@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+#[cfg(feature = "tantivy")]
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+#[cfg(feature = "tantivy")]
+use tantivy::tokenizer::{BoxTokenStream, Token, TokenFilter, TokenStream};
+
+// 不带声调的标准拼音音节, ü 写作 v
+const SYLLABLES: &[&str] = &[
+    "a", "ai", "an", "ang", "ao", "ba", "bai", "ban", "bang", "bao", "bei", "ben", "beng", "bi",
+    "bian", "biao", "bie", "bin", "bing", "bo", "bu", "ca", "cai", "can", "cang", "cao", "ce",
+    "cen", "ceng", "cha", "chai", "chan", "chang", "chao", "che", "chen", "cheng", "chi", "chong",
+    "chou", "chu", "chua", "chuai", "chuan", "chuang", "chui", "chun", "chuo", "ci", "cong", "cou",
+    "cu", "cuan", "cui", "cun", "cuo", "da", "dai", "dan", "dang", "dao", "de", "dei", "den",
+    "deng", "di", "dia", "dian", "diao", "die", "ding", "diu", "dong", "dou", "du", "duan", "dui",
+    "dun", "duo", "e", "ei", "en", "eng", "er", "fa", "fan", "fang", "fei", "fen", "feng", "fo",
+    "fou", "fu", "ga", "gai", "gan", "gang", "gao", "ge", "gei", "gen", "geng", "gong", "gou",
+    "gu", "gua", "guai", "guan", "guang", "gui", "gun", "guo", "ha", "hai", "han", "hang", "hao",
+    "he", "hei", "hen", "heng", "hong", "hou", "hu", "hua", "huai", "huan", "huang", "hui", "hun",
+    "huo", "ji", "jia", "jian", "jiang", "jiao", "jie", "jin", "jing", "jiong", "jiu", "ju",
+    "juan", "jue", "jun", "ka", "kai", "kan", "kang", "kao", "ke", "kei", "ken", "keng", "kong",
+    "kou", "ku", "kua", "kuai", "kuan", "kuang", "kui", "kun", "kuo", "la", "lai", "lan", "lang",
+    "lao", "le", "lei", "leng", "li", "lia", "lian", "liang", "liao", "lie", "lin", "ling", "liu",
+    "lo", "long", "lou", "lu", "luan", "lun", "luo", "lv", "lve", "ma", "mai", "man", "mang",
+    "mao", "me", "mei", "men", "meng", "mi", "mian", "miao", "mie", "min", "ming", "miu", "mo",
+    "mou", "mu", "na", "nai", "nan", "nang", "nao", "ne", "nei", "nen", "neng", "ni", "nian",
+    "niang", "niao", "nie", "nin", "ning", "niu", "nong", "nou", "nu", "nuan", "nun", "nuo", "nv",
+    "nve", "o", "ou", "pa", "pai", "pan", "pang", "pao", "pei", "pen", "peng", "pi", "pian",
+    "piao", "pie", "pin", "ping", "po", "pou", "pu", "qi", "qia", "qian", "qiang", "qiao", "qie",
+    "qin", "qing", "qiong", "qiu", "qu", "quan", "que", "qun", "ran", "rang", "rao", "re", "ren",
+    "reng", "ri", "rong", "rou", "ru", "rua", "ruan", "rui", "run", "ruo", "sa", "sai", "san",
+    "sang", "sao", "se", "sen", "seng", "sha", "shai", "shan", "shang", "shao", "she", "shei",
+    "shen", "sheng", "shi", "shou", "shu", "shua", "shuai", "shuan", "shuang", "shui", "shun",
+    "shuo", "si", "song", "sou", "su", "suan", "sui", "sun", "suo", "ta", "tai", "tan", "tang",
+    "tao", "te", "teng", "ti", "tian", "tiao", "tie", "ting", "tong", "tou", "tu", "tuan", "tui",
+    "tun", "tuo", "wa", "wai", "wan", "wang", "wei", "wen", "weng", "wo", "wu", "xi", "xia",
+    "xian", "xiang", "xiao", "xie", "xin", "xing", "xiong", "xiu", "xu", "xuan", "xue", "xun",
+    "ya", "yan", "yang", "yao", "ye", "yi", "yin", "ying", "yo", "yong", "you", "yu", "yuan",
+    "yue", "yun", "za", "zai", "zan", "zang", "zao", "ze", "zei", "zen", "zeng", "zha", "zhai",
+    "zhan", "zhang", "zhao", "zhe", "zhei", "zhen", "zheng", "zhi", "zhong", "zhou", "zhu", "zhua",
+    "zhuai", "zhuan", "zhuang", "zhui", "zhun", "zhuo", "zi", "zong", "zou", "zu", "zuan", "zui",
+    "zun", "zuo",
+];
+
+// 最长音节 "chuang"/"shuang"/"zhuang" 的长度
+const MAX_SYLLABLE_LEN: usize = 6;
+// 多音字组合展开的上限, 避免长词组合爆炸
+const MAX_READINGS: usize = 8;
+
+static SYLLABLE_SET: Lazy<HashSet<&'static str>> =
+    Lazy::new(|| SYLLABLES.iter().copied().collect());
+
+pub fn is_syllable(s: &str) -> bool {
+    SYLLABLE_SET.contains(s)
+}
+
+// 去掉声调数字, 统一小写, ü 写作 v
+fn normalize_syllable(s: &str) -> String {
+    s.trim()
+        .chars()
+        .filter(|c| !c.is_ascii_digit())
+        .map(|c| match c {
+            'ü' | 'Ü' => 'v',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// 将连写的拼音切分为音节, 如 "beijingdaxue" -> ["bei", "jing", "da", "xue"]
+/// 撇号和空格视为强制分隔("xi'an" -> ["xi", "an"]), 优先音节数最少的切分,
+/// 音节数相同时优先首音节更长的切分; 无法完整切分时返回 None
+pub fn segment_pinyin(input: &str) -> Option<Vec<String>> {
+    let normalized = normalize_syllable(input);
+    let mut syllables = Vec::new();
+    for chunk in normalized.split(|c: char| c == '\'' || c.is_whitespace()) {
+        if chunk.is_empty() {
+            continue;
+        }
+        if !chunk.is_ascii() {
+            return None;
+        }
+        syllables.extend(segment_chunk(chunk)?);
+    }
+    if syllables.is_empty() {
+        None
+    } else {
+        Some(syllables)
+    }
+}
+
+fn segment_chunk(chunk: &str) -> Option<Vec<String>> {
+    let n = chunk.len();
+    // best[i]: chunk[i..] 的最少音节数及首音节长度
+    let mut best: Vec<Option<(usize, usize)>> = vec![None; n + 1];
+    best[n] = Some((0, 0));
+    for i in (0..n).rev() {
+        for len in (1..=MAX_SYLLABLE_LEN.min(n - i)).rev() {
+            if !is_syllable(&chunk[i..i + len]) {
+                continue;
+            }
+            if let Some((count, _)) = best[i + len] {
+                if best[i].map(|(c, _)| count + 1 < c).unwrap_or(true) {
+                    best[i] = Some((count + 1, len));
+                }
+            }
+        }
+    }
+    best[0]?;
+    let mut syllables = Vec::new();
+    let mut i = 0;
+    while i < n {
+        let (_, len) = best[i]?;
+        syllables.push(chunk[i..i + len].to_string());
+        i += len;
+    }
+    Some(syllables)
+}
+
+/// 汉字到拼音的对照表, 一个字可以有多个读音
+/// 仓库不附带拼音数据, 由调用方加载
+#[derive(Debug, Clone, Default)]
+pub struct PinyinTable {
+    readings: HashMap<char, Vec<String>>,
+}
+
+impl PinyinTable {
+    /// 从文件加载, 每行一个 "字<TAB>读音1,读音2", 读音可带声调数字, 忽略空行
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|e| format!("open pinyin table {} error: {}", path, e))?;
+        let mut table = PinyinTable::default();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("read pinyin table {} error: {}", path, e))?;
+            let line = line.trim_start_matches('\u{feff}').trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || format!("{}:{} invalid pinyin entry: {:?}", path, i + 1, line);
+            let (word, readings) = line.split_once('\t').ok_or_else(invalid)?;
+            let mut chars = word.trim().chars();
+            let c = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(invalid()),
+            };
+            for reading in readings.split(',') {
+                if !table.insert(c, reading) {
+                    return Err(invalid());
+                }
+            }
+        }
+        Ok(table)
+    }
+
+    /// 添加一个读音, 读音不是合法音节时返回 false
+    pub fn insert(&mut self, c: char, reading: &str) -> bool {
+        let syllable = normalize_syllable(reading);
+        if !is_syllable(&syllable) {
+            return false;
+        }
+        let readings = self.readings.entry(c).or_default();
+        if !readings.contains(&syllable) {
+            readings.push(syllable);
+        }
+        true
+    }
+
+    pub fn readings(&self, c: char) -> &[String] {
+        self.readings.get(&c).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// 词的拼音首字母缩写, 如 "北京大学" -> ["bjdx"], 多音字产生多个缩写
+    /// 词中有字缺少读音时返回空
+    pub fn abbreviations(&self, word: &str) -> Vec<String> {
+        let mut abbreviations = Vec::new();
+        for key in word_readings(self, word) {
+            let abbreviation = key
+                .split(' ')
+                .filter_map(|syllable| syllable.chars().next())
+                .collect::<String>();
+            if !abbreviations.contains(&abbreviation) {
+                abbreviations.push(abbreviation);
+            }
+        }
+        abbreviations
+    }
+
+    pub fn len(&self) -> usize {
+        self.readings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.readings.is_empty()
+    }
+}
+
+/// 拼音到词的倒排索引, 用于把拼音输入映射为候选中文词
+#[derive(Debug, Clone, Default)]
+pub struct PinyinIndex {
+    words: HashMap<String, Vec<String>>,
+}
+
+impl PinyinIndex {
+    pub fn build<'a, I: IntoIterator<Item = &'a str>>(table: &PinyinTable, words: I) -> Self {
+        let mut index = PinyinIndex::default();
+        for word in words {
+            index.insert(table, word);
+        }
+        index
+    }
+
+    /// 按读音组合索引一个词, 词中有字缺少读音时忽略该词
+    pub fn insert(&mut self, table: &PinyinTable, word: &str) {
+        for key in word_readings(table, word) {
+            let words = self.words.entry(key).or_default();
+            if !words.iter().any(|w| w == word) {
+                words.push(word.to_string());
+            }
+        }
+    }
+
+    /// 返回与拼音输入读音完全一致的候选词, 按字典序排列
+    pub fn candidates(&self, input: &str) -> Vec<String> {
+        let key = match segment_pinyin(input) {
+            Some(syllables) => syllables.join(" "),
+            None => return Vec::new(),
+        };
+        let mut words = self.words.get(&key).cloned().unwrap_or_default();
+        words.sort();
+        words
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// 为中文词元追加拼音首字母缩写词元的 tantivy TokenFilter
+/// 缩写词元与原词元位置和偏移相同, 紧跟在原词元之后输出, 单字词元不追加
+#[cfg(feature = "tantivy")]
+#[derive(Debug, Clone)]
+pub struct PinyinAbbrFilter {
+    table: Arc<PinyinTable>,
+}
+
+#[cfg(feature = "tantivy")]
+impl PinyinAbbrFilter {
+    pub fn new(table: Arc<PinyinTable>) -> Self {
+        PinyinAbbrFilter { table }
+    }
+}
+
+#[cfg(feature = "tantivy")]
+impl TokenFilter for PinyinAbbrFilter {
+    fn transform<'a>(&self, token_stream: BoxTokenStream<'a>) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(PinyinAbbrStream {
+            table: self.table.clone(),
+            tail: token_stream,
+            token: Token::default(),
+            pending: Vec::new(),
+        })
+    }
+}
+
+#[cfg(feature = "tantivy")]
+pub struct PinyinAbbrStream<'a> {
+    table: Arc<PinyinTable>,
+    tail: BoxTokenStream<'a>,
+    token: Token,
+    // 待输出的缩写, 逆序存放
+    pending: Vec<String>,
+}
+
+#[cfg(feature = "tantivy")]
+impl<'a> TokenStream for PinyinAbbrStream<'a> {
+    fn advance(&mut self) -> bool {
+        if let Some(abbreviation) = self.pending.pop() {
+            self.token.text = abbreviation;
+            return true;
+        }
+        if !self.tail.advance() {
+            return false;
+        }
+        self.token.clone_from(self.tail.token());
+        if self.token.text.chars().nth(1).is_some() {
+            self.pending = self.table.abbreviations(&self.token.text);
+            self.pending
+                .retain(|abbreviation| *abbreviation != self.token.text);
+            self.pending.reverse();
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+// 展开词的读音组合, 每个组合为空格分隔的音节串, 最多 MAX_READINGS 个
+fn word_readings(table: &PinyinTable, word: &str) -> Vec<String> {
+    let mut keys = vec![String::new()];
+    for c in word.chars() {
+        let readings = table.readings(c);
+        if readings.is_empty() {
+            return Vec::new();
+        }
+        let mut next = Vec::new();
+        'outer: for key in keys.iter() {
+            for reading in readings {
+                if next.len() >= MAX_READINGS {
+                    break 'outer;
+                }
+                let mut key = key.clone();
+                if !key.is_empty() {
+                    key.push(' ');
+                }
+                key.push_str(reading);
+                next.push(key);
+            }
+        }
+        keys = next;
+    }
+    keys
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "tantivy")]
+    use tantivy::tokenizer::TextAnalyzer;
+
+    use super::*;
+    #[cfg(feature = "tantivy")]
+    use crate::core::ik_segmenter::TokenMode;
+    #[cfg(feature = "tantivy")]
+    use crate::IkTokenizer;
+
+    #[test]
+    fn test_segment_pinyin() {
+        let cases = [
+            ("beijingdaxue", Some(vec!["bei", "jing", "da", "xue"])),
+            ("xian", Some(vec!["xian"])),
+            ("xi'an", Some(vec!["xi", "an"])),
+            ("Zhong Guo", Some(vec!["zhong", "guo"])),
+            ("zhong1guo2", Some(vec!["zhong", "guo"])),
+            ("lvse", Some(vec!["lv", "se"])),
+            ("qxz", None),
+            ("", None),
+        ];
+        for (input, expected) in cases {
+            let expected = expected.map(|v| v.into_iter().map(String::from).collect::<Vec<_>>());
+            assert_eq!(segment_pinyin(input), expected, "input = {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_pinyin_index() {
+        let mut table = PinyinTable::default();
+        for (c, reading) in [
+            ('北', "bei3"),
+            ('京', "jing1"),
+            ('大', "da4"),
+            ('大', "dai4"),
+            ('学', "xue2"),
+            ('背', "bei4"),
+            ('景', "jing3"),
+        ] {
+            assert!(table.insert(c, reading));
+        }
+        assert!(!table.insert('北', "bxi"));
+        let index = PinyinIndex::build(&table, ["北京", "背景", "北京大学", "大学", "未知"]);
+        assert_eq!(index.candidates("beijing"), vec!["北京", "背景"]);
+        assert_eq!(index.candidates("beijingdaxue"), vec!["北京大学"]);
+        assert_eq!(index.candidates("daixue"), vec!["大学"]);
+        assert!(index.candidates("weizhi").is_empty());
+        assert_eq!(table.abbreviations("北京大学"), vec!["bjdx"]);
+        assert_eq!(table.abbreviations("大学"), vec!["dx"]);
+        assert!(table.abbreviations("未知").is_empty());
+    }
+
+    #[cfg(feature = "tantivy")]
+    #[test]
+    fn test_abbr_filter() {
+        let mut table = PinyinTable::default();
+        for (c, reading) in [('北', "bei"), ('京', "jing"), ('大', "da"), ('学', "xue")] {
+            table.insert(c, reading);
+        }
+        let analyzer = TextAnalyzer::from(IkTokenizer::new(TokenMode::SEARCH))
+            .filter(PinyinAbbrFilter::new(Arc::new(table)));
+        let mut stream = analyzer.token_stream("Hello北京大学");
+        let mut tokens = Vec::new();
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.position, token.offset_from));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                ("hello".to_string(), 0, 0),
+                ("北京大学".to_string(), 5, 5),
+                ("bjdx".to_string(), 5, 5),
+            ]
+        );
+    }
+}
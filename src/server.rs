@@ -0,0 +1,378 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::configuration::global_config;
+use crate::core::char_util::regularize_str;
+use crate::core::ik_segmenter::TokenMode;
+use crate::core::token_record::LuceneToken;
+use crate::with_thread_segmenter;
+
+// 读写请求的超时时间
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+// 请求体的最大字节数
+const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+// 响应后等待客户端关闭连接的时间
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+// 请求行及请求头的最大字节数
+const MAX_HEADER_SIZE: usize = 8 * 1024;
+
+// 每个处理线程排队等待的连接数, 队列满时暂停接受新连接
+const QUEUE_PER_THREAD: usize = 16;
+
+// accept 出错(如文件描述符耗尽)后等待一段时间再继续
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+#[derive(Deserialize)]
+struct AnalyzeRequest {
+    text: String,
+    // 未指定时使用配置中的 default_mode
+    #[serde(default)]
+    analyzer: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AnalyzeResponse<'a> {
+    tokens: &'a [LuceneToken<'a>],
+}
+
+/// 与 Elasticsearch _analyze 兼容的 HTTP 服务, 便于从 ES IK 插件迁移时对比分词结果
+///
+/// `POST /analyze`(或 `/_analyze`), 请求体为 `{"text": "...", "analyzer": "ik_smart"}`,
+/// 返回 `{"tokens": [{"token", "start_offset", "end_offset", "type", "position"}]}`
+/// analyzer 可以是 ik_smart、ik_max 或 ES IK 插件中的 ik_max_word
+/// 与 ES IK 插件相同, 全角字符转为半角后分词, 词元文本经过正规化(全角转半角、英文转小写),
+/// 偏移以 UTF-16 码元为单位
+/// 由固定数量的线程处理连接, 响应后关闭连接
+/// 不支持 chunked 请求体(返回 411), 请求头超过 8KB 返回 431, 请求体超过 10MB 返回 413
+pub struct AnalyzeServer {
+    listener: TcpListener,
+    threads: usize,
+}
+
+impl AnalyzeServer {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> Result<Self, String> {
+        let listener = TcpListener::bind(addr).map_err(|e| format!("bind error: {}", e))?;
+        Ok(AnalyzeServer {
+            listener,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        })
+    }
+
+    /// 处理连接的线程数, 默认为 CPU 核数
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, String> {
+        self.listener
+            .local_addr()
+            .map_err(|e| format!("local addr error: {}", e))
+    }
+
+    /// 持续处理请求, accept 出错时记录日志并继续
+    pub fn serve(&self) -> Result<(), String> {
+        let (sender, receiver) = sync_channel::<TcpStream>(self.threads * QUEUE_PER_THREAD);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..self.threads {
+            let receiver = receiver.clone();
+            thread::spawn(move || handle_connections(&receiver));
+        }
+        for stream in self.listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if sender.send(stream).is_err() {
+                        return Err("all analyze threads exited".to_string());
+                    }
+                }
+                Err(error) => {
+                    log::warn!("accept error: {}", error);
+                    thread::sleep(ACCEPT_BACKOFF);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn handle_connections(connections: &Mutex<Receiver<TcpStream>>) {
+    loop {
+        // 只在取连接时持有锁
+        let next = connections.lock().unwrap().recv();
+        let stream = match next {
+            Ok(stream) => stream,
+            Err(_) => return,
+        };
+        if let Err(error) = handle_connection(stream) {
+            log::warn!("analyze request failed: {}", error);
+        }
+    }
+}
+
+/// 按 ES _analyze 的格式对请求体分词, 返回响应的 JSON
+pub fn analyze(body: &[u8]) -> Result<String, String> {
+    let request = serde_json::from_slice::<AnalyzeRequest>(body)
+        .map_err(|e| format!("invalid analyze request: {}", e))?;
+    let mode = match request.analyzer.as_deref() {
+        None => global_config().get_default_mode(),
+        Some("ik_max_word") => TokenMode::INDEX,
+        Some(analyzer) => TokenMode::try_from(analyzer)?,
+    };
+    let mut records = with_thread_segmenter(|ik| ik.tokenize_records(&request.text, mode));
+    for record in records.iter_mut() {
+        record.text = regularize_str(&record.text);
+    }
+    let tokens = records
+        .iter()
+        .enumerate()
+        .map(|(position, record)| record.to_lucene(position))
+        .collect::<Vec<_>>();
+    serde_json::to_string(&AnalyzeResponse { tokens: &tokens }).map_err(|e| e.to_string())
+}
+
+fn handle_connection(mut stream: TcpStream) -> Result<(), String> {
+    let error = |e: std::io::Error| e.to_string();
+    stream.set_read_timeout(Some(IO_TIMEOUT)).map_err(error)?;
+    stream.set_write_timeout(Some(IO_TIMEOUT)).map_err(error)?;
+    let mut reader = BufReader::new(stream.try_clone().map_err(error)?);
+    let (status, body) = match read_request(&mut reader) {
+        Ok((method, path, body)) => route(&method, &path, &body),
+        Err(status) => error_response(status, request_error(status).to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=UTF-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason_phrase(status),
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).map_err(error)?;
+    // 请求中未读取的数据(如被拒绝的请求体)留在缓冲区时直接关闭会发送 RST, 客户端可能收不到响应
+    // 因此先关闭写端, 再在限定的时间内读完剩余数据
+    if stream.shutdown(Shutdown::Write).is_ok()
+        && stream.set_read_timeout(Some(DRAIN_TIMEOUT)).is_ok()
+    {
+        let _ = io::copy(&mut reader.take(MAX_BODY_SIZE as u64), &mut io::sink());
+    }
+    Ok(())
+}
+
+// 读取请求行、请求头及请求体, 格式错误或读取失败时返回对应的状态码
+fn read_request<R: BufRead>(reader: &mut R) -> Result<(String, String, Vec<u8>), u16> {
+    let mut header_budget = MAX_HEADER_SIZE;
+    let request_line = read_header_line(reader, &mut header_budget)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(400),
+    };
+    let mut content_length = 0;
+    loop {
+        let line = read_header_line(reader, &mut header_budget)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().map_err(|_| 400u16)?;
+            } else if name.eq_ignore_ascii_case("Transfer-Encoding") {
+                return Err(411);
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(413);
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|_| 400u16)?;
+    Ok((method, path, body))
+}
+
+// 读取一行请求头, 所有请求头共用 budget 字节的上限
+fn read_header_line<R: BufRead>(reader: &mut R, budget: &mut usize) -> Result<String, u16> {
+    let mut line = String::new();
+    let read = reader
+        .take(*budget as u64)
+        .read_line(&mut line)
+        .map_err(|_| 400u16)?;
+    if read == 0 {
+        return Err(if *budget == 0 { 431 } else { 400 });
+    }
+    if !line.ends_with('\n') {
+        return Err(if read == *budget { 431 } else { 400 });
+    }
+    *budget -= read;
+    Ok(line)
+}
+
+fn request_error(status: u16) -> &'static str {
+    match status {
+        411 => "chunked request body is not supported, send Content-Length",
+        413 => "request body too large",
+        431 => "request header too large",
+        _ => "malformed request",
+    }
+}
+
+fn route(method: &str, path: &str, body: &[u8]) -> (u16, String) {
+    // 忽略查询参数, 如 ?pretty
+    let path = path.split('?').next().unwrap_or(path);
+    match (method, path) {
+        ("POST", "/analyze" | "/_analyze") => match analyze(body) {
+            Ok(response) => (200, response),
+            Err(reason) => error_response(400, reason),
+        },
+        (_, "/analyze" | "/_analyze") => error_response(405, format!("{} not allowed", method)),
+        _ => error_response(404, format!("no handler for {}", path)),
+    }
+}
+
+// 与 ES 相同的错误格式
+fn error_response(status: u16, reason: String) -> (u16, String) {
+    let body = serde_json::json!({
+        "error": {"reason": reason},
+        "status": status,
+    });
+    (status, body.to_string())
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::Value;
+
+    use super::*;
+
+    fn request(addr: SocketAddr, raw: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(raw.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    fn post(addr: SocketAddr, path: &str, body: &str) -> (u16, Value) {
+        request(
+            addr,
+            &format!(
+                "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+                path,
+                body.len(),
+                body
+            ),
+        )
+    }
+
+    #[test]
+    fn test_analyze() {
+        let response = analyze("{\"text\": \"iPhone售价\", \"analyzer\": \"ik_smart\"}".as_bytes());
+        assert_eq!(
+            response.unwrap(),
+            "{\"tokens\":[{\"token\":\"iphone\",\"start_offset\":0,\"end_offset\":6,\"type\":\"ENGLISH\",\"position\":0},\
+             {\"token\":\"售价\",\"start_offset\":6,\"end_offset\":8,\"type\":\"CN_WORD\",\"position\":1}]}"
+        );
+        // 与 ES IK 插件相同, 全角字符按半角切分
+        let response =
+            analyze("{\"text\": \"ＡＢＣ１２３\", \"analyzer\": \"ik_max_word\"}".as_bytes());
+        assert_eq!(
+            response.unwrap(),
+            "{\"tokens\":[{\"token\":\"abc123\",\"start_offset\":0,\"end_offset\":6,\"type\":\"LETTER\",\"position\":0},\
+             {\"token\":\"abc\",\"start_offset\":0,\"end_offset\":3,\"type\":\"ENGLISH\",\"position\":1},\
+             {\"token\":\"123\",\"start_offset\":3,\"end_offset\":6,\"type\":\"ARABIC\",\"position\":2}]}"
+        );
+        assert!(analyze(b"{\"text\": \"a\", \"analyzer\": \"standard\"}").is_err());
+        assert!(analyze(b"{\"analyzer\": \"ik_smart\"}").is_err());
+    }
+
+    #[test]
+    fn test_server() {
+        let server = AnalyzeServer::bind("127.0.0.1:0").unwrap().with_threads(2);
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.serve());
+
+        let (status, body) = post(
+            addr,
+            "/_analyze?pretty",
+            "{\"text\": \"中华人民共和国\", \"analyzer\": \"ik_max_word\"}",
+        );
+        assert_eq!(status, 200);
+        let tokens = body["tokens"].as_array().unwrap();
+        assert!(tokens.len() > 1);
+        assert_eq!(tokens[0]["token"], "中华人民共和国");
+        assert_eq!(tokens[0]["end_offset"], 7);
+
+        let (status, body) = post(addr, "/analyze", "{\"text\": 1}");
+        assert_eq!(status, 400);
+        assert_eq!(body["status"], 400);
+        assert_eq!(post(addr, "/search", "{}").0, 404);
+        let (status, _) = request(addr, "GET /analyze HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 405);
+
+        let (status, body) = request(
+            addr,
+            "POST /analyze HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n",
+        );
+        assert_eq!(status, 411);
+        assert_eq!(body["status"], 411);
+        let long_header = format!(
+            "POST /analyze HTTP/1.1\r\nX-Long: {}\r\n\r\n",
+            "a".repeat(MAX_HEADER_SIZE)
+        );
+        assert_eq!(request(addr, &long_header).0, 431);
+
+        // 连接数超过线程数时排队处理
+        let handles = (0..8)
+            .map(|_| thread::spawn(move || post(addr, "/analyze", "{\"text\": \"北京\"}").0))
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 200);
+        }
+    }
+
+    #[test]
+    fn test_read_request() {
+        let mut raw = "POST /analyze HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}".as_bytes();
+        let (method, path, body) = read_request(&mut raw).unwrap();
+        assert_eq!(
+            (method.as_str(), path.as_str(), body.as_slice()),
+            ("POST", "/analyze", &b"{}"[..])
+        );
+        // 请求头不完整
+        assert_eq!(
+            read_request(&mut "POST /analyze HTTP/1.1\r\n".as_bytes()),
+            Err(400)
+        );
+        let oversized = "x".repeat(MAX_HEADER_SIZE + 1);
+        assert_eq!(read_request(&mut oversized.as_bytes()), Err(431));
+        let large_body = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_SIZE + 1
+        );
+        assert_eq!(read_request(&mut large_body.as_bytes()), Err(413));
+    }
+}
@@ -0,0 +1,47 @@
+use std::io;
+use std::thread::JoinHandle;
+
+use signal_hook::consts::SIGHUP;
+use signal_hook::iterator::Signals;
+
+use crate::dict::dictionary::reload_global_dict;
+
+/// 安装 SIGHUP 处理线程, 收到信号后重新加载 GLOBAL_DICT
+/// 与 nginx 等服务 `kill -HUP` 重新加载配置的运维方式一致
+pub fn install_reload_handler() -> io::Result<JoinHandle<()>> {
+    let mut signals = Signals::new([SIGHUP])?;
+    Ok(std::thread::spawn(move || {
+        for signal in signals.forever() {
+            log::info!("received signal {}, reloading dictionaries", signal);
+            let report = reload_global_dict();
+            if !report.is_ok() {
+                log::error!("reload dictionaries failed: {:?}", report.errors);
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::dict::dictionary::GLOBAL_DICT;
+    use crate::dict::reload::set_reload_callback;
+
+    #[test]
+    fn test_sighup_reload() {
+        let version = GLOBAL_DICT.read().unwrap().version();
+        let (sender, receiver) = mpsc::channel();
+        set_reload_callback(move |report| {
+            let _ = sender.send(report.clone());
+        });
+        install_reload_handler().unwrap();
+        signal_hook::low_level::raise(SIGHUP).unwrap();
+        let report = receiver.recv_timeout(Duration::from_secs(30)).unwrap();
+        assert!(report.is_ok(), "{:?}", report);
+        assert_eq!(report.version, version + 1);
+        assert_eq!(GLOBAL_DICT.read().unwrap().version(), version + 1);
+    }
+}
@@ -0,0 +1,44 @@
+//! tantivy 的适配层, 目前只支持 tantivy 0.18, 尚无按 tantivy 版本区分的 feature
+//! IkTokenizer/IkTokenStream 本身只依赖 tantivy::tokenizer::Token, 与 tantivy 版本相关的
+//! Tokenizer/TokenStream 实现及注册集中在这里; 0.20 起 Tokenizer::token_stream 改为取 &mut self
+//! 并返回关联类型, 支持其他主版本时需新增 feature 及同样的适配模块
+use tantivy::tokenizer::{BoxTokenStream, Token, TokenStream, Tokenizer, TokenizerManager};
+
+use crate::{IkTokenStream, IkTokenizer};
+
+/// ik_max 分词器在 TokenizerManager 中的名称, 与 ES IK 插件的 ik_max_word 对应
+pub const IK_MAX: &str = "ik_max";
+/// ik_smart 分词器在 TokenizerManager 中的名称
+pub const IK_SMART: &str = "ik_smart";
+
+/// 将 IkTokenizer::ik_max() 和 IkTokenizer::ik_smart() 分别以 "ik_max" 和 "ik_smart" 注册到 manager
+/// 字段通过 TextFieldIndexing::set_tokenizer 使用这两个名称
+pub fn register_ik_tokenizers(manager: &TokenizerManager) {
+    manager.register(IK_MAX, IkTokenizer::ik_max());
+    manager.register(IK_SMART, IkTokenizer::ik_smart());
+}
+
+impl Tokenizer for IkTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(self.ik_token_stream(text))
+    }
+}
+
+impl TokenStream for IkTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.index < self.tokens.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
@@ -0,0 +1,327 @@
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use tantivy::tokenizer::Token;
+
+use crate::config::configuration::{global_config, Configuration};
+use crate::core::char_util::{is_invisible_control, regularize, to_halfwidth};
+use crate::core::ik_segmenter::{is_useless_str, IKSegmenter, TokenMode, TokenizeOptions};
+use crate::core::lexeme::LexemeType;
+use crate::dict::dictionary::{DictOverlay, DictRef, Dictionary};
+use crate::dict::synonym::SynonymMap;
+use crate::with_thread_segmenter;
+
+#[derive(Debug, Clone)]
+pub struct IkTokenizer {
+    // 分词模式、是否转为小写、是否保留停止词等选项
+    options: TokenizeOptions,
+    // 在原词元的位置追加同义词词元
+    synonyms: Option<Arc<SynonymMap>>,
+    // 是否去掉零宽字符及双向文本控制符
+    sanitize_controls: bool,
+    // 按独立配置构造或带有叠加词条时使用的分词器, 为 None 时使用 with_thread_segmenter
+    pool: Option<Arc<SegmenterPool>>,
+}
+
+// 按独立配置及词典构造的分词器池, 分词时取出一个, 用完放回
+// 线程之间只在取放时短暂争用锁
+struct SegmenterPool {
+    cfg: Arc<dyn Configuration>,
+    dict: DictRef,
+    idle: Mutex<Vec<IKSegmenter>>,
+}
+
+impl SegmenterPool {
+    fn new(cfg: Arc<dyn Configuration>, dict: DictRef) -> Self {
+        SegmenterPool {
+            cfg,
+            dict,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut IKSegmenter) -> R) -> R {
+        let idle = self.idle.lock().unwrap().pop();
+        let mut ik =
+            idle.unwrap_or_else(|| IKSegmenter::build(self.cfg.as_ref(), self.dict.clone()));
+        let result = f(&mut ik);
+        self.idle.lock().unwrap().push(ik);
+        result
+    }
+}
+
+impl fmt::Debug for SegmenterPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SegmenterPool")
+            .field("dict", &self.dict)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IkTokenStream {
+    pub(crate) tokens: Vec<Token>,
+    // 与 tokens 一一对应的词元类型, 同义词词元与原词元相同
+    types: Vec<LexemeType>,
+    pub(crate) index: usize,
+}
+
+impl IkTokenStream {
+    /// 将所有词元的位置和偏移分别加上 position_base 和 offset_base
+    /// 用于多值字段, 使后一个值的词元接在前一个值之后
+    pub fn with_base(mut self, position_base: usize, offset_base: usize) -> Self {
+        for token in self.tokens.iter_mut() {
+            token.position += position_base;
+            token.offset_from += offset_base;
+            token.offset_to += offset_base;
+        }
+        self
+    }
+
+    /// 当前词元的类型, tantivy 的 Token 中不包含类型, 需要在 advance 之后通过它获取
+    /// 尚未调用 advance 时返回 None
+    pub fn lexeme_type(&self) -> Option<LexemeType> {
+        self.index
+            .checked_sub(1)
+            .and_then(|index| self.types.get(index))
+            .copied()
+    }
+
+    /// 所有词元的类型, 与词元一一对应
+    pub fn lexeme_types(&self) -> &[LexemeType] {
+        &self.types
+    }
+
+    /// 只保留 f 返回 true 的词元, 其余词元的位置不变
+    /// 如 .retain(|_, t| t != LexemeType::SPECIAL) 去掉标点
+    pub fn retain<F: FnMut(&Token, LexemeType) -> bool>(mut self, mut f: F) -> Self {
+        let keep = self
+            .tokens
+            .iter()
+            .zip(self.types.iter())
+            .map(|(token, lexeme_type)| f(token, *lexeme_type))
+            .collect::<Vec<_>>();
+        let mut flags = keep.iter();
+        self.tokens.retain(|_| *flags.next().unwrap());
+        let mut flags = keep.iter();
+        self.types.retain(|_| *flags.next().unwrap());
+        self
+    }
+}
+
+impl IkTokenizer {
+    pub fn new(mode: TokenMode) -> Self {
+        let cfg = global_config();
+        Self {
+            options: TokenizeOptions {
+                lowercase: true,
+                keep_stop_words: cfg.get_keep_stop_words(),
+                ..TokenizeOptions::new(mode)
+            },
+            synonyms: None,
+            sanitize_controls: cfg.get_sanitize_controls(),
+            pool: None,
+        }
+    }
+
+    /// 细粒度切分, 输出所有可能的词, 即 INDEX 模式, 用于索引
+    pub fn ik_max() -> Self {
+        IkTokenizer::new(TokenMode::INDEX)
+    }
+
+    /// 粗粒度切分, 即 SEARCH 模式, 用于查询
+    pub fn ik_smart() -> Self {
+        IkTokenizer::new(TokenMode::SEARCH)
+    }
+
+    /// 按指定的配置构造, 词典、子分词器选项、默认分词模式及同义词词典均取自 cfg,
+    /// 不使用全局配置和 GLOBAL_DICT, 用于从配置中心或代码中提供配置
+    pub fn with_configuration(cfg: Arc<dyn Configuration>) -> Result<Self, String> {
+        let dict = Arc::new(Dictionary::from_configuration(cfg.clone())?);
+        let synonyms = SynonymMap::from_config(cfg.as_ref())?.map(Arc::new);
+        Ok(Self {
+            options: TokenizeOptions {
+                lowercase: true,
+                keep_stop_words: cfg.get_keep_stop_words(),
+                ..TokenizeOptions::new(cfg.get_default_mode())
+            },
+            synonyms,
+            sanitize_controls: cfg.get_sanitize_controls(),
+            pool: Some(Arc::new(SegmenterPool::new(cfg, DictRef::Local(dict)))),
+        })
+    }
+
+    /// 设置分词模式, 数量词是否合并随模式变化, 见 TokenizeOptions::new
+    pub fn with_mode(mut self, mode: TokenMode) -> Self {
+        self.options = TokenizeOptions {
+            mode,
+            merge_quantifiers: mode == TokenMode::SEARCH,
+            ..self.options
+        };
+        self
+    }
+
+    /// 设置全部分词选项, 覆盖 with_mode、with_lowercase、with_keep_stop_words 的设置
+    /// 默认为 lowercase 为 true 的 TokenizeOptions::new(mode)
+    pub fn with_options(mut self, options: TokenizeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// 是否将英文字母转为小写, 默认为 true
+    /// 为 false 时词元文本保留原文的大小写, 全角字符仍转为半角
+    pub fn with_lowercase(mut self, lowercase: bool) -> Self {
+        self.options.lowercase = lowercase;
+        self
+    }
+
+    /// 是否保留停止词, 默认取自配置的 keep_stop_words
+    /// 为 true 时输出所有词元, 用于高亮及精确短语匹配
+    pub fn with_keep_stop_words(mut self, keep_stop_words: bool) -> Self {
+        self.options.keep_stop_words = keep_stop_words;
+        self
+    }
+
+    /// 是否在分词前去掉零宽字符(如 U+200B)及双向文本控制符(如 U+202E), 默认取自配置的 sanitize_controls
+    /// 这些字符不再切断词元或混入词元文本, 词元的偏移仍指向原文
+    pub fn with_sanitize_controls(mut self, sanitize_controls: bool) -> Self {
+        self.sanitize_controls = sanitize_controls;
+        self
+    }
+
+    /// 只对本分词器生效的扩展词, 与共享词典(GLOBAL_DICT 或 with_configuration 加载的词典)中的词一同参与匹配
+    /// 用于为某个字段补充专用词汇而不修改共享词典; 可多次调用, 词条累加
+    pub fn with_extra_words(self, words: &[&str]) -> Self {
+        self.with_overlay(|overlay| overlay.add_words(words))
+    }
+
+    /// 只对本分词器生效的停止词, 见 with_extra_words
+    pub fn with_extra_stop_words(self, words: &[&str]) -> Self {
+        self.with_overlay(|overlay| overlay.add_stop_words(words))
+    }
+
+    // 在当前使用的词典之上叠加词条, 之后使用独立的分词器池
+    fn with_overlay(mut self, f: impl FnOnce(&mut DictOverlay)) -> Self {
+        let (cfg, dict) = match self.pool.as_ref() {
+            Some(pool) => (pool.cfg.clone(), pool.dict.clone()),
+            None => (global_config(), DictRef::Global),
+        };
+        let mut overlay = DictOverlay::new(dict);
+        f(&mut overlay);
+        let dict = DictRef::Overlay(Arc::new(overlay));
+        self.pool = Some(Arc::new(SegmenterPool::new(cfg, dict)));
+        self
+    }
+
+    /// 在每个词元之后输出其同义词词元, 同义词与原词元的位置、偏移及 position_length 相同
+    /// 配置中的 synonyms_dict 可通过 SynonymMap::from_config 加载
+    pub fn with_synonyms(mut self, synonyms: Arc<SynonymMap>) -> Self {
+        self.synonyms = Some(synonyms);
+        self
+    }
+}
+
+/// 使用全局配置中的默认分词模式(default_mode 或 IK_MODE_DEFAULT)
+impl Default for IkTokenizer {
+    fn default() -> Self {
+        IkTokenizer::new(global_config().get_default_mode())
+    }
+}
+
+impl IkTokenizer {
+    // 使用本分词器的分词器池, 未按独立配置构造时使用本线程的分词器
+    fn with_segmenter<R>(&self, f: impl FnOnce(&mut IKSegmenter) -> R) -> R {
+        match self.pool.as_ref() {
+            Some(pool) => pool.with(f),
+            None => with_thread_segmenter(f),
+        }
+    }
+
+    /// 与 token_stream 相同, 但返回具体类型, 以便调用 IkTokenStream::with_base 等方法
+    pub fn ik_token_stream(&self, text: &str) -> IkTokenStream {
+        // 参与分词的字符及其在原文中的字节范围
+        let orig_chars = text
+            .char_indices()
+            .filter(|(_, c)| !(self.sanitize_controls && is_invisible_control(*c)))
+            .map(|(i, c)| (c, i..i + c.len_utf8()))
+            .collect::<Vec<_>>();
+        let regular_str = orig_chars
+            .iter()
+            .map(|(c, _)| regularize(*c))
+            .collect::<String>();
+        if is_useless_str(&regular_str) && !self.with_segmenter(|ik| ik.is_full_coverage()) {
+            return IkTokenStream {
+                tokens: Vec::new(),
+                types: Vec::new(),
+                index: 0,
+            };
+        }
+        // 保留大小写时, 词元文本取自只转换了全角字符的文本
+        let output_str = if self.options.lowercase {
+            regular_str.clone()
+        } else {
+            orig_chars.iter().map(|(c, _)| to_halfwidth(*c)).collect()
+        };
+        // 正规化逐字符转换, 三个字符串的字符位置与 orig_chars 一一对应, 偏移取自原文
+        let output_indices = char_byte_offsets(&output_str);
+        let regular_chars = regular_str.chars().collect::<Vec<_>>();
+        // regular_str 已转为小写, 大小写只影响输出的词元文本
+        let orig_tokens = self.with_segmenter(|ik| ik.tokenize_with(&regular_str, self.options));
+        let mut tokens = Vec::new();
+        let mut types = Vec::new();
+        for token in orig_tokens.iter() {
+            // 词元的偏移不包含首尾的空白(含正规化为空格的全角空格)
+            let span = trim_whitespace(
+                &regular_chars,
+                token.get_begin_position()..token.get_end_position(),
+            );
+            if span.is_empty() {
+                continue;
+            }
+            tokens.push(Token {
+                offset_from: orig_chars[span.start].1.start,
+                offset_to: orig_chars[span.end - 1].1.end,
+                position: token.get_begin(),
+                text: String::from(
+                    &output_str[output_indices[span.start]..output_indices[span.end]],
+                ),
+                position_length: token.get_length(),
+            });
+            types.push(token.lexeme_type);
+            if let Some(synonyms) = self.synonyms.as_ref() {
+                let original = tokens.last().unwrap().clone();
+                for synonym in synonyms.get(&original.text) {
+                    tokens.push(Token {
+                        text: synonym.clone(),
+                        ..original.clone()
+                    });
+                    types.push(token.lexeme_type);
+                }
+            }
+        }
+        IkTokenStream {
+            tokens,
+            types,
+            index: 0,
+        }
+    }
+}
+
+// 每个字符的起始字节偏移, 末尾附加字符串的字节长度
+fn char_byte_offsets(text: &str) -> Vec<usize> {
+    let mut offsets = text.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+    offsets.push(text.len());
+    offsets
+}
+
+// 去掉字符区间首尾的空白字符
+fn trim_whitespace(chars: &[char], mut span: Range<usize>) -> Range<usize> {
+    while span.start < span.end && chars[span.start].is_whitespace() {
+        span.start += 1;
+    }
+    while span.start < span.end && chars[span.end - 1].is_whitespace() {
+        span.end -= 1;
+    }
+    span
+}
@@ -0,0 +1,34 @@
+//! wasm-bindgen 导出的接口, 词典使用编译时嵌入的词典
+//! wasm-pack build --target web --no-default-features --features wasm
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::core::ik_segmenter::TokenMode;
+use crate::with_thread_segmenter;
+
+/// tokenize(text, mode): mode 为 "ik_smart" 或 "ik_max"
+/// 返回 [{text, type, position, start, end}], start/end 为 UTF-16 偏移, 可直接用于 String.prototype.slice
+#[wasm_bindgen]
+pub fn tokenize(text: &str, mode: &str) -> Result<Array, JsValue> {
+    let mode = TokenMode::try_from(mode).map_err(|e| JsValue::from_str(&e))?;
+    let records = with_thread_segmenter(|ik| ik.tokenize_records(text, mode));
+    let tokens = Array::new();
+    for record in records {
+        let token = Object::new();
+        Reflect::set(&token, &"text".into(), &record.text.into())?;
+        Reflect::set(&token, &"type".into(), &record.lexeme_type.as_str().into())?;
+        Reflect::set(&token, &"position".into(), &(record.position as u32).into())?;
+        Reflect::set(
+            &token,
+            &"start".into(),
+            &(record.offsets.utf16.start as u32).into(),
+        )?;
+        Reflect::set(
+            &token,
+            &"end".into(),
+            &(record.offsets.utf16.end as u32).into(),
+        )?;
+        tokens.push(&token);
+    }
+    Ok(tokens)
+}
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::core::ik_segmenter::{IKSegmenter, TokenizeOptions};
+use crate::core::token_record::LuceneToken;
+
+// 默认的队列容量, 即读取线程最多领先写出的文档数
+const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// 无状态的 NDJSON 分词 worker, 每行输入一个 JSON 文档, 每行输出一个文档的词元:
+///
+/// - 输入 `{"id": 1, "text": "中华人民共和国"}`
+/// - 输出 `{"id":1,"tokens":[{"end":21,"position":0,"start":0,"text":"中华人民共和国","type":"CN_WORD"}]}`
+///
+/// start/end 为词元在 text 中的字节偏移; with_lucene_names 可改为 ES _analyze 的命名;
+/// 全角字符先转为半角再分词(见 IKSegmenter::tokenize_records), 词元文本及偏移取自原文;
+/// 无法解析的行输出 `{"line": 行号, "error": 原因}` 并继续处理
+/// 读取、分词、写出在不同线程中进行, 之间使用有界队列, 输出跟不上时读取随之阻塞;
+/// 输出顺序与输入相同, 可以直接接在 Kafka 等消息队列的消费者之后
+#[derive(Debug, Clone)]
+pub struct Worker {
+    options: TokenizeOptions,
+    threads: usize,
+    queue_capacity: usize,
+    text_field: String,
+    id_field: String,
+    lucene_names: bool,
+}
+
+/// Worker::run 的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkerStats {
+    // 成功分词的文档数
+    pub documents: usize,
+    // 输出的词元数
+    pub tokens: usize,
+    // 无法解析的行数
+    pub errors: usize,
+}
+
+// 一行输入的处理结果
+struct Output {
+    line: String,
+    tokens: usize,
+    is_error: bool,
+}
+
+impl Worker {
+    /// 按 options 分词, 分词线程数默认为可用的 CPU 数
+    pub fn new(options: TokenizeOptions) -> Self {
+        Worker {
+            options,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            text_field: "text".to_string(),
+            id_field: "id".to_string(),
+            lucene_names: false,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// 读取与分词、分词与写出之间的队列容量
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity.max(1);
+        self
+    }
+
+    /// 文本所在的字段, 默认为 text
+    pub fn with_text_field(mut self, field: &str) -> Self {
+        self.text_field = field.to_string();
+        self
+    }
+
+    /// 原样输出的文档标识字段, 默认为 id, 文档中没有该字段时输出 null
+    pub fn with_id_field(mut self, field: &str) -> Self {
+        self.id_field = field.to_string();
+        self
+    }
+
+    /// 是否按 Lucene/ES 的命名输出词元, 默认为 false, 开启后每个词元为
+    /// `{"token":"中华人民共和国","start_offset":0,"end_offset":7,"type":"CN_WORD","position":0}`,
+    /// 偏移为 UTF-16 偏移, 与 ES _analyze 的输出逐字节相同, 见 LuceneToken
+    pub fn with_lucene_names(mut self, lucene_names: bool) -> Self {
+        self.lucene_names = lucene_names;
+        self
+    }
+
+    /// 从 input 读取文档直到结束, 结果写入 output
+    /// 读写出错时停止并返回错误, 已写出的结果不会回滚
+    pub fn run<R, W>(&self, input: R, mut output: W) -> Result<WorkerStats, String>
+    where
+        R: BufRead + Send,
+        W: Write,
+    {
+        // (序号, 行号, 行), 序号只计非空行, 用于按输入顺序写出
+        let (line_sender, line_receiver) =
+            sync_channel::<(usize, usize, String)>(self.queue_capacity);
+        let (output_sender, output_receiver) = sync_channel::<(usize, Output)>(self.queue_capacity);
+        let line_receiver = Arc::new(Mutex::new(line_receiver));
+        thread::scope(|scope| {
+            let reader = scope.spawn(move || -> Result<(), String> {
+                let mut seq = 0;
+                for (index, line) in input.lines().enumerate() {
+                    let line = line.map_err(|e| format!("read line {} error: {}", index + 1, e))?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    if line_sender.send((seq, index + 1, line)).is_err() {
+                        // 写出已停止
+                        break;
+                    }
+                    seq += 1;
+                }
+                Ok(())
+            });
+            for _ in 0..self.threads {
+                let line_receiver = line_receiver.clone();
+                let output_sender = output_sender.clone();
+                scope.spawn(move || {
+                    self.tokenize_lines(&line_receiver, |seq, output| {
+                        output_sender.send((seq, output)).is_ok()
+                    })
+                });
+            }
+            drop(output_sender);
+            let written = write_in_order(output_receiver, &mut output);
+            let read = reader.join().unwrap();
+            let stats = written?;
+            read?;
+            Ok(stats)
+        })
+    }
+
+    fn tokenize_lines(
+        &self,
+        lines: &Mutex<Receiver<(usize, usize, String)>>,
+        mut send: impl FnMut(usize, Output) -> bool,
+    ) {
+        let mut ik = IKSegmenter::new();
+        loop {
+            let next = lines.lock().unwrap().recv();
+            let (seq, line_no, line) = match next {
+                Ok(next) => next,
+                Err(_) => return,
+            };
+            let output = match self.tokenize_line(&mut ik, &line) {
+                Ok((line, tokens)) => Output {
+                    line,
+                    tokens,
+                    is_error: false,
+                },
+                Err(error) => Output {
+                    line: json!({"line": line_no, "error": error}).to_string(),
+                    tokens: 0,
+                    is_error: true,
+                },
+            };
+            if !send(seq, output) {
+                return;
+            }
+        }
+    }
+
+    fn tokenize_line(&self, ik: &mut IKSegmenter, line: &str) -> Result<(String, usize), String> {
+        let document = serde_json::from_str::<Map<String, Value>>(line)
+            .map_err(|e| format!("invalid json document: {}", e))?;
+        let text = match document.get(&self.text_field) {
+            Some(Value::String(text)) => text,
+            Some(_) => return Err(format!("field {:?} is not a string", self.text_field)),
+            None => return Err(format!("missing field {:?}", self.text_field)),
+        };
+        // 全角字符转为半角后分词, 词元文本及偏移取自原文
+        let records = ik.tokenize_records_with(text, self.options);
+        let id = document.get(&self.id_field).unwrap_or(&Value::Null);
+        if self.lucene_names {
+            let tokens = records
+                .iter()
+                .enumerate()
+                .map(|(position, record)| record.to_lucene(position))
+                .collect::<Vec<_>>();
+            let line = serde_json::to_string(&LuceneDocument {
+                id,
+                tokens: &tokens,
+            })
+            .map_err(|e| e.to_string())?;
+            return Ok((line, records.len()));
+        }
+        let tokens = records
+            .iter()
+            .enumerate()
+            .map(|(position, record)| {
+                json!({
+                    "text": record.text,
+                    "type": record.lexeme_type.as_str(),
+                    "position": position,
+                    "start": record.offsets.bytes.start,
+                    "end": record.offsets.bytes.end,
+                })
+            })
+            .collect::<Vec<_>>();
+        let count = tokens.len();
+        Ok((json!({"id": id, "tokens": tokens}).to_string(), count))
+    }
+}
+
+// Lucene 命名时输出的文档, 字段顺序固定
+#[derive(Serialize)]
+struct LuceneDocument<'a> {
+    id: &'a Value,
+    tokens: &'a [LuceneToken<'a>],
+}
+
+// 按序号写出, 先完成的结果暂存到前面的结果完成
+fn write_in_order<W: Write>(
+    outputs: Receiver<(usize, Output)>,
+    output: &mut W,
+) -> Result<WorkerStats, String> {
+    let mut stats = WorkerStats::default();
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    for (seq, result) in outputs {
+        pending.insert(seq, result);
+        while let Some(result) = pending.remove(&next) {
+            if result.is_error {
+                stats.errors += 1;
+            } else {
+                stats.documents += 1;
+                stats.tokens += result.tokens;
+            }
+            writeln!(output, "{}", result.line).map_err(|e| format!("write error: {}", e))?;
+            next += 1;
+        }
+    }
+    output.flush().map_err(|e| format!("write error: {}", e))?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::ik_segmenter::TokenMode;
+
+    #[test]
+    fn test_worker() {
+        let input = (0..100)
+            .map(|i| format!("{{\"id\": {}, \"text\": \"中华人民共和国\"}}\n", i))
+            .collect::<String>()
+            + "\nnot json\n{\"id\": \"x\", \"body\": \"iPhone\"}\n";
+        let mut output = Vec::new();
+        let stats = Worker::new(TokenMode::SEARCH.into())
+            .with_threads(4)
+            .with_queue_capacity(2)
+            .run(input.as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(
+            stats,
+            WorkerStats {
+                documents: 100,
+                tokens: 100,
+                errors: 2
+            }
+        );
+        let lines = String::from_utf8(output).unwrap();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 102);
+        for (i, line) in lines[..100].iter().enumerate() {
+            let value = serde_json::from_str::<Value>(line).unwrap();
+            assert_eq!(value["id"], i);
+            assert_eq!(value["tokens"][0]["text"], "中华人民共和国");
+            assert_eq!(value["tokens"][0]["end"], 21);
+        }
+        let error = serde_json::from_str::<Value>(lines[100]).unwrap();
+        assert_eq!(error["line"], 102);
+
+        let stats = Worker::new(TokenMode::SEARCH.into())
+            .with_text_field("body")
+            .run("{\"body\": \"iPhone\"}".as_bytes(), &mut Vec::new())
+            .unwrap();
+        assert_eq!(stats.tokens, 1);
+
+        // 全角字符按半角切分, 偏移取自原文
+        let mut output = Vec::new();
+        Worker::new(TokenMode::INDEX.into())
+            .run(
+                "{\"id\": 1, \"text\": \"ＡＢＣ１２３\"}".as_bytes(),
+                &mut output,
+            )
+            .unwrap();
+        let value = serde_json::from_slice::<Value>(&output).unwrap();
+        let tokens = value["tokens"].as_array().unwrap();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[1]["text"], "ＡＢＣ");
+        assert_eq!(tokens[1]["type"], "ENGLISH");
+        assert_eq!(tokens[2]["start"], 9);
+        assert_eq!(tokens[2]["end"], 18);
+    }
+
+    #[test]
+    fn test_lucene_names() {
+        let mut output = Vec::new();
+        Worker::new(TokenMode::SEARCH.into())
+            .with_lucene_names(true)
+            .run(
+                "{\"id\": 1, \"text\": \"中华人民共和国\"}".as_bytes(),
+                &mut output,
+            )
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"id\":1,\"tokens\":[{\"token\":\"中华人民共和国\",\"start_offset\":0,\"end_offset\":7,\"type\":\"CN_WORD\",\"position\":0}]}\n"
+        );
+    }
+}
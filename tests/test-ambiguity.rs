@@ -0,0 +1,29 @@
+//! 经典歧义切分的回归语料, 用例见 tests/fixtures/ambiguity.tsv
+//! 可用 ik_rs::eval::corpus::to_fixtures 由标准切分语料生成同样格式的用例
+use ik_rs::core::ik_segmenter::{IKSegmenter, TokenMode};
+use ik_rs::eval::corpus::load_fixtures;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ambiguity.tsv");
+
+#[test]
+fn test_ambiguity_regression() {
+    let cases = load_fixtures(FIXTURES).unwrap();
+    assert_eq!(cases.len(), 10);
+    let mut ik = IKSegmenter::new();
+    let mut failures = Vec::new();
+    for case in cases {
+        let text = case.text();
+        let tokens = ik
+            .tokenize(&text, TokenMode::SEARCH)
+            .iter()
+            .map(|l| l.get_lexeme_text().to_string())
+            .collect::<Vec<_>>();
+        if tokens != case.words {
+            failures.push(format!(
+                "{}: expected {:?}, got {:?}",
+                text, case.words, tokens
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
@@ -0,0 +1,128 @@
+//! ik 命令行工具的端到端测试
+#![cfg(feature = "cli")]
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn ik(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ik"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_tokenize_formats() {
+    let input = "iPhone售价5999元\n中华人民共和国\n";
+    let output = ik(&["tokenize", "--mode", "smart", "-"], input);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "iPhone 售价 5999 元\n中华人民共和国\n");
+
+    let output = ik(&["tokenize", "--mode", "smart", "--format", "json"], input);
+    let first = stdout(&output).lines().next().unwrap().to_string();
+    let value: serde_json::Value = serde_json::from_str(&first).unwrap();
+    assert_eq!(value["line"], 1);
+    assert_eq!(value["tokens"][1]["token"], "售价");
+    assert_eq!(value["tokens"][1]["start_offset"], 6);
+    assert_eq!(value["tokens"][1]["type"], "CN_WORD");
+    assert_eq!(value["tokens"][1]["position"], 1);
+
+    let output = ik(
+        &["tokenize", "--mode", "max", "--format", "tsv", "-"],
+        input,
+    );
+    let text = stdout(&output);
+    let mut lines = text.lines();
+    assert_eq!(
+        lines.next(),
+        Some("line\tposition\ttoken\tstart_offset\tend_offset\ttype")
+    );
+    assert_eq!(lines.next(), Some("1\t0\tiPhone\t0\t6\tENGLISH"));
+    assert!(text.contains("\n2\t0\t中华人民共和国\t0\t7\tCN_WORD\n"));
+
+    // 全角字符按半角切分, 输出原文
+    let output = ik(&["tokenize", "--mode", "smart", "-"], "ＡＢＣ１２３\n");
+    assert_eq!(stdout(&output), "ＡＢＣ１２３\n");
+}
+
+#[test]
+fn test_dict_check() {
+    let path = std::env::temp_dir().join(format!("ik-cli-{}.dic", std::process::id()));
+    std::fs::write(&path, "区块链\n区块链\n").unwrap();
+    let path = path.to_string_lossy().to_string();
+    let output = ik(&["dict", "check", "--format", "tsv", &path], "");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        format!("file\tline\tword\tkind\n{}\t2\t区块链\tDuplicate\n", path)
+    );
+}
+
+#[test]
+fn test_dict_freq() {
+    let corpus = "南京市长江大桥\n长江大桥的桥墩\n";
+    let output = ik(&["dict", "freq", "--min-count", "2", "-"], corpus);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "长江大桥\t2\n");
+    let output = ik(&["dict", "freq", "--max-match", "-"], corpus);
+    assert!(stdout(&output).starts_with("长江大桥\t2\n"));
+}
+
+#[test]
+fn test_coverage() {
+    let output = ik(
+        &["coverage", "--format", "tsv", "-"],
+        "中华人民共和国\n他说привет мир的\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "line\tstart\tend\ttext\tcontext\n2\t2\t12\tпривет мир\t他说привет мир的\n"
+    );
+}
+
+#[test]
+fn test_eval() {
+    let path = std::env::temp_dir().join(format!("ik-cli-{}.utf8", std::process::id()));
+    std::fs::write(&path, "南京市  长江大桥\n").unwrap();
+    let path = path.to_string_lossy().to_string();
+    let output = ik(&["eval", "--format", "json", &path], "");
+    std::fs::remove_file(&path).unwrap();
+    let value: serde_json::Value = serde_json::from_str(stdout(&output).trim()).unwrap();
+    assert_eq!(value["correct_words"], 2);
+    assert_eq!(value["f1"], 1.0);
+}
+
+#[test]
+fn test_bench_and_errors() {
+    let path = std::env::temp_dir().join(format!("ik-cli-{}.txt", std::process::id()));
+    std::fs::write(&path, "张三说的确实在理\n").unwrap();
+    let path = path.to_string_lossy().to_string();
+    let output = ik(
+        &["bench", "--format", "json", "--iterations", "3", &path],
+        "",
+    );
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    let value: serde_json::Value = serde_json::from_str(stdout(&output).trim()).unwrap();
+    assert_eq!(value["lines"], 3);
+    assert_eq!(value["chars"], 24);
+
+    let output = ik(&["tokenize", "--format", "xml"], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(ik(&["unknown"], "").status.code(), Some(2));
+}
@@ -0,0 +1,93 @@
+//! 后台持续 add_words 时分词线程不应被长时间阻塞
+//! 修改词典的并发设计(锁、快照替换等)前后需保证此用例通过, 吞吐对比见 benches/concurrent_update_bench.rs
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ik_rs::core::ik_segmenter::{IKSegmenter, TokenMode};
+use ik_rs::dict::dictionary::GLOBAL_DICT;
+
+const TEXT: &str = "张华考上了北京大学；李萍进了中等技术学校；我在百货公司当售货员";
+const READERS: usize = 4;
+const ROUNDS: usize = 200;
+
+// 单次分词允许的最长耗时, 远大于正常耗时, 只用于发现读者被饿死
+const MAX_STALL: Duration = Duration::from_secs(2);
+// 有写者时 p99 耗时相对于无写者时的最大倍数
+const MAX_P99_RATIO: u32 = 20;
+// p99 的下限, 正常耗时只有几十微秒, 避免调度抖动导致误报
+const MIN_P99: Duration = Duration::from_millis(5);
+
+// 多个线程各分词 ROUNDS 次, 返回全部耗时
+fn tokenize_latencies(expected: usize) -> Vec<Duration> {
+    let readers = (0..READERS)
+        .map(|_| {
+            thread::spawn(move || {
+                let mut ik = IKSegmenter::new();
+                (0..ROUNDS)
+                    .map(|_| {
+                        let start = Instant::now();
+                        assert_eq!(ik.tokenize(TEXT, TokenMode::SEARCH).len(), expected);
+                        start.elapsed()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+    readers
+        .into_iter()
+        .flat_map(|reader| reader.join().unwrap())
+        .collect()
+}
+
+fn p99(mut latencies: Vec<Duration>) -> Duration {
+    latencies.sort_unstable();
+    latencies[(latencies.len() * 99 / 100).min(latencies.len() - 1)]
+}
+
+#[test]
+fn test_tokenize_during_add_words() {
+    let expected = IKSegmenter::new().tokenize(TEXT, TokenMode::SEARCH).len();
+    // 无写者时的耗时作为基准
+    let baseline = p99(tokenize_latencies(expected));
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let stop = stop.clone();
+        thread::spawn(move || {
+            let mut bursts = 0;
+            while !stop.load(Ordering::Relaxed) {
+                let words = (0..100)
+                    .map(|i| format!("并发新词{}号{}", bursts, i))
+                    .collect::<Vec<_>>();
+                GLOBAL_DICT
+                    .write()
+                    .unwrap()
+                    .add_words(words.iter().map(String::as_str).collect());
+                bursts += 1;
+                thread::sleep(Duration::from_millis(1));
+            }
+            bursts
+        })
+    };
+    let latencies = tokenize_latencies(expected);
+    stop.store(true, Ordering::Relaxed);
+    let bursts = writer.join().unwrap();
+    assert!(bursts > 0);
+    let max = *latencies.iter().max().unwrap();
+    assert!(max < MAX_STALL, "tokenize stalled for {:?}", max);
+    let contended = p99(latencies);
+    let limit = (baseline * MAX_P99_RATIO).max(MIN_P99);
+    assert!(
+        contended < limit,
+        "p99 {:?} during add_words, baseline p99 {:?}",
+        contended,
+        baseline
+    );
+
+    // 写入的词对分词可见
+    let mut ik = IKSegmenter::new();
+    let tokens = ik.tokenize("并发新词0号1", TokenMode::SEARCH);
+    assert_eq!(tokens[0].get_lexeme_text(), "并发新词0号1");
+}
@@ -0,0 +1,49 @@
+mod tests {
+    use std::path::PathBuf;
+
+    use ik_rs::config::configuration::Configuration;
+    use ik_rs::core::ik_segmenter::TokenMode;
+    use ik_rs::{IkStatus, GLOBAL_IK};
+
+    struct MissingDictConfig;
+
+    impl Configuration for MissingDictConfig {
+        fn get_main_dictionary(&self) -> PathBuf {
+            PathBuf::from("/nonexistent/main.dic")
+        }
+        fn get_quantifier_dictionary(&self) -> PathBuf {
+            PathBuf::from("/nonexistent/quantifier.dic")
+        }
+        fn get_ext_dictionaries(&self) -> Vec<PathBuf> {
+            vec![]
+        }
+        fn get_ext_stop_word_dictionaries(&self) -> Vec<PathBuf> {
+            vec![]
+        }
+    }
+
+    #[cfg(not(feature = "embedded-dict"))]
+    #[test]
+    fn missing_dict_is_degraded() {
+        ik_rs::init_with_config(MissingDictConfig).unwrap();
+        let tokens = GLOBAL_IK
+            .lock()
+            .unwrap()
+            .tokenize("hello world", TokenMode::INDEX);
+        assert_eq!(tokens.len(), 2);
+        assert!(matches!(ik_rs::status(), IkStatus::Degraded { .. }));
+    }
+
+    // 启用 embedded-dict 时, 主词典文件不存在则使用嵌入的词典
+    #[cfg(feature = "embedded-dict")]
+    #[test]
+    fn missing_dict_uses_embedded() {
+        ik_rs::init_with_config(MissingDictConfig).unwrap();
+        let tokens = GLOBAL_IK
+            .lock()
+            .unwrap()
+            .tokenize("中华人民共和国", TokenMode::SEARCH);
+        assert_eq!(tokens[0].get_lexeme_text(), "中华人民共和国");
+        assert!(matches!(ik_rs::status(), IkStatus::Ready { .. }));
+    }
+}
@@ -0,0 +1,159 @@
+//! feature 组合矩阵, 防止 feature 门控的代码失修
+//! 默认只在当前启用的 feature 下运行冒烟测试;
+//! 矩阵需重新编译多次, 需显式运行(CI 中的 feature-matrix 任务): cargo test --test test-feature-matrix -- --ignored
+//! 矩阵对每个组合执行 cargo test --no-default-features --features <组合>, 即在该组合下重新编译并运行本文件
+//! 组合为: 不启用任何 feature、每个 feature 单独启用、任意两个 feature 同时启用及全部启用
+use std::path::Path;
+use std::process::Command;
+
+use ik_rs::core::ik_segmenter::{IKSegmenter, TokenMode};
+
+// 参与组合的 feature, 新增 feature 时需同步, 见 test_features_in_sync
+const FEATURES: [&str; 9] = [
+    "tantivy",
+    "signal-reload",
+    "fst",
+    "ffi",
+    "worker",
+    "embedded-dict",
+    "cli",
+    "server",
+    "remote-dict",
+];
+
+// 只用于特定目标平台的 feature, 不参与组合
+// wasm 由 CI 中的 wasm 任务检查: cargo check --target wasm32-unknown-unknown --no-default-features --features wasm
+const TARGET_FEATURES: [&str; 1] = ["wasm"];
+
+const SMOKE_CORPUS: [(&str, &[&str]); 3] = [
+    ("中华人民共和国", &["中华人民共和国"]),
+    ("张三说的确实在理", &["张三", "说", "的确", "实", "在理"]),
+    ("iPhone售价5999元", &["iPhone", "售价", "5999", "元"]),
+];
+
+#[test]
+fn test_smoke_corpus() {
+    let mut ik = IKSegmenter::new();
+    for (text, expected) in SMOKE_CORPUS {
+        let tokens = ik
+            .tokenize(text, TokenMode::SEARCH)
+            .iter()
+            .map(|l| l.get_lexeme_text().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, expected.to_vec(), "{}", text);
+    }
+}
+
+#[cfg(feature = "tantivy")]
+#[test]
+fn test_smoke_tantivy() {
+    use tantivy::tokenizer::Tokenizer;
+    let mut stream = ik_rs::IkTokenizer::ik_smart().token_stream("中华人民共和国");
+    assert!(stream.advance());
+    assert_eq!(stream.token().text, "中华人民共和国");
+}
+
+#[cfg(feature = "fst")]
+#[test]
+fn test_smoke_fst() {
+    use ik_rs::dict::dictionary::GLOBAL_DICT;
+    let dict = GLOBAL_DICT.read().unwrap().main_dict_fst().unwrap();
+    assert!(dict.exist("中华人民共和国".chars()));
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn test_smoke_ffi() {
+    let text = "中华人民共和国";
+    unsafe {
+        let array = ik_rs::ffi::ik_tokenize(text.as_ptr(), text.len(), ik_rs::ffi::IK_MODE_SEARCH);
+        assert_eq!(array.len, 1);
+        ik_rs::ffi::ik_token_array_free(array);
+    }
+}
+
+#[cfg(all(unix, feature = "signal-reload"))]
+#[test]
+fn test_smoke_signal_reload() {
+    // 只检查接口在该 feature 下可用, 不实际安装信号处理
+    let _install: fn() -> std::io::Result<_> = ik_rs::signal::install_reload_handler;
+}
+
+// FEATURES 及 TARGET_FEATURES 之外, Cargo.toml 中不应有其他 feature
+#[test]
+fn test_features_in_sync() {
+    let manifest =
+        std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml")).unwrap();
+    let mut declared = manifest
+        .lines()
+        .skip_while(|line| line.trim() != "[features]")
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, _)| name.trim())
+        .filter(|name| *name != "default" && !name.starts_with('#'))
+        .collect::<Vec<_>>();
+    declared.sort_unstable();
+    let mut features = FEATURES.to_vec();
+    features.extend(TARGET_FEATURES);
+    features.sort_unstable();
+    assert_eq!(declared, features);
+}
+
+// 两两组合而非全部 2^n 种组合, feature 之间的冲突多发生在两个 feature 之间
+fn feature_combinations() -> Vec<Vec<&'static str>> {
+    let mut combinations = vec![Vec::new()];
+    for (i, first) in FEATURES.iter().enumerate() {
+        combinations.push(vec![*first]);
+        for second in FEATURES[i + 1..].iter() {
+            combinations.push(vec![*first, *second]);
+        }
+    }
+    combinations.push(FEATURES.to_vec());
+    combinations
+}
+
+#[test]
+fn test_feature_combinations() {
+    let combinations = feature_combinations();
+    let n = FEATURES.len();
+    assert_eq!(combinations.len(), 1 + n + n * (n - 1) / 2 + 1);
+    // 任意两个 feature 都同时出现在某个组合中
+    for first in FEATURES {
+        for second in FEATURES {
+            assert!(combinations
+                .iter()
+                .any(|c| c.contains(&first) && c.contains(&second)));
+        }
+    }
+}
+
+#[test]
+#[ignore]
+fn test_feature_matrix() {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let root = env!("CARGO_MANIFEST_DIR");
+    // 使用独立的 target 目录, 避免与外层 cargo 争用构建锁
+    let target_dir = Path::new(root).join("target").join("feature-matrix");
+    let mut failures = Vec::new();
+    for combination in feature_combinations() {
+        let combination = combination.join(",");
+        let status = Command::new(&cargo)
+            .current_dir(root)
+            .args([
+                "test",
+                "--no-default-features",
+                "--test",
+                "test-feature-matrix",
+            ])
+            .args(["--features", &combination])
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .status()
+            .unwrap();
+        if !status.success() {
+            failures.push(combination);
+        }
+    }
+    assert!(failures.is_empty(), "failed combinations: {:?}", failures);
+}
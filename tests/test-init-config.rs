@@ -0,0 +1,32 @@
+mod tests {
+    use std::path::Path;
+
+    use ik_rs::config::default_config::{DefaultConfig, IK_CONFIG_NAME};
+    use ik_rs::core::ik_segmenter::TokenMode;
+    use ik_rs::dict::dictionary::Dictionary;
+    use ik_rs::{IkStatus, GLOBAL_IK};
+
+    #[test]
+    fn init_before_use() {
+        assert_eq!(ik_rs::status(), IkStatus::Uninitialized);
+        // 构造词典不会确定全局配置
+        let _dict = Dictionary::default();
+        let conf_file_path = Path::new(env!("CARGO_MANIFEST_DIR")).join(IK_CONFIG_NAME);
+        std::env::set_var("IK_KEEP_STOP_WORDS", "true");
+        let cfg = DefaultConfig::new(&conf_file_path).with_env().unwrap();
+        assert!(ik_rs::init_with_config(cfg).is_ok());
+        let tokens = GLOBAL_IK
+            .lock()
+            .unwrap()
+            .tokenize("中华人民共和国", TokenMode::SEARCH);
+        assert_eq!(tokens.len(), 1);
+        // 配置的 keep_stop_words 对 GLOBAL_IK 生效
+        let tokens = GLOBAL_IK
+            .lock()
+            .unwrap()
+            .tokenize("the 中华人民共和国", TokenMode::SEARCH);
+        assert_eq!(tokens[0].get_lexeme_text(), "the");
+        assert_eq!(ik_rs::status(), IkStatus::Ready { dict_version: 0 });
+        assert!(ik_rs::init_with_config(DefaultConfig::new(&conf_file_path)).is_err());
+    }
+}
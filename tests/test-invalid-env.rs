@@ -0,0 +1,27 @@
+mod tests {
+    use ik_rs::config::configuration::{config_error, global_config, load_default_config};
+    use ik_rs::config::default_config::DefaultConfig;
+    use ik_rs::core::ik_segmenter::TokenMode;
+    use ik_rs::{IkStatus, GLOBAL_IK};
+
+    // 无效的 IK_* 环境变量不会在首次分词时 panic, 而是使用内置默认配置并报告为 Degraded
+    #[test]
+    fn invalid_env_is_degraded() {
+        std::env::set_var("IK_AUTO_SCRIPT", "yes");
+        let error = load_default_config().unwrap_err();
+        assert!(error.starts_with("IK_AUTO_SCRIPT"), "{}", error);
+
+        let tokens = GLOBAL_IK
+            .lock()
+            .unwrap()
+            .tokenize("中华人民共和国", TokenMode::SEARCH);
+        assert_eq!(tokens.len(), 1);
+        assert!(!global_config().get_auto_script());
+        assert_eq!(config_error(), Some(error.as_str()));
+        assert!(matches!(
+            ik_rs::status(),
+            IkStatus::Degraded { error } if error.contains("IK_AUTO_SCRIPT")
+        ));
+        assert!(ik_rs::init_with_config(DefaultConfig::default()).is_err());
+    }
+}
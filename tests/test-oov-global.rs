@@ -0,0 +1,21 @@
+mod tests {
+    use ik_rs::core::ik_segmenter::TokenMode;
+    use ik_rs::dict::oov::{init_global_oov_collector, OovCollector};
+    use ik_rs::with_thread_segmenter;
+
+    // 全局收集器汇总 with_thread_segmenter 等未单独指定收集器的分词器
+    #[test]
+    fn global_collector() {
+        let collector = OovCollector::shared();
+        init_global_oov_collector(collector.clone()).unwrap();
+        assert!(init_global_oov_collector(OovCollector::shared()).is_err());
+
+        with_thread_segmenter(|ik| ik.tokenize("嵘峰风景", TokenMode::SEARCH));
+        std::thread::spawn(|| {
+            with_thread_segmenter(|ik| ik.tokenize("位于嵘峰", TokenMode::SEARCH));
+        })
+        .join()
+        .unwrap();
+        assert_eq!(collector.lock().unwrap().get("嵘峰"), 2);
+    }
+}
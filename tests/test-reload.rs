@@ -0,0 +1,24 @@
+mod tests {
+    use ik_rs::dict::dictionary::{reload_global_dict, GLOBAL_DICT};
+
+    // 重新加载时丢弃运行时添加的词, 报告中的变化相对于加载前的词典
+    #[test]
+    fn reload_reports_diff() {
+        GLOBAL_DICT
+            .write()
+            .unwrap()
+            .add_words(vec!["重载新词甲", "重载新词乙"]);
+        let version = GLOBAL_DICT.read().unwrap().version();
+        let report = reload_global_dict();
+        assert!(report.is_ok(), "{:?}", report.errors);
+        assert_eq!(report.main_dict.added, 0);
+        assert_eq!(report.main_dict.removed, 2);
+        assert_eq!(report.version, version + 1);
+        assert!(GLOBAL_DICT
+            .read()
+            .unwrap()
+            .find_entries("^重载新词")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "tantivy")]
 mod tests {
     use ik_rs::core::ik_segmenter::TokenMode;
     use ik_rs::IkTokenizer;
@@ -23,4 +24,18 @@ mod tests {
             .tokenizers()
             .register("ik-search", IkTokenizer::new(TokenMode::SEARCH));
     }
+
+    #[test]
+    fn register_by_name() {
+        let mut schema_builder = Schema::builder();
+        let text_field_indexing = TextFieldIndexing::default()
+            .set_tokenizer(ik_rs::IK_MAX)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        let text_options = TextOptions::default().set_indexing_options(text_field_indexing);
+        schema_builder.add_text_field("title", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        ik_rs::register_ik_tokenizers(index.tokenizers());
+        assert!(index.tokenizers().get("ik_max").is_some());
+        assert!(index.tokenizers().get("ik_smart").is_some());
+    }
 }